@@ -1,4 +1,5 @@
 use binary_options_tools::error::BinaryOptionsError;
+use binary_options_tools::expertoptions::error::ExpertOptionsError;
 use binary_options_tools::pocketoption::error::PocketError;
 use thiserror::Error;
 
@@ -8,6 +9,8 @@ pub enum UniError {
     BinaryOptions(String),
     #[error("An error occurred in the PocketOption client: {0}")]
     PocketOption(String),
+    #[error("An error occurred in the ExpertOptions client: {0}")]
+    ExpertOptions(String),
     #[error("An error occurred with UUID parsing: {0}")]
     Uuid(String),
     #[error("An error occurred with validator: {0}")]
@@ -30,3 +33,9 @@ impl From<PocketError> for UniError {
         UniError::PocketOption(e.to_string())
     }
 }
+
+impl From<ExpertOptionsError> for UniError {
+    fn from(e: ExpertOptionsError) -> Self {
+        UniError::ExpertOptions(e.to_string())
+    }
+}
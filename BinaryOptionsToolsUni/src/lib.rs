@@ -4,6 +4,7 @@ pub mod tracing;
 pub mod utils;
 
 // Re-export main types for easier access
+pub use platforms::expertoptions::{client::ExpertOptions, types::ExpertDeal};
 pub use platforms::pocketoption::{
     client::PocketOption,
     raw_handler::RawHandler,
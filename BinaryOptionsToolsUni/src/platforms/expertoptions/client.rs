@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use binary_options_tools::expertoptions::client::ExpertOptions as OriginalExpertOptions;
+
+use crate::error::UniError;
+use crate::platforms::pocketoption::types::{Action, Candle};
+
+use super::types::ExpertDeal;
+
+/// The client for interacting with the ExpertOptions platform.
+///
+/// Exposes the account and trading methods of
+/// `binary_options_tools::expertoptions::client::ExpertOptions`. Streaming (real-time
+/// subscriptions) is not available through UniFFI yet, see the README.
+#[derive(uniffi::Object)]
+pub struct ExpertOptions {
+    inner: OriginalExpertOptions,
+}
+
+#[uniffi::export(async_runtime = "tokio")]
+impl ExpertOptions {
+    /// Creates a new instance of the ExpertOptions client.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The authentication token of your ExpertOptions account.
+    /// * `demo` - `true` to trade on the demo account, `false` for the real one.
+    ///
+    /// # Examples
+    ///
+    /// ## Swift
+    /// ```swift
+    /// let api = try await ExpertOptions(token: "YOUR_TOKEN", demo: true)
+    /// let balance = await api.balance()
+    /// ```
+    ///
+    /// ## Kotlin
+    /// ```kotlin
+    /// val api = ExpertOptions("YOUR_TOKEN", true)
+    /// val balance = api.balance()
+    /// ```
+    #[uniffi::constructor]
+    pub async fn new(token: String, demo: bool) -> Result<Arc<Self>, UniError> {
+        let inner = OriginalExpertOptions::new(token, demo).await?;
+        Ok(Arc::new(Self { inner }))
+    }
+
+    /// Creates a new instance of the ExpertOptions client with a custom WebSocket URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The authentication token of your ExpertOptions account.
+    /// * `demo` - `true` to trade on the demo account, `false` for the real one.
+    /// * `url` - The custom WebSocket URL to connect to.
+    #[uniffi::constructor]
+    pub async fn new_with_url(
+        token: String,
+        demo: bool,
+        url: String,
+    ) -> Result<Arc<Self>, UniError> {
+        let inner = OriginalExpertOptions::new_with_url(token, demo, url).await?;
+        Ok(Arc::new(Self { inner }))
+    }
+
+    /// Gets the balance of the current account.
+    ///
+    /// # Returns
+    ///
+    /// The current balance, or `-1.0` if the server didn't send it yet.
+    #[uniffi::method]
+    pub async fn balance(&self) -> f64 {
+        self.inner.balance().await
+    }
+
+    /// Checks if the current account is the demo account.
+    #[uniffi::method]
+    pub async fn is_demo(&self) -> bool {
+        self.inner.is_demo().await
+    }
+
+    /// Switches between the demo (`true`) and the real (`false`) account.
+    #[uniffi::method]
+    pub async fn set_context(&self, demo: bool) -> Result<(), UniError> {
+        Ok(self.inner.set_context(demo).await?)
+    }
+
+    /// Opens a trade.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - The symbol of the asset to trade (e.g., "EURUSD").
+    /// * `action` - The direction of the trade (`Action.Call` or `Action.Put`).
+    /// * `time` - The duration of the trade in seconds.
+    /// * `amount` - The amount to trade.
+    ///
+    /// # Returns
+    ///
+    /// An `ExpertDeal` describing the opened trade.
+    #[uniffi::method]
+    pub async fn trade(
+        &self,
+        asset: String,
+        action: Action,
+        time: u32,
+        amount: f64,
+    ) -> Result<ExpertDeal, UniError> {
+        let (id, deal) = self.inner.trade(asset, action.into(), amount, time).await?;
+        Ok(ExpertDeal::new(id, deal))
+    }
+
+    /// Opens a "Call" (buy) trade.
+    ///
+    /// This is a convenience method that calls `trade` with `Action.Call`.
+    #[uniffi::method]
+    pub async fn buy(&self, asset: String, time: u32, amount: f64) -> Result<ExpertDeal, UniError> {
+        self.trade(asset, Action::Call, time, amount).await
+    }
+
+    /// Opens a "Put" (sell) trade.
+    ///
+    /// This is a convenience method that calls `trade` with `Action.Put`.
+    #[uniffi::method]
+    pub async fn sell(
+        &self,
+        asset: String,
+        time: u32,
+        amount: f64,
+    ) -> Result<ExpertDeal, UniError> {
+        self.trade(asset, Action::Put, time, amount).await
+    }
+
+    /// Gets the last candles of `period` seconds for `asset`.
+    #[uniffi::method]
+    pub async fn history(&self, asset: String, period: u32) -> Result<Vec<Candle>, UniError> {
+        let candles = self
+            .inner
+            .history(asset, period)
+            .await?
+            .into_iter()
+            .map(Candle::from)
+            .collect();
+        Ok(candles)
+    }
+
+    /// Disconnects and reconnects the client.
+    #[uniffi::method]
+    pub async fn reconnect(&self) -> Result<(), UniError> {
+        Ok(self.inner.reconnect().await?)
+    }
+
+    /// Shuts down the client and stops all background tasks.
+    #[uniffi::method]
+    pub async fn shutdown(self: Arc<Self>) -> Result<(), UniError> {
+        Ok(self.inner.clone().shutdown().await?)
+    }
+}
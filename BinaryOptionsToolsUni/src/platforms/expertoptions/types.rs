@@ -0,0 +1,74 @@
+use binary_options_tools::expertoptions::modules::trades::{
+    ExpertDeal as OriginalExpertDeal, TradeAction as OriginalTradeAction,
+};
+use rust_decimal::prelude::ToPrimitive;
+use uuid::Uuid;
+
+use crate::platforms::pocketoption::types::Action;
+
+impl From<OriginalTradeAction> for Action {
+    fn from(action: OriginalTradeAction) -> Self {
+        match action {
+            OriginalTradeAction::Call => Action::Call,
+            OriginalTradeAction::Put => Action::Put,
+        }
+    }
+}
+
+impl From<Action> for OriginalTradeAction {
+    fn from(action: Action) -> Self {
+        match action {
+            Action::Call => OriginalTradeAction::Call,
+            Action::Put => OriginalTradeAction::Put,
+        }
+    }
+}
+
+/// Represents a trade opened on ExpertOptions.
+///
+/// The server only confirms that the trade was opened, the result is not part of this record.
+///
+/// # Examples
+///
+/// ## Swift
+/// ```swift
+/// let deal = try await api.buy(asset: "EURUSD", time: 60, amount: 1.0)
+/// print("Trade \(deal.id) opened at \(deal.strikeTime)")
+/// ```
+///
+/// ## Kotlin
+/// ```kotlin
+/// val deal = api.buy("EURUSD", 60u, 1.0)
+/// println("Trade ${deal.id} opened at ${deal.strikeTime}")
+/// ```
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct ExpertDeal {
+    /// Id of the trade on the ExpertOptions servers.
+    pub id: u64,
+    /// Id given to the trade by the client.
+    pub trade_id: String,
+    pub asset_id: u32,
+    pub amount: f64,
+    pub action: Action,
+    /// Unix timestamp at which the trade was opened.
+    pub strike_time: i64,
+    /// Unix timestamp at which the trade expires.
+    pub exp_time: i64,
+    /// Price of the asset when the trade was opened, if the server sent it.
+    pub strike_rate: Option<f64>,
+}
+
+impl ExpertDeal {
+    pub(crate) fn new(trade_id: Uuid, deal: OriginalExpertDeal) -> Self {
+        Self {
+            id: deal.id,
+            trade_id: trade_id.to_string(),
+            asset_id: deal.asset_id,
+            amount: deal.amount.to_f64().unwrap_or_default(),
+            action: deal.action.into(),
+            strike_time: deal.strike_time,
+            exp_time: deal.exp_time,
+            strike_rate: deal.strike_rate.and_then(|rate| rate.to_f64()),
+        }
+    }
+}
@@ -1 +1,2 @@
+pub mod expertoptions;
 pub mod pocketoption;
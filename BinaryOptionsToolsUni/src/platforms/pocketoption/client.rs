@@ -33,7 +33,7 @@ pub struct PocketOption {
     inner: OriginalPocketOption,
 }
 
-#[uniffi::export]
+#[uniffi::export(async_runtime = "tokio")]
 impl PocketOption {
     /// Creates a new instance of the PocketOption client.
     ///
@@ -15,7 +15,7 @@ pub struct RawHandler {
     inner: InnerRawHandler,
 }
 
-#[uniffi::export]
+#[uniffi::export(async_runtime = "tokio")]
 impl RawHandler {
     /// Send a text message through this handler.
     ///
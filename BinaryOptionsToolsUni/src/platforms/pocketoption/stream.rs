@@ -30,7 +30,7 @@ impl SubscriptionStream {
     }
 }
 
-#[uniffi::export]
+#[uniffi::export(async_runtime = "tokio")]
 impl SubscriptionStream {
     /// Retrieves the next item from the stream.
     ///
@@ -0,0 +1,27 @@
+// swift-tools-version:5.7
+// Runs the tests against the bindings generated in `out/swift`, linked in `Sources` as SwiftPM
+// doesn't accept target paths outside of the package, see the README.
+import PackageDescription
+
+let package = Package(
+    name: "BinaryOptionsToolsUniTests",
+    targets: [
+        .systemLibrary(
+            name: "binary_options_tools_uniFFI",
+            path: "Sources/binary_options_tools_uniFFI"
+        ),
+        .target(
+            name: "BinaryOptionsToolsUni",
+            dependencies: ["binary_options_tools_uniFFI"],
+            path: "Sources/BinaryOptionsToolsUni"
+        ),
+        .testTarget(
+            name: "BinaryOptionsToolsUniTests",
+            dependencies: ["BinaryOptionsToolsUni"],
+            linkerSettings: [
+                .linkedLibrary("binary_options_tools_uni"),
+                .unsafeFlags(["-L../../target/release"]),
+            ]
+        ),
+    ]
+)
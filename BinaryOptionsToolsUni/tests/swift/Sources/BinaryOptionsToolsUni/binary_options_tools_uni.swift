@@ -0,0 +1 @@
+../../../../out/swift/binary_options_tools_uni.swift
\ No newline at end of file
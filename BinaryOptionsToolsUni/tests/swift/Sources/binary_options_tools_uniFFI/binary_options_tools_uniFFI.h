@@ -0,0 +1 @@
+../../../../out/swift/binary_options_tools_uniFFI.h
\ No newline at end of file
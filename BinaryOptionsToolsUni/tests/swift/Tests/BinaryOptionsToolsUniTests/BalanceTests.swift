@@ -0,0 +1,30 @@
+import BinaryOptionsToolsUni
+import XCTest
+
+/// Needs a valid session id in the `POCKET_OPTION_SSID` environment variable, skipped otherwise.
+final class BalanceTests: XCTestCase {
+    private func client() async throws -> PocketOption {
+        guard let ssid = ProcessInfo.processInfo.environment["POCKET_OPTION_SSID"] else {
+            throw XCTSkip("POCKET_OPTION_SSID is not set")
+        }
+        return try await PocketOption(ssid: ssid)
+    }
+
+    func testBalanceReturnsDouble() async throws {
+        let api = try await client()
+        // Leave time for the balance to be received after the connection
+        try await Task.sleep(nanoseconds: 5_000_000_000)
+
+        let balance: Double = await api.balance()
+        XCTAssertTrue(balance.isFinite)
+        XCTAssertGreaterThanOrEqual(balance, 0)
+    }
+
+    func testServerTimeIsAvailable() async throws {
+        let api = try await client()
+        try await Task.sleep(nanoseconds: 5_000_000_000)
+
+        let serverTime: Int64 = await api.serverTime()
+        XCTAssertGreaterThan(serverTime, 0)
+    }
+}
@@ -0,0 +1,38 @@
+import BinaryOptionsToolsUni
+import XCTest
+
+/// Needs a valid token in the `EXPERT_OPTIONS_TOKEN` environment variable, skipped otherwise.
+final class ExpertOptionsTests: XCTestCase {
+    private func client() async throws -> ExpertOptions {
+        guard let token = ProcessInfo.processInfo.environment["EXPERT_OPTIONS_TOKEN"] else {
+            throw XCTSkip("EXPERT_OPTIONS_TOKEN is not set")
+        }
+        return try await ExpertOptions(token: token, demo: true)
+    }
+
+    func testBalanceIsNotNegative() async throws {
+        let api = try await client()
+        // Leave time for the balance to be received after the connection
+        try await Task.sleep(nanoseconds: 5_000_000_000)
+
+        let balance: Double = await api.balance()
+        XCTAssertTrue(balance.isFinite)
+        XCTAssertGreaterThanOrEqual(balance, 0)
+        let isDemo = await api.isDemo()
+        XCTAssertTrue(isDemo)
+        try await api.shutdown()
+    }
+
+    func testUnknownAssetThrows() async throws {
+        let api = try await client()
+        do {
+            _ = try await api.buy(asset: "UNKNOWN", time: 60, amount: 1.0)
+            XCTFail("Trading an unknown asset should throw")
+        } catch let error as UniError {
+            guard case .ExpertOptions = error else {
+                return XCTFail("Unexpected error: \(error)")
+            }
+        }
+        try await api.shutdown()
+    }
+}
@@ -0,0 +1,37 @@
+import BinaryOptionsToolsUni
+import XCTest
+
+/// Runs without an account, checks the bindings load and call into the library.
+final class OfflineTests: XCTestCase {
+    func testValidatorChecksMessages() throws {
+        let validator = Validator.all(validators: [
+            Validator.startsWith(prefix: "42["),
+            try Validator.regex(pattern: "updateStream"),
+        ])
+
+        XCTAssertTrue(validator.check(message: "42[\"updateStream\"]"))
+        XCTAssertFalse(validator.check(message: "42[\"successauth\"]"))
+    }
+
+    func testInvalidRegexThrows() {
+        XCTAssertThrowsError(try Validator.regex(pattern: "(")) { error in
+            guard case UniError.Validator = error else {
+                return XCTFail("Unexpected error: \(error)")
+            }
+        }
+    }
+
+    func testInvalidSsidThrows() async {
+        // The session id is parsed before connecting, so this never reaches the server
+        do {
+            _ = try await PocketOption(ssid: "not a session id")
+            XCTFail("An invalid session id should throw")
+        } catch let error as UniError {
+            guard case .PocketOption = error else {
+                return XCTFail("Unexpected error: \(error)")
+            }
+        } catch {
+            XCTFail("Unexpected error: \(error)")
+        }
+    }
+}
@@ -4,7 +4,8 @@ from .BinaryOptionsToolsV2 import *  # noqa: F403
 from .BinaryOptionsToolsV2 import __doc__  # noqa: F401
 
 from .pocketoption import __all__ as __pocket_all__
+from .expertoptions import __all__ as __expert_all__
 from . import tracing
 from . import validator
 
-__all__ = __pocket_all__ + ["tracing", "validator"]
+__all__ = __pocket_all__ + __expert_all__ + ["tracing", "validator"]
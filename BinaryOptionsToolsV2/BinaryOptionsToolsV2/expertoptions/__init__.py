@@ -0,0 +1,16 @@
+"""
+Module for Expert Option related functionality.
+
+Contains asynchronous and synchronous clients for Expert Option trading.
+"""
+
+__all__ = [
+    "asyncronous",
+    "syncronous",
+    "ExpertOptionsAsync",
+    "ExpertOptions",
+]
+
+from . import asyncronous, syncronous
+from .asyncronous import ExpertOptionsAsync
+from .syncronous import ExpertOptions
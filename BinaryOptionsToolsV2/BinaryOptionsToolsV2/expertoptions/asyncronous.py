@@ -0,0 +1,106 @@
+from BinaryOptionsToolsV2 import RawExpertOptions, Logger
+from BinaryOptionsToolsV2.pocketoption.asyncronous import AsyncSubscription
+
+import json
+
+
+class ExpertOptionsAsync:
+    def __init__(self, token: str, demo: bool = True, url: str | None = None):
+        """
+        Initializes a new ExpertOptionsAsync instance.
+
+        This class provides an asynchronous interface for interacting with the Expert Option trading platform,
+        it mirrors the API of `PocketOptionAsync`.
+
+        Args:
+            token (str): Authentication token of the Expert Option account
+            demo (bool, optional): Whether to trade on the demo account. Defaults to True.
+            url (str | None, optional): Custom WebSocket server URL. Defaults to None, using platform's default URL.
+
+        Examples:
+            ```python
+            client = ExpertOptionsAsync("your-token")
+            balance = await client.balance()
+            ```
+        """
+        if url is not None:
+            self.client = RawExpertOptions.new_with_url(token, url, demo)
+        else:
+            self.client = RawExpertOptions(token, demo)
+        self.logger = Logger()
+
+    async def buy(self, asset: str, amount: float, time: int) -> tuple[str, dict]:
+        """
+        Places a buy (call) order for the specified asset.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD")
+            amount (float): Trade amount in account currency
+            time (int): Expiry time in seconds (e.g., 60 for 1 minute)
+
+        Returns:
+            tuple[str, dict]: Tuple containing (trade_id, trade_details)
+        """
+        (trade_id, trade) = await self.client.buy(asset, amount, time)
+        return trade_id, json.loads(trade)
+
+    async def sell(self, asset: str, amount: float, time: int) -> tuple[str, dict]:
+        """
+        Places a sell (put) order for the specified asset.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD")
+            amount (float): Trade amount in account currency
+            time (int): Expiry time in seconds (e.g., 60 for 1 minute)
+
+        Returns:
+            tuple[str, dict]: Tuple containing (trade_id, trade_details)
+        """
+        (trade_id, trade) = await self.client.sell(asset, amount, time)
+        return trade_id, json.loads(trade)
+
+    async def balance(self) -> float:
+        """
+        Retrieves current account balance.
+
+        Returns:
+            float: Account balance of the current account, -1 if it wasn't received yet
+        """
+        return await self.client.balance()
+
+    async def is_demo(self) -> bool:
+        """
+        Checks if the current account is a demo account.
+
+        Returns:
+            bool: True if using a demo account, False if using a real account
+        """
+        return await self.client.is_demo()
+
+    async def get_profile(self) -> dict:
+        """
+        Returns the profile of the user, waiting for the server to send it after connecting.
+
+        Returns:
+            dict: {"uid": int, "name": str, "balance": float, "account_type": "demo" | "real"}
+        """
+        return json.loads(await self.client.get_profile())
+
+    async def history(self, asset: str, period: int) -> list[dict]:
+        "Returns a list of dictionaries containing the latest candles of 'period' seconds available for the specified asset."
+        return json.loads(await self.client.history(asset, period))
+
+    async def subscribe_symbol(self, asset: str) -> AsyncSubscription:
+        """
+        Creates a real-time data subscription for an asset.
+
+        Args:
+            asset (str): Trading asset to subscribe to
+
+        Returns:
+            AsyncSubscription: Async iterator yielding real-time price updates
+        """
+        return AsyncSubscription(await self._subscribe_symbol_inner(asset))
+
+    async def _subscribe_symbol_inner(self, asset: str):
+        return await self.client.subscribe_symbol(asset)
@@ -0,0 +1,53 @@
+from .asyncronous import ExpertOptionsAsync
+from BinaryOptionsToolsV2.pocketoption.syncronous import SyncSubscription
+
+import asyncio
+
+
+class ExpertOptions:
+    def __init__(self, token: str, demo: bool = True, url: str | None = None):
+        """
+        Initializes a new ExpertOptions instance.
+
+        Synchronous version of `ExpertOptionsAsync`, every call runs on an event loop owned by the instance.
+
+        Args:
+            token (str): Authentication token of the Expert Option account
+            demo (bool, optional): Whether to trade on the demo account. Defaults to True.
+            url (str | None, optional): Custom WebSocket server URL. Defaults to None, using platform's default URL.
+        """
+        self.loop = asyncio.new_event_loop()
+        self._client = ExpertOptionsAsync(token, demo, url)
+
+    def __del__(self):
+        self.loop.close()
+
+    def buy(self, asset: str, amount: float, time: int) -> tuple[str, dict]:
+        "Takes the asset, and amount to place a buy trade that will expire in time (in seconds), returns a tuple with the id of the trade and the trade as a dict"
+        return self.loop.run_until_complete(self._client.buy(asset, amount, time))
+
+    def sell(self, asset: str, amount: float, time: int) -> tuple[str, dict]:
+        "Takes the asset, and amount to place a sell trade that will expire in time (in seconds), returns a tuple with the id of the trade and the trade as a dict"
+        return self.loop.run_until_complete(self._client.sell(asset, amount, time))
+
+    def balance(self) -> float:
+        "Returns the balance of the account"
+        return self.loop.run_until_complete(self._client.balance())
+
+    def is_demo(self) -> bool:
+        "Returns True if the client trades on the demo account"
+        return self.loop.run_until_complete(self._client.is_demo())
+
+    def get_profile(self) -> dict:
+        "Returns the profile of the user (uid, name, balance and account type) as a dict"
+        return self.loop.run_until_complete(self._client.get_profile())
+
+    def history(self, asset: str, period: int) -> list[dict]:
+        "Returns a list of dictionaries containing the latest candles of 'period' seconds available for the specified asset."
+        return self.loop.run_until_complete(self._client.history(asset, period))
+
+    def subscribe_symbol(self, asset: str) -> SyncSubscription:
+        """Returns a sync iterator over the associated asset, it will return real time raw candles while the 'ExpertOptions' class is loaded"""
+        return SyncSubscription(
+            self.loop.run_until_complete(self._client._subscribe_symbol_inner(asset))
+        )
@@ -2,6 +2,7 @@ from BinaryOptionsToolsV2.validator import Validator
 from BinaryOptionsToolsV2.config import Config
 from BinaryOptionsToolsV2 import RawPocketOption, Logger
 from datetime import timedelta
+from typing import Optional
 
 
 import asyncio
@@ -272,6 +273,34 @@ class PocketOptionAsync:
             trade = json.loads(trade)
             return trade_id, trade
 
+    async def multi_buy(self, orders: list[tuple[str, int, float]]) -> list[str]:
+        """
+        Places several buy (call) orders concurrently.
+
+        Args:
+            orders (list[tuple[str, int, float]]): Orders as (asset, time, amount), each one is checked
+                by the trade guards on its own
+
+        Returns:
+            list[str]: One JSON string per order, in the order of `orders`. Placed orders contain the
+                trade like `buy`, the others contain the `asset` and an `error` message.
+        """
+        return await self.client.multi_buy(orders)
+
+    async def multi_sell(self, orders: list[tuple[str, int, float]]) -> list[str]:
+        """
+        Places several sell (put) orders concurrently.
+
+        Args:
+            orders (list[tuple[str, int, float]]): Orders as (asset, time, amount), each one is checked
+                by the trade guards on its own
+
+        Returns:
+            list[str]: One JSON string per order, in the order of `orders`. Placed orders contain the
+                trade like `sell`, the others contain the `asset` and an `error` message.
+        """
+        return await self.client.multi_sell(orders)
+
     async def check_win(self, id: str) -> dict:
         """
         Checks the result of a specific trade.
@@ -315,6 +344,76 @@ class PocketOptionAsync:
 
         return await check(id)
 
+    async def cancel_order(self, trade_id: str) -> bool:
+        """
+        Cancels an open trade before its expiry.
+
+        PocketOption has no way to cancel an open trade, so this always raises an error for now.
+
+        Args:
+            trade_id (str): ID of the trade to cancel
+
+        Returns:
+            bool: True if the trade was cancelled
+
+        Raises:
+            ValueError: If trade_id is invalid
+            Exception: The cancellation isn't supported by the platform
+        """
+        return await self.client.cancel_order(trade_id)
+
+    async def check_wins(self, trade_ids: list[str], timeout_secs: float) -> list[dict]:
+        """
+        Checks the results of several trades concurrently.
+
+        Args:
+            trade_ids (list[str]): IDs of the trades to check
+            timeout_secs (float): Maximum time to wait for all the trades to close
+
+        Returns:
+            list[dict]: One entry per trade, in the order of `trade_ids`. Closed trades are returned
+                like `check_win` with the `result` key, the others contain the `id` and an `error` message
+                (e.g. a timeout).
+
+        Raises:
+            ValueError: If a trade_id is invalid
+        """
+        trades = []
+        for trade in await self.client.check_wins(trade_ids, timeout_secs):
+            trade = json.loads(trade)
+            if "error" not in trade:
+                win = trade["profit"]
+                if win > 0:
+                    trade["result"] = "win"
+                elif win == 0:
+                    trade["result"] = "draw"
+                else:
+                    trade["result"] = "loss"
+            trades.append(trade)
+        return trades
+
+    async def check_wins_stream(
+        self, trade_ids: list[str], timeout_secs: float = 300.0
+    ) -> AsyncSubscription:
+        """
+        Streams the results of several trades as they close, instead of waiting for all of them.
+
+        Args:
+            trade_ids (list[str]): IDs of the trades to check
+            timeout_secs (float, optional): Maximum time to wait for all the trades to close. Defaults to 300.
+
+        Returns:
+            AsyncSubscription: Async iterator yielding `[trade_id, trade]` in closing order, it ends once every
+                trade closed or the timeout elapsed. Trades that can't be checked are skipped.
+
+        Raises:
+            ValueError: If a trade_id is invalid
+        """
+        return AsyncSubscription(await self._check_wins_stream_inner(trade_ids, timeout_secs))
+
+    async def _check_wins_stream_inner(self, trade_ids: list[str], timeout_secs: float):
+        return await self.client.check_wins_stream(trade_ids, timeout_secs)
+
     async def get_candles(self, asset: str, period: int, offset: int = 0) -> list[dict]:
         """
         Retrieves historical candle data for an asset using raw 'changeSymbol' command.
@@ -444,6 +543,27 @@ class PocketOptionAsync:
         #     "The get_candles_advanced method is not implemented in the PocketOptionAsync class. "
         # )
 
+    async def get_candles_range(
+        self, asset: str, period: int, from_ts: int, to_ts: int
+    ) -> list[dict]:
+        """
+        Retrieves every candle of an asset between two timestamps.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            period (int): Candle timeframe in seconds (e.g., 60 for 1-minute candles)
+            from_ts (int): Unix timestamp of the start of the range
+            to_ts (int): Unix timestamp of the end of the range
+
+        Returns:
+            list[dict]: List of candles sorted by time, in the same format as `get_candles`
+
+        Note:
+            Large ranges are fetched with several requests, overlapping candles are removed
+        """
+        candles = await self.client.get_candles_range(asset, period, from_ts, to_ts)
+        return json.loads(candles)
+
     async def balance(self) -> float:
         """
         Retrieves current account balance.
@@ -470,6 +590,10 @@ class PocketOptionAsync:
         #     "The closed_deals method is not implemented in the PocketOptionAsync class. "
         # )
 
+    async def export_deals_csv(self, path: str) -> int:
+        "Writes all the closed deals to a CSV file sorted by close time and returns the number of rows written"
+        return await self.client.export_deals_csv(path)
+
     async def clear_closed_deals(self) -> None:
         "Removes all the closed deals from memory, this function doesn't return anything"
         await self.client.clear_closed_deals()
@@ -498,10 +622,103 @@ class PocketOptionAsync:
             return [payout.get(ast) for ast in asset]
         return payout
 
+    async def get_expiry_options(self, asset: str) -> list[int]:
+        """
+        Returns the expiry times accepted by `buy` and `sell` for an asset.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+
+        Returns:
+            list[int]: Expiry times in seconds, sorted
+
+        Raises:
+            ValueError: If the assets aren't loaded yet or the asset is unknown or inactive
+        """
+        return await self.client.get_expiry_options(asset)
+
     async def history(self, asset: str, period: int) -> list[dict]:
         "Returns a list of dictionaries containing the latest data available for the specified asset starting from 'period', the data is in the same format as the returned data of the 'get_candles' function."
         return json.loads(await self.client.history(asset, period))
 
+    async def batch_history(self, requests: list[tuple[str, int]]) -> dict:
+        """
+        Retrieves the history of several assets concurrently.
+
+        Args:
+            requests (list[tuple[str, int]]): List of (asset, period) pairs
+
+        Returns:
+            dict: {"ok": {"{asset}_{period}": list[dict]}, "errors": {"{asset}_{period}": str}}
+                with the candles of the successful requests and the error of the failed ones
+        """
+        return json.loads(await self.client.batch_history(requests))
+
+    async def get_recent_candles_df(self, asset: str, n: int, period: int = 60):
+        """
+        Returns the `n` most recent candles of an asset as a polars DataFrame.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            n (int): Number of candles to return
+            period (int, optional): Candle period in seconds. Defaults to 60.
+
+        Returns:
+            polars.DataFrame | str: DataFrame with the `open_time`, `open`, `high`, `low` and `close` columns,
+                or the same columns as a JSON string if polars isn't installed
+        """
+        candles = (await self.history(asset, period))[-n:] if n > 0 else []
+        columns = {
+            "open_time": [candle["timestamp"] for candle in candles],
+            "open": [candle["open"] for candle in candles],
+            "high": [candle["high"] for candle in candles],
+            "low": [candle["low"] for candle in candles],
+            "close": [candle["close"] for candle in candles],
+        }
+        try:
+            import polars as pl
+        except ImportError:
+            return json.dumps(columns)
+        return pl.DataFrame(columns)
+
+    async def payout_stream(self) -> AsyncSubscription:
+        """
+        Creates a stream of payout updates.
+
+        Returns:
+            AsyncSubscription: Async iterator yielding a dict of asset | payout every time the server changes any payout
+
+        Example:
+            ```python
+            async for payouts in await client.payout_stream():
+                print(payouts.get("EURUSD_otc"))
+            ```
+        """
+        return AsyncSubscription(await self._payout_stream_inner())
+
+    async def _payout_stream_inner(self):
+        return await self.client.payout_stream()
+
+    async def deal_events(self) -> AsyncSubscription:
+        """
+        Creates a stream of the deals as they are opened and closed.
+
+        Returns:
+            AsyncSubscription: Async iterator yielding `{"Opened": [id, deal]}` or `{"Closed": [id, deal]}` every time a deal changes state
+
+        Example:
+            ```python
+            async for event in await client.deal_events():
+                if "Closed" in event:
+                    trade_id, deal = event["Closed"]
+                    print(trade_id, deal["profit"])
+            ```
+        """
+        return AsyncSubscription(await self._deal_events_inner())
+
+    async def _deal_events_inner(self):
+        return await self.client.deal_events()
+
     async def _subscribe_symbol_inner(self, asset: str):
         return await self.client.subscribe_symbol(asset)
 
@@ -533,6 +750,34 @@ class PocketOptionAsync:
         """
         return AsyncSubscription(await self._subscribe_symbol_inner(asset))
 
+    async def subscribe_with_history(
+        self, asset: str, history_periods: int
+    ) -> tuple[list[dict], AsyncSubscription]:
+        """
+        Gets the recent candles of an asset and creates a real-time data subscription for it at the same time.
+
+        Args:
+            asset (str): Trading asset to subscribe to
+            history_periods (int): Period of the history candles, same as in `history`
+
+        Returns:
+            tuple[list[dict], AsyncSubscription]: The history candles, oldest first, and an async iterator yielding real-time price updates
+
+        Example:
+            ```python
+            history, subscription = await api.subscribe_with_history("EURUSD_otc", 60)
+            async for update in subscription:
+                print(f"Price update: {update}")
+            ```
+        """
+        history, subscription = await self._subscribe_with_history_inner(
+            asset, history_periods
+        )
+        return json.loads(history), AsyncSubscription(subscription)
+
+    async def _subscribe_with_history_inner(self, asset: str, history_periods: int):
+        return await self.client.subscribe_with_history(asset, history_periods)
+
     async def subscribe_symbol_chuncked(
         self, asset: str, chunck_size: int
     ) -> AsyncSubscription:
@@ -593,6 +838,45 @@ class PocketOptionAsync:
         """Returns the current server time as a UNIX timestamp"""
         return await self.client.get_server_time()
 
+    async def get_server_time_stream(self) -> AsyncSubscription:
+        """
+        Creates a stream of the server time.
+
+        Returns:
+            AsyncSubscription: Async iterator yielding the server time as a UNIX timestamp (float) every time a price update moves it forward, the timestamps never decrease
+
+        Example:
+            ```python
+            async for timestamp in await client.get_server_time_stream():
+                print(datetime.fromtimestamp(timestamp, tz=timezone.utc))
+            ```
+        """
+        return AsyncSubscription(await self._get_server_time_stream_inner())
+
+    async def _get_server_time_stream_inner(self):
+        return await self.client.get_server_time_stream()
+
+    async def get_server_time_poll(self, interval_ms: int) -> AsyncSubscription:
+        """
+        Creates a stream of the server time emitted on a fixed schedule.
+
+        Args:
+            interval_ms (int): Time between two updates in milliseconds
+
+        Returns:
+            AsyncSubscription: Async iterator yielding the server time as a UNIX timestamp (float) every `interval_ms`, even if no price update arrived, it ends when the client disconnects
+
+        Example:
+            ```python
+            async for timestamp in await client.get_server_time_poll(1000):
+                print(datetime.fromtimestamp(timestamp, tz=timezone.utc))
+            ```
+        """
+        return AsyncSubscription(await self._get_server_time_poll_inner(interval_ms))
+
+    async def _get_server_time_poll_inner(self, interval_ms: int):
+        return await self.client.get_server_time_poll(interval_ms)
+
     def is_demo(self) -> bool:
         """
         Checks if the current account is a demo account.
@@ -623,6 +907,86 @@ class PocketOptionAsync:
         """
         return self.client.is_demo()
 
+    def get_connection_info(self) -> dict:
+        """
+        Returns basic diagnostics about the current connection.
+
+        Returns:
+            dict: {"region": str | None, "connected_since": str | None, "avg_round_trip_ms": float | None}
+        """
+        return json.loads(self.client.get_connection_info())
+
+    def get_payout_history(self, asset: str, hours: int) -> str:
+        """
+        Returns the payout changes of an asset as a JSON string.
+
+        Only the changes of the last 24 hours are kept.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            hours (int): How many hours back to look
+
+        Returns:
+            str: JSON list of {"time": str, "payout": int}, oldest first
+        """
+        return self.client.get_payout_history(asset, hours)
+
+    def get_correlation_matrix(self) -> str:
+        """
+        Returns the correlation of the recent closes of every pair of subscribed assets as a JSON string.
+
+        Returns:
+            str: JSON object {"assets": list[str], "matrix": list[list[float | None]]} where `matrix[i][j]`
+            is the correlation of `assets[i]` and `assets[j]`, None when they don't share enough candles
+        """
+        return self.client.get_correlation_matrix()
+
+    def get_win_rate_stats(self) -> dict:
+        """
+        Returns the win rate statistics of the deals closed since the client was created.
+
+        Draws aren't counted.
+
+        Returns:
+            dict: {"wins": int, "losses": int, "win_rate": float, "profit_factor": float | None,
+            "total_profit": float, "total_loss": float, "consecutive_wins": int,
+            "consecutive_losses": int}, `profit_factor` is None when there are wins but no loss
+        """
+        return json.loads(self.client.get_win_rate_stats())
+
+    def get_candle_stream_stats(self, symbol: str) -> Optional[dict]:
+        """
+        Returns the tick statistics of a subscribed asset.
+
+        Args:
+            symbol (str): Asset symbol, e.g. "EURUSD_otc"
+
+        Returns:
+            dict | None: {"asset": str, "tick_rate_per_second": float, "candle_count": int, "gap_count": int,
+            "last_candle_ts": str | None}, None when the asset isn't subscribed
+        """
+        return json.loads(self.client.get_candle_stream_stats(symbol))
+
+    async def get_stats_json(self) -> str:
+        """
+        Returns the connection statistics as a JSON string.
+
+        The statistics of several processes can be aggregated by loading each string with `json.loads`.
+
+        Returns:
+            str: JSON object with the connection attempts, disconnections, message and byte counters, rates and uptime
+        """
+        return await self.client.get_stats_json()
+
+    async def health_check(self) -> dict:
+        """
+        Returns the health of the client in a single call.
+
+        Returns:
+            dict: {"is_connected": bool, "active_subscriptions": int, "server_time_drift_ms": int, "last_error": str | None, "balance": float}
+        """
+        return json.loads(await self.client.health_check())
+
     async def disconnect(self) -> None:
         """
         Disconnects the client while keeping the configuration intact.
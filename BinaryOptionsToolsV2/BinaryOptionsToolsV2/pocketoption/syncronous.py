@@ -2,6 +2,7 @@ from .asyncronous import PocketOptionAsync
 from BinaryOptionsToolsV2.config import Config
 from BinaryOptionsToolsV2.validator import Validator
 from datetime import timedelta
+from typing import Optional
 
 import asyncio
 import json
@@ -240,10 +241,38 @@ class PocketOption:
             self._client.sell(asset, amount, time, check_win)
         )
 
+    def multi_buy(self, orders: list[tuple[str, int, float]]) -> list[str]:
+        """Places several buy orders concurrently, each order is (asset, time, amount). Returns one JSON string per order in the same order, either the trade or the asset with an "error" message"""
+        return self.loop.run_until_complete(self._client.multi_buy(orders))
+
+    def multi_sell(self, orders: list[tuple[str, int, float]]) -> list[str]:
+        """Places several sell orders concurrently, each order is (asset, time, amount). Returns one JSON string per order in the same order, either the trade or the asset with an "error" message"""
+        return self.loop.run_until_complete(self._client.multi_sell(orders))
+
     def check_win(self, id: str) -> dict:
         """Returns a dictionary containing the trade data and the result of the trade ("win", "draw", "loss)"""
         return self.loop.run_until_complete(self._client.check_win(id))
 
+    def cancel_order(self, trade_id: str) -> bool:
+        """Cancels an open trade before its expiry. PocketOption has no way to cancel an open trade, so this always raises an error for now"""
+        return self.loop.run_until_complete(self._client.cancel_order(trade_id))
+
+    def check_wins(self, trade_ids: list[str], timeout_secs: float) -> list[dict]:
+        """Checks the results of several trades concurrently, returns one dict per trade in the same order, either the trade data with its result or the id with an "error" message"""
+        return self.loop.run_until_complete(
+            self._client.check_wins(trade_ids, timeout_secs)
+        )
+
+    def check_wins_stream(
+        self, trade_ids: list[str], timeout_secs: float = 300.0
+    ) -> SyncSubscription:
+        "Returns a sync iterator yielding [trade_id, trade] as the trades close, it ends once every trade closed or the timeout elapsed"
+        return SyncSubscription(
+            self.loop.run_until_complete(
+                self._client._check_wins_stream_inner(trade_ids, timeout_secs)
+            )
+        )
+
     def get_candles(self, asset: str, period: int, offset: int) -> list[dict]:
         """
         Takes the asset you want to get the candles and return a list of raw candles in dictionary format
@@ -287,6 +316,25 @@ class PocketOption:
             self._client.get_candles_advanced(asset, period, offset, time)
         )
 
+    def get_candles_range(
+        self, asset: str, period: int, from_ts: int, to_ts: int
+    ) -> list[dict]:
+        """
+        Retrieves every candle of an asset between two timestamps.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            period (int): Candle timeframe in seconds (e.g., 60 for 1-minute candles)
+            from_ts (int): Unix timestamp of the start of the range
+            to_ts (int): Unix timestamp of the end of the range
+
+        Returns:
+            list[dict]: List of candles sorted by time, in the same format as `get_candles`
+        """
+        return self.loop.run_until_complete(
+            self._client.get_candles_range(asset, period, from_ts, to_ts)
+        )
+
     def balance(self) -> float:
         "Returns the balance of the account"
         return self.loop.run_until_complete(self._client.balance())
@@ -299,6 +347,10 @@ class PocketOption:
         "Returns a list of all the closed deals as dictionaries"
         return self.loop.run_until_complete(self._client.closed_deals())
 
+    def export_deals_csv(self, path: str) -> int:
+        "Writes all the closed deals to a CSV file sorted by close time and returns the number of rows written"
+        return self.loop.run_until_complete(self._client.export_deals_csv(path))
+
     def clear_closed_deals(self) -> None:
         "Removes all the closed deals from memory, this function doesn't return anything"
         self.loop.run_until_complete(self._client.clear_closed_deals())
@@ -307,16 +359,51 @@ class PocketOption:
         "Returns a dict of asset | payout for each asset, if 'asset' is not None then it will return the payout of the asset or a list of the payouts for each asset it was passed"
         return self.loop.run_until_complete(self._client.payout(asset))
 
+    def get_expiry_options(self, asset: str) -> list[int]:
+        "Returns the sorted expiry times in seconds accepted by 'buy' and 'sell' for the asset"
+        return self.loop.run_until_complete(self._client.get_expiry_options(asset))
+
+    def payout_stream(self) -> SyncSubscription:
+        "Returns a sync iterator yielding a dict of asset | payout every time the server changes any payout"
+        return SyncSubscription(
+            self.loop.run_until_complete(self._client._payout_stream_inner())
+        )
+
+    def deal_events(self) -> SyncSubscription:
+        "Returns a sync iterator yielding a dict with an 'Opened' or 'Closed' key holding [id, deal] every time a deal is opened or closed"
+        return SyncSubscription(
+            self.loop.run_until_complete(self._client._deal_events_inner())
+        )
+
     def history(self, asset: str, period: int) -> list[dict]:
         "Returns a list of dictionaries containing the latest data available for the specified asset starting from 'period', the data is in the same format as the returned data of the 'get_candles' function."
         return self.loop.run_until_complete(self._client.history(asset, period))
 
+    def batch_history(self, requests: list[tuple[str, int]]) -> dict:
+        "Returns the history of several (asset, period) pairs fetched concurrently, as a dict with the candles under 'ok' and the errors under 'errors', keyed by '{asset}_{period}'."
+        return self.loop.run_until_complete(self._client.batch_history(requests))
+
+    def get_recent_candles_df(self, asset: str, n: int, period: int = 60):
+        "Returns the 'n' most recent candles of 'period' seconds of the asset as a polars DataFrame, or as a JSON string of the columns if polars isn't installed."
+        return self.loop.run_until_complete(
+            self._client.get_recent_candles_df(asset, n, period)
+        )
+
     def subscribe_symbol(self, asset: str) -> SyncSubscription:
         """Returns a sync iterator over the associated asset, it will return real time raw candles and will return new candles while the 'PocketOption' class is loaded if the class is droped then the iterator will fail"""
         return SyncSubscription(
             self.loop.run_until_complete(self._client._subscribe_symbol_inner(asset))
         )
 
+    def subscribe_with_history(
+        self, asset: str, history_periods: int
+    ) -> tuple[list[dict], SyncSubscription]:
+        """Returns the recent candles of the asset, oldest first, and a sync iterator over its real time raw candles, both requested at the same time"""
+        history, subscription = self.loop.run_until_complete(
+            self._client._subscribe_with_history_inner(asset, history_periods)
+        )
+        return json.loads(history), SyncSubscription(subscription)
+
     def subscribe_symbol_chuncked(
         self, asset: str, chunck_size: int
     ) -> SyncSubscription:
@@ -355,6 +442,48 @@ class PocketOption:
         """Returns the current server time as a UNIX timestamp"""
         return self.loop.run_until_complete(self._client.get_server_time())
 
+    def get_server_time_stream(self) -> SyncSubscription:
+        "Returns a sync iterator yielding the server time as a UNIX timestamp (float) every time a price update moves it forward, the timestamps never decrease"
+        return SyncSubscription(
+            self.loop.run_until_complete(self._client._get_server_time_stream_inner())
+        )
+
+    def get_server_time_poll(self, interval_ms: int) -> SyncSubscription:
+        "Returns a sync iterator yielding the server time as a UNIX timestamp (float) every `interval_ms` milliseconds, even if no price update arrived, it ends when the client disconnects"
+        return SyncSubscription(
+            self.loop.run_until_complete(
+                self._client._get_server_time_poll_inner(interval_ms)
+            )
+        )
+
+    def get_connection_info(self) -> dict:
+        "Returns a dict with the server region, the connection start time and the average keep-alive round trip in milliseconds"
+        return self._client.get_connection_info()
+
+    def get_payout_history(self, asset: str, hours: int) -> str:
+        "Returns a JSON list of {'time', 'payout'} with the payout changes of the asset during the last hours, oldest first. Only the last 24 hours are kept"
+        return self._client.get_payout_history(asset, hours)
+
+    def get_correlation_matrix(self) -> str:
+        "Returns a JSON object with the subscribed 'assets' and the square 'matrix' of their correlations, null when two assets don't share enough candles"
+        return self._client.get_correlation_matrix()
+
+    def get_win_rate_stats(self) -> dict:
+        "Returns a dict with the wins, losses, win rate, profit factor and streaks of the deals closed since the client was created"
+        return self._client.get_win_rate_stats()
+
+    def get_candle_stream_stats(self, symbol: str) -> Optional[dict]:
+        "Returns a dict with the tick rate, candle count, gap count and last candle time of a subscribed asset, None if it isn't subscribed"
+        return self._client.get_candle_stream_stats(symbol)
+
+    def get_stats_json(self) -> str:
+        "Returns the connection statistics (attempts, disconnections, message and byte counters, rates and uptime) as a JSON string"
+        return self.loop.run_until_complete(self._client.get_stats_json())
+
+    def health_check(self) -> dict:
+        "Returns a dict with the connection state, active subscriptions, server time drift, last error and balance of the client"
+        return self.loop.run_until_complete(self._client.health_check())
+
     def is_demo(self) -> bool:
         """
         Checks if the current account is a demo account.
@@ -1,4 +1,7 @@
-use binary_options_tools::{error::BinaryOptionsError, pocketoption::error::PocketError};
+use binary_options_tools::{
+    error::BinaryOptionsError, expertoptions::error::ExpertOptionsError,
+    pocketoption::error::PocketError,
+};
 use pyo3::{PyErr, exceptions::PyValueError};
 use thiserror::Error;
 use uuid::Uuid;
@@ -9,6 +12,8 @@ pub enum BinaryErrorPy {
     BinaryOptionsError(Box<BinaryOptionsError>),
     #[error("PocketOptionError, {0}")]
     PocketOptionError(Box<PocketError>),
+    #[error("ExpertOptionsError, {0}")]
+    ExpertOptionsError(Box<ExpertOptionsError>),
 
     #[error("Uninitialized, {0}")]
     Uninitialized(String),
@@ -43,3 +48,9 @@ impl From<PocketError> for BinaryErrorPy {
         BinaryErrorPy::PocketOptionError(Box::new(value))
     }
 }
+
+impl From<ExpertOptionsError> for BinaryErrorPy {
+    fn from(value: ExpertOptionsError) -> Self {
+        BinaryErrorPy::ExpertOptionsError(Box::new(value))
+    }
+}
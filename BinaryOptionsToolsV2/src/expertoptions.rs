@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use binary_options_tools::expertoptions::client::ExpertOptions;
+use binary_options_tools::expertoptions::modules::trades::ExpertDeal;
+use futures_util::StreamExt;
+use pyo3::{Bound, IntoPyObjectExt, PyAny, PyResult, Python, pyclass, pymethods};
+use pyo3_async_runtimes::tokio::future_into_py;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::error::BinaryErrorPy;
+use crate::pocketoption::StreamIterator;
+use crate::runtime::get_runtime;
+
+/// Returns the id of the trade and the deal as a JSON string.
+fn trade_result(trade: (Uuid, ExpertDeal)) -> Result<Vec<String>, BinaryErrorPy> {
+    let deal = serde_json::to_string(&trade.1)?;
+    Ok(vec![trade.0.to_string(), deal])
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct RawExpertOptions {
+    client: ExpertOptions,
+}
+
+#[pymethods]
+impl RawExpertOptions {
+    #[new]
+    #[pyo3(signature = (token, demo = true))]
+    pub fn new(token: String, demo: bool, py: Python<'_>) -> PyResult<Self> {
+        let runtime = get_runtime(py)?;
+        runtime.block_on(async move {
+            let client = ExpertOptions::new(token, demo)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(Self { client })
+        })
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (token, url, demo = true))]
+    pub fn new_with_url(py: Python<'_>, token: String, url: String, demo: bool) -> PyResult<Self> {
+        let runtime = get_runtime(py)?;
+        runtime.block_on(async move {
+            let client = ExpertOptions::new_with_url(token, demo, url)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(Self { client })
+        })
+    }
+
+    pub async fn is_demo(&self) -> bool {
+        self.client.is_demo().await
+    }
+
+    pub fn buy<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        amount: f64,
+        time: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let trade = client
+                .buy(asset, amount, time)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            let result = trade_result(trade)?;
+            Python::attach(|py| result.into_py_any(py))
+        })
+    }
+
+    pub fn sell<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        amount: f64,
+        time: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let trade = client
+                .sell(asset, amount, time)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            let result = trade_result(trade)?;
+            Python::attach(|py| result.into_py_any(py))
+        })
+    }
+
+    pub async fn balance(&self) -> f64 {
+        self.client.balance().await
+    }
+
+    pub fn get_profile<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let profile = client.profile().await.map_err(BinaryErrorPy::from)?;
+            Ok(serde_json::to_string(&profile).map_err(BinaryErrorPy::from)?)
+        })
+    }
+
+    pub fn history<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let candles = client
+                .history(asset, period)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(serde_json::to_string(&candles).map_err(BinaryErrorPy::from)?)
+        })
+    }
+
+    pub fn subscribe_symbol<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            // A period of 0 yields every price update, like `SubscriptionType::none`
+            let subscription = client
+                .subscribe(symbol, 0)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+
+            let boxed_stream = subscription.to_stream().boxed().fuse();
+            let stream = Arc::new(Mutex::new(boxed_stream));
+
+            Python::attach(|py| StreamIterator { stream }.into_py_any(py))
+        })
+    }
+}
@@ -2,6 +2,7 @@
 
 mod config;
 mod error;
+mod expertoptions;
 mod logs;
 mod pocketoption;
 mod runtime;
@@ -9,6 +10,7 @@ mod stream;
 mod validator;
 
 // use config::PyConfig;
+use expertoptions::RawExpertOptions;
 use logs::{LogBuilder, Logger, StreamLogsIterator, StreamLogsLayer, start_tracing};
 use pocketoption::{RawPocketOption, RawStreamIterator, StreamIterator};
 use pyo3::prelude::*;
@@ -22,6 +24,7 @@ fn BinaryOptionsTools(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<StreamLogsIterator>()?;
     m.add_class::<StreamLogsLayer>()?;
     m.add_class::<RawPocketOption>()?;
+    m.add_class::<RawExpertOptions>()?;
     m.add_class::<Logger>()?;
     m.add_class::<LogBuilder>()?;
     m.add_class::<StreamIterator>()?;
@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::str;
 use std::sync::Arc;
 use std::time::Duration;
@@ -6,6 +5,7 @@ use std::time::Duration;
 use binary_options_tools::pocketoption::candle::{Candle, SubscriptionType};
 use binary_options_tools::pocketoption::error::PocketResult;
 use binary_options_tools::pocketoption::pocket_client::PocketOption;
+use binary_options_tools::pocketoption::types::Deal;
 // use binary_options_tools::pocketoption::types::base::RawWebsocketMessage;
 // use binary_options_tools::pocketoption::types::update::DataCandle;
 // use binary_options_tools::pocketoption::ws::stream::StreamAsset;
@@ -13,6 +13,7 @@ use binary_options_tools::pocketoption::pocket_client::PocketOption;
 use async_stream;
 use binary_options_tools::validator::Validator as CrateValidator;
 use binary_options_tools::validator::Validator;
+use chrono::DateTime;
 use futures_util::StreamExt;
 use futures_util::stream::{BoxStream, Fuse};
 use pyo3::{Bound, IntoPyObjectExt, Py, PyAny, PyResult, Python, pyclass, pymethods};
@@ -40,6 +41,24 @@ fn arc_message_to_string(msg: &std::sync::Arc<tungstenite::Message>) -> String {
     message_to_string(msg.as_ref())
 }
 
+/// Serializes the results of `multi_buy`/`multi_sell`, the failed orders contain the asset and an `error` message.
+fn multi_trade_json(
+    orders: &[(String, u32, f64)],
+    results: Vec<PocketResult<(Uuid, Deal)>>,
+) -> Result<Vec<String>, BinaryErrorPy> {
+    orders
+        .iter()
+        .zip(results)
+        .map(|((asset, _, _), result)| match result {
+            Ok((_, deal)) => serde_json::to_string(&deal),
+            Err(e) => serde_json::to_string(
+                &serde_json::json!({ "asset": asset, "error": e.to_string() }),
+            ),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(BinaryErrorPy::from)
+}
+
 /// Send a raw message and wait for the response
 async fn send_raw_message_and_wait(
     client: &PocketOption,
@@ -73,7 +92,7 @@ pub struct RawPocketOption {
 
 #[pyclass]
 pub struct StreamIterator {
-    stream: Arc<Mutex<Fuse<BoxStream<'static, PocketResult<Candle>>>>>,
+    pub(crate) stream: Arc<Mutex<Fuse<BoxStream<'static, PocketResult<Candle>>>>>,
 }
 
 #[pyclass]
@@ -206,10 +225,65 @@ impl RawPocketOption {
         })
     }
 
+    /// Creates a client with the SSID read from the `POCKET_OPTION_SSID` environment variable.
+    #[staticmethod]
+    pub fn from_env(py: Python<'_>) -> PyResult<Self> {
+        let runtime = get_runtime(py)?;
+        runtime.block_on(async move {
+            let client = PocketOption::new_from_env()
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(Self { client })
+        })
+    }
+
     pub fn is_demo(&self) -> bool {
         self.client.is_demo()
     }
 
+    pub fn get_connection_info(&self) -> PyResult<String> {
+        let info = self.client.connection_info();
+        Ok(serde_json::to_string(&info).map_err(BinaryErrorPy::from)?)
+    }
+
+    pub fn get_payout_history(&self, asset: String, hours: u32) -> PyResult<String> {
+        let history: Vec<_> = self
+            .client
+            .payout_history(&asset, hours)
+            .into_iter()
+            .map(|(time, payout)| serde_json::json!({ "time": time, "payout": payout }))
+            .collect();
+        Ok(serde_json::to_string(&history).map_err(BinaryErrorPy::from)?)
+    }
+
+    pub fn get_correlation_matrix(&self) -> PyResult<String> {
+        let matrix = self.client.correlation_matrix();
+        Ok(serde_json::to_string(&matrix).map_err(BinaryErrorPy::from)?)
+    }
+
+    pub fn get_win_rate_stats(&self) -> PyResult<String> {
+        let stats = self.client.win_rate_stats();
+        Ok(serde_json::to_string(&stats).map_err(BinaryErrorPy::from)?)
+    }
+
+    pub fn get_candle_stream_stats(&self, symbol: String) -> PyResult<String> {
+        let stats = self.client.candle_stream_stats(&symbol);
+        Ok(serde_json::to_string(&stats).map_err(BinaryErrorPy::from)?)
+    }
+
+    pub fn get_stats_json<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move { Ok(client.get_stats_json().await) })
+    }
+
+    pub fn health_check<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let status = client.health_check().await;
+            Ok(serde_json::to_string(&status).map_err(BinaryErrorPy::from)?)
+        })
+    }
+
     pub fn buy<'py>(
         &self,
         py: Python<'py>,
@@ -248,6 +322,30 @@ impl RawPocketOption {
         })
     }
 
+    pub fn multi_buy<'py>(
+        &self,
+        py: Python<'py>,
+        orders: Vec<(String, u32, f64)>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let results = multi_trade_json(&orders, client.multi_buy(orders.clone()).await)?;
+            Python::attach(|py| results.into_py_any(py))
+        })
+    }
+
+    pub fn multi_sell<'py>(
+        &self,
+        py: Python<'py>,
+        orders: Vec<(String, u32, f64)>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let results = multi_trade_json(&orders, client.multi_sell(orders.clone()).await)?;
+            Python::attach(|py| results.into_py_any(py))
+        })
+    }
+
     pub fn check_win<'py>(&self, py: Python<'py>, trade_id: String) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
         future_into_py(py, async move {
@@ -263,6 +361,80 @@ impl RawPocketOption {
         })
     }
 
+    pub fn cancel_order<'py>(
+        &self,
+        py: Python<'py>,
+        trade_id: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let uuid = Uuid::parse_str(&trade_id).map_err(BinaryErrorPy::from)?;
+            let cancelled = client
+                .cancel_order(uuid)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(cancelled)
+        })
+    }
+
+    pub fn check_wins<'py>(
+        &self,
+        py: Python<'py>,
+        trade_ids: Vec<String>,
+        timeout_secs: f64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let ids = trade_ids
+                .iter()
+                .map(|id| Uuid::parse_str(id))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(BinaryErrorPy::from)?;
+            let timeout = Duration::try_from_secs_f64(timeout_secs)
+                .map_err(|e| BinaryErrorPy::NotAllowed(format!("Invalid timeout: {e}")))?;
+            let results = client.result_many(ids.clone(), timeout).await;
+            let results = ids
+                .into_iter()
+                .zip(results)
+                .map(|(id, result)| match result {
+                    Ok(deal) => serde_json::to_string(&deal),
+                    Err(e) => serde_json::to_string(
+                        &serde_json::json!({ "id": id, "error": e.to_string() }),
+                    ),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(BinaryErrorPy::from)?;
+            Python::attach(|py| results.into_py_any(py))
+        })
+    }
+
+    /// Returns an iterator over `[id, deal]` JSON arrays, in the order the trades close.
+    pub fn check_wins_stream<'py>(
+        &self,
+        py: Python<'py>,
+        trade_ids: Vec<String>,
+        timeout_secs: f64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let ids = trade_ids
+                .iter()
+                .map(|id| Uuid::parse_str(id))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(BinaryErrorPy::from)?;
+            let timeout = Duration::try_from_secs_f64(timeout_secs)
+                .map_err(|e| BinaryErrorPy::NotAllowed(format!("Invalid timeout: {e}")))?;
+            let boxed_stream = client
+                .result_stream(ids, timeout)
+                .map(|result| Ok(serde_json::to_string(&result).unwrap_or_default()))
+                .boxed()
+                .fuse();
+            let stream = Arc::new(Mutex::new(boxed_stream));
+
+            Python::attach(|py| RawStreamIterator { stream }.into_py_any(py))
+        })
+    }
+
     pub fn get_deal_end_time<'py>(
         &self,
         py: Python<'py>,
@@ -332,6 +504,34 @@ impl RawPocketOption {
         })
     }
 
+    pub fn get_candles_range<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+        from_ts: i64,
+        to_ts: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let from = DateTime::from_timestamp(from_ts, 0).ok_or_else(|| {
+                BinaryErrorPy::NotAllowed(format!("Invalid timestamp: {from_ts}"))
+            })?;
+            let to = DateTime::from_timestamp(to_ts, 0).ok_or_else(|| {
+                BinaryErrorPy::NotAllowed(format!("Invalid timestamp: {to_ts}"))
+            })?;
+            let res = client
+                .get_candles_range(asset, period, from, to)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::attach(|py| {
+                serde_json::to_string(&res)
+                    .map_err(BinaryErrorPy::from)?
+                    .into_py_any(py)
+            })
+        })
+    }
+
     pub async fn balance(&self) -> f64 {
         self.client.balance().await
     }
@@ -348,6 +548,17 @@ impl RawPocketOption {
         })
     }
 
+    pub fn export_deals_csv<'py>(&self, py: Python<'py>, path: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let rows = client
+                .export_deals_csv(std::path::Path::new(&path))
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(rows)
+        })
+    }
+
     pub fn clear_closed_deals<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
         future_into_py(py, async move {
@@ -365,17 +576,20 @@ impl RawPocketOption {
         // Work in progress - this feature is not yet implemented in the new API
         match self.client.assets().await {
             Some(assets) => {
-                let payouts: HashMap<&String, i32> = assets
-                    .0
-                    .iter()
-                    .filter_map(|(asset, symbol)| if symbol.is_active { Some((asset, symbol.payout)) } else { None })
-                    .collect();
-                Ok(serde_json::to_string(&payouts).map_err(BinaryErrorPy::from)?)
+                Ok(serde_json::to_string(&assets.payouts()).map_err(BinaryErrorPy::from)?)
             }
             None => Err(BinaryErrorPy::Uninitialized("Assets not initialized yet.".into()).into()),
         }
     }
 
+    pub async fn get_expiry_options(&self, asset: String) -> PyResult<Vec<u32>> {
+        Ok(self
+            .client
+            .get_asset_expiry_options(&asset)
+            .await
+            .map_err(BinaryErrorPy::from)?)
+    }
+
     pub fn history<'py>(
         &self,
         py: Python<'py>,
@@ -397,6 +611,95 @@ impl RawPocketOption {
         })
     }
 
+    /// Returns a JSON object with the candles of the successful requests under `ok` and the
+    /// error message of the failed ones under `errors`, both keyed by `"{asset}_{period}"`.
+    pub fn batch_history<'py>(
+        &self,
+        py: Python<'py>,
+        requests: Vec<(String, u32)>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let res = client
+                .batch_history(requests)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            let errors: std::collections::HashMap<String, String> = res
+                .errors
+                .into_iter()
+                .map(|(key, e)| (key, e.to_string()))
+                .collect();
+            let json = serde_json::json!({ "ok": res.ok, "errors": errors });
+            Python::attach(|py| {
+                serde_json::to_string(&json)
+                    .map_err(BinaryErrorPy::from)?
+                    .into_py_any(py)
+            })
+        })
+    }
+
+    pub fn payout_stream<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let boxed_stream = client
+                .payout_stream()
+                .map(|payouts| Ok(serde_json::to_string(&payouts).unwrap_or_default()))
+                .boxed()
+                .fuse();
+            let stream = Arc::new(Mutex::new(boxed_stream));
+
+            Python::attach(|py| RawStreamIterator { stream }.into_py_any(py))
+        })
+    }
+
+    pub fn deal_events<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let boxed_stream = client
+                .deal_events()
+                .await
+                .map_err(BinaryErrorPy::from)?
+                .map(|event| Ok(serde_json::to_string(&event).unwrap_or_default()))
+                .boxed()
+                .fuse();
+            let stream = Arc::new(Mutex::new(boxed_stream));
+
+            Python::attach(|py| RawStreamIterator { stream }.into_py_any(py))
+        })
+    }
+
+    pub fn get_server_time_stream<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let boxed_stream = client
+                .watch_server_time()
+                .map(|time| Ok((time.timestamp_millis() as f64 / 1000.0).to_string()))
+                .boxed()
+                .fuse();
+            let stream = Arc::new(Mutex::new(boxed_stream));
+
+            Python::attach(|py| RawStreamIterator { stream }.into_py_any(py))
+        })
+    }
+
+    pub fn get_server_time_poll<'py>(
+        &self,
+        py: Python<'py>,
+        interval_ms: u64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let boxed_stream = client
+                .server_time_poll(Duration::from_millis(interval_ms))
+                .map(|time| Ok((time.timestamp_millis() as f64 / 1000.0).to_string()))
+                .boxed()
+                .fuse();
+            let stream = Arc::new(Mutex::new(boxed_stream));
+
+            Python::attach(|py| RawStreamIterator { stream }.into_py_any(py))
+        })
+    }
+
     pub fn subscribe_symbol<'py>(
         &self,
         py: Python<'py>,
@@ -416,6 +719,27 @@ impl RawPocketOption {
         })
     }
 
+    pub fn subscribe_with_history<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: String,
+        history_periods: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let (history, subscription) = client
+                .subscribe_with_initial_history(symbol, SubscriptionType::none(), history_periods)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            let history = serde_json::to_string(&history).map_err(BinaryErrorPy::from)?;
+
+            let boxed_stream = subscription.to_stream().boxed().fuse();
+            let stream = Arc::new(Mutex::new(boxed_stream));
+
+            Python::attach(|py| (history, StreamIterator { stream }).into_py_any(py))
+        })
+    }
+
     pub fn subscribe_symbol_chuncked<'py>(
         &self,
         py: Python<'py>,
@@ -780,3 +1104,4 @@ impl RawStreamIterator {
         })
     }
 }
+
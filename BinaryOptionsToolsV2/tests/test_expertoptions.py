@@ -0,0 +1,18 @@
+import asyncio
+
+from BinaryOptionsToolsV2.BinaryOptionsToolsV2.expertoptions.asyncronous import (
+    ExpertOptionsAsync,
+)
+
+
+async def main(token):
+    api = ExpertOptionsAsync(token)
+    await asyncio.sleep(5)
+    balance = await api.balance()
+    assert isinstance(balance, float), f"Balance should be a float, got {type(balance)}"
+    print(f"Balance: {balance}")
+
+
+if __name__ == "__main__":
+    token = input("Write your token: ")
+    asyncio.run(main(token))
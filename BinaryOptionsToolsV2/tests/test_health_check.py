@@ -0,0 +1,27 @@
+import asyncio
+
+from BinaryOptionsToolsV2.BinaryOptionsToolsV2.pocketoption.asyncronous import (
+    PocketOptionAsync,
+)
+
+
+async def main(ssid):
+    api = PocketOptionAsync(ssid)
+    await asyncio.sleep(5)
+    health = await api.health_check()
+    print(health)
+    for key in ["is_connected", "active_subscriptions", "balance", "server_time_drift_ms"]:
+        assert key in health, f"Missing '{key}' in health_check result"
+
+    stream = await api.subscribe_symbol("EURUSD_otc")
+    async for _ in stream:
+        break
+    stats = api.get_candle_stream_stats("EURUSD_otc")
+    print(stats)
+    assert stats is not None and stats["asset"] == "EURUSD_otc"
+    assert api.get_candle_stream_stats("NOT_SUBSCRIBED") is None
+
+
+if __name__ == "__main__":
+    ssid = input("Write your ssid: ")
+    asyncio.run(main(ssid))
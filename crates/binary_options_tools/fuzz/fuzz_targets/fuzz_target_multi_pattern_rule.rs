@@ -0,0 +1,18 @@
+#![no_main]
+
+use binary_options_tools::pocketoption::types::MultiPatternRule;
+use binary_options_tools_core_pre::reimports::Message;
+use binary_options_tools_core_pre::traits::Rule;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let rule = MultiPatternRule::new(vec![
+        r#"451-["updateStream",{"#,
+        r#"451-["updateHistoryNewFast","#,
+    ]);
+    // The text frame may arm the rule, the binary one is matched against it
+    rule.call(&Message::text(String::from_utf8_lossy(data).into_owned()));
+    rule.call(&Message::binary(data.to_vec()));
+    rule.reset();
+    assert!(!rule.call(&Message::binary(data.to_vec())));
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use binary_options_tools::pocketoption::ssid::Ssid;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    // Invalid SSIDs must be reported as errors, never panic
+    if let Ok(ssid) = Ssid::parse(data) {
+        let _ = ssid.to_string();
+    }
+});
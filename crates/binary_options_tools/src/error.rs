@@ -18,6 +18,11 @@ pub enum BinaryOptionsError {
     /// General error with a message
     #[error("General error: {0}")]
     General(String),
+
+    /// Couldn't build a polars DataFrame
+    #[cfg(feature = "polars")]
+    #[error("Polars error: {0}")]
+    Polars(#[from] polars::prelude::PolarsError),
 }
 
 pub type BinaryOptionsResult<T> = Result<T, BinaryOptionsError>;
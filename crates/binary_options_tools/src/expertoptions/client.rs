@@ -6,15 +6,26 @@ use binary_options_tools_core_pre::{
     error::CoreError,
     testing::{TestingWrapper, TestingWrapperBuilder},
 };
+use rust_decimal::prelude::ToPrimitive;
 use tokio::task::JoinHandle;
+use uuid::Uuid;
 
 use crate::{
     expertoptions::{
         connect::ExpertConnect,
         error::{ExpertOptionsError, ExpertOptionsResult},
-        modules::{keep_alive::PongModule, profile::ProfileModule},
+        modules::{
+            balance::BalanceModule,
+            history::HistoryApiModule,
+            keep_alive::PongModule,
+            profile::ProfileModule,
+            subscriptions::{SubscriptionsApiModule, subscription_type},
+            trades::{ExpertDeal, TradeAction, TradesApiModule},
+        },
         state::State,
+        types::{AccountType, UserProfile},
     },
+    pocketoption::{candle::Candle, modules::subscriptions::SubscriptionStream},
     utils::PrintMiddleware,
 };
 
@@ -28,16 +39,38 @@ pub struct ExpertOptions {
 impl ExpertOptions {
     fn builder(token: impl ToString, demo: bool) -> ExpertOptionsResult<ClientBuilder<State>> {
         let state = State::new(token.to_string(), demo);
+        Ok(Self::builder_with_state(state))
+    }
 
-        Ok(ClientBuilder::new(ExpertConnect, state)
+    fn builder_with_state(state: State) -> ClientBuilder<State> {
+        ClientBuilder::new(ExpertConnect, state)
             .with_middleware(Box::new(PrintMiddleware))
             // .with_lightweight_handler(|msg, _, _| Box::pin(print_handler(msg)))
             .with_lightweight_module::<PongModule>()
-            .with_module::<ProfileModule>())
+            .with_lightweight_module::<BalanceModule>()
+            .with_module::<ProfileModule>()
+            .with_module::<TradesApiModule>()
+            .with_module::<HistoryApiModule>()
+            .with_module::<SubscriptionsApiModule>()
     }
 
     pub async fn new(token: impl ToString, demo: bool) -> ExpertOptionsResult<Self> {
         let builder = Self::builder(token, demo)?;
+        Self::start(builder).await
+    }
+
+    /// Creates a new client connecting to `url` instead of the ExpertOptions regions.
+    pub async fn new_with_url(
+        token: impl ToString,
+        demo: bool,
+        url: String,
+    ) -> ExpertOptionsResult<Self> {
+        let mut state = State::new(token.to_string(), demo);
+        state.default_connection_url = Some(url);
+        Self::start(Self::builder_with_state(state)).await
+    }
+
+    async fn start(builder: ClientBuilder<State>) -> ExpertOptionsResult<Self> {
         let (client, mut runner) = builder.build().await?;
 
         let _runner = tokio::spawn(async move { runner.run().await });
@@ -59,6 +92,110 @@ impl ExpertOptions {
         }
     }
 
+    /// Returns the id of `asset`, failing if it isn't a known asset.
+    async fn asset_id(&self, asset: &str) -> ExpertOptionsResult<u32> {
+        self.client
+            .state
+            .assets
+            .read()
+            .await
+            .as_ref()
+            .and_then(|assets| assets.id(asset))
+            .ok_or_else(|| ExpertOptionsError::InvalidAsset(asset.to_string()))
+    }
+
+    /// Opens a trade on `asset` for `amount` that expires after `time` seconds.
+    pub async fn trade(
+        &self,
+        asset: impl ToString,
+        action: TradeAction,
+        amount: f64,
+        time: u32,
+    ) -> ExpertOptionsResult<(Uuid, ExpertDeal)> {
+        let asset_id = self.asset_id(&asset.to_string()).await?;
+        if let Some(handle) = self.client.get_handle::<TradesApiModule>().await {
+            handle.open_trade(asset_id, action, amount, time).await
+        } else {
+            Err(CoreError::ModuleNotFound("TradesApiModule".into()).into())
+        }
+    }
+
+    /// Places a new buy (call) trade.
+    pub async fn buy(
+        &self,
+        asset: impl ToString,
+        amount: f64,
+        time: u32,
+    ) -> ExpertOptionsResult<(Uuid, ExpertDeal)> {
+        self.trade(asset, TradeAction::Call, amount, time).await
+    }
+
+    /// Places a new sell (put) trade.
+    pub async fn sell(
+        &self,
+        asset: impl ToString,
+        amount: f64,
+        time: u32,
+    ) -> ExpertOptionsResult<(Uuid, ExpertDeal)> {
+        self.trade(asset, TradeAction::Put, amount, time).await
+    }
+
+    /// Gets the last `HISTORY_CANDLES` candles of `period` seconds for `asset`.
+    pub async fn history(
+        &self,
+        asset: impl ToString,
+        period: u32,
+    ) -> ExpertOptionsResult<Vec<Candle>> {
+        let asset = asset.to_string();
+        let asset_id = self.asset_id(&asset).await?;
+        if let Some(handle) = self.client.get_handle::<HistoryApiModule>().await {
+            handle.history(asset, asset_id, period).await
+        } else {
+            Err(CoreError::ModuleNotFound("HistoryApiModule".into()).into())
+        }
+    }
+
+    /// Subscribes to the real-time prices of `asset`, the stream yields candles of `period`
+    /// seconds, or every price update if `period` is zero. Up to 5 assets can be subscribed at once.
+    pub async fn subscribe(
+        &self,
+        asset: impl ToString,
+        period: u32,
+    ) -> ExpertOptionsResult<SubscriptionStream> {
+        let asset = asset.to_string();
+        self.asset_id(&asset).await?;
+        if let Some(handle) = self.client.get_handle::<SubscriptionsApiModule>().await {
+            handle.subscribe(asset, subscription_type(period)).await
+        } else {
+            Err(CoreError::ModuleNotFound("SubscriptionsApiModule".into()).into())
+        }
+    }
+
+    /// Returns the balance of the current account, or `-1.0` if it's not known yet.
+    pub async fn balance(&self) -> f64 {
+        self.client
+            .state
+            .get_balance()
+            .await
+            .to_f64()
+            .unwrap_or(-1.0)
+    }
+
+    /// Returns the profile of the user, waiting for the server to send it after connecting.
+    ///
+    /// The balance and the account type follow the current account, they are updated by
+    /// `set_context` and by the balance updates of the server.
+    pub async fn profile(&self) -> ExpertOptionsResult<UserProfile> {
+        let mut profile = self.client.state.wait_for_profile().await;
+        let demo = self.is_demo().await;
+        profile.account_type = AccountType::from_demo(demo);
+        if let Some(balance) = self.client.state.balance.read().await.as_ref() {
+            let balance = if demo { balance.demo } else { balance.real };
+            profile.balance = balance.to_f64().unwrap_or(profile.balance);
+        }
+        Ok(profile)
+    }
+
     /// Checks if the current account is a demo account.
     pub async fn is_demo(&self) -> bool {
         self.client.state.is_demo().await
@@ -104,7 +241,280 @@ impl ExpertOptions {
 mod tests {
     use std::time::Duration;
 
+    use binary_options_tools_core_pre::{reimports::Message, testing::MockWebSocketServer};
+    use serde_json::{Value, json};
+    use tracing::Instrument;
+    use tracing_test::traced_test;
+
     use super::*;
+    use crate::expertoptions::modules::history::HISTORY_CANDLES;
+    use crate::expertoptions::modules::profile::Demo;
+
+    const MOCK_TOKEN: &str = "mocktoken";
+
+    /// Mock server answering the startup messages with a single `EURUSD` asset.
+    async fn mock_server() -> MockWebSocketServer {
+        let server = MockWebSocketServer::bind().await.unwrap();
+        let profile = json!({"action": "multipleAction", "ns": 2, "message": {"actions": [
+            {"action": "assets", "ns": 3, "message": {"assets": [
+                {"id": 142, "symbol": "EURUSD", "name": "EUR/USD", "is_active": 1}
+            ]}}
+        ]}});
+        server
+            .expect_send("multipleAction")
+            .respond_with(Message::binary(profile.to_string().into_bytes()));
+        server
+    }
+
+    /// Connects to the mock server and waits for the assets to be loaded.
+    async fn connect(server: &MockWebSocketServer) -> ExpertOptions {
+        let api = ExpertOptions::new_with_url(MOCK_TOKEN, true, server.url())
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while api.client.state.assets.read().await.is_none() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("Assets not loaded in time");
+        api
+    }
+
+    #[tokio::test]
+    async fn test_mock_buy_wire_format() {
+        let server = mock_server().await;
+        server
+            .expect_send(r#"{"action":"expertOption""#)
+            .reply_with(|msg| {
+                let request: Value = serde_json::from_slice(&msg.clone().into_data()).unwrap();
+                let trade = &request["message"];
+                let response = json!({"action": "expertOption", "ns": request["ns"], "message": {"option": {
+                    "id": 1,
+                    "asset_id": trade["assetid"],
+                    "amount": trade["amount"],
+                    "type": trade["type"],
+                    "strike_time": trade["strike_time"],
+                    "exp_time": trade["expiration_time"],
+                }}});
+                vec![Message::binary(response.to_string().into_bytes())]
+            });
+        let api = connect(&server).await;
+
+        let (_, deal) = api.buy("EURUSD", 10.0, 60).await.unwrap();
+        assert_eq!(deal.asset_id, 142);
+        assert_eq!(deal.action, TradeAction::Call);
+        assert_eq!(deal.exp_time - deal.strike_time, 60);
+
+        let request = server
+            .wait_for_send("expertOption", Duration::from_secs(5))
+            .await
+            .unwrap();
+        let request: Value = serde_json::from_slice(&request.into_data()).unwrap();
+        assert_eq!(request["token"], MOCK_TOKEN);
+        assert_eq!(request["message"]["type"], "call");
+        assert_eq!(request["message"]["assetid"], 142);
+        assert_eq!(request["message"]["amount"], 10.0);
+        assert_eq!(request["message"]["is_demo"], 1);
+        server.verify().unwrap();
+
+        let unknown = api.sell("UNKNOWN", 10.0, 60).await;
+        assert!(matches!(unknown, Err(ExpertOptionsError::InvalidAsset(_))));
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_history() {
+        let server = mock_server().await;
+        server
+            .expect_send(r#"{"action":"assetHistoryCandles""#)
+            .reply_with(|msg| {
+                let request: Value = serde_json::from_slice(&msg.clone().into_data()).unwrap();
+                let response = json!({"action": "assetHistoryCandles", "ns": request["ns"], "message": {"candles": [
+                    [1700000000, 1.1, 1.2, 1.0, 1.15],
+                    [1700000060, 1.15, 1.25, 1.1, 1.2],
+                ]}});
+                vec![Message::binary(response.to_string().into_bytes())]
+            });
+        let api = connect(&server).await;
+
+        let candles = api.history("EURUSD", 60).await.unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].symbol, "EURUSD");
+        assert_eq!(candles[0].timestamp, 1700000000.0);
+        assert_eq!(candles[1].close.to_string(), "1.2");
+
+        let request = server
+            .wait_for_send("assetHistoryCandles", Duration::from_secs(5))
+            .await
+            .unwrap();
+        let request: Value = serde_json::from_slice(&request.into_data()).unwrap();
+        assert_eq!(request["token"], MOCK_TOKEN);
+        assert_eq!(request["message"]["assetid"], 142);
+        assert_eq!(request["message"]["timeframes"], json!([60]));
+        let period = &request["message"]["periods"][0];
+        assert_eq!(
+            period[1].as_i64().unwrap() - period[0].as_i64().unwrap(),
+            60 * HISTORY_CANDLES as i64
+        );
+        server.verify().unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_mock_history_span() {
+        let server = mock_server().await;
+        server
+            .expect_send(r#"{"action":"assetHistoryCandles""#)
+            .reply_with(|msg| {
+                let request: Value = serde_json::from_slice(&msg.clone().into_data()).unwrap();
+                let response = json!({"action": "assetHistoryCandles", "ns": request["ns"], "message": {"candles": []}});
+                vec![Message::binary(response.to_string().into_bytes())]
+            });
+        let api = connect(&server).await;
+
+        api.history("EURUSD", 60)
+            .instrument(tracing::info_span!("caller", request = "history-42"))
+            .await
+            .unwrap();
+        // The module logs from its own task, inside the span of the caller
+        logs_assert(|lines| {
+            lines
+                .iter()
+                .find(|line| line.contains("Requesting the 60s candles of EURUSD"))
+                .ok_or("No log from the history module".to_string())
+                .and_then(
+                    |line| match line.contains("caller{request=\"history-42\"}") {
+                        true => Ok(()),
+                        false => Err(format!("Caller span missing: {line}")),
+                    },
+                )
+        });
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_subscribe() {
+        let server = mock_server().await;
+        server
+            .expect_send(r#"{"action":"subscribeCandles""#)
+            .respond_with(Message::binary(
+                json!({"action": "candles", "message": {"candles": [
+                    {"assetId": 142, "points": [[1700000000.0, 1.1], [1700000000.5, 1.2]]}
+                ]}})
+                .to_string()
+                .into_bytes(),
+            ));
+        let api = connect(&server).await;
+
+        let mut stream = api.subscribe("EURUSD", 0).await.unwrap();
+        assert_eq!(stream.asset(), "EURUSD");
+        for (timestamp, close) in [(1700000000.0, "1.1"), (1700000000.5, "1.2")] {
+            let candle = tokio::time::timeout(Duration::from_secs(5), stream.receive())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(candle.symbol, "EURUSD");
+            assert_eq!(candle.timestamp, timestamp);
+            assert_eq!(candle.close.to_string(), close);
+        }
+
+        let request = server
+            .wait_for_send("subscribeCandles", Duration::from_secs(5))
+            .await
+            .unwrap();
+        let request: Value = serde_json::from_slice(&request.into_data()).unwrap();
+        assert_eq!(request["token"], MOCK_TOKEN);
+        assert_eq!(request["message"]["assetsIds"], json!([142]));
+
+        let duplicate = api.subscribe("EURUSD", 0).await;
+        assert!(matches!(
+            duplicate,
+            Err(ExpertOptionsError::SubscriptionFailed(_))
+        ));
+        let unknown = api.subscribe("UNKNOWN", 60).await;
+        assert!(matches!(unknown, Err(ExpertOptionsError::InvalidAsset(_))));
+
+        drop(stream);
+        server
+            .wait_for_send("unsubscribeCandles", Duration::from_secs(5))
+            .await
+            .unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_balance() {
+        let server = mock_server().await;
+        server
+            .expect_send("balance-update")
+            .respond_with(Message::binary(
+                json!({"action": "balance", "message": {"demo_balance": 9876.5}})
+                    .to_string()
+                    .into_bytes(),
+            ));
+        let api = connect(&server).await;
+        assert_eq!(api.balance().await, -1.0);
+
+        api.client
+            .send_text("balance-update".to_string())
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while api.balance().await != 9876.5 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("Balance not updated in time");
+        // The real balance wasn't sent yet
+        api.client.state.set_demo(Demo::new(false)).await;
+        assert_eq!(api.balance().await, -1.0);
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_profile() {
+        let server = MockWebSocketServer::bind().await.unwrap();
+        let profile = json!({"action": "multipleAction", "ns": 2, "message": {"actions": [
+            {"action": "profile", "ns": 2, "message": {"profile": {
+                "id": 125792466,
+                "name": "Jane",
+                "surname": "Doe",
+                "is_demo": 1,
+                "demo_balance": 9993.5,
+                "real_balance": 12.5,
+                "currency_id": 0
+            }}}
+        ]}});
+        server
+            .expect_send("multipleAction")
+            .respond_with(Message::binary(profile.to_string().into_bytes()));
+        let api = ExpertOptions::new_with_url(MOCK_TOKEN, true, server.url())
+            .await
+            .unwrap();
+
+        let profile = tokio::time::timeout(Duration::from_secs(5), api.profile())
+            .await
+            .expect("Profile not received in time")
+            .unwrap();
+        assert_eq!(
+            profile,
+            UserProfile {
+                uid: 125792466,
+                name: "Jane Doe".to_string(),
+                balance: 9993.5,
+                account_type: AccountType::Demo,
+            }
+        );
+        // The balance and the account type follow the current account
+        api.client.state.set_demo(Demo::new(false)).await;
+        let profile = api.profile().await.unwrap();
+        assert_eq!(profile.balance, 12.5);
+        assert_eq!(profile.account_type, AccountType::Real);
+        api.shutdown().await.unwrap();
+    }
 
     #[tokio::test]
     async fn test_expert_options_connection() {
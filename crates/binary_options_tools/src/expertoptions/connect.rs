@@ -3,8 +3,7 @@ use std::sync::Arc;
 use binary_options_tools_core_pre::{
     connector::{Connector as ConnectorTrait, ConnectorError, ConnectorResult},
     reimports::{
-        Connector, MaybeTlsStream, Request, WebSocketStream, connect_async_tls_with_config,
-        generate_key,
+        MaybeTlsStream, Request, WebSocketStream, connect_async_tls_with_config, generate_key,
     },
 };
 use futures_util::{StreamExt, stream::FuturesUnordered};
@@ -23,6 +22,10 @@ impl ConnectorTrait<State> for ExpertConnect {
         &self,
         state: Arc<State>,
     ) -> ConnectorResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+        if let Some(url) = state.default_connection_url.clone() {
+            info!(target: "ExpertConnect", "Connecting to ExpertOptions at {url}");
+            return try_connect(state.user_agent().await, url).await;
+        }
         // Implement connection logic here
         let mut futures = FuturesUnordered::new();
         let url = Regions::regions_str().into_iter().map(String::from); // No demo region for ExpertOptions
@@ -59,12 +62,6 @@ pub async fn try_connect(
     agent: String,
     url: String,
 ) -> ConnectorResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
-    let tls_connector: native_tls::TlsConnector = native_tls::TlsConnector::builder()
-        .build()
-        .map_err(|e| ConnectorError::Tls(e.to_string()))?;
-
-    let connector = Connector::NativeTls(tls_connector);
-
     let t_url = Url::parse(&url).map_err(|e| ConnectorError::UrlParsing(e.to_string()))?;
     let host = t_url
         .host_str()
@@ -82,7 +79,9 @@ pub async fn try_connect(
         .body(())
         .map_err(|e| ConnectorError::HttpRequestBuild(e.to_string()))?;
 
-    let (ws, _) = connect_async_tls_with_config(request, None, false, Some(connector))
+    // No explicit connector: the TLS backend selected through the `native-tls` / `rustls`
+    // features is used for `wss://` urls
+    let (ws, _) = connect_async_tls_with_config(request, None, false, None)
         .await
         .map_err(|e| ConnectorError::Custom(e.to_string()))?;
     Ok(ws)
@@ -10,6 +10,18 @@ pub enum ExpertOptionsError {
 
     #[error("Failed to join task: {0}")]
     Core(#[from] Box<CoreError>),
+
+    #[error("Invalid asset: {0}")]
+    InvalidAsset(String),
+
+    #[error("Failed to open trade: {0}")]
+    TradeFailed(String),
+
+    #[error("Failed to get history: {0}")]
+    HistoryFailed(String),
+
+    #[error("Failed to subscribe: {0}")]
+    SubscriptionFailed(String),
 }
 
 pub type ExpertOptionsResult<T> = Result<T, ExpertOptionsError>;
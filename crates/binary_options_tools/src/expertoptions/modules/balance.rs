@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use binary_options_tools_core_pre::{
+    error::{CoreError, CoreResult},
+    reimports::{AsyncReceiver, AsyncSender, Message},
+    traits::{LightweightModule, Rule},
+};
+use rust_decimal::{Decimal, dec};
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::expertoptions::{
+    Action,
+    state::{Balance, State},
+};
+
+// Id of the balance update messages
+const BALANCE: &str = "balance";
+
+/// Balance update sent by the server after every balance change, the account that didn't
+/// change may be missing.
+#[derive(Deserialize, Debug)]
+struct BalanceUpdate {
+    demo_balance: Option<Decimal>,
+    real_balance: Option<Decimal>,
+}
+
+/// Keeps `State::balance` up to date with the balance updates of the server.
+pub struct BalanceModule {
+    ws_receiver: AsyncReceiver<Arc<Message>>,
+    state: Arc<State>,
+}
+
+#[async_trait::async_trait]
+impl LightweightModule<State> for BalanceModule {
+    fn new(
+        state: Arc<State>,
+        _ws_sender: AsyncSender<Message>,
+        ws_receiver: AsyncReceiver<Arc<Message>>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self { ws_receiver, state }
+    }
+
+    async fn run(&mut self) -> CoreResult<()> {
+        while let Ok(msg) = self.ws_receiver.recv().await {
+            if let Message::Binary(data) = &*msg {
+                match Action::from_json::<BalanceUpdate>(data) {
+                    Ok(update) => self.update_balance(update).await,
+                    Err(e) => {
+                        warn!(target: "BalanceModule", "Failed to parse balance update, {e}")
+                    }
+                }
+            }
+        }
+        Err(CoreError::LightweightModuleLoop("BalanceModule".into()))
+    }
+
+    fn rule() -> Box<dyn Rule + Send + Sync> {
+        Box::new(BalanceRule)
+    }
+}
+
+impl BalanceModule {
+    async fn update_balance(&self, update: BalanceUpdate) {
+        let mut balance = self.state.balance.write().await;
+        // Unknown balances stay at -1 until the server sends them
+        let current = balance.get_or_insert(Balance {
+            real: dec!(-1),
+            demo: dec!(-1),
+        });
+        if let Some(demo) = update.demo_balance {
+            current.demo = demo;
+        }
+        if let Some(real) = update.real_balance {
+            current.real = real;
+        }
+    }
+}
+
+struct BalanceRule;
+
+impl Rule for BalanceRule {
+    fn call(&self, msg: &Message) -> bool {
+        match msg {
+            Message::Binary(data) => {
+                data.starts_with(format!("{{\"action\":\"{BALANCE}\"").as_bytes())
+            }
+            _ => false,
+        }
+    }
+
+    fn reset(&self) {
+        // No state to reset
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use binary_options_tools_core_pre::error::{CoreError, CoreResult};
+use binary_options_tools_core_pre::reimports::{AsyncReceiver, AsyncSender, Message};
+use binary_options_tools_core_pre::traits::{ApiModule, Rule};
+use binary_options_tools_macros::ActionImpl;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::select;
+use tracing::{Instrument, debug, warn};
+use uuid::Uuid;
+
+use crate::expertoptions::error::{ExpertOptionsError, ExpertOptionsResult};
+use crate::expertoptions::modules::Command;
+use crate::expertoptions::state::State;
+use crate::expertoptions::{Action, ActionName};
+use crate::pocketoption::candle::Candle;
+use crate::utils::command_bus::CommandBus;
+
+// List of ids for Action responses
+const HISTORY: &str = "assetHistoryCandles";
+const ERROR: &str = "error";
+
+/// Number of candles requested by `ExpertOptions::history`.
+pub const HISTORY_CANDLES: u32 = 100;
+
+#[derive(Debug)]
+pub enum Request {
+    History {
+        asset: String,
+        asset_id: u32,
+        period: u32,
+    },
+}
+
+#[derive(Debug)]
+pub enum Response {
+    History(Vec<Candle>),
+    Error(String),
+}
+
+#[derive(Debug, Serialize, ActionImpl)]
+#[action(name = "assetHistoryCandles")]
+struct AssetHistoryCandles {
+    #[serde(rename = "assetid")]
+    asset_id: u32,
+    periods: Vec<(i64, i64)>,
+    timeframes: Vec<u32>,
+}
+
+/// Candle as sent by the server: `[timestamp, open, high, low, close]`.
+#[derive(Deserialize)]
+struct RawCandle(i64, Decimal, Decimal, Decimal, Decimal);
+
+#[derive(Deserialize)]
+struct HistoryCandles {
+    candles: Vec<RawCandle>,
+}
+
+#[derive(Clone)]
+pub struct HistoryHandle {
+    bus: CommandBus<Request, Response>,
+}
+
+impl HistoryHandle {
+    /// Requests the last `HISTORY_CANDLES` candles of `period` seconds for the asset.
+    pub async fn history(
+        &self,
+        asset: String,
+        asset_id: u32,
+        period: u32,
+    ) -> ExpertOptionsResult<Vec<Candle>> {
+        let response = self
+            .bus
+            .send(Request::History {
+                asset,
+                asset_id,
+                period,
+            })
+            .await?;
+        match response {
+            Response::History(candles) => Ok(candles),
+            Response::Error(e) => Err(ExpertOptionsError::HistoryFailed(e)),
+        }
+    }
+}
+
+/// History module, requests historical candles and matches the responses with the requests using the `ns` field.
+pub struct HistoryApiModule {
+    ws_receiver: AsyncReceiver<Arc<Message>>,
+    ws_sender: AsyncSender<Message>,
+    command_receiver: AsyncReceiver<Command<Request>>,
+    command_responder: AsyncSender<Command<Response>>,
+    state: Arc<State>,
+    /// Requests waiting for a response, keyed by `ns`, with the symbol of the requested asset
+    pending: HashMap<u64, (Uuid, String)>,
+}
+
+impl HistoryApiModule {
+    async fn request_history(
+        &mut self,
+        id: Uuid,
+        asset: String,
+        asset_id: u32,
+        period: u32,
+    ) -> CoreResult<()> {
+        debug!(target: "HistoryApiModule", "Requesting the {period}s candles of {asset}");
+        let end = Utc::now().timestamp();
+        let start = end - (period * HISTORY_CANDLES) as i64;
+        let request = AssetHistoryCandles {
+            asset_id,
+            periods: vec![(start, end)],
+            timeframes: vec![period],
+        };
+        let ns = self.state.next_ns();
+        let message = request
+            .to_value()
+            .map_err(|e| CoreError::Other(e.to_string()))?;
+        let msg = Action::new(
+            request.name().to_string(),
+            self.state.token.clone(),
+            ns,
+            message,
+        )
+        .to_message()?;
+        self.pending.insert(ns, (id, asset));
+        self.ws_sender.send(msg).await?;
+        Ok(())
+    }
+
+    async fn handle_response(&mut self, action: Action) -> CoreResult<()> {
+        let Some((id, asset)) = action.ns.and_then(|ns| self.pending.remove(&ns)) else {
+            debug!(target: "HistoryApiModule", "Ignoring response without pending request: {}", action.id());
+            return Ok(());
+        };
+        let response = match action.id() {
+            HISTORY => match action.take::<HistoryCandles>() {
+                Ok(history) => Response::History(
+                    history
+                        .candles
+                        .into_iter()
+                        .map(|RawCandle(timestamp, open, high, low, close)| Candle {
+                            symbol: asset.clone(),
+                            timestamp: timestamp as f64,
+                            open,
+                            high,
+                            low,
+                            close,
+                            volume: None,
+                        })
+                        .collect(),
+                ),
+                Err(e) => Response::Error(format!("Failed to parse history response: {e}")),
+            },
+            _ => Response::Error(action.message.to_string()),
+        };
+        self.command_responder
+            .send(Command::from_id(id, response, None))
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ApiModule<State> for HistoryApiModule {
+    type Command = Command<Request>;
+    type CommandResponse = Command<Response>;
+    type Handle = HistoryHandle;
+
+    fn new(
+        shared_state: Arc<State>,
+        command_receiver: AsyncReceiver<Self::Command>,
+        command_responder: AsyncSender<Self::CommandResponse>,
+        message_receiver: AsyncReceiver<Arc<Message>>,
+        to_ws_sender: AsyncSender<Message>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            ws_receiver: message_receiver,
+            ws_sender: to_ws_sender,
+            command_receiver,
+            command_responder,
+            state: shared_state,
+            pending: HashMap::new(),
+        }
+    }
+
+    fn create_handle(
+        sender: AsyncSender<Self::Command>,
+        receiver: AsyncReceiver<Self::CommandResponse>,
+    ) -> Self::Handle {
+        HistoryHandle {
+            bus: CommandBus::new(sender, receiver),
+        }
+    }
+
+    async fn run(&mut self) -> CoreResult<()> {
+        loop {
+            select! {
+                Ok(msg) = self.ws_receiver.recv() => {
+                    if let Message::Binary(data) = msg.as_ref() {
+                        match serde_json::from_slice::<Action>(data) {
+                            Ok(action) => self.handle_response(action).await?,
+                            Err(e) => warn!(target: "HistoryApiModule", "Failed to parse history response: {e}"),
+                        }
+                    }
+                },
+                Ok(cmd) = self.command_receiver.recv() => {
+                    let id = cmd.id();
+                    match cmd.data() {
+                        Request::History { asset, asset_id, period } => {
+                            self.request_history(id, asset.clone(), *asset_id, *period).instrument(cmd.span().clone()).await?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn rule(_: Arc<State>) -> Box<dyn Rule + Send + Sync> {
+        Box::new(HistoryResponseRule)
+    }
+}
+
+/// Rule matching history responses and error responses.
+struct HistoryResponseRule;
+
+impl Rule for HistoryResponseRule {
+    fn call(&self, msg: &Message) -> bool {
+        if AssetHistoryCandlesRule.call(msg) {
+            return true;
+        }
+        match msg {
+            Message::Binary(data) => {
+                data.starts_with(format!("{{\"action\":\"{ERROR}\"").as_bytes())
+            }
+            _ => false,
+        }
+    }
+
+    fn reset(&self) { /* stateless */
+    }
+}
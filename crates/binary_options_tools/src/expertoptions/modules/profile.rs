@@ -1,5 +1,6 @@
 use crate::expertoptions::modules::Command;
-use crate::expertoptions::types::{Asset, Assets, MultiRule};
+use crate::expertoptions::types::{AccountType, Asset, Assets, MultiRule, UserProfile};
+use crate::utils::command_bus::CommandBus;
 use crate::utils::serialize::bool2int;
 
 use std::collections::HashMap;
@@ -9,11 +10,12 @@ use binary_options_tools_core_pre::error::{CoreError, CoreResult};
 use binary_options_tools_core_pre::reimports::{AsyncReceiver, AsyncSender, Message};
 use binary_options_tools_core_pre::traits::{ApiModule, ReconnectCallback, Rule};
 use binary_options_tools_macros::ActionImpl;
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use tokio::select;
-use tracing::debug;
+use tracing::{Instrument, debug};
+use uuid::Uuid;
 
 use crate::expertoptions::state::{Balance, State};
 use crate::expertoptions::{Action, ActionName};
@@ -42,6 +44,11 @@ const GET_CANDLES_TIMEFRAMES: &str = "getCandlesTimeFrames";
 // List of structs to get important data
 #[derive(Deserialize)]
 struct Profile {
+    id: u64,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    surname: String,
     demo_balance: Decimal,
     real_balance: Decimal,
     #[serde(with = "bool2int")]
@@ -59,28 +66,19 @@ struct CandlesTimeFrames {
 
 #[derive(Clone)]
 pub struct ProfileHandle {
-    sender: AsyncSender<Command<Request>>,
-    receiver: AsyncReceiver<Command<Response>>,
+    bus: CommandBus<Request, Response>,
 }
 
 impl ProfileHandle {
     /// Request switching context to demo/real. Fire-and-forget.
     pub async fn set_context(&self, is_demo: bool) -> CoreResult<()> {
-        let (id, cmd) = Command::new(Request::SetContext(Demo::new(is_demo)));
-        self.sender.send(cmd).await?;
-        loop {
-            match self.receiver.recv().await {
-                Ok(cmd) => {
-                    if id == cmd.id() {
-                        match cmd.data() {
-                            Response::Success => return Ok(()),
-                            Response::Error(e) => return Err(CoreError::Other(e.to_string())),
-                        }
-                    }
-                    // Continue waiting for the correct response
-                }
-                Err(e) => return Err(CoreError::from(e)),
-            }
+        match self
+            .bus
+            .send(Request::SetContext(Demo::new(is_demo)))
+            .await?
+        {
+            Response::Success => Ok(()),
+            Response::Error(e) => Err(CoreError::Other(e)),
         }
     }
 }
@@ -133,6 +131,22 @@ impl Demo {
 }
 
 impl ProfileModule {
+    /// Updates the state and sends `setContext`, always answering with `Response::Success` for now.
+    async fn set_context(&self, id: Uuid, demo: Demo) -> CoreResult<()> {
+        debug!(target: "ProfileModule", "Switching the account to demo: {}", demo.is_demo);
+        self.state.set_demo(demo.clone()).await;
+        let token = self.state.token.clone();
+        let msg = demo
+            .action(token)
+            .map_err(|e| CoreError::Other(e.to_string()))?
+            .to_message()?;
+        self.ws_sender.send(msg).await?;
+        self.command_responder
+            .send(Command::from_id(id, Response::Success, None))
+            .await?;
+        Ok(())
+    }
+
     async fn parse_profile(&self, actions: Vec<Action>) -> CoreResult<()> {
         for action in actions {
             match action.id() {
@@ -161,6 +175,21 @@ impl ProfileModule {
                         demo: balance_profile.demo_balance,
                         real: balance_profile.real_balance,
                     };
+                    let current_balance = if balance_profile.is_demo {
+                        balance.demo
+                    } else {
+                        balance.real
+                    };
+                    self.state
+                        .set_profile(UserProfile {
+                            uid: balance_profile.id,
+                            name: format!("{} {}", balance_profile.name, balance_profile.surname)
+                                .trim()
+                                .to_string(),
+                            balance: current_balance.to_f64().unwrap_or_default(),
+                            account_type: AccountType::from_demo(balance_profile.is_demo),
+                        })
+                        .await;
                     self.state.set_balance(balance).await;
                     self.state
                         .set_demo(Demo::new(balance_profile.is_demo))
@@ -218,7 +247,9 @@ impl ApiModule<State> for ProfileModule {
         sender: AsyncSender<Self::Command>,
         receiver: AsyncReceiver<Self::CommandResponse>,
     ) -> Self::Handle {
-        ProfileHandle { sender, receiver }
+        ProfileHandle {
+            bus: CommandBus::new(sender, receiver),
+        }
     }
 
     /// The main run loop for the module's background task.
@@ -255,13 +286,7 @@ impl ApiModule<State> for ProfileModule {
                     let id = cmd.id();
                     match cmd.data() {
                         Request::SetContext(demo) => {
-                            // Update state and send setContext
-                            self.state.set_demo(demo.clone()).await;
-                            let token = self.state.token.clone();
-                            let msg = demo.clone().action(token).map_err(|e| CoreError::Other(e.to_string()))?.to_message()?;
-                            self.ws_sender.send(msg).await?;
-                            // For now always respond with Success
-                            self.command_responder.send(Command::from_id(id, Response::Success)).await?;
+                            self.set_context(id, demo.clone()).instrument(cmd.span().clone()).await?;
                         }
                     }
                 }
@@ -326,23 +351,73 @@ impl ProfileModule {
     }
 }
 
+/// Actions sent inside the `multipleAction` request when connecting.
+#[derive(Debug, Serialize, ActionImpl)]
+#[serde(untagged)]
+enum StartupAction {
+    UserGroup,
+    Profile,
+    Assets,
+    GetCurrency,
+    GetCountries,
+    Environment(Value),
+    DefaultSubscribeCandles {
+        timeframes: Vec<u32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    SetTimeZone {
+        time_zone: i32,
+    },
+    // The request doesn't use the same casing as the response
+    #[action(wire_name = "getCandlesTimeframes")]
+    GetCandlesTimeFrames,
+}
+
+impl StartupAction {
+    /// Serializes the action as an element of the `multipleAction` list.
+    fn to_entry(&self, token: &str, ns: u64) -> CoreResult<Value> {
+        let mut entry = json!({"action": self.name(), "ns": ns, "token": token});
+        let message = self
+            .to_value()
+            .map_err(|e| CoreError::Other(e.to_string()))?;
+        if !message.is_null() {
+            entry["message"] = message;
+        }
+        Ok(entry)
+    }
+}
+
 /// Build a multipleAction Action with a minimal placeholder payload.
 pub fn multiple_action_action(
     token: String,
     timezone: i32,
 ) -> binary_options_tools_core_pre::error::CoreResult<Action> {
     // Placeholder minimal structure; extend actions list as needed
-    let payload = json!({"actions":[
-        {"action":"userGroup","ns":1,"token":token},
-        {"action":"profile","ns":2,"token":token},
-        {"action":"assets","ns":3,"token":token},
-        {"action":"getCurrency","ns":2,"token":token},
-        {"action":"getCountries","ns":5,"token":token},
-        {"action":"environment","message":{"supportedFeatures":["achievements","trade_result_share","tournaments","referral","twofa","inventory","deposit_withdrawal_error_handling","report_a_problem_form","ftt_trade","stocks_trade","stocks_trade_demo","predictions_trade","predictions_trade_demo"],"supportedAbTests":["tournament_glow","floating_exp_time","tutorial","tutorial_account_type","tutorial_account_type_reg","tutorial_stocks","tutorial_first_deal","tutorial_predictions","hide_education_section","in_app_update_android_3","auto_consent_reg","battles_4th_5th_place_rewards","show_achievements_bottom_sheet","promo_story_priority","force_lang_in_app","one_click_deposit","app_theme_select","achievents_badge","chart_hide_soc_trade","candles_autozoom_off","ra_welcome_popup","required_report_msg","2fa_hide_havecode_msg","show_welcome_screen_learn_earn","confirm_event_deals"],"supportedInventoryItems":["riskless_deal","profit","eopoints","tournaments_prize_x3","mystery_box","special_deposit_bonus","cashback_offer"]},"ns":6,"token":token},
-        {"action":"defaultSubscribeCandles","message":{"timeframes":[0,5]},"ns":7,"token":token},
-        {"action":"setTimeZone","message":{"timeZone":timezone},"ns":8,"token":token},
-        {"action":"getCandlesTimeframes","ns":9,"token":token}
-    ]});
+    let actions = [
+        (StartupAction::UserGroup, 1),
+        (StartupAction::Profile, 2),
+        (StartupAction::Assets, 3),
+        (StartupAction::GetCurrency, 2),
+        (StartupAction::GetCountries, 5),
+        (StartupAction::Environment(json!({"supportedFeatures":["achievements","trade_result_share","tournaments","referral","twofa","inventory","deposit_withdrawal_error_handling","report_a_problem_form","ftt_trade","stocks_trade","stocks_trade_demo","predictions_trade","predictions_trade_demo"],"supportedAbTests":["tournament_glow","floating_exp_time","tutorial","tutorial_account_type","tutorial_account_type_reg","tutorial_stocks","tutorial_first_deal","tutorial_predictions","hide_education_section","in_app_update_android_3","auto_consent_reg","battles_4th_5th_place_rewards","show_achievements_bottom_sheet","promo_story_priority","force_lang_in_app","one_click_deposit","app_theme_select","achievents_badge","chart_hide_soc_trade","candles_autozoom_off","ra_welcome_popup","required_report_msg","2fa_hide_havecode_msg","show_welcome_screen_learn_earn","confirm_event_deals"],"supportedInventoryItems":["riskless_deal","profit","eopoints","tournaments_prize_x3","mystery_box","special_deposit_bonus","cashback_offer"]})), 6),
+        (
+            StartupAction::DefaultSubscribeCandles {
+                timeframes: vec![0, 5],
+            },
+            7,
+        ),
+        (
+            StartupAction::SetTimeZone {
+                time_zone: timezone,
+            },
+            8,
+        ),
+        (StartupAction::GetCandlesTimeFrames, 9),
+    ]
+    .iter()
+    .map(|(action, ns)| action.to_entry(&token, *ns))
+    .collect::<CoreResult<Vec<_>>>()?;
+    let payload = json!({ "actions": actions });
     Ok(Action::new("multipleAction".to_string(), token, 2, payload))
 }
 
@@ -368,3 +443,46 @@ impl Rule for MultipleActionRule {
     fn reset(&self) { /* stateless */
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{StartupAction, multiple_action_action};
+    use crate::expertoptions::ActionName;
+
+    #[test]
+    fn test_startup_action_wire_names() {
+        let cases = [
+            (StartupAction::UserGroup, "userGroup"),
+            (StartupAction::Profile, "profile"),
+            (StartupAction::Assets, "assets"),
+            (StartupAction::GetCurrency, "getCurrency"),
+            (StartupAction::GetCountries, "getCountries"),
+            (StartupAction::Environment(json!({})), "environment"),
+            (
+                StartupAction::DefaultSubscribeCandles { timeframes: vec![] },
+                "defaultSubscribeCandles",
+            ),
+            (StartupAction::SetTimeZone { time_zone: 0 }, "setTimeZone"),
+            (StartupAction::GetCandlesTimeFrames, "getCandlesTimeframes"),
+        ];
+        for (action, wire_name) in cases {
+            assert_eq!(action.wire_name(), wire_name);
+            let serialized = action.action("token".to_string()).unwrap().to_json();
+            assert!(serialized.starts_with(&format!("{{\"action\":\"{wire_name}\"")));
+        }
+    }
+
+    #[test]
+    fn test_multiple_action_payload() {
+        let action = multiple_action_action("token".to_string(), 120).unwrap();
+        let actions = action.message["actions"].as_array().unwrap();
+        assert_eq!(actions.len(), 9);
+        assert_eq!(
+            actions[7],
+            json!({"action": "setTimeZone", "message": {"timeZone": 120}, "ns": 8, "token": "token"})
+        );
+        assert!(actions[0].get("message").is_none());
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use binary_options_tools_core_pre::error::{CoreError, CoreResult};
+use binary_options_tools_core_pre::reimports::{
+    AsyncReceiver, AsyncSender, Message, bounded_async,
+};
+use binary_options_tools_core_pre::traits::{ApiModule, ReconnectCallback, Rule};
+use binary_options_tools_macros::ActionImpl;
+use serde::{Deserialize, Serialize};
+use tokio::select;
+use tokio::sync::RwLock;
+use tracing::{Instrument, debug, warn};
+use uuid::Uuid;
+
+use crate::expertoptions::error::{ExpertOptionsError, ExpertOptionsResult};
+use crate::expertoptions::state::State;
+use crate::expertoptions::{Action, ActionName};
+use crate::pocketoption::candle::SubscriptionType;
+use crate::pocketoption::error::PocketError;
+use crate::pocketoption::modules::subscriptions::{
+    Command, CommandResponse, StreamData, SubscriptionError, SubscriptionStream,
+};
+use crate::utils::command_bus::{self, CommandBus};
+
+// List of ids for Action responses
+const CANDLES: &str = "candles";
+
+/// Maximum number of concurrent subscriptions allowed
+const MAX_SUBSCRIPTIONS: usize = 5;
+const MAX_CHANNEL_CAPACITY: usize = 64;
+
+/// Starts the price updates of the assets, the ExpertOptions counterpart of `changeSymbol`.
+#[derive(Debug, Serialize, ActionImpl)]
+#[action(name = "subscribeCandles")]
+struct SubscribeCandles {
+    #[serde(rename = "assetsIds")]
+    asset_ids: Vec<u32>,
+}
+
+/// Stops the price updates of the assets.
+#[derive(Debug, Serialize, ActionImpl)]
+#[action(name = "unsubscribeCandles")]
+struct UnsubscribeCandles {
+    #[serde(rename = "assetsIds")]
+    asset_ids: Vec<u32>,
+}
+
+/// Price updates of an asset as sent by the server, `points` are `[timestamp, price]` pairs.
+#[derive(Deserialize)]
+struct AssetPoints {
+    #[serde(rename = "assetId")]
+    asset_id: u32,
+    points: Vec<(f64, f64)>,
+}
+
+#[derive(Deserialize)]
+struct Candles {
+    candles: Vec<AssetPoints>,
+}
+
+/// Active subscriptions keyed by asset id, with the symbol of the asset
+type Subscriptions = Arc<RwLock<HashMap<u32, (String, AsyncSender<StreamData>)>>>;
+
+/// Handle for interacting with the `SubscriptionsApiModule`.
+#[derive(Clone)]
+pub struct SubscriptionsHandle {
+    bus: CommandBus<Command, CommandResponse>,
+    /// Given to the streams, which unsubscribe without waiting for the response once dropped
+    sender: AsyncSender<command_bus::Command<Command>>,
+}
+
+impl SubscriptionsHandle {
+    /// Subscribe to an asset's real-time price updates, the candles are built with `sub_type`.
+    pub async fn subscribe(
+        &self,
+        asset: String,
+        sub_type: SubscriptionType,
+    ) -> ExpertOptionsResult<SubscriptionStream> {
+        match self
+            .bus
+            .send(Command::Subscribe {
+                asset: asset.clone(),
+            })
+            .await?
+        {
+            CommandResponse::SubscriptionSuccess { stream_receiver } => Ok(
+                SubscriptionStream::new(stream_receiver, self.sender.clone(), asset, sub_type),
+            ),
+            CommandResponse::SubscriptionFailed { error } => {
+                Err(ExpertOptionsError::SubscriptionFailed(error.to_string()))
+            }
+            response => Err(ExpertOptionsError::SubscriptionFailed(format!(
+                "Unexpected response to subscribe: {response:?}"
+            ))),
+        }
+    }
+
+    /// Returns the number of active subscriptions.
+    pub async fn get_active_subscriptions_count(&self) -> ExpertOptionsResult<u32> {
+        match self.bus.send(Command::SubscriptionCount).await? {
+            CommandResponse::SubscriptionCount(count) => Ok(count),
+            response => Err(CoreError::Other(format!(
+                "Unexpected response to get_active_subscriptions_count: {response:?}"
+            ))
+            .into()),
+        }
+    }
+}
+
+/// Callback resubscribing to the active assets after a reconnection
+struct SubscriptionCallback {
+    active_subscriptions: Subscriptions,
+}
+
+#[async_trait]
+impl ReconnectCallback<State> for SubscriptionCallback {
+    async fn call(&self, state: Arc<State>, ws_sender: &AsyncSender<Message>) -> CoreResult<()> {
+        let asset_ids: Vec<u32> = self
+            .active_subscriptions
+            .read()
+            .await
+            .keys()
+            .copied()
+            .collect();
+        if !asset_ids.is_empty() {
+            send_action(ws_sender, &state, &SubscribeCandles { asset_ids }).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Subscriptions module, sends `subscribeCandles` for every subscribed asset and forwards the
+/// price updates to the stream of the asset.
+///
+/// It shares the commands and the `SubscriptionStream` of the PocketOption subscriptions module,
+/// only subscribing, unsubscribing and listing the subscriptions are supported.
+pub struct SubscriptionsApiModule {
+    state: Arc<State>,
+    command_receiver: AsyncReceiver<command_bus::Command<Command>>,
+    command_responder: AsyncSender<command_bus::Command<CommandResponse>>,
+    message_receiver: AsyncReceiver<Arc<Message>>,
+    to_ws_sender: AsyncSender<Message>,
+    active_subscriptions: Subscriptions,
+}
+
+impl SubscriptionsApiModule {
+    /// Returns the id of `asset`, failing if it isn't a known asset.
+    async fn asset_id(&self, asset: &str) -> Result<u32, PocketError> {
+        self.state
+            .assets
+            .read()
+            .await
+            .as_ref()
+            .and_then(|assets| assets.id(asset))
+            .ok_or_else(|| PocketError::InvalidAsset(asset.to_string()))
+    }
+
+    async fn subscribe(&mut self, asset: String) -> Result<AsyncReceiver<StreamData>, PocketError> {
+        let asset_id = self.asset_id(&asset).await?;
+        let mut active = self.active_subscriptions.write().await;
+        if active.contains_key(&asset_id) {
+            return Err(SubscriptionError::SubscriptionAlreadyExists.into());
+        }
+        if active.len() >= MAX_SUBSCRIPTIONS {
+            return Err(SubscriptionError::MaxSubscriptionsReached.into());
+        }
+        let subscribe = SubscribeCandles {
+            asset_ids: vec![asset_id],
+        };
+        send_action(&self.to_ws_sender, &self.state, &subscribe).await?;
+        let (stream_sender, stream_receiver) = bounded_async(MAX_CHANNEL_CAPACITY);
+        active.insert(asset_id, (asset, stream_sender));
+        Ok(stream_receiver)
+    }
+
+    /// Removes the subscription of `asset`, returns false if there was none.
+    async fn unsubscribe(&mut self, asset: &str) -> CoreResult<bool> {
+        let mut active = self.active_subscriptions.write().await;
+        let Some(asset_id) = active
+            .iter()
+            .find_map(|(id, (symbol, _))| (symbol == asset).then_some(*id))
+        else {
+            return Ok(false);
+        };
+        if let Some((_, stream_sender)) = active.remove(&asset_id) {
+            // The stream may be gone already, it must not block the module either
+            let _ = stream_sender.try_send(StreamData::Terminated {
+                reason: "Unsubscribed from main module".to_string(),
+            });
+        }
+        let unsubscribe = UnsubscribeCandles {
+            asset_ids: vec![asset_id],
+        };
+        send_action(&self.to_ws_sender, &self.state, &unsubscribe).await?;
+        Ok(true)
+    }
+
+    async fn forward_points(&self, candles: Candles) {
+        let active = self.active_subscriptions.read().await;
+        for AssetPoints { asset_id, points } in candles.candles {
+            let Some((asset, stream_sender)) = active.get(&asset_id) else {
+                // Updates of assets without subscription are ignored
+                continue;
+            };
+            for (timestamp, price) in points {
+                let update = StreamData::Update {
+                    asset: asset.clone(),
+                    price,
+                    timestamp,
+                };
+                if let Err(e) = stream_sender.send(update).await {
+                    warn!(target: "SubscriptionsApiModule", "Failed to forward price update of {asset}: {e}");
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn handle_command(&mut self, request_id: Uuid, cmd: Command) -> CoreResult<()> {
+        let response = match cmd {
+            Command::Subscribe { asset } => match self.subscribe(asset).await {
+                Ok(stream_receiver) => CommandResponse::SubscriptionSuccess { stream_receiver },
+                Err(e) => CommandResponse::SubscriptionFailed { error: Box::new(e) },
+            },
+            Command::Unsubscribe { asset } => {
+                if self.unsubscribe(&asset).await? {
+                    CommandResponse::UnsubscriptionSuccess
+                } else {
+                    CommandResponse::UnsubscriptionFailed {
+                        error: Box::new(PocketError::General("Subscription not found".to_string())),
+                    }
+                }
+            }
+            Command::SubscriptionCount => CommandResponse::SubscriptionCount(
+                self.active_subscriptions.read().await.len() as u32,
+            ),
+            Command::ListActive => {
+                let mut assets: Vec<String> = self
+                    .active_subscriptions
+                    .read()
+                    .await
+                    .values()
+                    .map(|(asset, _)| asset.clone())
+                    .collect();
+                assets.sort();
+                CommandResponse::ActiveList(assets)
+            }
+            Command::DrainDeadLetters => CommandResponse::DeadLetters(Vec::new()),
+            // The metadata of the subscriptions isn't tracked for ExpertOptions
+            Command::GetInfo { .. } => CommandResponse::Info(None),
+            Command::History { .. } | Command::SubscribeWithHistory { .. } => {
+                CommandResponse::SubscriptionFailed {
+                    error: Box::new(PocketError::NotSupported(
+                        "history through the ExpertOptions subscriptions module".to_string(),
+                    )),
+                }
+            }
+            // Subscriptions are answered right away, there is nothing left to cancel
+            Command::Cancel(_) => return Ok(()),
+        };
+        self.command_responder
+            .send(command_bus::Command::from_id(request_id, response, None))
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ApiModule<State> for SubscriptionsApiModule {
+    type Command = command_bus::Command<Command>;
+    type CommandResponse = command_bus::Command<CommandResponse>;
+    type Handle = SubscriptionsHandle;
+
+    fn new(
+        state: Arc<State>,
+        command_receiver: AsyncReceiver<Self::Command>,
+        command_responder: AsyncSender<Self::CommandResponse>,
+        message_receiver: AsyncReceiver<Arc<Message>>,
+        to_ws_sender: AsyncSender<Message>,
+    ) -> Self {
+        Self {
+            state,
+            command_receiver,
+            command_responder,
+            message_receiver,
+            to_ws_sender,
+            active_subscriptions: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn create_handle(
+        sender: AsyncSender<Self::Command>,
+        receiver: AsyncReceiver<Self::CommandResponse>,
+    ) -> Self::Handle {
+        SubscriptionsHandle {
+            bus: CommandBus::new(sender.clone(), receiver).with_cancel(Command::Cancel),
+            sender,
+        }
+    }
+
+    async fn run(&mut self) -> CoreResult<()> {
+        loop {
+            select! {
+                Ok(cmd) = self.command_receiver.recv() => {
+                    let span = cmd.span().clone();
+                    self.handle_command(cmd.id(), cmd.into_data()).instrument(span).await?;
+                }
+                Ok(msg) = self.message_receiver.recv() => {
+                    if let Message::Binary(data) = msg.as_ref() {
+                        match Action::from_json::<Candles>(data) {
+                            Ok(candles) => self.forward_points(candles).await,
+                            Err(e) => warn!(target: "SubscriptionsApiModule", "Failed to parse price update: {e}"),
+                        }
+                    } else {
+                        debug!(target: "SubscriptionsApiModule", "Ignoring message: {:?}", msg);
+                    }
+                }
+            }
+        }
+    }
+
+    fn callback(&self) -> CoreResult<Option<Box<dyn ReconnectCallback<State>>>> {
+        Ok(Some(Box::new(SubscriptionCallback {
+            active_subscriptions: self.active_subscriptions.clone(),
+        })))
+    }
+
+    fn rule(_: Arc<State>) -> Box<dyn Rule + Send + Sync> {
+        Box::new(PriceUpdateRule)
+    }
+}
+
+async fn send_action(
+    ws_sender: &AsyncSender<Message>,
+    state: &State,
+    action: &impl ActionName,
+) -> CoreResult<()> {
+    let message = action
+        .to_value()
+        .map_err(|e| CoreError::Other(e.to_string()))?;
+    let msg = Action::new(
+        action.name().to_string(),
+        state.token.clone(),
+        state.next_ns(),
+        message,
+    )
+    .to_message()?;
+    ws_sender.send(msg).await?;
+    Ok(())
+}
+
+/// Rule matching the price updates.
+struct PriceUpdateRule;
+
+impl Rule for PriceUpdateRule {
+    fn call(&self, msg: &Message) -> bool {
+        match msg {
+            Message::Binary(data) => {
+                data.starts_with(format!("{{\"action\":\"{CANDLES}\"").as_bytes())
+            }
+            _ => false,
+        }
+    }
+
+    fn reset(&self) { /* stateless */
+    }
+}
+
+/// Candles of `period` seconds, every price update is sent as a candle if `period` is zero.
+pub(crate) fn subscription_type(period: u32) -> SubscriptionType {
+    if period == 0 {
+        SubscriptionType::none()
+    } else {
+        SubscriptionType::time(Duration::from_secs(period.into()))
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use binary_options_tools_core_pre::error::{CoreError, CoreResult};
+use binary_options_tools_core_pre::reimports::{AsyncReceiver, AsyncSender, Message};
+use binary_options_tools_core_pre::traits::{ApiModule, Rule};
+use binary_options_tools_macros::ActionImpl;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::select;
+use tracing::{Instrument, debug, warn};
+use uuid::Uuid;
+
+use crate::expertoptions::error::{ExpertOptionsError, ExpertOptionsResult};
+use crate::expertoptions::modules::Command;
+use crate::expertoptions::state::State;
+use crate::expertoptions::{Action, ActionName};
+use crate::utils::command_bus::CommandBus;
+use crate::utils::serialize::bool2int;
+
+// List of ids for Action responses
+const OPEN_TRADE: &str = "expertOption";
+const ERROR: &str = "error";
+
+#[derive(Debug)]
+pub enum Request {
+    OpenTrade {
+        asset_id: u32,
+        action: TradeAction,
+        amount: f64,
+        time: u32,
+    },
+}
+
+#[derive(Debug)]
+pub enum Response {
+    TradeOpened(ExpertDeal),
+    Error(String),
+}
+
+/// Direction of an ExpertOptions trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeAction {
+    Call,
+    Put,
+}
+
+/// A trade opened on ExpertOptions, as returned by the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpertDeal {
+    pub id: u64,
+    pub asset_id: u32,
+    pub amount: Decimal,
+    #[serde(rename = "type")]
+    pub action: TradeAction,
+    pub strike_time: i64,
+    #[serde(alias = "expiration_time")]
+    pub exp_time: i64,
+    #[serde(default)]
+    pub strike_rate: Option<Decimal>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, ActionImpl)]
+#[action(name = "expertOption")]
+struct OpenTrade {
+    #[serde(rename = "type")]
+    action: TradeAction,
+    amount: f64,
+    #[serde(rename = "assetid")]
+    asset_id: u32,
+    strike_time: i64,
+    expiration_time: i64,
+    #[serde(with = "bool2int")]
+    is_demo: bool,
+    rate_index: u32,
+}
+
+#[derive(Deserialize)]
+struct TradeOpened {
+    option: ExpertDeal,
+}
+
+#[derive(Clone)]
+pub struct TradesHandle {
+    bus: CommandBus<Request, Response>,
+}
+
+impl TradesHandle {
+    /// Opens a trade and waits for the server confirmation.
+    pub async fn open_trade(
+        &self,
+        asset_id: u32,
+        action: TradeAction,
+        amount: f64,
+        time: u32,
+    ) -> ExpertOptionsResult<(Uuid, ExpertDeal)> {
+        let (id, response) = self
+            .bus
+            .request(Request::OpenTrade {
+                asset_id,
+                action,
+                amount,
+                time,
+            })
+            .await?;
+        match response {
+            Response::TradeOpened(deal) => Ok((id, deal)),
+            Response::Error(e) => Err(ExpertOptionsError::TradeFailed(e)),
+        }
+    }
+}
+
+/// Trades module, opens trades and matches the server confirmations with the requests using the `ns` field.
+pub struct TradesApiModule {
+    ws_receiver: AsyncReceiver<Arc<Message>>,
+    ws_sender: AsyncSender<Message>,
+    command_receiver: AsyncReceiver<Command<Request>>,
+    command_responder: AsyncSender<Command<Response>>,
+    state: Arc<State>,
+    /// Requests waiting for a confirmation, keyed by `ns`
+    pending: HashMap<u64, Uuid>,
+}
+
+impl TradesApiModule {
+    async fn open_trade(
+        &mut self,
+        id: Uuid,
+        asset_id: u32,
+        action: TradeAction,
+        amount: f64,
+        time: u32,
+    ) -> CoreResult<()> {
+        let strike_time = Utc::now().timestamp();
+        let trade = OpenTrade {
+            action,
+            amount,
+            asset_id,
+            strike_time,
+            expiration_time: strike_time + time as i64,
+            is_demo: self.state.is_demo().await,
+            rate_index: 1,
+        };
+        let ns = self.state.next_ns();
+        let message = trade
+            .to_value()
+            .map_err(|e| CoreError::Other(e.to_string()))?;
+        let msg = Action::new(
+            trade.name().to_string(),
+            self.state.token.clone(),
+            ns,
+            message,
+        )
+        .to_message()?;
+        self.pending.insert(ns, id);
+        self.ws_sender.send(msg).await?;
+        Ok(())
+    }
+
+    async fn handle_response(&mut self, action: Action) -> CoreResult<()> {
+        let Some(id) = action.ns.and_then(|ns| self.pending.remove(&ns)) else {
+            debug!(target: "TradesApiModule", "Ignoring response without pending trade: {}", action.id());
+            return Ok(());
+        };
+        let response = match action.id() {
+            OPEN_TRADE => match action.take::<TradeOpened>() {
+                Ok(opened) => Response::TradeOpened(opened.option),
+                Err(e) => Response::Error(format!("Failed to parse trade confirmation: {e}")),
+            },
+            _ => Response::Error(action.message.to_string()),
+        };
+        self.command_responder
+            .send(Command::from_id(id, response, None))
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ApiModule<State> for TradesApiModule {
+    type Command = Command<Request>;
+    type CommandResponse = Command<Response>;
+    type Handle = TradesHandle;
+
+    fn new(
+        shared_state: Arc<State>,
+        command_receiver: AsyncReceiver<Self::Command>,
+        command_responder: AsyncSender<Self::CommandResponse>,
+        message_receiver: AsyncReceiver<Arc<Message>>,
+        to_ws_sender: AsyncSender<Message>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            ws_receiver: message_receiver,
+            ws_sender: to_ws_sender,
+            command_receiver,
+            command_responder,
+            state: shared_state,
+            pending: HashMap::new(),
+        }
+    }
+
+    fn create_handle(
+        sender: AsyncSender<Self::Command>,
+        receiver: AsyncReceiver<Self::CommandResponse>,
+    ) -> Self::Handle {
+        TradesHandle {
+            bus: CommandBus::new(sender, receiver),
+        }
+    }
+
+    async fn run(&mut self) -> CoreResult<()> {
+        loop {
+            select! {
+                Ok(msg) = self.ws_receiver.recv() => {
+                    if let Message::Binary(data) = msg.as_ref() {
+                        match serde_json::from_slice::<Action>(data) {
+                            Ok(action) => self.handle_response(action).await?,
+                            Err(e) => warn!(target: "TradesApiModule", "Failed to parse trade response: {e}"),
+                        }
+                    }
+                },
+                Ok(cmd) = self.command_receiver.recv() => {
+                    let id = cmd.id();
+                    match cmd.data() {
+                        Request::OpenTrade { asset_id, action, amount, time } => {
+                            self.open_trade(id, *asset_id, *action, *amount, *time).instrument(cmd.span().clone()).await?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn rule(_: Arc<State>) -> Box<dyn Rule + Send + Sync> {
+        Box::new(TradeResponseRule)
+    }
+}
+
+/// Rule matching trade confirmations and error responses.
+struct TradeResponseRule;
+
+impl Rule for TradeResponseRule {
+    fn call(&self, msg: &Message) -> bool {
+        if OpenTradeRule.call(msg) {
+            return true;
+        }
+        match msg {
+            Message::Binary(data) => {
+                data.starts_with(format!("{{\"action\":\"{ERROR}\"").as_bytes())
+            }
+            _ => false,
+        }
+    }
+
+    fn reset(&self) { /* stateless */
+    }
+}
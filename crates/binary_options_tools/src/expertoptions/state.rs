@@ -1,9 +1,17 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use binary_options_tools_core_pre::traits::AppState;
 use chrono::Local;
 use rust_decimal::{Decimal, dec};
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
+
+use crate::expertoptions::{
+    modules::profile::Demo,
+    types::{Assets, UserProfile},
+};
 
-use crate::expertoptions::{modules::profile::Demo, types::Assets};
+/// First `ns` used for requests, the lower ones are used by the startup messages.
+const FIRST_REQUEST_NS: u64 = 100;
 
 pub struct Config {
     pub user_agent: String,
@@ -17,6 +25,8 @@ pub struct Balance {
 pub struct State {
     /// Session ID for the account
     pub token: String,
+    /// Connection URL to use instead of the regions, if specified.
+    pub default_connection_url: Option<String>,
     /// Balance of the account
     pub balance: RwLock<Option<Balance>>,
     /// Indicates if the account is a demo account
@@ -31,6 +41,12 @@ pub struct State {
     pub points_timeframe: RwLock<Decimal>,
     /// Assets
     pub assets: RwLock<Option<Assets>>,
+    /// Profile of the user, set once the server sends it
+    pub profile: RwLock<Option<UserProfile>>,
+    /// Notified every time the profile is updated
+    profile_notify: Notify,
+    /// Next `ns` used to match request responses, the lower ones are used by the startup messages
+    next_ns: AtomicU64,
 }
 
 impl Config {
@@ -60,16 +76,25 @@ impl State {
         dbg!(timezone);
         State {
             token,
+            default_connection_url: None,
             balance: RwLock::new(None),
             demo: RwLock::new(Demo::new(demo)),
             config: RwLock::new(Config::default()),
             timezone: RwLock::new(timezone), // Default to UTC
             get_candles_timeframes: RwLock::new(Vec::new()),
             assets: RwLock::new(None),
+            profile: RwLock::new(None),
+            profile_notify: Notify::new(),
             points_timeframe: RwLock::new(dec!(0.5)), // Default to .5 seconds
+            next_ns: AtomicU64::new(FIRST_REQUEST_NS),
         }
     }
 
+    /// Returns a new `ns` to identify a request and its response.
+    pub fn next_ns(&self) -> u64 {
+        self.next_ns.fetch_add(1, Ordering::Relaxed)
+    }
+
     pub async fn set_demo(&self, demo: Demo) {
         *self.demo.write().await = demo;
     }
@@ -86,6 +111,23 @@ impl State {
         *self.assets.write().await = Some(assets);
     }
 
+    pub async fn set_profile(&self, profile: UserProfile) {
+        *self.profile.write().await = Some(profile);
+        self.profile_notify.notify_waiters();
+    }
+
+    /// Waits until the profile is received, returns immediately if it already was.
+    pub async fn wait_for_profile(&self) -> UserProfile {
+        loop {
+            // Create the future before checking so an update in between is not missed.
+            let notified = self.profile_notify.notified();
+            if let Some(profile) = self.profile.read().await.clone() {
+                return profile;
+            }
+            notified.await;
+        }
+    }
+
     pub async fn set_balance(&self, balance: Balance) {
         *self.balance.write().await = Some(balance);
     }
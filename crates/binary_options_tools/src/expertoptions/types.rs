@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use crate::utils::serialize::bool2int;
 use binary_options_tools_core_pre::traits::Rule;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 #[derive(Deserialize)]
@@ -18,6 +18,35 @@ pub struct Asset {
 
 pub struct Assets(pub HashMap<String, Asset>);
 
+/// Account a trade is placed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountType {
+    Demo,
+    Real,
+}
+
+impl AccountType {
+    pub fn from_demo(is_demo: bool) -> Self {
+        if is_demo {
+            AccountType::Demo
+        } else {
+            AccountType::Real
+        }
+    }
+}
+
+/// Profile of the authenticated user, sent by the server after the startup messages.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UserProfile {
+    pub uid: u64,
+    /// Display name, the name followed by the surname
+    pub name: String,
+    /// Balance of the current account
+    pub balance: f64,
+    pub account_type: AccountType,
+}
+
 pub struct MultiRule {
     rules: Vec<Box<dyn Rule + Send + Sync>>,
 }
@@ -20,11 +20,15 @@
 //! - Structured logging with tracing
 //! - Timeout handling with custom macros
 //! - Stream processing capabilities
+//! - TLS through `rustls` (default feature) or the system library with the `native-tls` feature
 //!
 //! // Use the streaming utilities for real-time data processing
 //! // Serialize and deserialize data with the provided macros
 //! // Apply timeouts to async operations
 //! ```
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+compile_error!("Enable one of the `native-tls` or `rustls` features to connect over `wss://`");
+
 pub mod expertoptions;
 pub mod pocketoption;
 
@@ -49,7 +53,7 @@ mod tests {
     use tracing::debug;
 
     use binary_options_tools_core_pre::utils::tracing::start_tracing;
-    use binary_options_tools_macros::{deserialize, serialize, timeout};
+    use binary_options_tools_macros::{Config, deserialize, serialize, timeout};
     #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
     struct Test {
         name: String,
@@ -80,4 +84,84 @@ mod tests {
 
         this_is_a_test(Tester).await
     }
+
+    #[derive(Debug)]
+    struct DebugCounter {
+        count: u32,
+    }
+
+    impl DebugCounter {
+        #[timeout(1, tracing(level = "info"))]
+        async fn increment(&mut self, by: u32) -> anyhow::Result<u32> {
+            self.count += by;
+            Ok(self.count)
+        }
+
+        #[timeout(1)]
+        async fn into_count(self) -> anyhow::Result<u32> {
+            Ok(self.count)
+        }
+    }
+
+    struct Counter {
+        count: u32,
+    }
+
+    impl Counter {
+        #[timeout(1, tracing(level = "info", skip(_unused)))]
+        async fn get(&self, _unused: Tester) -> anyhow::Result<u32> {
+            Ok(self.count)
+        }
+
+        #[timeout(1, tracing(level = "debug"))]
+        async fn slow(&self) -> anyhow::Result<u32> {
+            sleep(Duration::from_secs(5)).await;
+            Ok(self.count)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_macro_on_methods() {
+        let mut debug_counter = DebugCounter { count: 1 };
+        assert_eq!(debug_counter.increment(2).await.unwrap(), 3);
+        assert_eq!(debug_counter.into_count().await.unwrap(), 3);
+
+        let counter = Counter { count: 7 };
+        assert_eq!(counter.get(Tester).await.unwrap(), 7);
+        assert!(counter.slow().await.is_err());
+    }
+
+    #[derive(Config)]
+    struct Limits {
+        #[config(default = 4)]
+        max_subscriptions: Option<usize>,
+        max_retries: Option<u32>,
+    }
+
+    #[test]
+    fn test_config_option_fields() {
+        // Unset, with and without a default
+        let config = LimitsConfigBuilder::new().build().unwrap();
+        assert!(!config.is_max_subscriptions_set());
+        assert_eq!(config.max_subscriptions(), 4);
+        assert!(!config.is_max_retries_set());
+        assert_eq!(config.max_retries(), 0);
+
+        // Set through the builder, with and without a default
+        let config = LimitsConfigBuilder::new()
+            .with_max_subscriptions(8)
+            .with_max_retries(3)
+            .build()
+            .unwrap();
+        assert!(config.is_max_subscriptions_set());
+        assert_eq!(config.max_subscriptions(), 8);
+        assert!(config.is_max_retries_set());
+        assert_eq!(config.max_retries(), 3);
+
+        // The setters accept both `T` and `Option<T>`
+        config.set_max_subscriptions(None).unwrap();
+        assert_eq!(config.max_subscriptions(), 4);
+        config.set_max_retries(5).unwrap();
+        assert_eq!(config.get_max_retries().unwrap(), Some(5));
+    }
 }
@@ -1,6 +1,11 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
+use futures_util::Stream;
 use rust_decimal::{
     Decimal, dec,
     prelude::{FromPrimitive, ToPrimitive},
@@ -240,22 +245,213 @@ impl Candle {
 #[derive(Clone)]
 pub enum SubscriptionType {
     None,
+    /// Aggregates every `size` messages of the stream, whatever they contain.
     Chunk {
         size: usize,        // Number of candles to aggregate
         current: usize,     // Current aggregated candle count
         candle: BaseCandle, // Current aggregated candle
     },
-    Time {
-        start_time: Option<f64>,
-        duration: Duration,
-        candle: BaseCandle,
-    },
+    /// Emits a candle once `duration` elapsed since its first tick.
+    Time(TimeCandleAggregator),
     TimeAligned {
         duration: Duration,
+        /// Shift of the period boundaries, in seconds, always lower than `duration`.
+        offset: u32,
         candle: BaseCandle,
         /// Stores the timestamp for the end of the current aggregation window.
         next_boundary: Option<f64>,
     },
+    /// Emits an OHLCV candle every `size` price ticks, regardless of the time they span.
+    TickCount {
+        size: usize,        // Number of ticks per candle
+        current: usize,     // Ticks already aggregated in the current candle
+        candle: BaseCandle, // Current aggregated candle
+    },
+    /// Emits an OHLCV candle every `tick_threshold` ticks, see [`VolumeClockAggregator`].
+    VolumeClock(VolumeClockAggregator),
+    /// User-defined aggregation, see [`CandleAggregator`].
+    Custom(Box<dyn CandleAggregator + Send>),
+}
+
+/// Aggregation strategy of the ticks of a subscription, used by [`SubscriptionType::custom`].
+///
+/// The aggregator must be `Clone` as the subscription types are cloned with their state.
+pub trait CandleAggregator: CloneCandleAggregator {
+    /// Adds a tick to the current candle, returns the candle once it is complete.
+    fn update(&mut self, tick: &BaseCandle) -> PocketResult<Option<BaseCandle>>;
+
+    /// Discards the candle in progress, the next tick opens a new one.
+    fn reset(&mut self);
+}
+
+/// Clones the boxed aggregators, implemented for every `Clone` aggregator.
+pub trait CloneCandleAggregator {
+    fn clone_box(&self) -> Box<dyn CandleAggregator + Send>;
+}
+
+impl<T: CandleAggregator + Clone + Send + 'static> CloneCandleAggregator for T {
+    fn clone_box(&self) -> Box<dyn CandleAggregator + Send> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn CandleAggregator + Send> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Emits a candle once `duration` elapsed since its first tick.
+#[derive(Clone)]
+pub struct TimeCandleAggregator {
+    duration: Duration,
+    start_time: Option<f64>,
+    candle: BaseCandle,
+}
+
+impl TimeCandleAggregator {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            start_time: None,
+            candle: BaseCandle::default(),
+        }
+    }
+}
+
+impl CandleAggregator for TimeCandleAggregator {
+    fn update(&mut self, tick: &BaseCandle) -> PocketResult<Option<BaseCandle>> {
+        let Some(start_time) = self.start_time else {
+            self.start_time = Some(tick.timestamp);
+            self.candle = tick.clone();
+            return Ok(None);
+        };
+        self.candle.timestamp = tick.timestamp;
+        self.candle.high = self.candle.high.max(tick.high);
+        self.candle.low = self.candle.low.min(tick.low);
+        self.candle.close = tick.close;
+
+        let elapsed = (tick.timestamp()
+            - DateTime::from_timestamp(start_time as i64, 0).unwrap_or_else(Utc::now))
+        .to_std()
+        .map_err(|_| {
+            PocketError::General("Time calculation error in conditional update".to_string())
+        })?;
+
+        if elapsed >= self.duration {
+            self.start_time = None; // Reset for next period
+            Ok(Some(self.candle.clone()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.start_time = None;
+    }
+}
+
+/// Emits a candle every `tick_threshold` ticks, aligning the candles on the tick volume instead of
+/// the time, the volume of the ticks is summed when available.
+#[derive(Clone)]
+pub struct VolumeClockAggregator {
+    tick_threshold: u64,
+    tick_count: u64,
+    last_tick_count: u64,
+    candle: BaseCandle,
+}
+
+impl VolumeClockAggregator {
+    pub fn new(tick_threshold: u64) -> Self {
+        Self {
+            tick_threshold,
+            tick_count: 0,
+            last_tick_count: 0,
+            candle: BaseCandle::default(),
+        }
+    }
+
+    /// Number of ticks aggregated in each candle
+    pub fn tick_threshold(&self) -> u64 {
+        self.tick_threshold
+    }
+
+    /// Number of ticks aggregated in the last candle emitted, 0 before the first one.
+    pub fn last_tick_count(&self) -> u64 {
+        self.last_tick_count
+    }
+}
+
+impl CandleAggregator for VolumeClockAggregator {
+    fn update(&mut self, tick: &BaseCandle) -> PocketResult<Option<BaseCandle>> {
+        if self.tick_count == 0 {
+            self.candle = tick.clone();
+        } else {
+            self.candle.timestamp = tick.timestamp;
+            self.candle.high = self.candle.high.max(tick.high);
+            self.candle.low = self.candle.low.min(tick.low);
+            self.candle.close = tick.close;
+            if let (Some(v_agg), Some(v_new)) = (&mut self.candle.volume, tick.volume) {
+                *v_agg += v_new;
+            } else if tick.volume.is_some() {
+                self.candle.volume = tick.volume;
+            }
+        }
+        self.tick_count += 1;
+
+        if self.tick_count >= self.tick_threshold {
+            self.last_tick_count = self.tick_count;
+            self.tick_count = 0; // Start a new candle with the next tick
+            Ok(Some(self.candle.clone()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.tick_count = 0;
+    }
+}
+
+/// Emits a candle once the price moved more than `threshold` away from the open of the candle,
+/// the next tick opens a new candle.
+#[derive(Clone)]
+pub struct PriceChangeTrigger {
+    threshold: f64,
+    candle: Option<BaseCandle>,
+}
+
+impl PriceChangeTrigger {
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            candle: None,
+        }
+    }
+}
+
+impl CandleAggregator for PriceChangeTrigger {
+    fn update(&mut self, tick: &BaseCandle) -> PocketResult<Option<BaseCandle>> {
+        let candle = match &mut self.candle {
+            Some(candle) => {
+                candle.timestamp = tick.timestamp;
+                candle.high = candle.high.max(tick.high);
+                candle.low = candle.low.min(tick.low);
+                candle.close = tick.close;
+                candle
+            }
+            None => self.candle.insert(tick.clone()),
+        };
+        if (candle.close - candle.open).abs() > self.threshold {
+            Ok(self.candle.take())
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.candle = None;
+    }
 }
 
 impl BaseCandle {
@@ -280,6 +476,61 @@ impl BaseCandle {
     pub fn timestamp(&self) -> DateTime<Utc> {
         DateTime::from_timestamp(self.timestamp as i64, 0).unwrap_or_else(Utc::now)
     }
+
+    /// Reads the ticks of a CSV file with `timestamp,price` columns, each tick becomes a candle
+    /// with a single price.
+    ///
+    /// Timestamps are either Unix seconds (fractions allowed) or ISO 8601 dates, dates without
+    /// an offset are read as UTC.
+    pub fn from_tick_csv(
+        path: &Path,
+    ) -> PocketResult<impl Iterator<Item = PocketResult<BaseCandle>>> {
+        let reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path)?;
+        Ok(reader.into_deserialize().map(|row| {
+            let row: TickRow = row?;
+            Ok(BaseCandle::from((
+                parse_tick_timestamp(&row.timestamp)?,
+                row.price,
+            )))
+        }))
+    }
+
+    /// Turns `(timestamp, price)` ticks into single price candles, for in-memory test data.
+    pub fn from_tick_vec(ticks: Vec<(f64, f64)>) -> impl Iterator<Item = BaseCandle> {
+        ticks.into_iter().map(BaseCandle::from)
+    }
+}
+
+/// A row of the files read by `BaseCandle::from_tick_csv`.
+#[derive(Deserialize)]
+struct TickRow {
+    timestamp: String,
+    price: f64,
+}
+
+fn parse_tick_timestamp(timestamp: &str) -> PocketResult<f64> {
+    if let Ok(seconds) = timestamp.parse::<f64>() {
+        return Ok(seconds);
+    }
+    let datetime = DateTime::parse_from_rfc3339(timestamp)
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S%.f")
+                .map(|naive| naive.and_utc())
+        })
+        .map_err(|e| PocketError::General(format!("Invalid tick timestamp '{timestamp}': {e}")))?;
+    Ok(datetime.timestamp_micros() as f64 / 1_000_000.0)
+}
+
+/// End of the aligned window containing `timestamp`, windows start at `offset` seconds past each
+/// multiple of `duration`.
+fn aligned_boundary(timestamp: f64, duration: Duration, offset: u32) -> f64 {
+    let duration_secs = duration.as_secs_f64();
+    let offset = f64::from(offset);
+    let bucket_id = ((timestamp - offset) / duration_secs).floor();
+    (bucket_id + 1.0) * duration_secs + offset
 }
 
 impl SubscriptionType {
@@ -295,15 +546,44 @@ impl SubscriptionType {
         }
     }
 
-    pub fn time(duration: Duration) -> Self {
-        SubscriptionType::Time {
-            start_time: None,
-            duration,
+    /// Emits a candle every `n` ticks, the volume of the ticks is summed when available.
+    pub fn tick_count(n: usize) -> Self {
+        SubscriptionType::TickCount {
+            size: n,
+            current: 0,
             candle: BaseCandle::default(),
         }
     }
 
+    pub fn time(duration: Duration) -> Self {
+        SubscriptionType::Time(TimeCandleAggregator::new(duration))
+    }
+
+    /// Emits a candle every `tick_threshold` ticks, see [`VolumeClockAggregator`].
+    pub fn volume_clock(tick_threshold: u64) -> Self {
+        SubscriptionType::VolumeClock(VolumeClockAggregator::new(tick_threshold))
+    }
+
+    /// Aggregates the ticks with a user-defined strategy, e.g. a [`PriceChangeTrigger`].
+    pub fn custom(aggregator: Box<dyn CandleAggregator + Send>) -> Self {
+        SubscriptionType::Custom(aggregator)
+    }
+
     pub fn time_aligned(duration: Duration) -> PocketResult<Self> {
+        Self::time_aligned_with_offset(duration, 0)
+    }
+
+    /// Same as [`time_aligned`](Self::time_aligned) but the period boundaries are shifted by
+    /// `offset_seconds`, e.g. an offset of 3 on 5 seconds candles closes them at 00:03, 00:08, 00:13...
+    ///
+    /// Returns `PocketError::InvalidOffset` if the offset isn't lower than the duration.
+    pub fn time_aligned_with_offset(duration: Duration, offset_seconds: u32) -> PocketResult<Self> {
+        if u64::from(offset_seconds) >= duration.as_secs() {
+            return Err(PocketError::InvalidOffset {
+                offset: offset_seconds,
+                duration,
+            });
+        }
         if !(24 * 60 * 60 % duration.as_secs() == 0) {
             warn!(
                 "Unsupported duration for time-aligned subscription: {:?}",
@@ -315,6 +595,7 @@ impl SubscriptionType {
         }
         Ok(SubscriptionType::TimeAligned {
             duration,
+            offset: offset_seconds,
             candle: BaseCandle::default(),
             next_boundary: None,
         })
@@ -347,41 +628,11 @@ impl SubscriptionType {
                 }
             }
 
-            SubscriptionType::Time {
-                start_time,
-                duration,
-                candle,
-            } => {
-                if start_time.is_none() {
-                    *start_time = Some(new_candle.timestamp);
-                    *candle = new_candle.clone();
-                    return Ok(None);
-                }
-
-                // Update the aggregated candle
-                candle.timestamp = new_candle.timestamp;
-                candle.high = candle.high.max(new_candle.high);
-                candle.low = candle.low.min(new_candle.low);
-                candle.close = new_candle.close;
-
-                let elapsed = (new_candle.timestamp()
-                    - DateTime::from_timestamp(start_time.unwrap() as i64, 0)
-                        .unwrap_or_else(Utc::now))
-                .to_std()
-                .map_err(|_| {
-                    PocketError::General("Time calculation error in conditional update".to_string())
-                })?;
-
-                if elapsed >= *duration {
-                    *start_time = None; // Reset for next period
-                    Ok(Some(candle.clone()))
-                } else {
-                    Ok(None)
-                }
-            }
+            SubscriptionType::Time(aggregator) => aggregator.update(new_candle),
 
             SubscriptionType::TimeAligned {
                 duration,
+                offset,
                 candle,
                 next_boundary,
             } => {
@@ -390,10 +641,8 @@ impl SubscriptionType {
                     None => {
                         // First candle ever processed. Initialize the state.
                         *candle = new_candle.clone();
-                        let duration_secs = duration.as_secs_f64();
-                        let bucket_id = (new_candle.timestamp / duration_secs).floor();
-                        let new_boundary = (bucket_id + 1.0) * duration_secs;
-                        *next_boundary = Some(new_boundary);
+                        *next_boundary =
+                            Some(aligned_boundary(new_candle.timestamp, *duration, *offset));
 
                         // It's the first candle, so the window can't be complete yet.
                         return Ok(None);
@@ -423,15 +672,66 @@ impl SubscriptionType {
                     *candle = new_candle.clone();
 
                     // 3. Calculate the boundary for this new period.
-                    let duration_secs = duration.as_secs_f64();
-                    let bucket_id = (new_candle.timestamp / duration_secs).floor();
-                    let new_boundary = (bucket_id + 1.0) * duration_secs;
-                    *next_boundary = Some(new_boundary);
+                    *next_boundary =
+                        Some(aligned_boundary(new_candle.timestamp, *duration, *offset));
 
                     // 4. Return the candle that was just completed.
                     Ok(Some(completed_candle))
                 }
             }
+
+            SubscriptionType::TickCount {
+                size,
+                current,
+                candle,
+            } => {
+                if *current == 0 {
+                    *candle = new_candle.clone();
+                } else {
+                    candle.timestamp = new_candle.timestamp;
+                    candle.high = candle.high.max(new_candle.high);
+                    candle.low = candle.low.min(new_candle.low);
+                    candle.close = new_candle.close;
+                    if let (Some(v_agg), Some(v_new)) = (&mut candle.volume, new_candle.volume) {
+                        *v_agg += v_new;
+                    } else if new_candle.volume.is_some() {
+                        candle.volume = new_candle.volume;
+                    }
+                }
+                *current += 1;
+
+                if *current >= *size {
+                    *current = 0; // Start a new candle with the next tick
+                    Ok(Some(candle.clone()))
+                } else {
+                    Ok(None)
+                }
+            }
+
+            SubscriptionType::VolumeClock(aggregator) => aggregator.update(new_candle),
+
+            SubscriptionType::Custom(aggregator) => aggregator.update(new_candle),
+        }
+    }
+
+    /// Discards the candle in progress, e.g. once the subscription was re-established after a
+    /// reconnection, so the ticks missed in between don't end up in a single candle.
+    pub fn reset(&mut self) {
+        match self {
+            SubscriptionType::None => {}
+            SubscriptionType::Chunk { current, .. }
+            | SubscriptionType::TickCount { current, .. } => *current = 0,
+            SubscriptionType::TimeAligned {
+                candle,
+                next_boundary,
+                ..
+            } => {
+                *candle = BaseCandle::default();
+                *next_boundary = None;
+            }
+            SubscriptionType::Time(aggregator) => aggregator.reset(),
+            SubscriptionType::VolumeClock(aggregator) => aggregator.reset(),
+            SubscriptionType::Custom(aggregator) => aggregator.reset(),
         }
     }
 }
@@ -477,14 +777,377 @@ impl TryFrom<(BaseCandle, String)> for Candle {
     }
 }
 
+/// Converts the candles to a DataFrame with the `open_time`, `open`, `high`, `low` and `close`
+/// columns, `open_time` being the unix timestamp of the candle start.
+#[cfg(feature = "polars")]
+pub fn to_polars_dataframe(candles: &[Candle]) -> BinaryOptionsResult<polars::frame::DataFrame> {
+    use polars::prelude::{Column, DataFrame};
+
+    let prices = |price: fn(&Candle) -> Decimal| {
+        candles
+            .iter()
+            .map(|candle| {
+                let value = price(candle);
+                value.to_f64().ok_or(BinaryOptionsError::from(value))
+            })
+            .collect::<BinaryOptionsResult<Vec<f64>>>()
+    };
+    let open_time: Vec<f64> = candles.iter().map(|candle| candle.timestamp).collect();
+    Ok(DataFrame::new(vec![
+        Column::new("open_time".into(), open_time),
+        Column::new("open".into(), prices(|candle| candle.open)?),
+        Column::new("high".into(), prices(|candle| candle.high)?),
+        Column::new("low".into(), prices(|candle| candle.low)?),
+        Column::new("close".into(), prices(|candle| candle.close)?),
+    ])?)
+}
+
+/// Stream adapter yielding overlapping windows of the last `n` candles.
+///
+/// Nothing is yielded until `n` candles were received, then every new candle produces a window
+/// with the `n` most recent candles, oldest first. Errors from the inner stream are forwarded
+/// without affecting the window.
+pub struct RollingCandleWindow<S> {
+    inner: S,
+    size: usize,
+    window: VecDeque<Candle>,
+}
+
+impl<S> RollingCandleWindow<S>
+where
+    S: Stream<Item = PocketResult<Candle>> + Unpin,
+{
+    /// Wraps `inner`, yielding windows of `n` candles.
+    pub fn rolling(inner: S, n: usize) -> Self {
+        Self {
+            inner,
+            size: n.max(1),
+            window: VecDeque::with_capacity(n.max(1)),
+        }
+    }
+
+    /// Number of candles in each window
+    pub fn window_size(&self) -> usize {
+        self.size
+    }
+
+    /// Converts the candles accumulated so far, oldest first, with [`to_polars_dataframe`].
+    #[cfg(feature = "polars")]
+    pub fn to_polars_dataframe(&self) -> BinaryOptionsResult<polars::frame::DataFrame> {
+        let candles: Vec<Candle> = self.window.iter().cloned().collect();
+        to_polars_dataframe(&candles)
+    }
+}
+
+impl<S> Stream for RollingCandleWindow<S>
+where
+    S: Stream<Item = PocketResult<Candle>> + Unpin,
+{
+    type Item = PocketResult<Vec<Candle>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(candle))) => {
+                    if self.window.len() == self.size {
+                        self.window.pop_front();
+                    }
+                    self.window.push_back(candle);
+                    if self.window.len() == self.size {
+                        return Poll::Ready(Some(Ok(self.window.iter().cloned().collect())));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use futures_util::StreamExt;
+
     use super::*;
 
+    #[tokio::test]
+    async fn test_rolling_window() {
+        let candles = (0..20).map(|i| {
+            Candle::new("EURUSD_otc".to_string(), i as f64, 1.0 + i as f64 / 100.0)
+                .map_err(PocketError::from)
+        });
+        let windows: Vec<Vec<Candle>> =
+            RollingCandleWindow::rolling(futures_util::stream::iter(candles), 14)
+                .map(|window| window.unwrap())
+                .collect()
+                .await;
+        assert_eq!(windows.len(), 7);
+        for (i, window) in windows.iter().enumerate() {
+            assert_eq!(window.len(), 14);
+            assert_eq!(window[0].timestamp, i as f64);
+            assert_eq!(window[13].timestamp, (i + 13) as f64);
+        }
+    }
+
+    #[test]
+    fn test_tick_count() {
+        let mut sub_type = SubscriptionType::tick_count(10);
+        let candles: Vec<BaseCandle> = (0..30)
+            .filter_map(|i| {
+                let tick = BaseCandle::from((1_700_000_000.0 + i as f64 * 0.5, 1.0 + i as f64));
+                sub_type.update(&tick).unwrap()
+            })
+            .collect();
+        assert_eq!(candles.len(), 3);
+        for (i, candle) in candles.iter().enumerate() {
+            let first = (i * 10) as f64;
+            assert_eq!(candle.open, 1.0 + first);
+            assert_eq!(candle.low, 1.0 + first);
+            assert_eq!(candle.high, 1.0 + first + 9.0);
+            assert_eq!(candle.close, 1.0 + first + 9.0);
+            assert_eq!(candle.timestamp, 1_700_000_000.0 + (first + 9.0) * 0.5);
+        }
+    }
+
+    #[test]
+    fn test_reset() {
+        fn feed(sub_type: &mut SubscriptionType, timestamp: f64, price: f64) -> Option<BaseCandle> {
+            sub_type
+                .update(&BaseCandle::from((1_700_000_000.0 + timestamp, price)))
+                .unwrap()
+        }
+
+        let mut sub_type = SubscriptionType::time_aligned(Duration::from_secs(5)).unwrap();
+        feed(&mut sub_type, 1.0, 1.0);
+        feed(&mut sub_type, 2.0, 9.0);
+        sub_type.reset();
+        // The partial candle is gone, the next tick opens a new window
+        assert!(feed(&mut sub_type, 31.0, 2.0).is_none());
+        let candle = feed(&mut sub_type, 36.0, 3.0).unwrap();
+        assert_eq!(candle.open, 2.0);
+        assert_eq!(candle.high, 2.0);
+        assert_eq!(candle.timestamp, 1_700_000_035.0);
+
+        let mut sub_type = SubscriptionType::custom(Box::new(PriceChangeTrigger::new(0.05)));
+        feed(&mut sub_type, 0.0, 1.0);
+        sub_type.reset();
+        assert!(feed(&mut sub_type, 1.0, 1.06).is_none());
+
+        let mut sub_type = SubscriptionType::volume_clock(3);
+        feed(&mut sub_type, 0.0, 1.0);
+        feed(&mut sub_type, 1.0, 1.0);
+        sub_type.reset();
+        assert!(feed(&mut sub_type, 2.0, 2.0).is_none());
+        assert!(feed(&mut sub_type, 3.0, 2.0).is_none());
+        assert_eq!(feed(&mut sub_type, 4.0, 2.0).unwrap().open, 2.0);
+    }
+
+    #[test]
+    fn test_volume_clock() {
+        let mut sub_type = SubscriptionType::volume_clock(25);
+        let mut candles = Vec::new();
+        for i in 0..100 {
+            let tick = BaseCandle {
+                volume: Some(2.0),
+                ..BaseCandle::from((1_700_000_000.0 + i as f64, 1.0 + i as f64))
+            };
+            if let Some(candle) = sub_type.update(&tick).unwrap() {
+                let SubscriptionType::VolumeClock(aggregator) = &sub_type else {
+                    unreachable!()
+                };
+                candles.push((candle, aggregator.last_tick_count()));
+            }
+        }
+        assert_eq!(candles.len(), 4);
+        for (i, (candle, tick_count)) in candles.iter().enumerate() {
+            let first = (i * 25) as f64;
+            assert_eq!(*tick_count, 25);
+            assert_eq!(candle.open, 1.0 + first);
+            assert_eq!(candle.close, 1.0 + first + 24.0);
+            // The volume of the ticks, not their count
+            assert_eq!(candle.volume, Some(50.0));
+        }
+    }
+
+    #[test]
+    fn test_price_change_trigger() {
+        let mut sub_type = SubscriptionType::custom(Box::new(PriceChangeTrigger::new(0.05)));
+        let prices = [1.0, 1.02, 0.97, 1.06, 1.1, 1.12, 1.14, 1.2];
+        let candles: Vec<(usize, BaseCandle)> = prices
+            .iter()
+            .enumerate()
+            .filter_map(|(i, price)| {
+                let tick = BaseCandle::from((1_700_000_000.0 + i as f64, *price));
+                sub_type.update(&tick).unwrap().map(|candle| (i, candle))
+            })
+            .collect();
+        // 1.06 is 0.06 above the open of 1.0, then 1.2 is 0.1 above the open of 1.1
+        assert_eq!(candles.len(), 2);
+        let (i, first) = &candles[0];
+        assert_eq!(*i, 3);
+        assert_eq!(first.open, 1.0);
+        assert_eq!(first.low, 0.97);
+        assert_eq!(first.close, 1.06);
+        let (i, second) = &candles[1];
+        assert_eq!(*i, 7);
+        assert_eq!(second.open, 1.1);
+        assert_eq!(second.close, 1.2);
+        assert_eq!(second.timestamp, 1_700_000_007.0);
+
+        // The state is kept when the subscription type is cloned
+        let mut trigger = PriceChangeTrigger::new(0.05);
+        trigger
+            .update(&BaseCandle::from((1_700_000_000.0, 1.0)))
+            .unwrap();
+        let mut cloned = SubscriptionType::custom(Box::new(trigger)).clone();
+        assert!(
+            cloned
+                .update(&BaseCandle::from((1_700_000_001.0, 1.06)))
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_time_aggregator() {
+        let mut sub_type = SubscriptionType::time(Duration::from_secs(5));
+        let candles: Vec<BaseCandle> = (0..12)
+            .filter_map(|i| {
+                let tick = BaseCandle::from((1_700_000_000.0 + i as f64, 1.0 + i as f64));
+                sub_type.update(&tick).unwrap()
+            })
+            .collect();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].open, 1.0);
+        assert_eq!(candles[0].close, 6.0);
+        assert_eq!(candles[1].open, 7.0);
+        assert_eq!(candles[1].close, 12.0);
+
+        // A tick older than the first one of the candle is an error
+        let mut sub_type = SubscriptionType::time(Duration::from_secs(5));
+        sub_type
+            .update(&BaseCandle::from((1_700_000_010.0, 1.0)))
+            .unwrap();
+        assert!(
+            sub_type
+                .update(&BaseCandle::from((1_700_000_005.0, 1.0)))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_time_aligned_with_offset() {
+        let mut sub_type =
+            SubscriptionType::time_aligned_with_offset(Duration::from_secs(5), 3).unwrap();
+        // 1_700_000_000 is a multiple of 5, the windows are [..03, ..08), [..08, ..13), [..13, ..18)
+        let candles: Vec<BaseCandle> = (0..16)
+            .filter_map(|i| {
+                let tick = BaseCandle::from((1_700_000_000.0 + i as f64, 1.0 + i as f64));
+                sub_type.update(&tick).unwrap()
+            })
+            .collect();
+        assert_eq!(candles.len(), 3);
+        assert_eq!(candles[0].timestamp, 1_700_000_003.0);
+        assert_eq!(candles[0].open, 1.0);
+        assert_eq!(candles[0].close, 3.0);
+        assert_eq!(candles[1].timestamp, 1_700_000_008.0);
+        assert_eq!(candles[1].open, 4.0);
+        assert_eq!(candles[1].close, 8.0);
+        assert_eq!(candles[2].timestamp, 1_700_000_013.0);
+        assert_eq!(candles[2].open, 9.0);
+
+        // Without an offset the same ticks close at multiples of 5
+        let mut sub_type = SubscriptionType::time_aligned(Duration::from_secs(5)).unwrap();
+        let timestamps: Vec<f64> = (0..16)
+            .filter_map(|i| {
+                let tick = BaseCandle::from((1_700_000_000.0 + i as f64, 1.0));
+                sub_type.update(&tick).unwrap()
+            })
+            .map(|candle| candle.timestamp)
+            .collect();
+        assert_eq!(
+            timestamps,
+            vec![1_700_000_005.0, 1_700_000_010.0, 1_700_000_015.0]
+        );
+
+        assert!(matches!(
+            SubscriptionType::time_aligned_with_offset(Duration::from_secs(5), 5),
+            Err(PocketError::InvalidOffset { offset: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_tick_csv() {
+        let ticks = vec![
+            (1_700_000_000.0, 1.1),
+            (1_700_000_000.5, 1.2),
+            (1_700_000_001.25, 1.15),
+        ];
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = csv::Writer::from_path(file.path()).unwrap();
+        writer.write_record(["timestamp", "price"]).unwrap();
+        for candle in BaseCandle::from_tick_vec(ticks.clone()) {
+            writer
+                .write_record([candle.timestamp.to_string(), candle.close.to_string()])
+                .unwrap();
+        }
+        writer
+            .write_record(["2023-11-14T22:13:22.5Z", "1.3"])
+            .unwrap();
+        writer.write_record(["2023-11-14T22:13:23", "1.4"]).unwrap();
+        writer.flush().unwrap();
+
+        let candles: Vec<BaseCandle> = BaseCandle::from_tick_csv(file.path())
+            .unwrap()
+            .collect::<PocketResult<_>>()
+            .unwrap();
+        assert_eq!(candles.len(), 5);
+        for (candle, (timestamp, price)) in candles.iter().zip(&ticks) {
+            assert_eq!(candle.timestamp, *timestamp);
+            assert_eq!(candle.open, *price);
+            assert_eq!(candle.close, *price);
+        }
+        assert_eq!(candles[3].timestamp, 1_700_000_002.5);
+        assert_eq!(candles[3].close, 1.3);
+        assert_eq!(candles[4].timestamp, 1_700_000_003.0);
+    }
+
+    #[test]
+    fn test_from_tick_csv_invalid_timestamp() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "timestamp,price\nyesterday,1.1\n").unwrap();
+        let mut candles = BaseCandle::from_tick_csv(file.path()).unwrap();
+        assert!(matches!(candles.next(), Some(Err(PocketError::General(_)))));
+    }
+
     #[test]
     fn test_parse_base_candles() {
         let data = r#"[1754529180,0.92124,0.92155,0.92162,0.92124]"#;
         let candle: BaseCandle = serde_json::from_str(data).unwrap();
         assert_eq!(candle.timestamp, 1754529180.0);
     }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_to_polars_dataframe() {
+        let candles: Vec<Candle> = (0..5)
+            .map(|i| Candle {
+                symbol: "EURUSD_otc".to_string(),
+                timestamp: 1_700_000_000.0 + 60.0 * i as f64,
+                open: dec!(1.10),
+                high: dec!(1.12),
+                low: dec!(1.09),
+                close: dec!(1.11),
+                volume: None,
+            })
+            .collect();
+
+        let df = to_polars_dataframe(&candles).unwrap();
+        assert_eq!(df.height(), 5);
+        assert_eq!(
+            df.get_column_names(),
+            ["open_time", "open", "high", "low", "close"]
+        );
+    }
 }
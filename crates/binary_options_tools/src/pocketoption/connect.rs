@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use binary_options_tools_core_pre::{
     connector::{Connector, ConnectorError, ConnectorResult},
@@ -10,7 +10,7 @@ use tracing::{info, warn};
 
 use crate::{
     pocketoption::utils::try_connect,
-    pocketoption::{ssid::Ssid, state::State},
+    pocketoption::{proxy::ProxyConfig, state::State},
 };
 use futures_util::StreamExt;
 
@@ -18,32 +18,57 @@ use futures_util::StreamExt;
 pub struct PocketConnect;
 
 impl PocketConnect {
+    /// Headers of the WebSocket upgrade request on top of the protocol ones: the `User-Agent`
+    /// of the session, replaced by the headers set with `StateBuilder::with_header`.
+    pub fn custom_headers(state: &State) -> HashMap<String, String> {
+        let mut headers = HashMap::from([("User-Agent".to_string(), state.ssid().user_agent())]);
+        headers.extend(state.headers.clone());
+        headers
+    }
+
     async fn connect_multiple(
         &self,
         url: Vec<String>,
-        ssid: Ssid,
-    ) -> ConnectorResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+        headers: &HashMap<String, String>,
+        pinned_certificates: &[String],
+        proxy: Option<&ProxyConfig>,
+        timeout: Duration,
+    ) -> ConnectorResult<(WebSocketStream<MaybeTlsStream<TcpStream>>, String)> {
+        let mut all_timed_out = !url.is_empty();
         let mut futures = FuturesUnordered::new();
         for u in url {
             futures.push(async {
                 info!(target: "PocketConnectThread", "Connecting to PocketOption at {}", u);
-                try_connect(ssid.clone(), u.clone())
+                try_connect(u.clone(), headers, pinned_certificates, proxy, timeout)
                     .await
+                    .map(|stream| (stream, u.clone()))
                     .map_err(|e| (e, u))
             });
         }
+        let mut pinning_failure = None;
         while let Some(result) = futures.next().await {
             match result {
-                Ok(stream) => {
+                Ok(connected) => {
                     info!(target: "PocketConnect", "Successfully connected to PocketOption");
-                    return Ok(stream);
+                    return Ok(connected);
+                }
+                Err((e, u)) => {
+                    warn!(target: "PocketConnect", "Failed to connect to {}: {}", u, e);
+                    all_timed_out &= matches!(e, ConnectorError::Timeout);
+                    if e.is_fatal() {
+                        pinning_failure = Some(e);
+                    }
                 }
-                Err((e, u)) => warn!(target: "PocketConnect", "Failed to connect to {}: {}", u, e),
             }
         }
-        Err(ConnectorError::Custom(
-            "Failed to connect to any of the provided URLs".to_string(),
-        ))
+        // A server with an unexpected certificate makes every new attempt pointless
+        Err(pinning_failure.unwrap_or_else(|| {
+            if all_timed_out {
+                ConnectorError::Timeout
+            } else {
+                ConnectorError::Custom("Failed to connect to any of the provided URLs".to_string())
+            }
+        }))
     }
 }
 
@@ -53,19 +78,41 @@ impl Connector<State> for PocketConnect {
         &self,
         state: Arc<State>,
     ) -> ConnectorResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
-        let creds = state.ssid.clone();
+        let creds = state.ssid();
+        let headers = Self::custom_headers(&state);
         let url = state.default_connection_url.clone();
         if let Some(url) = url {
             info!(target: "PocketConnect", "Connecting to PocketOption at {}", url);
-            return try_connect(creds, url)
-                .await
-                .map_err(|e| ConnectorError::Custom(e.to_string()));
+            let stream = try_connect(
+                url.clone(),
+                &headers,
+                &state.pinned_certificates,
+                state.proxy.as_ref(),
+                state.connection_timeout,
+            )
+            .await
+            .map_err(|e| match e {
+                ConnectorError::Timeout | ConnectorError::CertificatePinning(_) => e,
+                e => ConnectorError::Custom(e.to_string()),
+            })?;
+            state.set_connected_to(&url);
+            return Ok(stream);
         }
         let urls = creds
             .servers()
             .await
             .map_err(|e| ConnectorError::Core(e.to_string()))?;
-        self.connect_multiple(urls, creds).await
+        let (stream, url) = self
+            .connect_multiple(
+                urls,
+                &headers,
+                &state.pinned_certificates,
+                state.proxy.as_ref(),
+                state.connection_timeout,
+            )
+            .await?;
+        state.set_connected_to(&url);
+        Ok(stream)
     }
 
     async fn disconnect(&self) -> ConnectorResult<()> {
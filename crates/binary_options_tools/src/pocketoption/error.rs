@@ -35,14 +35,68 @@ pub enum PocketError {
         duration: Duration,
     },
 
+    /// The connection handshake with the server didn't complete in time.
+    #[error("Connection timed out after {0:?}")]
+    ConnectionTimeout(Duration),
+
     #[error("General error: {0}")]
     General(String),
 
+    /// The operation is not supported by the platform.
+    #[error("Not supported: {0}")]
+    NotSupported(String),
+
+    /// A required environment variable is not set.
+    #[error("Missing environment variable: {0}")]
+    MissingEnvVar(String),
+
+    /// The configuration file is invalid.
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+
     #[error("Subscription error: {0}")]
     Subscription(#[from] SubscriptionError),
 
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    /// The history doesn't reach the start of the live stream.
+    #[error("History gap for {asset}: last candle is {gap:?} old, expected at most {max_gap:?}")]
+    HistoryGap {
+        asset: String,
+        gap: Duration,
+        max_gap: Duration,
+    },
+
+    /// A trade guard refused the trade.
+    #[error("Trade rejected: {0}")]
+    TradeRejected(String),
+
+    /// The server certificate doesn't match any of the pinned fingerprints.
+    #[error("Certificate pinning failure, server certificate fingerprint: {0}")]
+    CertificatePinningFailure(String),
+
+    /// The next Martingale step would trade more than the maximum amount.
+    #[error("Trade amount {amount} exceeds the maximum of {max}")]
+    MaxTradeAmountExceeded { amount: f64, max: f64 },
+
+    /// The SSID given to `set_demo` or `set_live` is for the other kind of account.
+    #[error("SSID demo flag mismatch: expected isDemo = {expected}, got {actual}")]
+    SsidDemoMismatch { expected: bool, actual: bool },
+
+    /// The deadline of a `DeadlineSubscriptionStream` was reached.
+    #[error("Stream deadline passed")]
+    DeadlinePassed,
+
+    /// The offset of a time-aligned subscription isn't lower than its duration.
+    #[error("Invalid offset {offset}s for a time-aligned subscription of {duration:?}")]
+    InvalidOffset { offset: u32, duration: Duration },
 }
 
 pub type PocketResult<T> = Result<T, PocketError>;
@@ -0,0 +1,328 @@
+//! Risk rules checked before every trade.
+//!
+//! Guards are registered with `PocketOption::set_trade_guard` and are checked in registration
+//! order by `PocketOption::trade`, the first rejection cancels the trade before anything is sent
+//! to the server. Guards keeping track of the trades are notified when a trade placed by the
+//! client is opened and when the server reports a closed deal.
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::pocketoption::{
+    state::State,
+    types::{Action, Deal},
+};
+
+/// A risk rule deciding whether a trade may be placed.
+#[async_trait]
+pub trait TradeGuard: Send + Sync {
+    /// Returns the reason of the rejection if the trade must not be placed.
+    async fn allow_trade(
+        &self,
+        asset: &str,
+        action: Action,
+        amount: f64,
+        time: u32,
+    ) -> Result<(), String>;
+
+    /// Called once a trade placed by the client is opened.
+    async fn on_trade_opened(&self, _deal: &Deal) {}
+
+    /// Called when the server reports a closed deal, it may not have been placed by the client
+    /// and the same deal may be reported again after a reconnection.
+    async fn on_trade_closed(&self, _deal: &Deal) {}
+}
+
+#[derive(Debug, Default)]
+struct DailyLoss {
+    day: Option<NaiveDate>,
+    profit: f64,
+    counted: HashSet<Uuid>,
+}
+
+/// Rejects the trades that could make the net loss of the deals closed today (UTC) exceed the
+/// limit, the amount of the trade counting as a potential loss.
+///
+/// The day is decided with the server clock once the guard is bound to the client state with
+/// `with_state`, which `PocketOption::set_daily_loss_limit` does, and with the local clock
+/// otherwise.
+#[derive(Debug)]
+pub struct MaxDailyLossGuard {
+    max_loss: f64,
+    today: Mutex<DailyLoss>,
+    /// Weak so the guard, stored in the state, doesn't keep it alive
+    state: Option<Weak<State>>,
+}
+
+/// Name of [`MaxDailyLossGuard`] in the risk management docs.
+pub type DailyLossLimitGuard = MaxDailyLossGuard;
+
+impl MaxDailyLossGuard {
+    /// Creates a guard allowing at most `max_loss` of net loss per day.
+    pub fn new(max_loss: f64) -> Self {
+        Self {
+            max_loss,
+            today: Mutex::new(DailyLoss::default()),
+            state: None,
+        }
+    }
+
+    /// Decides the current day with `State::corrected_now` instead of the local clock.
+    pub fn with_state(mut self, state: &Arc<State>) -> Self {
+        self.state = Some(Arc::downgrade(state));
+        self
+    }
+
+    async fn now(&self) -> DateTime<Utc> {
+        match self.state.as_ref().and_then(Weak::upgrade) {
+            Some(state) => state.corrected_now().await,
+            None => Utc::now(),
+        }
+    }
+
+    /// Net loss of the deals closed today, negative if the day is profitable.
+    pub async fn loss_today(&self) -> f64 {
+        let day = self.now().await.date_naive();
+        let today = self
+            .today
+            .lock()
+            .expect("Failed to acquire daily loss lock");
+        if today.day == Some(day) {
+            -today.profit
+        } else {
+            0.0
+        }
+    }
+
+    /// Counts the deals already closed in `state`, e.g. the ones closed before the guard was
+    /// registered. The deals already counted are skipped.
+    pub(crate) async fn load_closed_deals(&self, state: &State) {
+        for deal in state.trade_state.get_closed_deals().await.values() {
+            self.on_trade_closed(deal).await;
+        }
+    }
+}
+
+#[async_trait]
+impl TradeGuard for MaxDailyLossGuard {
+    async fn allow_trade(&self, _: &str, _: Action, amount: f64, _: u32) -> Result<(), String> {
+        let loss = self.loss_today().await;
+        if loss + amount > self.max_loss {
+            return Err(format!(
+                "Daily loss limit exceeded: lost {loss:.2} today, the trade risks {amount:.2}, limit is {:.2}",
+                self.max_loss
+            ));
+        }
+        Ok(())
+    }
+
+    async fn on_trade_closed(&self, deal: &Deal) {
+        let day = deal.close_timestamp.date_naive();
+        if day != self.now().await.date_naive() {
+            return;
+        }
+        let mut today = self
+            .today
+            .lock()
+            .expect("Failed to acquire daily loss lock");
+        if today.day != Some(day) {
+            *today = DailyLoss {
+                day: Some(day),
+                ..Default::default()
+            };
+        }
+        if today.counted.insert(deal.id) {
+            today.profit += deal.profit;
+        }
+    }
+}
+
+/// Rejects the trades while the maximum number of trades placed by the client is open.
+#[derive(Debug)]
+pub struct MaxConcurrentTradesGuard {
+    max_trades: usize,
+    opened: Mutex<HashSet<Uuid>>,
+}
+
+impl MaxConcurrentTradesGuard {
+    /// Creates a guard allowing at most `max_trades` open trades at the same time.
+    pub fn new(max_trades: usize) -> Self {
+        Self {
+            max_trades,
+            opened: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl TradeGuard for MaxConcurrentTradesGuard {
+    async fn allow_trade(&self, _: &str, _: Action, _: f64, _: u32) -> Result<(), String> {
+        let opened = self
+            .opened
+            .lock()
+            .expect("Failed to acquire opened trades lock")
+            .len();
+        if opened >= self.max_trades {
+            return Err(format!(
+                "Too many open trades: {opened} open, limit is {}",
+                self.max_trades
+            ));
+        }
+        Ok(())
+    }
+
+    async fn on_trade_opened(&self, deal: &Deal) {
+        self.opened
+            .lock()
+            .expect("Failed to acquire opened trades lock")
+            .insert(deal.id);
+    }
+
+    async fn on_trade_closed(&self, deal: &Deal) {
+        self.opened
+            .lock()
+            .expect("Failed to acquire opened trades lock")
+            .remove(&deal.id);
+    }
+}
+
+/// Rejects the trades placed less than `cooldown` after the previous one.
+#[derive(Debug)]
+pub struct CooldownGuard {
+    cooldown: Duration,
+    last_trade: Mutex<Option<Instant>>,
+}
+
+impl CooldownGuard {
+    /// Creates a guard requiring at least `cooldown` between two trades.
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            last_trade: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl TradeGuard for CooldownGuard {
+    async fn allow_trade(&self, _: &str, _: Action, _: f64, _: u32) -> Result<(), String> {
+        let last_trade = *self
+            .last_trade
+            .lock()
+            .expect("Failed to acquire last trade lock");
+        match last_trade {
+            Some(last_trade) if last_trade.elapsed() < self.cooldown => Err(format!(
+                "Cooldown active: last trade {:?} ago, cooldown is {:?}",
+                last_trade.elapsed(),
+                self.cooldown
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    async fn on_trade_opened(&self, _: &Deal) {
+        *self
+            .last_trade
+            .lock()
+            .expect("Failed to acquire last trade lock") = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{DateTime, Utc};
+
+    use super::{DailyLossLimitGuard, MaxDailyLossGuard, TradeGuard};
+    use crate::pocketoption::{
+        ssid::Ssid,
+        state::{State, StateBuilder},
+        types::{Action, Deal},
+    };
+
+    fn state() -> Arc<State> {
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        Arc::new(StateBuilder::default().ssid(ssid).build().unwrap())
+    }
+
+    fn deal(profit: f64, close_timestamp: DateTime<Utc>) -> Deal {
+        Deal {
+            open_timestamp: close_timestamp - chrono::Duration::seconds(60),
+            close_timestamp,
+            amount: 10.0,
+            close_price: 1.0,
+            ..Deal::mock(profit)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_daily_loss_guard() {
+        let state = state();
+        let guard = MaxDailyLossGuard::new(55.0);
+        // Losses of the previous days are not counted, the deals closed before the guard was
+        // created are
+        state
+            .trade_state
+            .update_closed_deals(vec![
+                deal(-100.0, Utc::now() - chrono::Duration::days(1)),
+                deal(-10.0, Utc::now()),
+            ])
+            .await;
+        guard.load_closed_deals(&state).await;
+        guard.load_closed_deals(&state).await;
+        assert_eq!(guard.loss_today().await, 10.0);
+
+        for _ in 0..4 {
+            guard
+                .allow_trade("EURUSD_otc", Action::Call, 10.0, 60)
+                .await
+                .unwrap();
+            guard.on_trade_closed(&deal(-10.0, Utc::now())).await;
+        }
+        assert_eq!(guard.loss_today().await, 50.0);
+        let error = guard
+            .allow_trade("EURUSD_otc", Action::Call, 10.0, 60)
+            .await
+            .unwrap_err();
+        assert!(error.starts_with("Daily loss limit exceeded"), "{error}");
+        // A smaller trade still fits in the limit, and the wins offset the losses
+        guard
+            .allow_trade("EURUSD_otc", Action::Call, 5.0, 60)
+            .await
+            .unwrap();
+        guard.on_trade_closed(&deal(9.2, Utc::now())).await;
+        guard
+            .allow_trade("EURUSD_otc", Action::Call, 10.0, 60)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_max_daily_loss_guard_server_day() {
+        let state = state();
+        // The server is already a day ahead of the local clock
+        state.server_time.write().await.offset = chrono::Duration::days(1);
+        let guard = DailyLossLimitGuard::new(15.0).with_state(&state);
+        let server_now = state.corrected_now().await;
+
+        guard.on_trade_closed(&deal(-10.0, Utc::now())).await;
+        assert_eq!(guard.loss_today().await, 0.0);
+        guard.on_trade_closed(&deal(-10.0, server_now)).await;
+        assert_eq!(guard.loss_today().await, 10.0);
+        assert!(
+            guard
+                .allow_trade("EURUSD_otc", Action::Call, 10.0, 60)
+                .await
+                .is_err()
+        );
+    }
+}
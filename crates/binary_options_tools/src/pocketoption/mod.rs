@@ -1,8 +1,12 @@
 pub mod candle;
 pub mod connect;
 pub mod error;
+pub mod guards;
 pub mod modules;
+pub mod proxy;
 pub mod regions;
+#[cfg(feature = "trade_simulation")]
+pub mod simulation;
 pub mod ssid;
 pub mod state;
 
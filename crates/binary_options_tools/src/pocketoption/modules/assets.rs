@@ -36,7 +36,19 @@ impl LightweightModule<State> for AssetsModule {
             if let Message::Binary(text) = &*msg {
                 if let Ok(assets) = serde_json::from_slice::<Assets>(text) {
                     debug!("Loaded assets: {:?}", assets.names());
-                    self.state.set_assets(assets).await;
+                    let payouts_changed = {
+                        let previous = self.state.assets.read().await;
+                        self.state.record_payout_changes(previous.as_ref(), &assets);
+                        previous
+                            .as_ref()
+                            .is_none_or(|previous| previous.payouts() != assets.payouts())
+                    };
+                    if payouts_changed {
+                        self.state.set_assets(assets.clone()).await;
+                        self.state.notify_payout_subscribers(&assets);
+                    } else {
+                        self.state.set_assets(assets).await;
+                    }
                 } else {
                     warn!("Failed to parse assets message: {:?}", text);
                 }
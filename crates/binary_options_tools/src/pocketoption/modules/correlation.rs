@@ -0,0 +1,349 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, RwLock as SyncRwLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use binary_options_tools_core_pre::{
+    error::{CoreError, CoreResult},
+    reimports::{AsyncReceiver, AsyncSender, Message},
+    traits::{LightweightModule, Rule},
+};
+use serde::Serialize;
+
+use crate::pocketoption::{
+    state::State,
+    types::{StreamData, TwoStepRule},
+};
+
+/// Minimum number of shared closes needed to compute a correlation.
+const MIN_CORRELATION_SAMPLES: usize = 3;
+
+/// Rolling closes of the subscribed assets, used to compute their pairwise correlation.
+///
+/// The price updates are grouped in candles of `period`, only the closes of the last `window`
+/// candles of every asset are kept.
+#[derive(Debug)]
+pub struct CorrelationTracker {
+    window: usize,
+    period: Duration,
+    /// Closes of every asset keyed by candle index (timestamp divided by the period), oldest first.
+    closes: SyncRwLock<HashMap<String, VecDeque<(i64, f64)>>>,
+}
+
+impl CorrelationTracker {
+    pub fn new(window: usize, period: Duration) -> Self {
+        Self {
+            window,
+            period,
+            closes: SyncRwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Records a price update, it becomes the close of the candle containing `timestamp`.
+    ///
+    /// Updates older than the last candle of the asset are ignored.
+    pub fn update(&self, asset: &str, timestamp: f64, price: f64) {
+        let index = (timestamp / self.period.as_secs_f64().max(f64::EPSILON)).floor() as i64;
+        let mut closes = self
+            .closes
+            .write()
+            .expect("Failed to acquire correlation lock");
+        let closes = closes.entry(asset.to_string()).or_default();
+        match closes.back_mut() {
+            Some((last, close)) if *last == index => *close = price,
+            Some((last, _)) if *last > index => {}
+            _ => {
+                closes.push_back((index, price));
+                while closes.len() > self.window {
+                    closes.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Forgets the closes of every asset.
+    pub fn clear(&self) {
+        self.closes
+            .write()
+            .expect("Failed to acquire correlation lock")
+            .clear();
+    }
+
+    /// Pearson's r of the closes of the candles both assets share.
+    ///
+    /// Returns `None` if an asset is unknown, if they share less than 3 candles or if the
+    /// price of one of them didn't move.
+    pub fn correlation(&self, asset_a: &str, asset_b: &str) -> Option<f64> {
+        let closes = self
+            .closes
+            .read()
+            .expect("Failed to acquire correlation lock");
+        pearson_aligned(closes.get(asset_a)?, closes.get(asset_b)?)
+    }
+
+    /// Correlation of every pair of assets with enough shared candles, the assets of a pair
+    /// are sorted by name.
+    pub fn all_correlations(&self) -> HashMap<(String, String), f64> {
+        let closes = self
+            .closes
+            .read()
+            .expect("Failed to acquire correlation lock");
+        let mut assets: Vec<_> = closes.keys().collect();
+        assets.sort();
+        let mut correlations = HashMap::new();
+        for (i, asset_a) in assets.iter().enumerate() {
+            for asset_b in &assets[i + 1..] {
+                if let Some(r) = pearson_aligned(&closes[*asset_a], &closes[*asset_b]) {
+                    correlations.insert((asset_a.to_string(), asset_b.to_string()), r);
+                }
+            }
+        }
+        correlations
+    }
+}
+
+/// Square correlation matrix, `matrix[i][j]` is the correlation of `assets[i]` and `assets[j]`.
+///
+/// Unlike the map of `CorrelationTracker::all_correlations` it serializes cleanly to JSON.
+/// Pairs without enough shared candles are `NaN`, serialized as `null`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CorrelationMatrix {
+    /// Assets of the rows and columns, sorted by name.
+    pub assets: Vec<String>,
+    pub matrix: Vec<Vec<f64>>,
+}
+
+impl CorrelationMatrix {
+    /// Builds the matrix of the assets found in `map`, the diagonal is always 1.
+    pub fn from_map(map: &HashMap<(String, String), f64>) -> Self {
+        let mut assets: Vec<String> = map
+            .keys()
+            .flat_map(|(a, b)| [a.clone(), b.clone()])
+            .collect();
+        assets.sort();
+        assets.dedup();
+        let position = |asset: &String| assets.binary_search(asset).ok();
+        let mut matrix = vec![vec![f64::NAN; assets.len()]; assets.len()];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        for ((a, b), r) in map {
+            if let (Some(i), Some(j)) = (position(a), position(b)) {
+                matrix[i][j] = *r;
+                matrix[j][i] = *r;
+            }
+        }
+        Self { assets, matrix }
+    }
+
+    /// Formats the matrix as CSV with the assets as header and first column, unknown
+    /// correlations are left empty.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!(",{}\n", self.assets.join(","));
+        for (asset, row) in self.assets.iter().zip(&self.matrix) {
+            let values: Vec<String> = row
+                .iter()
+                .map(|r| {
+                    if r.is_nan() {
+                        String::new()
+                    } else {
+                        r.to_string()
+                    }
+                })
+                .collect();
+            csv.push_str(&format!("{asset},{}\n", values.join(",")));
+        }
+        csv
+    }
+}
+
+/// Pearson's r of the closes sharing the same candle index.
+fn pearson_aligned(a: &VecDeque<(i64, f64)>, b: &VecDeque<(i64, f64)>) -> Option<f64> {
+    let (mut xs, mut ys) = (Vec::new(), Vec::new());
+    let (mut a, mut b) = (a.iter().peekable(), b.iter().peekable());
+    while let (Some((index_a, x)), Some((index_b, y))) = (a.peek(), b.peek()) {
+        match index_a.cmp(index_b) {
+            std::cmp::Ordering::Less => {
+                a.next();
+            }
+            std::cmp::Ordering::Greater => {
+                b.next();
+            }
+            std::cmp::Ordering::Equal => {
+                xs.push(*x);
+                ys.push(*y);
+                a.next();
+                b.next();
+            }
+        }
+    }
+    pearson(&xs, &ys)
+}
+
+fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    if xs.len() < MIN_CORRELATION_SAMPLES {
+        return None;
+    }
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some((cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0))
+}
+
+/// Feeds the price updates of the subscribed assets to the `CorrelationTracker` of the state.
+pub struct CorrelationModule {
+    receiver: AsyncReceiver<Arc<Message>>,
+    state: Arc<State>,
+}
+
+#[async_trait]
+impl LightweightModule<State> for CorrelationModule {
+    fn new(
+        state: Arc<State>,
+        _: AsyncSender<Message>,
+        ws_receiver: AsyncReceiver<Arc<Message>>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            receiver: ws_receiver,
+            state,
+        }
+    }
+
+    async fn run(&mut self) -> CoreResult<()> {
+        while let Ok(msg) = self.receiver.recv().await {
+            if let Message::Binary(data) = &*msg
+                && let Ok(data) = serde_json::from_slice::<StreamData>(data)
+            {
+                self.state
+                    .correlation
+                    .update(&data.symbol, data.timestamp, data.price);
+            }
+        }
+        Err(CoreError::LightweightModuleLoop(
+            "CorrelationModule".to_string(),
+        ))
+    }
+
+    fn rule() -> Box<dyn Rule + Send + Sync> {
+        Box::new(TwoStepRule::new(r#"451-["updateStream","#))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::*;
+
+    const SAMPLES: usize = 100;
+
+    /// Asset name and the function giving its price at `x` in `[0, 1)`.
+    type Series<'a> = (&'a str, fn(f64) -> f64);
+
+    fn tracker_with(series: &[Series]) -> CorrelationTracker {
+        let tracker = CorrelationTracker::new(SAMPLES, Duration::from_secs(1));
+        for i in 0..SAMPLES {
+            let x = i as f64 / SAMPLES as f64;
+            for (asset, price) in series {
+                tracker.update(asset, i as f64, price(x));
+            }
+        }
+        tracker
+    }
+
+    #[test]
+    fn test_correlation() {
+        let tracker = tracker_with(&[
+            ("A", |x| (x * TAU).sin()),
+            ("B", |x| 2.0 * (x * TAU).sin() + 1.0),
+            ("C", |x| -(x * TAU).sin()),
+            ("D", |x| (x * TAU).cos()),
+        ]);
+
+        assert!((tracker.correlation("A", "B").unwrap() - 1.0).abs() < 0.01);
+        assert!((tracker.correlation("A", "C").unwrap() + 1.0).abs() < 0.01);
+        assert!(tracker.correlation("A", "D").unwrap().abs() < 0.01);
+        assert_eq!(tracker.correlation("A", "E"), None);
+
+        let all = tracker.all_correlations();
+        assert_eq!(all.len(), 6);
+        assert!((all[&("B".to_string(), "C".to_string())] + 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_correlation_window() {
+        let tracker = CorrelationTracker::new(3, Duration::from_secs(60));
+        // Only the last update of a candle is kept
+        tracker.update("A", 0.0, 100.0);
+        tracker.update("A", 30.0, 1.0);
+        for (i, price) in [2.0, 3.0, 4.0].into_iter().enumerate() {
+            tracker.update("A", 60.0 * (i + 1) as f64, price);
+        }
+        for (i, price) in [1.0, 2.0, 4.0, 8.0].into_iter().enumerate() {
+            tracker.update("B", 60.0 * i as f64, price);
+        }
+        // Only the last 3 candles are shared, flat prices have no correlation
+        let r = tracker.correlation("A", "B").unwrap();
+        assert!((r - pearson(&[2.0, 3.0, 4.0], &[2.0, 4.0, 8.0]).unwrap()).abs() < 1e-9);
+        for i in 1..=3 {
+            tracker.update("C", 60.0 * i as f64, 1.0);
+        }
+        assert_eq!(tracker.correlation("A", "C"), None);
+    }
+
+    #[test]
+    fn test_correlation_matrix() {
+        let tracker = tracker_with(&[
+            ("A", |x| (x * TAU).sin()),
+            ("B", |x| 2.0 * (x * TAU).sin() + 1.0),
+            ("C", |x| -(x * TAU).sin()),
+        ]);
+        let matrix = CorrelationMatrix::from_map(&tracker.all_correlations());
+
+        assert_eq!(matrix.assets, ["A", "B", "C"]);
+        for i in 0..3 {
+            assert_eq!(matrix.matrix[i][i], 1.0);
+            for j in 0..3 {
+                assert_eq!(matrix.matrix[i][j], matrix.matrix[j][i]);
+            }
+        }
+        assert!((matrix.matrix[0][2] + 1.0).abs() < 0.01);
+
+        let csv = matrix.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], ",A,B,C");
+        assert!(lines[1].starts_with("A,1,"));
+        let json: serde_json::Value = serde_json::to_value(&matrix).unwrap();
+        assert_eq!(json["matrix"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_correlation_matrix_missing_pair() {
+        let map = HashMap::from([
+            (("A".to_string(), "B".to_string()), 0.5),
+            (("B".to_string(), "C".to_string()), 0.25),
+        ]);
+        let matrix = CorrelationMatrix::from_map(&map);
+
+        assert!(matrix.matrix[0][2].is_nan() && matrix.matrix[2][0].is_nan());
+        assert_eq!(matrix.to_csv().lines().nth(1), Some("A,1,0.5,"));
+        let json = serde_json::to_string(&matrix).unwrap();
+        assert!(json.contains("[1.0,0.5,null]"), "{json}");
+    }
+}
@@ -1,18 +1,19 @@
 use std::{
+    collections::HashSet,
     sync::{
-        Arc,
-        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
     },
     time::Duration,
 };
 
 use async_trait::async_trait;
 use binary_options_tools_core_pre::{
-    error::CoreError,
-    reimports::{AsyncReceiver, AsyncSender, Message},
+    error::{CoreError, CoreResult},
+    reimports::{AsyncReceiver, AsyncSender, Message, bounded_async},
     traits::{ApiModule, Rule},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::info;
 use uuid::Uuid;
 
@@ -21,20 +22,151 @@ use crate::pocketoption::{
     state::State,
     types::Deal,
 };
+use crate::utils::command_bus::{self, CommandBus};
 
 const UPDATE_OPENED_DEALS: &str = r#"451-["updateOpenedDeals","#;
 const UPDATE_CLOSED_DEALS: &str = r#"451-["updateClosedDeals","#;
 const SUCCESS_CLOSE_ORDER: &str = r#"451-["successcloseOrder","#;
 
+/// Size of the buffer of every deal events receiver, events are dropped for slow receivers.
+const DEAL_EVENTS_BUFFER: usize = 64;
+
 #[derive(Debug)]
 pub enum Command {
     CheckResult(Uuid),
+    WatchOpen,
+    /// Sent when the caller of the request with this id stopped waiting, never answered
+    Cancel(Uuid),
 }
 
 #[derive(Debug)]
 pub enum CommandResponse {
     CheckResult(Box<Deal>),
     DealNotFound(Uuid),
+    WatchOpen(AsyncReceiver<DealEvent>),
+}
+
+/// Change of state of a deal, as announced by the server.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum DealEvent {
+    Opened(Uuid, Deal),
+    Closed(Uuid, Deal),
+}
+
+/// Win rate and profit factor of the closed deals, updated for every deal the server closes.
+///
+/// Deals closing with a zero profit are draws, they aren't counted and keep the streaks.
+/// Every deal is only counted once, even when the server reports it again.
+#[derive(Debug, Default)]
+pub struct WinRateTracker {
+    /// Ids of the deals already counted.
+    counted: Mutex<HashSet<Uuid>>,
+    wins: AtomicU64,
+    losses: AtomicU64,
+    /// Sum of the profits of the winning deals, as `f64` bits.
+    total_profit: AtomicU64,
+    /// Sum of the losses of the losing deals as a positive value, as `f64` bits.
+    total_loss: AtomicU64,
+    consecutive_wins: AtomicU32,
+    consecutive_losses: AtomicU32,
+}
+
+/// Point-in-time copy of the [`WinRateTracker`] statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct WinRateSnapshot {
+    pub wins: u64,
+    pub losses: u64,
+    pub win_rate: f64,
+    pub profit_factor: f64,
+    pub total_profit: f64,
+    pub total_loss: f64,
+    pub consecutive_wins: u32,
+    pub consecutive_losses: u32,
+}
+
+impl WinRateTracker {
+    /// Counts a closed deal, returns false if it was already counted.
+    pub fn record(&self, deal: &Deal) -> bool {
+        if !self
+            .counted
+            .lock()
+            .expect("Failed to acquire counted deals lock")
+            .insert(deal.id)
+        {
+            return false;
+        }
+        if deal.profit > 0.0 {
+            self.wins.fetch_add(1, Ordering::Relaxed);
+            add_f64(&self.total_profit, deal.profit);
+            self.consecutive_wins.fetch_add(1, Ordering::Relaxed);
+            self.consecutive_losses.store(0, Ordering::Relaxed);
+        } else if deal.profit < 0.0 {
+            self.losses.fetch_add(1, Ordering::Relaxed);
+            add_f64(&self.total_loss, -deal.profit);
+            self.consecutive_losses.fetch_add(1, Ordering::Relaxed);
+            self.consecutive_wins.store(0, Ordering::Relaxed);
+        }
+        true
+    }
+
+    /// Share of winning deals among the won and lost ones, `0.0` before the first one.
+    pub fn win_rate(&self) -> f64 {
+        let wins = self.wins.load(Ordering::Relaxed);
+        let total = wins + self.losses.load(Ordering::Relaxed);
+        if total == 0 {
+            0.0
+        } else {
+            wins as f64 / total as f64
+        }
+    }
+
+    /// Total profit divided by total loss, infinite if there was a win but no loss yet and
+    /// `0.0` without any win.
+    pub fn profit_factor(&self) -> f64 {
+        let profit = load_f64(&self.total_profit);
+        let loss = load_f64(&self.total_loss);
+        if loss > 0.0 {
+            profit / loss
+        } else if profit > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        }
+    }
+
+    /// Number of wins since the last loss.
+    pub fn consecutive_wins(&self) -> u32 {
+        self.consecutive_wins.load(Ordering::Relaxed)
+    }
+
+    /// Number of losses since the last win.
+    pub fn consecutive_losses(&self) -> u32 {
+        self.consecutive_losses.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> WinRateSnapshot {
+        WinRateSnapshot {
+            wins: self.wins.load(Ordering::Relaxed),
+            losses: self.losses.load(Ordering::Relaxed),
+            win_rate: self.win_rate(),
+            profit_factor: self.profit_factor(),
+            total_profit: load_f64(&self.total_profit),
+            total_loss: load_f64(&self.total_loss),
+            consecutive_wins: self.consecutive_wins(),
+            consecutive_losses: self.consecutive_losses(),
+        }
+    }
+}
+
+fn load_f64(value: &AtomicU64) -> f64 {
+    f64::from_bits(value.load(Ordering::Relaxed))
+}
+
+fn add_f64(value: &AtomicU64, amount: f64) {
+    // `fetch_update` only fails if the closure returns `None`
+    let _ = value.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+        Some((f64::from_bits(bits) + amount).to_bits())
+    });
 }
 
 enum ExpectedMessage {
@@ -53,29 +185,27 @@ struct CloseOrder {
 
 #[derive(Clone)]
 pub struct DealsHandle {
-    sender: AsyncSender<Command>,
-    receiver: AsyncReceiver<CommandResponse>,
+    bus: CommandBus<Command, CommandResponse>,
 }
 
 impl DealsHandle {
     pub async fn check_result(&self, trade_id: Uuid) -> PocketResult<Deal> {
-        self.sender
-            .send(Command::CheckResult(trade_id))
-            .await
-            .map_err(CoreError::from)?;
-        loop {
-            match self.receiver.recv().await {
-                Ok(CommandResponse::CheckResult(deal)) => {
-                    if trade_id == deal.id {
-                        return Ok(*deal);
-                    } else {
-                        // If the request ID does not match, continue waiting for the correct response
-                        continue;
-                    }
-                }
-                Ok(CommandResponse::DealNotFound(id)) => return Err(PocketError::DealNotFound(id)),
-                Err(e) => return Err(CoreError::from(e).into()),
-            }
+        match self.bus.send(Command::CheckResult(trade_id)).await? {
+            CommandResponse::CheckResult(deal) => Ok(*deal),
+            CommandResponse::DealNotFound(id) => Err(PocketError::DealNotFound(id)),
+            response => Err(PocketError::General(format!(
+                "Unexpected response to check_result: {response:?}"
+            ))),
+        }
+    }
+
+    /// Returns a receiver of the [`DealEvent`]s emitted from now on.
+    pub async fn watch_open(&self) -> PocketResult<AsyncReceiver<DealEvent>> {
+        match self.bus.send(Command::WatchOpen).await? {
+            CommandResponse::WatchOpen(receiver) => Ok(receiver),
+            response => Err(PocketError::General(format!(
+                "Unexpected response to watch_open: {response:?}"
+            ))),
         }
     }
 
@@ -84,39 +214,13 @@ impl DealsHandle {
         trade_id: Uuid,
         timeout: Duration,
     ) -> PocketResult<Deal> {
-        self.sender
-            .send(Command::CheckResult(trade_id))
+        tokio::time::timeout(timeout, self.check_result(trade_id))
             .await
-            .map_err(CoreError::from)?;
-
-        let timeout_future = tokio::time::sleep(timeout);
-        tokio::pin!(timeout_future);
-
-        loop {
-            tokio::select! {
-                result = self.receiver.recv() => {
-                    match result {
-                        Ok(CommandResponse::CheckResult(deal)) => {
-                            if trade_id == deal.id {
-                                return Ok(*deal);
-                            } else {
-                                // If the request ID does not match, continue waiting for the correct response
-                                continue;
-                            }
-                        },
-                        Ok(CommandResponse::DealNotFound(id)) => return Err(PocketError::DealNotFound(id)),
-                        Err(e) => return Err(CoreError::from(e).into()),
-                    }
-                }
-                _ = &mut timeout_future => {
-                    return Err(PocketError::Timeout {
-                        task: "check_result".to_string(),
-                        context: format!("Waiting for trade '{trade_id}' result"),
-                        duration: timeout,
-                    });
-                }
-            }
-        }
+            .map_err(|_| PocketError::Timeout {
+                task: "check_result".to_string(),
+                context: format!("Waiting for trade '{trade_id}' result"),
+                duration: timeout,
+            })?
     }
 }
 
@@ -125,15 +229,78 @@ impl DealsHandle {
 pub struct DealsApiModule {
     state: Arc<State>,
     ws_receiver: AsyncReceiver<Arc<Message>>,
-    command_receiver: AsyncReceiver<Command>,
-    command_responder: AsyncSender<CommandResponse>,
-    waitlist: Vec<Uuid>,
+    command_receiver: AsyncReceiver<command_bus::Command<Command>>,
+    command_responder: AsyncSender<command_bus::Command<CommandResponse>>,
+    /// Pending result requests, as (request id, trade id)
+    waitlist: Vec<(Uuid, Uuid)>,
+    /// Receivers of the deal events, the closed ones are removed on the next event
+    watchers: Vec<AsyncSender<DealEvent>>,
+    /// Deals already announced as opened to the watchers
+    announced: HashSet<Uuid>,
+}
+
+impl DealsApiModule {
+    async fn respond(&self, request_id: Uuid, response: CommandResponse) -> CoreResult<()> {
+        self.command_responder
+            .send(command_bus::Command::from_id(request_id, response, None))
+            .await?;
+        Ok(())
+    }
+
+    /// Answers the pending result requests of the trades that are now closed.
+    async fn answer_waitlist(&mut self) -> CoreResult<()> {
+        let mut remaining = Vec::with_capacity(self.waitlist.len());
+        for (request_id, trade_id) in std::mem::take(&mut self.waitlist) {
+            match self.state.trade_state.get_closed_deal(trade_id).await {
+                Some(deal) => {
+                    info!("Trade closed: {:?}", deal);
+                    self.respond(request_id, CommandResponse::CheckResult(Box::new(deal)))
+                        .await?;
+                }
+                None => remaining.push((request_id, trade_id)),
+            }
+        }
+        self.waitlist = remaining;
+        Ok(())
+    }
+
+    /// Sends the event to every watcher, slow watchers with a full buffer miss it.
+    fn notify_watchers(&mut self, event: DealEvent) {
+        self.watchers
+            .retain(|watcher| watcher.try_send(event.clone()).is_ok());
+    }
+
+    /// Emits an `Opened` event for every deal that wasn't announced yet.
+    fn announce_opened(&mut self, deals: &[Deal]) {
+        for deal in deals {
+            if self.announced.insert(deal.id) {
+                self.notify_watchers(DealEvent::Opened(deal.id, deal.clone()));
+            }
+        }
+    }
+
+    /// Updates the closed deals and emits a `Closed` event for the newly closed ones.
+    ///
+    /// The deals the server sends again, like after a reconnection, are only announced once even
+    /// if the closed deals were cleared in between.
+    async fn close_deals(&mut self, deals: Vec<Deal>) {
+        self.state
+            .trade_state
+            .update_closed_deals(deals.clone())
+            .await;
+        for deal in deals {
+            if self.state.notify_trade_closed(&deal).await {
+                self.announced.remove(&deal.id);
+                self.notify_watchers(DealEvent::Closed(deal.id, deal));
+            }
+        }
+    }
 }
 
 #[async_trait]
 impl ApiModule<State> for DealsApiModule {
-    type Command = Command;
-    type CommandResponse = CommandResponse;
+    type Command = command_bus::Command<Command>;
+    type CommandResponse = command_bus::Command<CommandResponse>;
     type Handle = DealsHandle;
 
     fn new(
@@ -149,6 +316,8 @@ impl ApiModule<State> for DealsApiModule {
             command_receiver,
             command_responder,
             waitlist: Vec::new(),
+            watchers: Vec::new(),
+            announced: HashSet::new(),
         }
     }
 
@@ -156,7 +325,9 @@ impl ApiModule<State> for DealsApiModule {
         sender: AsyncSender<Self::Command>,
         receiver: AsyncReceiver<Self::CommandResponse>,
     ) -> Self::Handle {
-        DealsHandle { sender, receiver }
+        DealsHandle {
+            bus: CommandBus::new(sender, receiver).with_cancel(Command::Cancel),
+        }
     }
 
     async fn run(&mut self) -> binary_options_tools_core_pre::error::CoreResult<()> {
@@ -190,6 +361,7 @@ impl ApiModule<State> for DealsApiModule {
                                     // Handle UpdateOpenedDeals
                                     match serde_json::from_slice::<Vec<Deal>>(data) {
                                         Ok(deals) => {
+                                            self.announce_opened(&deals);
                                             self.state.trade_state.update_opened_deals(deals).await;
                                         },
                                         Err(e) => return Err(CoreError::from(e)),
@@ -199,17 +371,8 @@ impl ApiModule<State> for DealsApiModule {
                                     // Handle UpdateClosedDeals
                                     match serde_json::from_slice::<Vec<Deal>>(data) {
                                         Ok(deals) => {
-                                            self.state.trade_state.update_closed_deals(deals).await;
-                                            // Check if some trades of the waitlist are now closed
-                                            let mut remove = Vec::new();
-                                            for id in &self.waitlist {
-                                                if let Some(deal) = self.state.trade_state.get_closed_deal(*id).await {
-                                                    info!("Trade closed: {:?}", deal);
-                                                    self.command_responder.send(CommandResponse::CheckResult(Box::new(deal))).await?;
-                                                    remove.push(*id);
-                                                }
-                                            }
-                                            self.waitlist.retain(|id| !remove.contains(id));
+                                            self.close_deals(deals).await;
+                                            self.answer_waitlist().await?;
                                         },
                                         Err(e) => return Err(CoreError::from(e)),
                                     }
@@ -218,18 +381,8 @@ impl ApiModule<State> for DealsApiModule {
                                     // Handle SuccessCloseOrder
                                     match serde_json::from_slice::<CloseOrder>(data) {
                                         Ok(close_order) => {
-                                            self.state.trade_state.update_closed_deals(close_order.deals).await;
-                                            // Check if some trades of the waitlist are now closed
-                                            let mut remove = Vec::new();
-                                            for id in &self.waitlist {
-                                                if let Some(deal) = self.state.trade_state.get_closed_deal(*id).await {
-                                                    info!("Trade closed: {:?}", deal);
-                                                    self.command_responder.send(CommandResponse::CheckResult(Box::new(deal))).await?;
-                                                    remove.push(*id);
-                                                }
-                                            }
-                                            self.waitlist.retain(|id| !remove.contains(id));
-
+                                            self.close_deals(close_order.deals).await;
+                                            self.answer_waitlist().await?;
                                         },
                                         Err(e) => return Err(CoreError::from(e)),
                                     }
@@ -243,21 +396,30 @@ impl ApiModule<State> for DealsApiModule {
 
                 }
                 Ok(cmd) = self.command_receiver.recv() => {
-                    match cmd {
+                    let request_id = cmd.id();
+                    match cmd.into_data() {
                         Command::CheckResult(trade_id) => {
                             if self.state.trade_state.contains_opened_deal(trade_id).await {
                                 // If the deal is still opened, add it to the waitlist
-                                self.waitlist.push(trade_id);
+                                self.waitlist.push((request_id, trade_id));
                             } else if let Some(deal) = self.state.trade_state.get_closed_deal(trade_id).await {
                                 // If the deal is already closed, send the result immediately
-                                self.command_responder.send(CommandResponse::CheckResult(Box::new(deal))).await?;
+                                self.respond(request_id, CommandResponse::CheckResult(Box::new(deal))).await?;
                             } else {
                                 // If the deal is not found, send a DealNotFound response
-                                self.command_responder.send(CommandResponse::DealNotFound(trade_id)).await?;
+                                self.respond(request_id, CommandResponse::DealNotFound(trade_id)).await?;
                             }
                             // Implement logic to check the result of a trade
                             // For example, wait for the deal to close and return the result
                         }
+                        Command::WatchOpen => {
+                            let (sender, receiver) = bounded_async(DEAL_EVENTS_BUFFER);
+                            self.watchers.push(sender);
+                            self.respond(request_id, CommandResponse::WatchOpen(receiver)).await?;
+                        }
+                        Command::Cancel(request_id) => {
+                            self.waitlist.retain(|(id, _)| *id != request_id);
+                        }
                     }
                 }
             }
@@ -326,3 +488,128 @@ impl Rule for DealsUpdateRule {
         self.valid.store(false, Ordering::SeqCst)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use binary_options_tools_core_pre::{reimports::bounded_async, traits::ApiModule};
+
+    use super::{DealEvent, DealsApiModule, WinRateTracker};
+    use crate::pocketoption::{
+        error::PocketError,
+        ssid::Ssid,
+        state::{State, StateBuilder},
+        types::Deal,
+    };
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_handle_send_sync() {
+        // This will fail to compile if the handle is not Send + Sync
+        assert_send_sync::<<DealsApiModule as ApiModule<State>>::Handle>();
+    }
+
+    #[tokio::test]
+    async fn test_check_result_timeout_leaves_waitlist() {
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        let state = Arc::new(StateBuilder::default().ssid(ssid).build().unwrap());
+        let deal = Deal::mock(0.0);
+        state.trade_state.add_opened_deal(deal.clone()).await;
+        let (command_sender, command_receiver) = bounded_async(8);
+        let (response_sender, response_receiver) = bounded_async(8);
+        let (_message_sender, message_receiver) = bounded_async(8);
+        let (ws_sender, _ws_receiver) = bounded_async(8);
+        let mut module = DealsApiModule::new(
+            state,
+            command_receiver,
+            response_sender,
+            message_receiver,
+            ws_sender,
+        );
+        let handle = DealsApiModule::create_handle(command_sender, response_receiver);
+
+        let (result, _) = tokio::join!(
+            handle.check_result_with_timeout(deal.id, Duration::from_millis(50)),
+            tokio::time::timeout(Duration::from_millis(200), module.run())
+        );
+        assert!(matches!(result, Err(PocketError::Timeout { .. })));
+        assert!(module.waitlist.is_empty());
+    }
+
+    #[test]
+    fn test_win_rate_tracker() {
+        let tracker = WinRateTracker::default();
+        assert_eq!(tracker.win_rate(), 0.0);
+        assert_eq!(tracker.profit_factor(), 0.0);
+
+        for _ in 0..5 {
+            tracker.record(&Deal::mock(-1.0));
+        }
+        for _ in 0..10 {
+            tracker.record(&Deal::mock(0.92));
+        }
+        // Draws don't count
+        tracker.record(&Deal::mock(0.0));
+
+        let stats = tracker.snapshot();
+        assert_eq!(stats.wins, 10);
+        assert_eq!(stats.losses, 5);
+        assert!((stats.win_rate - 0.667).abs() < 0.001);
+        assert!(stats.profit_factor > 1.0);
+        assert!((stats.profit_factor - 9.2 / 5.0).abs() < 1e-9);
+        assert_eq!(stats.consecutive_wins, 10);
+        assert_eq!(stats.consecutive_losses, 0);
+
+        let deal = Deal::mock(-1.0);
+        assert!(tracker.record(&deal));
+        assert_eq!(tracker.consecutive_wins(), 0);
+        assert_eq!(tracker.consecutive_losses(), 1);
+        // Reported again by the server
+        assert!(!tracker.record(&deal));
+        assert_eq!(tracker.snapshot().losses, 6);
+        assert_eq!(tracker.consecutive_losses(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_closed_deal_announced_once() {
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        let state = Arc::new(StateBuilder::default().ssid(ssid).build().unwrap());
+        let (_command_sender, command_receiver) = bounded_async(8);
+        let (response_sender, _response_receiver) = bounded_async(8);
+        let (_message_sender, message_receiver) = bounded_async(8);
+        let (ws_sender, _ws_receiver) = bounded_async(8);
+        let mut module = DealsApiModule::new(
+            state.clone(),
+            command_receiver,
+            response_sender,
+            message_receiver,
+            ws_sender,
+        );
+        let (watcher, events) = bounded_async(8);
+        module.watchers.push(watcher);
+
+        let deal = Deal::mock(0.92);
+        module.close_deals(vec![deal.clone()]).await;
+        state.trade_state.clear_closed_deals().await;
+        // Sent again by the server after a reconnection
+        module.close_deals(vec![deal.clone()]).await;
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events.recv().await.unwrap(),
+            DealEvent::Closed(deal.id, deal.clone())
+        );
+        assert_eq!(state.win_rate.snapshot().wins, 1);
+        assert!(
+            state
+                .trade_state
+                .get_closed_deals()
+                .await
+                .contains_key(&deal.id)
+        );
+    }
+}
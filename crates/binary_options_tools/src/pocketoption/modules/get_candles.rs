@@ -6,6 +6,7 @@ use binary_options_tools_core_pre::{
     reimports::{AsyncReceiver, AsyncSender, Message},
     traits::{ApiModule, Rule},
 };
+use chrono::{DateTime, Utc};
 use rust_decimal::{Decimal, prelude::FromPrimitive};
 use serde::{Deserialize, Serialize};
 use tokio::select;
@@ -23,6 +24,9 @@ use crate::{
     },
 };
 
+/// Maximum number of candles the server returns for a single `loadHistoryPeriod` request.
+pub const MAX_CANDLES_PER_REQUEST: i64 = 1000;
+
 const LOAD_HISTORY_PERIOD_PATTERNS: [&str; 2] = [
     r#"451-["loadHistoryPeriodFast","#,
     r#"451-["loadHistoryPeriod","#,
@@ -197,6 +201,62 @@ impl GetCandlesHandle {
             }
         }
     }
+
+    /// Gets the candles between `from` and `to` for a specific asset.
+    ///
+    /// The range is split in several requests of at most `MAX_CANDLES_PER_REQUEST` candles,
+    /// starting from `to` and going back in time until `from` is covered or the server stops
+    /// returning candles.
+    ///
+    /// # Arguments
+    /// * `asset` - Trading symbol (e.g., "EURUSD_otc")
+    /// * `period` - Time period for each candle in seconds
+    /// * `from` - Start of the range
+    /// * `to` - End of the range
+    ///
+    /// # Returns
+    /// The candles inside the range, sorted by timestamp without duplicates
+    pub async fn get_candles_range(
+        &self,
+        asset: impl ToString,
+        period: i64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> PocketResult<Vec<Candle>> {
+        if period <= 0 {
+            return Err(PocketError::General(format!(
+                "Invalid candle period: {period}"
+            )));
+        }
+        let asset = asset.to_string();
+        let (from, to) = (from.timestamp(), to.timestamp());
+        let mut candles = Vec::new();
+        let mut end = to;
+        while end > from {
+            let offset = (end - from).min(MAX_CANDLES_PER_REQUEST * period);
+            let page = self
+                .get_candles_advanced(&asset, period, end, offset)
+                .await?;
+            let Some(oldest) = page.iter().map(|c| c.timestamp as i64).min() else {
+                break;
+            };
+            candles.extend(page);
+            if oldest <= from {
+                break;
+            }
+            end = (end - offset).min(oldest);
+        }
+        Ok(merge_candle_pages(candles, from, to))
+    }
+}
+
+/// Sorts the candles by timestamp, removes the duplicates from overlapping pages and
+/// drops the candles outside `from..=to`.
+fn merge_candle_pages(mut candles: Vec<Candle>, from: i64, to: i64) -> Vec<Candle> {
+    candles.retain(|c| (from as f64..=to as f64).contains(&c.timestamp));
+    candles.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+    candles.dedup_by(|a, b| a.timestamp == b.timestamp);
+    candles
 }
 
 /// API module for handling candle data requests.
@@ -318,3 +378,38 @@ impl ApiModule<State> for GetCandlesApiModule {
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_handle_send_sync() {
+        // This will fail to compile if the handle is not Send + Sync
+        assert_send_sync::<<GetCandlesApiModule as ApiModule<State>>::Handle>();
+    }
+
+    fn candle(timestamp: i64) -> Candle {
+        Candle {
+            symbol: "EURUSD_otc".to_string(),
+            timestamp: timestamp as f64,
+            open: Decimal::ONE,
+            high: Decimal::ONE,
+            low: Decimal::ONE,
+            close: Decimal::ONE,
+            volume: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_candle_pages() {
+        // Second page overlaps the first one on 120 and 180
+        let first = [300, 240, 180, 120].map(candle);
+        let second = [180, 120, 60, 0].map(candle);
+        let merged = merge_candle_pages(first.into_iter().chain(second).collect(), 60, 240);
+        let timestamps: Vec<f64> = merged.iter().map(|c| c.timestamp).collect();
+        assert_eq!(timestamps, vec![60.0, 120.0, 180.0, 240.0]);
+    }
+}
@@ -1,10 +1,12 @@
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use binary_options_tools_core_pre::{
     error::{CoreError, CoreResult},
     reimports::{AsyncReceiver, AsyncSender, Message},
-    traits::{LightweightModule, Rule},
+    traits::{LightweightModule, Rule, TimedLightweightModule},
 };
 use tracing::{debug, warn};
 // use tracing::info;
@@ -14,6 +16,8 @@ use crate::pocketoption::state::State;
 const SID_BASE: &str = r#"0{"sid":"#;
 const SID: &str = r#"40{"sid":"#;
 const SUCCESSAUTH: &str = r#"451-["successauth","#;
+/// Number of keep-alive round trips averaged in `ConnectionInfo::avg_round_trip_ms`.
+const ROUND_TRIP_SAMPLES: usize = 10;
 
 pub struct InitModule {
     ws_sender: AsyncSender<Message>,
@@ -23,6 +27,9 @@ pub struct InitModule {
 
 pub struct KeepAliveModule {
     ws_sender: AsyncSender<Message>,
+    state: Arc<State>,
+    ping_sent_at: Option<Instant>,
+    round_trips: VecDeque<f64>,
 }
 
 #[async_trait]
@@ -54,7 +61,7 @@ impl LightweightModule<State> for InitModule {
                                 self.ws_sender.send(Message::text("40")).await?;
                             }
                             _ if text.starts_with(SID) => {
-                                self.ws_sender.send(Message::text(self.state.ssid.to_string())).await.inspect_err(|e| {
+                                self.ws_sender.send(Message::text(self.state.ssid().to_string())).await.inspect_err(|e| {
                                     warn!(target: "KeepAliveModule", "Failed to send SSID: {}", e);
                                 })?;
                             }
@@ -105,23 +112,83 @@ impl LightweightModule<State> for InitModule {
 }
 
 #[async_trait]
-impl LightweightModule<State> for KeepAliveModule {
-    fn new(_: Arc<State>, ws_sender: AsyncSender<Message>, _: AsyncReceiver<Arc<Message>>) -> Self {
-        Self { ws_sender }
+impl TimedLightweightModule<State> for KeepAliveModule {
+    fn new(state: Arc<State>, ws_sender: AsyncSender<Message>) -> Self {
+        Self {
+            ws_sender,
+            state,
+            ping_sent_at: None,
+            round_trips: VecDeque::with_capacity(ROUND_TRIP_SAMPLES),
+        }
     }
 
-    async fn run(&mut self) -> CoreResult<()> {
-        loop {
-            // Send a keep-alive message every 20 seconds.
-            tokio::time::sleep(std::time::Duration::from_secs(20)).await;
-            self.ws_sender.send(Message::text(r#"42["ps"]"#)).await?;
+    fn interval() -> Duration {
+        // Send a keep-alive message every 20 seconds.
+        Duration::from_secs(20)
+    }
+
+    async fn tick(&mut self, _: Arc<State>, _: &AsyncSender<Message>) -> CoreResult<()> {
+        self.ws_sender.send(Message::text(r#"42["ps"]"#)).await?;
+        self.ping_sent_at = Some(Instant::now());
+        Ok(())
+    }
+
+    async fn on_message(&mut self, _: Arc<Message>) -> CoreResult<()> {
+        // The server doesn't acknowledge `ps` directly, so the first text message received
+        // after it is taken as its pong.
+        if let Some(sent_at) = self.ping_sent_at.take() {
+            if self.round_trips.len() == ROUND_TRIP_SAMPLES {
+                self.round_trips.pop_front();
+            }
+            self.round_trips
+                .push_back(sent_at.elapsed().as_secs_f64() * 1000.0);
+            let average = self.round_trips.iter().sum::<f64>() / self.round_trips.len() as f64;
+            self.state.set_avg_round_trip(average);
         }
+        Ok(())
     }
 
     fn rule() -> Box<dyn Rule + Send + Sync> {
-        Box::new(|msg: &Message| {
-            debug!(target: "LightweightModule", "Routing rule for KeepAliveModule: {msg:?}");
-            false
-        })
+        Box::new(|msg: &Message| matches!(msg, Message::Text(_)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use binary_options_tools_core_pre::{
+        reimports::{Message, bounded_async},
+        traits::TimedLightweightModule,
+    };
+
+    use super::{KeepAliveModule, ROUND_TRIP_SAMPLES};
+    use crate::pocketoption::{ssid::Ssid, state::StateBuilder};
+
+    #[tokio::test]
+    async fn test_keep_alive_round_trip_average() {
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        let state = Arc::new(StateBuilder::default().ssid(ssid).build().unwrap());
+        let (sender, receiver) = bounded_async(32);
+        let mut module = KeepAliveModule::new(state.clone(), sender.clone());
+
+        // Messages without a pending ping are ignored.
+        module
+            .on_message(Arc::new(Message::text("2")))
+            .await
+            .unwrap();
+        assert_eq!(state.connection_info().avg_round_trip_ms, None);
+
+        for _ in 0..ROUND_TRIP_SAMPLES + 2 {
+            module.tick(state.clone(), &sender).await.unwrap();
+            module
+                .on_message(Arc::new(Message::text("2")))
+                .await
+                .unwrap();
+        }
+        assert_eq!(module.round_trips.len(), ROUND_TRIP_SAMPLES);
+        assert!(state.connection_info().avg_round_trip_ms.unwrap() >= 0.0);
+        assert_eq!(receiver.len(), ROUND_TRIP_SAMPLES + 2);
     }
 }
@@ -1,5 +1,6 @@
 pub mod assets;
 pub mod balance;
+pub mod correlation;
 pub mod deals;
 pub mod get_candles;
 /// Module implementations for PocketOption client
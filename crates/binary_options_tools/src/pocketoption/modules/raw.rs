@@ -8,7 +8,8 @@ use binary_options_tools_core_pre::reimports::{
 };
 use binary_options_tools_core_pre::traits::{ApiModule, Rule};
 use tokio::select;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, broadcast};
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::pocketoption::error::PocketResult;
@@ -16,6 +17,9 @@ use crate::pocketoption::state::State;
 use crate::traits::ValidatorTrait;
 use crate::validator::Validator;
 
+/// Size of the message buffer of every handler stream and filtered receiver.
+const RAW_STREAM_BUFFER: usize = 64;
+
 /// Outgoing message to WS
 #[derive(Clone, Debug)]
 pub enum Outgoing {
@@ -45,6 +49,7 @@ pub enum CommandResponse {
         command_id: Uuid,
         id: Uuid,
         stream_receiver: AsyncReceiver<Arc<Message>>,
+        fan_out: broadcast::Sender<Arc<Message>>,
     },
     Removed {
         command_id: Uuid,
@@ -82,11 +87,13 @@ impl RawHandle {
                     command_id: cid,
                     id,
                     stream_receiver,
+                    fan_out,
                 }) if cid == command_id => {
                     return Ok(RawHandler {
                         id,
                         sender: self.sender.clone(),
                         receiver: stream_receiver,
+                        fan_out,
                     });
                 }
                 Ok(_) => continue,
@@ -121,6 +128,8 @@ pub struct RawHandler {
     id: Uuid,
     sender: AsyncSender<Command>,
     receiver: AsyncReceiver<Arc<Message>>,
+    /// Copies of the stream sent by the module to the filtered receivers
+    fan_out: broadcast::Sender<Arc<Message>>,
 }
 
 impl RawHandler {
@@ -166,6 +175,35 @@ impl RawHandler {
     pub fn subscribe(&self) -> AsyncReceiver<Arc<Message>> {
         self.receiver.clone()
     }
+
+    /// Get a receiver of the messages matching the validator for which `pred` also returns true.
+    ///
+    /// Every filtered receiver gets its own copy of the messages, independently of `wait_next` and
+    /// the `subscribe` receivers. The module still waits for room in the main stream, so it must
+    /// keep being read as well.
+    pub fn subscribe_filtered(
+        &self,
+        pred: impl Fn(&Message) -> bool + Send + Sync + 'static,
+    ) -> AsyncReceiver<Arc<Message>> {
+        let mut messages = self.fan_out.subscribe();
+        let (sender, receiver) = bounded_async(RAW_STREAM_BUFFER);
+        tokio::spawn(async move {
+            loop {
+                match messages.recv().await {
+                    Ok(msg) => {
+                        if pred(&msg) && sender.send(msg).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(target: "RawHandler", "Filtered receiver lagging, skipped {skipped} messages");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        receiver
+    }
 }
 
 impl Drop for RawHandler {
@@ -178,6 +216,13 @@ impl Drop for RawHandler {
     }
 }
 
+/// Destinations of the messages matching a validator.
+struct Sink {
+    stream: AsyncSender<Arc<Message>>,
+    /// Feeds the receivers of `RawHandler::subscribe_filtered`
+    fan_out: broadcast::Sender<Arc<Message>>,
+}
+
 /// Main module processing and routing messages to per-validator streams
 pub struct RawApiModule {
     state: Arc<State>,
@@ -185,7 +230,7 @@ pub struct RawApiModule {
     command_responder: AsyncSender<CommandResponse>,
     message_receiver: AsyncReceiver<Arc<Message>>,
     to_ws_sender: AsyncSender<Message>,
-    sinks: Arc<RwLock<HashMap<Uuid, AsyncSender<Arc<Message>>>>>,
+    sinks: Arc<RwLock<HashMap<Uuid, Sink>>>,
     keep_alive_msgs: Arc<RwLock<HashMap<Uuid, Outgoing>>>,
 }
 
@@ -261,9 +306,10 @@ impl ApiModule<State> for RawApiModule {
                             if let Some(msg) = keep_alive.clone() {
                                 self.keep_alive_msgs.write().await.insert(id, msg);
                             }
-                            let (tx, rx) = bounded_async(64);
-                            self.sinks.write().await.insert(id, tx);
-                            self.command_responder.send(CommandResponse::Created { command_id, id, stream_receiver: rx }).await?;
+                            let (tx, rx) = bounded_async(RAW_STREAM_BUFFER);
+                            let (fan_out, _) = broadcast::channel(RAW_STREAM_BUFFER);
+                            self.sinks.write().await.insert(id, Sink { stream: tx, fan_out: fan_out.clone() });
+                            self.command_responder.send(CommandResponse::Created { command_id, id, stream_receiver: rx, fan_out }).await?;
                         }
                         Command::Remove { id, command_id } => {
                             let existed_state = self.state.remove_raw_validator(&id);
@@ -288,11 +334,13 @@ impl ApiModule<State> for RawApiModule {
                     };
                     if content.is_empty() { continue; }
                     let validators = self.state.raw_validators.read().expect("Failed to acquire read lock").clone();
-                    let sinks = self.sinks.read().await.clone();
+                    let sinks = self.sinks.read().await;
                     for (id, validator) in validators.into_iter() {
                         if validator.call(content.as_str())
-                            && let Some(tx) = sinks.get(&id) {
-                                let _ = tx.send(msg.clone()).await; // best effort
+                            && let Some(sink) = sinks.get(&id) {
+                                // The filtered receivers are fed first so they don't wait for the main stream
+                                let _ = sink.fan_out.send(msg.clone());
+                                let _ = sink.stream.send(msg.clone()).await;
                             }
                     }
                 }
@@ -339,3 +387,71 @@ impl ApiModule<State> for RawApiModule {
         })))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pocketoption::{ssid::Ssid, state::StateBuilder};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_handle_send_sync() {
+        // This will fail to compile if the handle is not Send + Sync
+        assert_send_sync::<<RawApiModule as ApiModule<State>>::Handle>();
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_filtered() {
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        let state = Arc::new(StateBuilder::default().ssid(ssid).build().unwrap());
+        let (command_sender, command_receiver) = bounded_async(8);
+        let (response_sender, response_receiver) = bounded_async(8);
+        let (message_sender, message_receiver) = bounded_async(8);
+        let (ws_sender, _ws_receiver) = bounded_async(8);
+        let mut module = RawApiModule::new(
+            state,
+            command_receiver,
+            response_sender,
+            message_receiver,
+            ws_sender,
+        );
+        let module_task = tokio::spawn(async move { module.run().await });
+        let handle = RawApiModule::create_handle(command_sender, response_receiver);
+        let handler = handle
+            .create(Validator::contains("USD".to_string()), None)
+            .await
+            .unwrap();
+
+        let text_of = |msg: &Message| msg.to_text().unwrap_or_default().to_string();
+        let eur = handler.subscribe_filtered(move |msg| text_of(msg).contains("EURUSD"));
+        let gbp = handler.subscribe_filtered(move |msg| text_of(msg).contains("GBPUSD"));
+
+        for text in ["EURUSD 1", "GBPUSD 1", "USDJPY 1", "EURUSD 2", "AUDCAD 1"] {
+            message_sender
+                .send(Arc::new(Message::text(text)))
+                .await
+                .unwrap();
+        }
+        let next = |receiver: AsyncReceiver<Arc<Message>>| async move {
+            let msg = tokio::time::timeout(std::time::Duration::from_secs(1), receiver.recv())
+                .await
+                .expect("No message received")
+                .unwrap();
+            msg.to_text().unwrap().to_string()
+        };
+        assert_eq!(next(eur.clone()).await, "EURUSD 1");
+        assert_eq!(next(eur.clone()).await, "EURUSD 2");
+        assert_eq!(next(gbp.clone()).await, "GBPUSD 1");
+        // The main stream still gets every message matching the validator
+        for text in ["EURUSD 1", "GBPUSD 1", "USDJPY 1", "EURUSD 2"] {
+            assert_eq!(handler.wait_next().await.unwrap().to_text().unwrap(), text);
+        }
+        // Give the tasks some time to forward anything else
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(eur.is_empty());
+        assert!(gbp.is_empty());
+        module_task.abort();
+    }
+}
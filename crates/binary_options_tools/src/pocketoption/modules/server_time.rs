@@ -6,6 +6,7 @@ use binary_options_tools_core_pre::{
     reimports::{AsyncReceiver, AsyncSender, Message},
     traits::{LightweightModule, Rule},
 };
+use chrono::DateTime;
 use tracing::debug;
 
 use crate::pocketoption::{
@@ -16,6 +17,8 @@ use crate::pocketoption::{
 pub struct ServerTimeModule {
     receiver: AsyncReceiver<Arc<Message>>,
     state: Arc<State>,
+    /// Latest timestamp sent to the subscribers, older updates of other assets are not sent
+    latest: f64,
 }
 
 #[async_trait]
@@ -31,6 +34,7 @@ impl LightweightModule<State> for ServerTimeModule {
         Self {
             receiver: ws_receiver,
             state,
+            latest: 0.0,
         }
     }
 
@@ -43,6 +47,18 @@ impl LightweightModule<State> for ServerTimeModule {
                 // Process the candle data
                 debug!("Received candle data: {:?}", candle);
                 self.state.update_server_time(candle.timestamp).await;
+                if candle.timestamp >= self.latest {
+                    self.latest = candle.timestamp;
+                    if let Some(time) =
+                        DateTime::from_timestamp_millis((candle.timestamp * 1000.0) as i64)
+                    {
+                        self.state.notify_server_time_subscribers(time);
+                    }
+                }
+                #[cfg(feature = "trade_simulation")]
+                self.state
+                    .simulation
+                    .update_price(&candle.symbol, candle.price);
             }
         }
         Err(CoreError::LightweightModuleLoop(
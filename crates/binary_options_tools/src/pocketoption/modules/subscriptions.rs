@@ -7,24 +7,35 @@ use binary_options_tools_core_pre::{
     reimports::{AsyncReceiver, AsyncSender, Message},
     traits::{ApiModule, Rule},
 };
+use chrono::{DateTime, Utc};
 use core::fmt;
 use futures_util::stream::unfold;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use std::{collections::HashMap, time::Duration};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
 use tokio::select;
-use tokio::sync::RwLock;
-use tracing::{debug, warn};
+use tokio::sync::{Notify, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, sleep_until};
+use tracing::{Instrument, debug, warn};
 use uuid::Uuid;
 
-use crate::pocketoption::candle::{BaseCandle, SubscriptionType};
+use crate::pocketoption::candle::{BaseCandle, RollingCandleWindow, SubscriptionType};
 use crate::pocketoption::error::PocketError;
-use crate::pocketoption::types::{MultiPatternRule, StreamData as RawCandle};
+use crate::pocketoption::types::{
+    MultiPatternRule, StreamData as RawCandle, StreamStats, SubscriptionInfo,
+};
 use crate::pocketoption::{
     candle::Candle, // Assuming this exists in your types
     error::PocketResult,
     state::State,
 };
+use crate::utils::command_bus::{self, CommandBus};
 
 #[derive(Serialize)]
 pub struct ChangeSymbol {
@@ -33,14 +44,31 @@ pub struct ChangeSymbol {
     pub period: i64,
 }
 
+/// Payload of the `updateHistoryNewFast` messages, the fast updates may only carry the ticks.
 #[derive(Deserialize)]
 pub struct History {
     pub asset: String,
     pub period: u32,
+    #[serde(default)]
     pub candles: Vec<BaseCandle>,
+    /// Latest ticks of the asset as `[timestamp, price]`.
+    #[serde(default)]
     pub history: Vec<Vec<f64>>,
 }
 
+impl History {
+    /// Returns the `(timestamp, price)` of the most recent tick.
+    pub fn latest_tick(&self) -> Option<(f64, f64)> {
+        self.history
+            .iter()
+            .filter_map(|tick| match tick[..] {
+                [timestamp, price, ..] => Some((timestamp, price)),
+                _ => None,
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(untagged)]
 pub enum ServerResponse {
@@ -74,17 +102,23 @@ pub enum SubscriptionError {
 #[derive(Debug)]
 pub enum Command {
     /// Subscribe to an asset's stream
-    Subscribe { asset: String, command_id: Uuid },
+    Subscribe { asset: String },
     /// Unsubscribe from an asset's stream
-    Unsubscribe { asset: String, command_id: Uuid },
+    Unsubscribe { asset: String },
     /// History
-    History {
-        asset: String,
-        period: u32,
-        command_id: Uuid,
-    },
+    History { asset: String, period: u32 },
+    /// Subscribe to an asset's stream and request its history at the same time
+    SubscribeWithHistory { asset: String, period: u32 },
     /// Requests the number of active subscriptions
     SubscriptionCount,
+    /// Requests the assets currently subscribed
+    ListActive,
+    /// Takes the price updates that couldn't be delivered to their stream
+    DrainDeadLetters,
+    /// Requests the metadata of an asset's subscription
+    GetInfo { asset: String },
+    /// Sent when the caller of the request with this id stopped waiting, never answered
+    Cancel(Uuid),
 }
 
 /// Response enum for subscription commands
@@ -92,33 +126,39 @@ pub enum Command {
 pub enum CommandResponse {
     /// Successful subscription with stream receiver
     SubscriptionSuccess {
-        command_id: Uuid,
         stream_receiver: AsyncReceiver<StreamData>,
     },
-    /// Subscription failed
-    SubscriptionFailed {
-        command_id: Uuid,
-        error: Box<PocketError>,
+    /// Successful `SubscribeWithHistory`, sent once the history arrived
+    SubscriptionWithHistory {
+        stream_receiver: AsyncReceiver<StreamData>,
+        data: Vec<Candle>,
     },
+    /// Subscription failed
+    SubscriptionFailed { error: Box<PocketError> },
     /// History Response
-    History { command_id: Uuid, data: Vec<Candle> },
+    History(Vec<Candle>),
     /// Unsubscription successful
-    UnsubscriptionSuccess { command_id: Uuid },
+    UnsubscriptionSuccess,
     /// Unsubscription failed
-    UnsubscriptionFailed {
-        command_id: Uuid,
-        error: Box<PocketError>,
-    },
+    UnsubscriptionFailed { error: Box<PocketError> },
     /// Returns the number of active subscriptions
     SubscriptionCount(u32),
+    /// Returns the assets currently subscribed, sorted alphabetically
+    ActiveList(Vec<String>),
+    /// Returns the price updates that couldn't be delivered
+    DeadLetters(Vec<StreamData>),
+    /// Returns the metadata of a subscription, `None` if the asset isn't subscribed
+    Info(Option<SubscriptionInfo>),
 }
 
 /// Represents the data sent through the subscription stream.
 pub struct SubscriptionStream {
     receiver: AsyncReceiver<StreamData>,
-    sender: AsyncSender<Command>,
+    sender: AsyncSender<command_bus::Command<Command>>,
     asset: String,
     sub_type: SubscriptionType,
+    /// Last candle returned by `receive`
+    last_candle: Option<Candle>,
 }
 
 /// Data sent through the subscription stream
@@ -134,6 +174,77 @@ pub enum StreamData {
     Terminated { reason: String },
     /// Unsubscribe signal (stream should end gracefully)
     Unsubscribe,
+    /// The subscription was re-established after a reconnection, the candle in progress is
+    /// discarded
+    Resubscribed,
+}
+
+/// Bounded queue keeping the price updates that couldn't be forwarded to their stream.
+///
+/// Once full, the oldest update is evicted for every new one and counted in `dropped_count`.
+#[derive(Debug)]
+pub struct DeadLetterQueue {
+    capacity: usize,
+    /// Undelivered updates as (asset, price, timestamp)
+    letters: Mutex<VecDeque<(String, f64, f64)>>,
+    dropped_count: AtomicU64,
+}
+
+impl DeadLetterQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            letters: Mutex::new(VecDeque::with_capacity(capacity)),
+            dropped_count: AtomicU64::new(0),
+        }
+    }
+
+    /// Stores an undelivered update, evicting the oldest one if the queue is full.
+    pub fn push(&self, asset: String, price: f64, timestamp: f64) {
+        let mut letters = self
+            .letters
+            .lock()
+            .expect("Failed to acquire dead letters lock");
+        if self.capacity == 0 {
+            self.dropped_count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        if letters.len() >= self.capacity {
+            letters.pop_front();
+            self.dropped_count.fetch_add(1, Ordering::Relaxed);
+        }
+        letters.push_back((asset, price, timestamp));
+    }
+
+    /// Takes all the stored updates, oldest first.
+    pub fn drain(&self) -> Vec<StreamData> {
+        self.letters
+            .lock()
+            .expect("Failed to acquire dead letters lock")
+            .drain(..)
+            .map(|(asset, price, timestamp)| StreamData::Update {
+                asset,
+                price,
+                timestamp,
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters
+            .lock()
+            .expect("Failed to acquire dead letters lock")
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of updates lost because the queue was full.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped_count.load(Ordering::Relaxed)
+    }
 }
 
 /// Callback for when there is a disconnection
@@ -145,8 +256,9 @@ struct SubscriptionCallback {
 /// Handle for interacting with the `SubscriptionsApiModule`.
 #[derive(Clone)]
 pub struct SubscriptionsHandle {
-    sender: AsyncSender<Command>,
-    receiver: AsyncReceiver<CommandResponse>,
+    bus: CommandBus<Command, CommandResponse>,
+    /// Given to the streams, which unsubscribe without waiting for the response once dropped
+    sender: AsyncSender<command_bus::Command<Command>>,
 }
 
 impl SubscriptionsHandle {
@@ -156,7 +268,7 @@ impl SubscriptionsHandle {
     /// * `asset` - The asset symbol to subscribe to
     ///
     /// # Returns
-    /// * `PocketResult<(Uuid, AsyncReceiver<StreamData>)>` - Subscription ID and data receiver
+    /// * `PocketResult<SubscriptionStream>` - The stream of the asset
     ///
     /// # Errors
     /// * Returns error if maximum subscriptions reached
@@ -166,90 +278,33 @@ impl SubscriptionsHandle {
         asset: String,
         sub_type: SubscriptionType,
     ) -> PocketResult<SubscriptionStream> {
-        // TODO: Implement subscription logic
-        // 1. Generate subscription ID
-        // 2. Send Command::Subscribe
-        // 3. Wait for CommandResponse::SubscriptionSuccess
-        // 4. Return subscription ID and stream receiver
-        let id = Uuid::new_v4();
-        self.sender
+        match self
+            .bus
             .send(Command::Subscribe {
                 asset: asset.clone(),
-                command_id: id,
             })
-            .await
-            .map_err(CoreError::from)?;
-        // Wait for the subscription response
-
-        loop {
-            match self.receiver.recv().await {
-                Ok(CommandResponse::SubscriptionSuccess {
-                    command_id,
-                    stream_receiver,
-                }) => {
-                    if command_id == id {
-                        return Ok(SubscriptionStream {
-                            receiver: stream_receiver,
-                            sender: self.sender.clone(),
-                            asset,
-                            sub_type,
-                        });
-                    } else {
-                        // If the request ID does not match, continue waiting for the correct response
-                        continue;
-                    }
-                }
-                Ok(CommandResponse::SubscriptionFailed { command_id, error }) => {
-                    if command_id == id {
-                        return Err(*error);
-                    }
-                    continue;
-                }
-                Ok(_) => continue,
-                Err(e) => return Err(CoreError::from(e).into()),
-            }
+            .await?
+        {
+            CommandResponse::SubscriptionSuccess { stream_receiver } => Ok(
+                SubscriptionStream::new(stream_receiver, self.sender.clone(), asset, sub_type),
+            ),
+            CommandResponse::SubscriptionFailed { error } => Err(*error),
+            response => Err(unexpected_response("subscribe", response)),
         }
     }
 
     /// Unsubscribe from an asset's stream.
     ///
     /// # Arguments
-    /// * `subscription_id` - The ID of the subscription to cancel
+    /// * `asset` - The asset symbol to unsubscribe from
     ///
     /// # Returns
     /// * `PocketResult<()>` - Success or error
     pub async fn unsubscribe(&self, asset: String) -> PocketResult<()> {
-        // TODO: Implement unsubscription logic
-        // 1. Send Command::Unsubscribe
-        // 2. Wait for CommandResponse::UnsubscriptionSuccess
-        let id = Uuid::new_v4();
-        self.sender
-            .send(Command::Unsubscribe {
-                asset,
-                command_id: id,
-            })
-            .await
-            .map_err(CoreError::from)?;
-        // Wait for the unsubscription response
-        loop {
-            match self.receiver.recv().await {
-                Ok(CommandResponse::UnsubscriptionSuccess { command_id }) => {
-                    if command_id == id {
-                        return Ok(());
-                    } else {
-                        // If the request ID does not match, continue waiting for the correct response
-                        continue;
-                    }
-                }
-                Ok(CommandResponse::UnsubscriptionFailed { command_id, error }) => {
-                    if command_id == id {
-                        return Err(*error);
-                    }
-                    continue;
-                }
-                Ok(_) => continue,
-                Err(e) => return Err(CoreError::from(e).into()),
-            }
+        match self.bus.send(Command::Unsubscribe { asset }).await? {
+            CommandResponse::UnsubscriptionSuccess => Ok(()),
+            CommandResponse::UnsubscriptionFailed { error } => Err(*error),
+            response => Err(unexpected_response("unsubscribe", response)),
         }
     }
 
@@ -258,19 +313,52 @@ impl SubscriptionsHandle {
     /// # Returns
     /// * `PocketResult<usize>` - Number of active subscriptions
     pub async fn get_active_subscriptions_count(&self) -> PocketResult<u32> {
-        self.sender
-            .send(Command::SubscriptionCount)
-            .await
-            .map_err(CoreError::from)?;
-        // Wait for the subscription count response
-        loop {
-            match self.receiver.recv().await {
-                Ok(CommandResponse::SubscriptionCount(count)) => {
-                    return Ok(count);
-                }
-                Ok(_) => continue,
-                Err(e) => return Err(CoreError::from(e).into()),
-            }
+        match self.bus.send(Command::SubscriptionCount).await? {
+            CommandResponse::SubscriptionCount(count) => Ok(count),
+            response => Err(unexpected_response(
+                "get_active_subscriptions_count",
+                response,
+            )),
+        }
+    }
+
+    /// Get the assets currently subscribed.
+    ///
+    /// # Returns
+    /// * `PocketResult<Vec<String>>` - Subscribed assets, sorted alphabetically
+    pub async fn list_active(&self) -> PocketResult<Vec<String>> {
+        match self.bus.send(Command::ListActive).await? {
+            CommandResponse::ActiveList(assets) => Ok(assets),
+            response => Err(unexpected_response("list_active", response)),
+        }
+    }
+
+    /// Take the price updates that couldn't be delivered because their stream was closed.
+    ///
+    /// # Returns
+    /// * `PocketResult<Vec<StreamData>>` - The undelivered updates, oldest first
+    pub async fn drain_dead_letters(&self) -> PocketResult<Vec<StreamData>> {
+        match self.bus.send(Command::DrainDeadLetters).await? {
+            CommandResponse::DeadLetters(letters) => Ok(letters),
+            response => Err(unexpected_response("drain_dead_letters", response)),
+        }
+    }
+
+    /// Get the metadata of an asset's subscription.
+    ///
+    /// # Returns
+    /// * `PocketResult<SubscriptionInfo>` - The metadata, an error if the asset isn't subscribed
+    pub async fn get_subscription_info(&self, asset: &str) -> PocketResult<SubscriptionInfo> {
+        match self
+            .bus
+            .send(Command::GetInfo {
+                asset: asset.to_string(),
+            })
+            .await?
+        {
+            CommandResponse::Info(info) => info
+                .ok_or_else(|| PocketError::General(format!("No subscription found for {asset}"))),
+            response => Err(unexpected_response("get_subscription_info", response)),
         }
     }
 
@@ -284,6 +372,41 @@ impl SubscriptionsHandle {
             .map(|count| count as usize == MAX_SUBSCRIPTIONS)
     }
 
+    /// Subscribe to an asset's real-time data stream and request its history at the same time.
+    ///
+    /// Both requests are handled in the same module iteration, so the history ends right where
+    /// the stream starts.
+    ///
+    /// # Arguments
+    /// * `asset` - The asset symbol to subscribe to
+    /// * `period` - The period of the history candles
+    /// * `sub_type` - How the stream candles are aggregated
+    pub async fn subscribe_with_history(
+        &self,
+        asset: String,
+        period: u32,
+        sub_type: SubscriptionType,
+    ) -> PocketResult<(Vec<Candle>, SubscriptionStream)> {
+        match self
+            .bus
+            .send(Command::SubscribeWithHistory {
+                asset: asset.clone(),
+                period,
+            })
+            .await?
+        {
+            CommandResponse::SubscriptionWithHistory {
+                stream_receiver,
+                data,
+            } => Ok((
+                data,
+                SubscriptionStream::new(stream_receiver, self.sender.clone(), asset, sub_type),
+            )),
+            CommandResponse::SubscriptionFailed { error } => Err(*error),
+            response => Err(unexpected_response("subscribe_with_history", response)),
+        }
+    }
+
     /// Gets the history for an asset with its period
     /// # Arguments
     /// * `asset` - The asset symbol
@@ -291,43 +414,52 @@ impl SubscriptionsHandle {
     /// # Returns
     /// * `PocketResult<Vec<Candle>>` - Vector of candles
     pub async fn history(&self, asset: String, period: u32) -> PocketResult<Vec<Candle>> {
-        let id = Uuid::new_v4();
-        self.sender
-            .send(Command::History {
-                asset,
-                period,
-                command_id: id,
-            })
-            .await
-            .map_err(CoreError::from)?;
-        // Wait for the history response
-        loop {
-            match self.receiver.recv().await {
-                Ok(CommandResponse::History { command_id, data }) => {
-                    if command_id == id {
-                        return Ok(data);
-                    } else {
-                        // If the request ID does not match, continue waiting for the correct response
-                        continue;
-                    }
-                }
-                Ok(_) => continue,
-                Err(e) => return Err(CoreError::from(e).into()),
-            }
+        match self.bus.send(Command::History { asset, period }).await? {
+            CommandResponse::History(data) => Ok(data),
+            response => Err(unexpected_response("history", response)),
         }
     }
 }
 
+fn unexpected_response(request: &str, response: CommandResponse) -> PocketError {
+    PocketError::General(format!("Unexpected response to {request}: {response:?}"))
+}
+
 /// The API module for handling subscription operations.
 pub struct SubscriptionsApiModule {
-    command_receiver: AsyncReceiver<Command>,
-    command_responder: AsyncSender<CommandResponse>,
+    state: Arc<State>,
+    command_receiver: AsyncReceiver<command_bus::Command<Command>>,
+    command_responder: AsyncSender<command_bus::Command<CommandResponse>>,
     message_receiver: AsyncReceiver<Arc<Message>>,
     to_ws_sender: AsyncSender<Message>,
 
     /// Active subscriptions mapped by subscription symbol
     active_subscriptions: Arc<RwLock<HashMap<String, AsyncSender<StreamData>>>>,
-    histories: Arc<RwLock<Vec<(String, u32, Uuid)>>>,
+    /// Requests waiting for the history of an asset
+    histories: Vec<PendingHistory>,
+    /// Price updates that couldn't be forwarded to their stream
+    dead_letters: DeadLetterQueue,
+    /// Subscriptions waiting for their first update, keyed by asset
+    pending_subscriptions: HashMap<String, PendingSubscription>,
+    /// Time the server has to confirm a subscription
+    subscription_timeout: Duration,
+}
+
+/// A `Command::History` or `Command::SubscribeWithHistory` answered once the server sends the
+/// history of the asset.
+struct PendingHistory {
+    asset: String,
+    period: u32,
+    request_id: Uuid,
+    /// Stream of a `SubscribeWithHistory`, sent with the history
+    stream_receiver: Option<AsyncReceiver<StreamData>>,
+}
+
+/// A `Command::Subscribe` whose `SubscriptionSuccess` is sent once the server streams the asset.
+struct PendingSubscription {
+    request_id: Uuid,
+    stream_receiver: AsyncReceiver<StreamData>,
+    deadline: Instant,
 }
 
 #[async_trait]
@@ -335,8 +467,10 @@ impl ReconnectCallback<State> for SubscriptionCallback {
     async fn call(&self, _: Arc<State>, ws_sender: &AsyncSender<Message>) -> CoreResult<()> {
         tokio::time::sleep(Duration::from_secs(2)).await; // FIXME: This is a temporary delay, it may need to be fine tuned
         // Resubscribe to all active subscriptions
-        for symbol in self.active_subscriptions.read().await.keys() {
+        for (symbol, stream_sender) in self.active_subscriptions.read().await.iter() {
             tokio::time::sleep(Duration::from_secs(1)).await;
+            // Drop the candles started before the disconnection, the stream may already be closed
+            let _ = stream_sender.send(StreamData::Resubscribed).await;
             // Resubscribe to each active subscription
             send_subscribe_message(ws_sender, symbol, 1).await?;
         }
@@ -346,12 +480,12 @@ impl ReconnectCallback<State> for SubscriptionCallback {
 
 #[async_trait]
 impl ApiModule<State> for SubscriptionsApiModule {
-    type Command = Command;
-    type CommandResponse = CommandResponse;
+    type Command = command_bus::Command<Command>;
+    type CommandResponse = command_bus::Command<CommandResponse>;
     type Handle = SubscriptionsHandle;
 
     fn new(
-        _: Arc<State>,
+        state: Arc<State>,
         command_receiver: AsyncReceiver<Self::Command>,
         command_responder: AsyncSender<Self::CommandResponse>,
         message_receiver: AsyncReceiver<Arc<Message>>,
@@ -363,7 +497,11 @@ impl ApiModule<State> for SubscriptionsApiModule {
             message_receiver,
             to_ws_sender,
             active_subscriptions: Arc::new(RwLock::new(HashMap::new())),
-            histories: Arc::new(RwLock::new(Vec::new())),
+            histories: Vec::new(),
+            dead_letters: DeadLetterQueue::new(state.dead_letter_capacity),
+            pending_subscriptions: HashMap::new(),
+            subscription_timeout: state.subscription_timeout,
+            state,
         }
     }
 
@@ -371,7 +509,10 @@ impl ApiModule<State> for SubscriptionsApiModule {
         sender: AsyncSender<Self::Command>,
         receiver: AsyncReceiver<Self::CommandResponse>,
     ) -> Self::Handle {
-        SubscriptionsHandle { sender, receiver }
+        SubscriptionsHandle {
+            bus: CommandBus::new(sender.clone(), receiver).with_cancel(Command::Cancel),
+            sender,
+        }
     }
 
     async fn run(&mut self) -> CoreResult<()> {
@@ -383,75 +524,18 @@ impl ApiModule<State> for SubscriptionsApiModule {
         // 4. Forwarding data to appropriate streams
         //
         loop {
+            let next_deadline = self
+                .pending_subscriptions
+                .values()
+                .map(|pending| pending.deadline)
+                .min();
             select! {
+                _ = sleep_until(next_deadline.unwrap_or_else(Instant::now)), if next_deadline.is_some() => {
+                    self.expire_pending_subscriptions().await?;
+                },
                 Ok(cmd) = self.command_receiver.recv() => {
-                    match cmd {
-                        Command::Subscribe { asset, command_id } => {
-                            // TODO: Handle subscription request
-                            // 1. Check if max subscriptions reached
-                            // 2. Create stream channel
-                            // 3. Send WebSocket subscription message
-                            // 4. Store subscription info
-                            // 5. Send success response with stream receiver
-
-                            if self.is_max_subscriptions_reached().await {
-                                self.command_responder.send(CommandResponse::SubscriptionFailed {
-                                    command_id,
-                                    error: Box::new(SubscriptionError::MaxSubscriptionsReached.into()),
-                                }).await?;
-                                continue;
-                            } else {
-                                // Create stream channel
-                                self.send_subscribe_message(&asset, 1).await?;
-                                let (stream_sender, stream_receiver) = bounded_async(MAX_CHANNEL_CAPACITY);
-                                self.add_subscription(asset.clone(), stream_sender).await.map_err(|e| CoreError::Other(e.to_string()))?;
-
-
-                                // Send success response with stream receiver
-                                self.command_responder.send(CommandResponse::SubscriptionSuccess {
-                                    command_id,
-                                    stream_receiver,
-                                }).await?;
-                            }
-                        },
-                        Command::Unsubscribe { asset, command_id } => {
-                            // TODO: Handle unsubscription request
-                            // 1. Find subscription by ID
-                            // 2. Send unsubscribe message to WebSocket
-                            // 3. Send Unsubscribe signal to stream
-                            // 4. Remove from active subscriptions
-                            // 5. Send success response
-                            match self.remove_subscription(&asset).await {
-                                Ok(b) => {
-                                    // Send Unsubscribe signal to stream
-                                    if b {
-                                        self.command_responder.send(CommandResponse::UnsubscriptionSuccess { command_id }).await?;
-                                    } else {
-                                        // Subscription not found, send failure response
-                                        self.command_responder.send(CommandResponse::UnsubscriptionFailed {
-                                            command_id,
-                                            error: Box::new(PocketError::General("Subscription not found".to_string())),
-                                        }).await?;
-                                    }
-                                },
-                                Err(e) => {
-                                    // Subscription not found, send failure response
-                                    self.command_responder.send(CommandResponse::UnsubscriptionFailed {
-                                        command_id,
-                                        error: Box::new(e.into()),
-                                    }).await?;
-                                }
-                            }
-                        },
-                        Command::SubscriptionCount => {
-                            let count = self.active_subscriptions.read().await.len() as u32;
-                            self.command_responder.send(CommandResponse::SubscriptionCount(count)).await?;
-                        },
-                        Command::History { asset, period, command_id } => {
-                            self.send_subscribe_message(&asset, period).await?;
-                            self.histories.write().await.push((asset, period, command_id));
-                        }
-                    }
+                    let span = cmd.span().clone();
+                    self.handle_command(cmd.id(), cmd.into_data()).instrument(span).await?;
                 },
                 Ok(msg) = self.message_receiver.recv() => {
                     // TODO: Handle incoming WebSocket messages
@@ -464,30 +548,23 @@ impl ApiModule<State> for SubscriptionsApiModule {
                             // Parse the message for asset data
                             match serde_json::from_slice::<ServerResponse>(data) {
                                 Ok(ServerResponse::Candle(data)) => {
+                                    self.confirm_subscription(&data.symbol).await?;
                                     // Forward data to stream
                                     if let Err(e) = self.forward_data_to_stream(&data.symbol, data.price, data.timestamp).await {
-                                        warn!(target: "SubscriptionsApiModule", "Failed to forward data: {}", e);
+                                        warn!(target: "SubscriptionsApiModule", "Failed to forward data, storing it in the dead letter queue: {}", e);
+                                        self.dead_letters.push(data.symbol, data.price, data.timestamp);
                                     }
                                 },
                                 Ok(ServerResponse::History(data)) => {
-                                    let mut id = None;
-                                    self.histories.write().await.retain(|(asset, period, c_id)| {
-                                        if asset == &data.asset && *period == data.period {
-                                            id = Some(*c_id);
-                                            false
-                                        } else {
-                                            true
-                                        }
-                                    });
-                                    if let Some(command_id) = id {
-                                        let candles = data.candles.into_iter().map(|c| Candle::try_from((c, data.asset.clone()))).collect::<Result<Vec<_>, _>>().map_err(|e| CoreError::Other(e.to_string()))?;
-                                        if let Err(e) = self.command_responder.send(CommandResponse::History {
-                                            command_id,
-                                            data: candles
-                                        }).await {
-                                            warn!(target: "SubscriptionsApiModule", "Failed to send history response: {}", e);
-                                        }
+                                    self.confirm_subscription(&data.asset).await?;
+                                    // Only the newest tick is forwarded, the older ones were already streamed or predate the subscription
+                                    if let Some((timestamp, price)) = data.latest_tick()
+                                        && let Err(e) = self.forward_data_to_stream(&data.asset, price, timestamp).await
+                                    {
+                                        warn!(target: "SubscriptionsApiModule", "Failed to forward data, storing it in the dead letter queue: {}", e);
+                                        self.dead_letters.push(data.asset.clone(), price, timestamp);
                                     }
+                                    self.answer_histories(data).await?;
                                 }
                                 Err(e) => {
                                     warn!(target: "SubscriptionsApiModule", "Received data: {:?}",  String::from_utf8(data.to_vec()));
@@ -528,6 +605,182 @@ impl ApiModule<State> for SubscriptionsApiModule {
 }
 
 impl SubscriptionsApiModule {
+    async fn respond(&self, request_id: Uuid, response: CommandResponse) -> CoreResult<()> {
+        self.command_responder
+            .send(command_bus::Command::from_id(request_id, response, None))
+            .await?;
+        Ok(())
+    }
+
+    async fn handle_command(&mut self, request_id: Uuid, cmd: Command) -> CoreResult<()> {
+        match cmd {
+            Command::Subscribe { asset } => {
+                if self.is_max_subscriptions_reached().await {
+                    self.respond(
+                        request_id,
+                        CommandResponse::SubscriptionFailed {
+                            error: Box::new(SubscriptionError::MaxSubscriptionsReached.into()),
+                        },
+                    )
+                    .await?;
+                } else {
+                    // Create stream channel
+                    self.send_subscribe_message(&asset, 1).await?;
+                    let (stream_sender, stream_receiver) = bounded_async(MAX_CHANNEL_CAPACITY);
+                    self.add_subscription(asset.clone(), 1, stream_sender)
+                        .await
+                        .map_err(|e| CoreError::Other(e.to_string()))?;
+
+                    // The success response is sent with the first update of the asset
+                    self.pending_subscriptions.insert(
+                        asset,
+                        PendingSubscription {
+                            request_id,
+                            stream_receiver,
+                            deadline: Instant::now() + self.subscription_timeout,
+                        },
+                    );
+                }
+            }
+            Command::Unsubscribe { asset } => {
+                if let Some(pending) = self.pending_subscriptions.remove(&asset) {
+                    self.respond(
+                        pending.request_id,
+                        CommandResponse::SubscriptionFailed {
+                            error: Box::new(PocketError::General(
+                                "Unsubscribed before the server confirmed the subscription"
+                                    .to_string(),
+                            )),
+                        },
+                    )
+                    .await?;
+                }
+                let response = match self.remove_subscription(&asset).await {
+                    Ok(true) => CommandResponse::UnsubscriptionSuccess,
+                    Ok(false) => CommandResponse::UnsubscriptionFailed {
+                        error: Box::new(PocketError::General("Subscription not found".to_string())),
+                    },
+                    Err(e) => CommandResponse::UnsubscriptionFailed {
+                        error: Box::new(e.into()),
+                    },
+                };
+                self.respond(request_id, response).await?;
+            }
+            Command::SubscriptionCount => {
+                let count = self.active_subscriptions.read().await.len() as u32;
+                self.respond(request_id, CommandResponse::SubscriptionCount(count))
+                    .await?;
+            }
+            Command::ListActive => {
+                let mut assets: Vec<String> = self
+                    .active_subscriptions
+                    .read()
+                    .await
+                    .keys()
+                    .cloned()
+                    .collect();
+                assets.sort();
+                self.respond(request_id, CommandResponse::ActiveList(assets))
+                    .await?;
+            }
+            Command::DrainDeadLetters => {
+                let letters = self.dead_letters.drain();
+                self.respond(request_id, CommandResponse::DeadLetters(letters))
+                    .await?;
+            }
+            Command::GetInfo { asset } => {
+                let info = self
+                    .state
+                    .stream_stats
+                    .read()
+                    .expect("Failed to acquire stream stats lock")
+                    .get(&asset)
+                    .map(|stats| stats.to_info(&asset));
+                self.respond(request_id, CommandResponse::Info(info))
+                    .await?;
+            }
+            Command::SubscribeWithHistory { asset, period } => {
+                let (stream_sender, stream_receiver) = bounded_async(MAX_CHANNEL_CAPACITY);
+                // The same changeSymbol message subscribes to the asset and loads its history
+                match self
+                    .add_subscription(asset.clone(), period, stream_sender)
+                    .await
+                {
+                    Ok(()) => {
+                        self.send_subscribe_message(&asset, period).await?;
+                        self.histories.push(PendingHistory {
+                            asset,
+                            period,
+                            request_id,
+                            stream_receiver: Some(stream_receiver),
+                        });
+                    }
+                    Err(e) => {
+                        self.respond(
+                            request_id,
+                            CommandResponse::SubscriptionFailed { error: Box::new(e) },
+                        )
+                        .await?;
+                    }
+                }
+            }
+            Command::History { asset, period } => {
+                self.send_subscribe_message(&asset, period).await?;
+                self.histories.push(PendingHistory {
+                    asset,
+                    period,
+                    request_id,
+                    stream_receiver: None,
+                });
+            }
+            Command::Cancel(id) => {
+                self.histories.retain(|pending| pending.request_id != id);
+                let cancelled = self
+                    .pending_subscriptions
+                    .iter()
+                    .find_map(|(asset, pending)| (pending.request_id == id).then(|| asset.clone()));
+                if let Some(asset) = cancelled {
+                    // Nobody would read the stream
+                    self.pending_subscriptions.remove(&asset);
+                    self.active_subscriptions.write().await.remove(&asset);
+                    self.remove_subscription_data(&asset);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers every request waiting for the history the server just sent.
+    async fn answer_histories(&mut self, data: History) -> CoreResult<()> {
+        let (answered, waiting) = std::mem::take(&mut self.histories)
+            .into_iter()
+            .partition(|pending| pending.asset == data.asset && pending.period == data.period);
+        self.histories = waiting;
+        let answered: Vec<PendingHistory> = answered;
+        if answered.is_empty() {
+            return Ok(());
+        }
+        let candles = data
+            .candles
+            .into_iter()
+            .map(|c| Candle::try_from((c, data.asset.clone())))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Other(e.to_string()))?;
+        for pending in answered {
+            let response = match pending.stream_receiver {
+                Some(stream_receiver) => CommandResponse::SubscriptionWithHistory {
+                    stream_receiver,
+                    data: candles.clone(),
+                },
+                None => CommandResponse::History(candles.clone()),
+            };
+            if let Err(e) = self.respond(pending.request_id, response).await {
+                warn!(target: "SubscriptionsApiModule", "Failed to send history response: {}", e);
+            }
+        }
+        Ok(())
+    }
+
     /// Check if maximum subscriptions limit is reached.
     ///
     /// # Returns
@@ -541,6 +794,7 @@ impl SubscriptionsApiModule {
     /// # Arguments
     /// * `subscription_id` - The subscription ID
     /// * `asset` - The asset symbol
+    /// * `period` - The period sent with `changeSymbol`, used to detect gaps in the stream
     /// * `stream_sender` - The sender for stream data
     ///
     /// # Returns
@@ -548,6 +802,7 @@ impl SubscriptionsApiModule {
     async fn add_subscription(
         &mut self,
         asset: String,
+        period: u32,
         stream_sender: AsyncSender<StreamData>,
     ) -> PocketResult<()> {
         if self.is_max_subscriptions_reached().await {
@@ -560,6 +815,11 @@ impl SubscriptionsApiModule {
         }
 
         // Add to active subscriptions
+        self.state
+            .stream_stats
+            .write()
+            .expect("Failed to acquire stream stats lock")
+            .insert(asset.clone(), StreamStats::new(period));
         self.active_subscriptions
             .write()
             .await
@@ -567,6 +827,15 @@ impl SubscriptionsApiModule {
         Ok(())
     }
 
+    /// Forget the tick statistics and the metadata of `asset`.
+    fn remove_subscription_data(&mut self, asset: &str) {
+        self.state
+            .stream_stats
+            .write()
+            .expect("Failed to acquire stream stats lock")
+            .remove(asset);
+    }
+
     /// Remove a subscription.
     ///
     /// # Arguments
@@ -579,7 +848,9 @@ impl SubscriptionsApiModule {
         // 1. Remove from active_subscriptions
         // 2. Remove from asset_to_subscription
         // 3. Return removed subscription info
-        if let Some(stream_sender) = self.active_subscriptions.write().await.remove(asset) {
+        let removed = self.active_subscriptions.write().await.remove(asset);
+        if let Some(stream_sender) = removed {
+            self.remove_subscription_data(asset);
             stream_sender.send(StreamData::Terminated { reason: "Unsubscribed from main module".to_string() })
                 .await.inspect_err(|e| warn!(target: "SubscriptionsApiModule", "Failed to send termination signal: {}", e))?;
             return Ok(true);
@@ -597,6 +868,51 @@ impl SubscriptionsApiModule {
         Ok(())
     }
 
+    /// Send the success response of a pending subscription, the server started streaming `asset`.
+    async fn confirm_subscription(&mut self, asset: &str) -> CoreResult<()> {
+        if let Some(pending) = self.pending_subscriptions.remove(asset) {
+            self.respond(
+                pending.request_id,
+                CommandResponse::SubscriptionSuccess {
+                    stream_receiver: pending.stream_receiver,
+                },
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Fail the pending subscriptions the server didn't confirm in time and drop their stream.
+    async fn expire_pending_subscriptions(&mut self) -> CoreResult<()> {
+        let now = Instant::now();
+        let expired: Vec<String> = self
+            .pending_subscriptions
+            .iter()
+            .filter(|(_, pending)| pending.deadline <= now)
+            .map(|(asset, _)| asset.clone())
+            .collect();
+        for asset in expired {
+            if let Some(pending) = self.pending_subscriptions.remove(&asset) {
+                warn!(target: "SubscriptionsApiModule", "The server didn't confirm the subscription to {} in time", asset);
+                self.active_subscriptions.write().await.remove(&asset);
+                self.remove_subscription_data(&asset);
+                let error = PocketError::Timeout {
+                    task: "subscribe".to_string(),
+                    context: asset,
+                    duration: self.subscription_timeout,
+                };
+                self.respond(
+                    pending.request_id,
+                    CommandResponse::SubscriptionFailed {
+                        error: Box::new(error),
+                    },
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
     /// Send subscription message to WebSocket.
     ///
     /// # Arguments
@@ -612,7 +928,7 @@ impl SubscriptionsApiModule {
     /// * `asset` - The asset symbol
     /// * `candle` - The candle data
     async fn forward_data_to_stream(
-        &self,
+        &mut self,
         asset: &str,
         price: f64,
         timestamp: f64,
@@ -622,6 +938,15 @@ impl SubscriptionsApiModule {
         // 2. Send StreamData::Candle to stream
         // 3. Handle send errors (stream might be closed)
         if let Some(stream_sender) = self.active_subscriptions.read().await.get(asset) {
+            if let Some(stats) = self
+                .state
+                .stream_stats
+                .write()
+                .expect("Failed to acquire stream stats lock")
+                .get_mut(asset)
+            {
+                stats.record(price, timestamp);
+            }
             stream_sender
                 .send(StreamData::Update {
                     asset: asset.to_string(),
@@ -637,6 +962,23 @@ impl SubscriptionsApiModule {
 }
 
 impl SubscriptionStream {
+    /// Creates a stream reading the updates from `receiver`, `sender` gets the `Unsubscribe`
+    /// command once the stream is dropped.
+    pub(crate) fn new(
+        receiver: AsyncReceiver<StreamData>,
+        sender: AsyncSender<command_bus::Command<Command>>,
+        asset: String,
+        sub_type: SubscriptionType,
+    ) -> Self {
+        Self {
+            receiver,
+            sender,
+            asset,
+            sub_type,
+            last_candle: None,
+        }
+    }
+
     /// Get the asset symbol for this subscription stream
     pub fn asset(&self) -> &str {
         &self.asset
@@ -645,14 +987,10 @@ impl SubscriptionStream {
     /// Unsubscribe from the stream
     pub async fn unsubscribe(self) -> PocketResult<()> {
         // Send unsubscribe command through the main handle
-        let command_id = Uuid::new_v4();
-        self.sender
-            .send(Command::Unsubscribe {
-                asset: self.asset.clone(),
-                command_id,
-            })
-            .await
-            .map_err(CoreError::from)?;
+        let (_, cmd) = command_bus::Command::new(Command::Unsubscribe {
+            asset: self.asset.clone(),
+        });
+        self.sender.send(cmd).await.map_err(CoreError::from)?;
 
         // We don't need to wait for response since we're consuming self
         Ok(())
@@ -670,6 +1008,7 @@ impl SubscriptionStream {
                     if asset == self.asset {
                         let candle = self.process_update(timestamp, price)?;
                         if let Some(candle) = candle {
+                            self.last_candle = Some(candle.clone());
                             return Ok(candle);
                         }
                         // Continue if no candle is ready yet
@@ -682,6 +1021,9 @@ impl SubscriptionStream {
                 Ok(StreamData::Unsubscribe) => {
                     return Err(PocketError::General("Stream unsubscribed".to_string()));
                 }
+                Ok(StreamData::Resubscribed) => {
+                    self.sub_type.reset();
+                }
                 Err(e) => {
                     return Err(CoreError::from(e).into());
                 }
@@ -689,6 +1031,30 @@ impl SubscriptionStream {
         }
     }
 
+    /// Last candle returned by `receive`, without waiting for or consuming the next one
+    pub fn peek(&self) -> Option<&Candle> {
+        self.last_candle.as_ref()
+    }
+
+    /// Alias of [`SubscriptionStream::peek`]
+    pub fn latest_candle(&self) -> Option<&Candle> {
+        self.peek()
+    }
+
+    /// Receives the next `n` candles and converts them with
+    /// [`to_polars_dataframe`](crate::pocketoption::candle::to_polars_dataframe).
+    #[cfg(feature = "polars")]
+    pub async fn to_polars_dataframe(
+        &mut self,
+        n: usize,
+    ) -> crate::error::BinaryOptionsResult<polars::frame::DataFrame> {
+        let mut candles = Vec::with_capacity(n);
+        for _ in 0..n {
+            candles.push(self.receive().await?);
+        }
+        crate::pocketoption::candle::to_polars_dataframe(&candles)
+    }
+
     /// Process an incoming price update based on subscription type
     fn process_update(&mut self, timestamp: f64, price: f64) -> PocketResult<Option<Candle>> {
         let asset = self.asset().to_string();
@@ -734,6 +1100,477 @@ impl SubscriptionStream {
     pub fn subscription_type(&self) -> &SubscriptionType {
         &self.sub_type
     }
+
+    /// Yield overlapping windows of the last `n` candles, see [`RollingCandleWindow`].
+    pub fn rolling_window(
+        self,
+        n: usize,
+    ) -> RollingCandleWindow<impl futures_util::Stream<Item = PocketResult<Candle>> + 'static> {
+        RollingCandleWindow::rolling(self.to_stream(), n)
+    }
+
+    /// Only yield the candles for which `predicate` returns `true`.
+    pub fn filter<P>(self, predicate: P) -> FilteredSubscriptionStream<P>
+    where
+        P: Fn(&Candle) -> bool + Send,
+    {
+        FilteredSubscriptionStream {
+            inner: self,
+            predicate,
+        }
+    }
+
+    /// Transform every candle received with `f`.
+    pub fn map_candle<F, B>(self, f: F) -> MappedSubscriptionStream<F, B>
+    where
+        F: Fn(Candle) -> B + Send,
+    {
+        MappedSubscriptionStream {
+            inner: self,
+            predicate: accept_all,
+            f,
+            _output: PhantomData,
+        }
+    }
+
+    /// Build candles every `tick_threshold` ticks instead of a time period, see
+    /// [`VolumeClockStream`].
+    pub fn with_volume_clock(mut self, tick_threshold: u64) -> VolumeClockStream {
+        self.sub_type = SubscriptionType::volume_clock(tick_threshold);
+        VolumeClockStream {
+            inner: self,
+            tick_threshold,
+        }
+    }
+
+    /// End the stream at the wall-clock time `at`, see [`DeadlineSubscriptionStream`].
+    pub fn deadline(self, at: DateTime<Utc>) -> DeadlineSubscriptionStream {
+        DeadlineSubscriptionStream::new(self, at)
+    }
+
+    /// Keep up to `capacity` candles while the consumer is busy, see [`BufferedSubscriptionStream`].
+    pub fn buffer_until_ready(self, capacity: usize) -> BufferedSubscriptionStream {
+        BufferedSubscriptionStream::new(self, capacity)
+    }
+
+    /// Split the stream in `n` receivers getting every candle, in the same order.
+    ///
+    /// A task forwards the candles to the receivers, the slowest one sets the pace. The task
+    /// stops once the stream ends or every receiver is dropped, the subscription is kept.
+    pub fn into_split(mut self, n: usize) -> Vec<SubscriptionReceiver> {
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| {
+                let (sender, receiver) = bounded_async(SPLIT_BUFFER);
+                (
+                    sender,
+                    SubscriptionReceiver {
+                        receiver,
+                        asset: self.asset.clone(),
+                    },
+                )
+            })
+            .unzip();
+        tokio::spawn(async move {
+            let mut senders: Vec<AsyncSender<Result<Candle, String>>> = senders;
+            while !senders.is_empty() {
+                let item = self.receive().await.map_err(|e| e.to_string());
+                let ended = item.is_err();
+                let mut open = Vec::with_capacity(senders.len());
+                for sender in senders {
+                    if sender.send(item.clone()).await.is_ok() {
+                        open.push(sender);
+                    }
+                }
+                senders = open;
+                if ended {
+                    break;
+                }
+            }
+        });
+        receivers
+    }
+}
+
+/// Number of candles buffered for each receiver of `SubscriptionStream::into_split`.
+const SPLIT_BUFFER: usize = 64;
+
+/// One of the receivers created by `SubscriptionStream::into_split`.
+pub struct SubscriptionReceiver {
+    receiver: AsyncReceiver<Result<Candle, String>>,
+    asset: String,
+}
+
+impl SubscriptionReceiver {
+    /// Get the asset symbol for this subscription stream
+    pub fn asset(&self) -> &str {
+        &self.asset
+    }
+
+    /// Receive the next candle from the stream
+    pub async fn receive(&mut self) -> PocketResult<Candle> {
+        match self.receiver.recv().await {
+            Ok(Ok(candle)) => Ok(candle),
+            Ok(Err(error)) => Err(PocketError::General(error)),
+            Err(e) => Err(CoreError::from(e).into()),
+        }
+    }
+
+    /// Convert to a futures Stream
+    pub fn to_stream(self) -> impl futures_util::Stream<Item = PocketResult<Candle>> + 'static {
+        Box::pin(unfold(self, |mut stream| async move {
+            let result = stream.receive().await;
+            Some((result, stream))
+        }))
+    }
+}
+
+fn accept_all(_: &Candle) -> bool {
+    true
+}
+
+/// A `SubscriptionStream` that skips the candles rejected by a predicate.
+pub struct FilteredSubscriptionStream<P> {
+    inner: SubscriptionStream,
+    predicate: P,
+}
+
+impl<P> FilteredSubscriptionStream<P>
+where
+    P: Fn(&Candle) -> bool + Send,
+{
+    /// Receive the next candle accepted by the predicate
+    pub async fn receive(&mut self) -> PocketResult<Candle> {
+        loop {
+            let candle = self.inner.receive().await?;
+            if (self.predicate)(&candle) {
+                return Ok(candle);
+            }
+        }
+    }
+
+    /// Transform every accepted candle with `f`.
+    pub fn map_candle<F, B>(self, f: F) -> MappedSubscriptionStream<F, B, P>
+    where
+        F: Fn(Candle) -> B + Send,
+    {
+        MappedSubscriptionStream {
+            inner: self.inner,
+            predicate: self.predicate,
+            f,
+            _output: PhantomData,
+        }
+    }
+
+    /// Get the asset symbol for this subscription stream
+    pub fn asset(&self) -> &str {
+        self.inner.asset()
+    }
+
+    /// Convert to a futures Stream
+    pub fn to_stream(self) -> impl futures_util::Stream<Item = PocketResult<Candle>> + 'static
+    where
+        P: 'static,
+    {
+        Box::pin(unfold(self, |mut stream| async move {
+            let result = stream.receive().await;
+            Some((result, stream))
+        }))
+    }
+}
+
+/// A `SubscriptionStream` that transforms each candle with a closure.
+///
+/// The optional `P` predicate is applied before the transformation, which is how
+/// `stream.filter(pred).map_candle(f)` is represented.
+pub struct MappedSubscriptionStream<F, B, P = fn(&Candle) -> bool> {
+    inner: SubscriptionStream,
+    predicate: P,
+    f: F,
+    _output: PhantomData<fn() -> B>,
+}
+
+impl<F, B, P> MappedSubscriptionStream<F, B, P>
+where
+    F: Fn(Candle) -> B + Send,
+    P: Fn(&Candle) -> bool + Send,
+{
+    /// Receive the next transformed candle
+    pub async fn receive(&mut self) -> PocketResult<B> {
+        loop {
+            let candle = self.inner.receive().await?;
+            if (self.predicate)(&candle) {
+                return Ok((self.f)(candle));
+            }
+        }
+    }
+
+    /// Get the asset symbol for this subscription stream
+    pub fn asset(&self) -> &str {
+        self.inner.asset()
+    }
+
+    /// Convert to a futures Stream
+    pub fn to_stream(self) -> impl futures_util::Stream<Item = PocketResult<B>> + 'static
+    where
+        F: 'static,
+        B: 'static,
+        P: 'static,
+    {
+        Box::pin(unfold(self, |mut stream| async move {
+            let result = stream.receive().await;
+            Some((result, stream))
+        }))
+    }
+}
+
+/// A candle of a [`VolumeClockStream`] with the number of ticks it aggregates.
+#[derive(Debug, Clone)]
+pub struct VolumeClockCandle {
+    pub candle: Candle,
+    pub tick_count: u64,
+}
+
+/// A `SubscriptionStream` emitting a candle every `tick_threshold` ticks.
+///
+/// The candles are built by [`SubscriptionType::VolumeClock`], their volume is the summed volume
+/// of the ticks, as PocketOption doesn't send it this is usually `None`.
+pub struct VolumeClockStream {
+    inner: SubscriptionStream,
+    tick_threshold: u64,
+}
+
+impl VolumeClockStream {
+    /// Get the asset symbol for this subscription stream
+    pub fn asset(&self) -> &str {
+        self.inner.asset()
+    }
+
+    /// Number of ticks aggregated in each candle
+    pub fn tick_threshold(&self) -> u64 {
+        self.tick_threshold
+    }
+
+    /// Receive the next volume-clock candle
+    pub async fn receive(&mut self) -> PocketResult<VolumeClockCandle> {
+        let candle = self.inner.receive().await?;
+        let tick_count = match self.inner.subscription_type() {
+            SubscriptionType::VolumeClock(aggregator) => aggregator.last_tick_count(),
+            _ => self.tick_threshold,
+        };
+        Ok(VolumeClockCandle { candle, tick_count })
+    }
+
+    /// Convert to a futures Stream
+    pub fn to_stream(
+        self,
+    ) -> impl futures_util::Stream<Item = PocketResult<VolumeClockCandle>> + 'static {
+        Box::pin(unfold(self, |mut stream| async move {
+            let result = stream.receive().await;
+            Some((result, stream))
+        }))
+    }
+}
+
+/// A `SubscriptionStream` ending at a wall-clock time.
+///
+/// Once `at` is reached `receive` returns `PocketError::DeadlinePassed`, which is also the last
+/// item of `to_stream`. Wrap `receive` in `tokio::time::timeout` to also bound each candle.
+pub struct DeadlineSubscriptionStream {
+    inner: SubscriptionStream,
+    at: DateTime<Utc>,
+    deadline: tokio::time::Instant,
+}
+
+impl DeadlineSubscriptionStream {
+    fn new(inner: SubscriptionStream, at: DateTime<Utc>) -> Self {
+        // A deadline in the past gives a negative duration, which ends the stream right away
+        let remaining = (at - Utc::now()).to_std().unwrap_or_default();
+        Self {
+            inner,
+            at,
+            deadline: tokio::time::Instant::now() + remaining,
+        }
+    }
+
+    /// Get the asset symbol for this subscription stream
+    pub fn asset(&self) -> &str {
+        self.inner.asset()
+    }
+
+    /// Wall-clock time at which the stream ends
+    pub fn at(&self) -> DateTime<Utc> {
+        self.at
+    }
+
+    /// Receive the next candle, or `PocketError::DeadlinePassed` once the deadline is reached
+    pub async fn receive(&mut self) -> PocketResult<Candle> {
+        if tokio::time::Instant::now() >= self.deadline {
+            return Err(PocketError::DeadlinePassed);
+        }
+        tokio::time::timeout_at(self.deadline, self.inner.receive())
+            .await
+            .map_err(|_| PocketError::DeadlinePassed)?
+    }
+
+    /// Convert to a futures Stream, ending after `PocketError::DeadlinePassed` is yielded
+    pub fn to_stream(self) -> impl futures_util::Stream<Item = PocketResult<Candle>> + 'static {
+        Box::pin(unfold(Some(self), |stream| async move {
+            let mut stream = stream?;
+            let result = stream.receive().await;
+            let next = match result {
+                Err(PocketError::DeadlinePassed) => None,
+                _ => Some(stream),
+            };
+            Some((result, next))
+        }))
+    }
+}
+
+/// Candles read ahead by a `BufferedSubscriptionStream`, shared with its reading task.
+struct CandleBuffer {
+    candles: Mutex<VecDeque<Candle>>,
+    /// Error that ended the underlying stream, returned once the candles are consumed.
+    end: Mutex<Option<String>>,
+    ready: Notify,
+    dropped: AtomicU64,
+}
+
+impl CandleBuffer {
+    /// Converts the buffered candles, oldest first, without consuming them.
+    #[cfg(feature = "polars")]
+    fn to_polars_dataframe(&self) -> crate::error::BinaryOptionsResult<polars::frame::DataFrame> {
+        let candles: Vec<Candle> = self
+            .candles
+            .lock()
+            .expect("Failed to acquire candle buffer lock")
+            .iter()
+            .cloned()
+            .collect();
+        crate::pocketoption::candle::to_polars_dataframe(&candles)
+    }
+}
+
+/// A `SubscriptionStream` read ahead by a task, so a slow consumer doesn't stall the
+/// subscriptions module.
+///
+/// The candles wait in a buffer of bounded capacity until `receive` is called, oldest first.
+/// Once the buffer is full the oldest candle is dropped and counted in `dropped`. Dropping the
+/// stream stops the task and unsubscribes like dropping the `SubscriptionStream`.
+pub struct BufferedSubscriptionStream {
+    buffer: Arc<CandleBuffer>,
+    asset: String,
+    task: JoinHandle<()>,
+}
+
+impl BufferedSubscriptionStream {
+    fn new(mut inner: SubscriptionStream, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let buffer = Arc::new(CandleBuffer {
+            candles: Mutex::new(VecDeque::with_capacity(capacity)),
+            end: Mutex::new(None),
+            ready: Notify::new(),
+            dropped: AtomicU64::new(0),
+        });
+        let asset = inner.asset().to_string();
+        let shared = buffer.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                match inner.receive().await {
+                    Ok(candle) => {
+                        let mut candles = shared
+                            .candles
+                            .lock()
+                            .expect("Failed to acquire candle buffer lock");
+                        if candles.len() >= capacity {
+                            candles.pop_front();
+                            shared.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        candles.push_back(candle);
+                    }
+                    Err(e) => {
+                        *shared
+                            .end
+                            .lock()
+                            .expect("Failed to acquire candle buffer lock") = Some(e.to_string());
+                        shared.ready.notify_one();
+                        break;
+                    }
+                }
+                shared.ready.notify_one();
+            }
+        });
+        Self {
+            buffer,
+            asset,
+            task,
+        }
+    }
+
+    /// Get the asset symbol for this subscription stream
+    pub fn asset(&self) -> &str {
+        &self.asset
+    }
+
+    /// Number of candles dropped because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.buffer.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Number of candles waiting to be received
+    pub fn buffered(&self) -> usize {
+        self.buffer
+            .candles
+            .lock()
+            .expect("Failed to acquire candle buffer lock")
+            .len()
+    }
+
+    /// Converts the candles waiting to be received, oldest first, without consuming them.
+    #[cfg(feature = "polars")]
+    pub fn to_polars_dataframe(
+        &self,
+    ) -> crate::error::BinaryOptionsResult<polars::frame::DataFrame> {
+        self.buffer.to_polars_dataframe()
+    }
+
+    /// Receive the oldest buffered candle, waiting for one if the buffer is empty
+    pub async fn receive(&mut self) -> PocketResult<Candle> {
+        loop {
+            if let Some(candle) = self
+                .buffer
+                .candles
+                .lock()
+                .expect("Failed to acquire candle buffer lock")
+                .pop_front()
+            {
+                return Ok(candle);
+            }
+            if let Some(reason) = self
+                .buffer
+                .end
+                .lock()
+                .expect("Failed to acquire candle buffer lock")
+                .clone()
+            {
+                return Err(PocketError::General(reason));
+            }
+            self.buffer.ready.notified().await;
+        }
+    }
+
+    /// Convert to a futures Stream
+    pub fn to_stream(self) -> impl futures_util::Stream<Item = PocketResult<Candle>> + 'static {
+        Box::pin(unfold(self, |mut stream| async move {
+            let result = stream.receive().await;
+            Some((result, stream))
+        }))
+    }
+}
+
+impl Drop for BufferedSubscriptionStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
 }
 
 // Add Clone implementation for SubscriptionStream
@@ -744,6 +1581,7 @@ impl Clone for SubscriptionStream {
             sender: self.sender.clone(),
             asset: self.asset.clone(),
             sub_type: self.sub_type.clone(),
+            last_candle: self.last_candle.clone(),
         }
     }
 }
@@ -785,15 +1623,502 @@ impl Drop for SubscriptionStream {
         // This will notify the main module to remove this subscription
         // We don't need to wait for response since we're consuming self
         // and it will be dropped anyway
-        let _ = self
-            .sender
+        let (_, cmd) = command_bus::Command::new(Command::Unsubscribe {
+            asset: self.asset.clone(),
+        });
+        let _ = self.sender.as_sync().send(cmd).inspect_err(|e| {
+            warn!(target: "SubscriptionStream", "Failed to send unsubscribe command: {}", e);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use binary_options_tools_core_pre::reimports::{Message, bounded_async};
+    use chrono::Utc;
+    use futures_util::StreamExt;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use binary_options_tools_core_pre::traits::ApiModule;
+
+    use super::{
+        DeadLetterQueue, StreamData, SubscriptionStream, SubscriptionsApiModule, VolumeClockCandle,
+    };
+    use crate::pocketoption::{
+        candle::SubscriptionType,
+        error::PocketError,
+        ssid::Ssid,
+        state::{State, StateBuilder},
+    };
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_handle_send_sync() {
+        // This will fail to compile if the handle is not Send + Sync
+        assert_send_sync::<<SubscriptionsApiModule as ApiModule<State>>::Handle>();
+    }
+
+    fn stream_with_prices(prices: &[f64]) -> SubscriptionStream {
+        let (data_sender, receiver) = bounded_async(prices.len() + 1);
+        let (sender, _command_receiver) = bounded_async(8);
+        for (i, price) in prices.iter().enumerate() {
+            data_sender
+                .as_sync()
+                .send(StreamData::Update {
+                    asset: "EURUSD_otc".to_string(),
+                    price: *price,
+                    timestamp: i as f64,
+                })
+                .unwrap();
+        }
+        data_sender
             .as_sync()
-            .send(Command::Unsubscribe {
-                asset: self.asset.clone(),
-                command_id: Uuid::new_v4(),
+            .send(StreamData::Terminated {
+                reason: "test finished".to_string(),
+            })
+            .unwrap();
+        SubscriptionStream::new(
+            receiver,
+            sender,
+            "EURUSD_otc".to_string(),
+            SubscriptionType::none(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_map_candle() {
+        let mut stream = stream_with_prices(&[1.0, 2.0]).map_candle(|c| c.close);
+        assert_eq!(stream.receive().await.unwrap(), Decimal::from(1));
+        assert_eq!(stream.receive().await.unwrap(), Decimal::from(2));
+        assert!(stream.receive().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_peek() {
+        let mut stream = stream_with_prices(&[1.0, 2.0, 3.0]);
+        assert!(stream.peek().is_none());
+        stream.receive().await.unwrap();
+        let second = stream.receive().await.unwrap();
+        let peeked = stream.peek().unwrap();
+        assert_eq!(
+            (peeked.timestamp, peeked.close),
+            (second.timestamp, second.close)
+        );
+        assert_eq!(stream.latest_candle().unwrap().close, Decimal::from(2));
+        // Peeking doesn't consume the third candle
+        assert_eq!(stream.receive().await.unwrap().close, Decimal::from(3));
+    }
+
+    #[tokio::test]
+    async fn test_filter_then_map_candle() {
+        let stream = stream_with_prices(&[1.0, 5.0, 2.0, 7.0])
+            .filter(|c| c.close > Decimal::from(3))
+            .map_candle(|c| (c.timestamp, c.close.to_string()));
+        let results: Vec<_> = stream.to_stream().take(3).collect().await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &(1.0, "5".to_string()));
+        assert_eq!(results[1].as_ref().unwrap(), &(3.0, "7".to_string()));
+        assert!(results[2].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_into_split() {
+        let prices = [1.0, 2.0, 3.0, 4.0];
+        let receivers = stream_with_prices(&prices).into_split(3);
+        assert_eq!(receivers.len(), 3);
+        for receiver in receivers {
+            let results: Vec<_> = receiver.to_stream().take(prices.len() + 1).collect().await;
+            let closes: Vec<_> = results[..prices.len()]
+                .iter()
+                .map(|candle| candle.as_ref().unwrap().close)
+                .collect();
+            assert_eq!(
+                closes,
+                prices.map(|price| Decimal::try_from(price).unwrap())
+            );
+            assert!(results[prices.len()].is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_active() {
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        let state: Arc<State> = Arc::new(StateBuilder::default().ssid(ssid).build().unwrap());
+        let (command_sender, command_receiver) = bounded_async(8);
+        let (response_sender, response_receiver) = bounded_async(8);
+        let (message_sender, message_receiver) = bounded_async(8);
+        let (ws_sender, ws_receiver) = bounded_async(64);
+        let (mut module, handle) = SubscriptionsApiModule::new_combined(
+            state,
+            command_receiver,
+            command_sender,
+            response_receiver,
+            response_sender,
+            message_receiver,
+            ws_sender,
+        );
+        let module_task = tokio::spawn(async move { module.run().await });
+
+        // The server confirms every changeSymbol request with a price update
+        let server = tokio::spawn(async move {
+            while let Ok(msg) = ws_receiver.recv().await {
+                let text = msg.to_text().unwrap().to_string();
+                if !text.contains("changeSymbol") {
+                    continue;
+                }
+                let asset = text.split('"').nth(5).unwrap().to_string();
+                let update = json!([[asset, 1751906100.0, 1.1]]).to_string();
+                message_sender
+                    .send(Arc::new(Message::binary(update.into_bytes())))
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let _eurusd = handle
+            .subscribe("EURUSD_otc".to_string(), SubscriptionType::none())
+            .await
+            .unwrap();
+        let _aapl = handle
+            .subscribe("#AAPL_otc".to_string(), SubscriptionType::none())
+            .await
+            .unwrap();
+        assert_eq!(
+            handle.list_active().await.unwrap(),
+            vec!["#AAPL_otc".to_string(), "EURUSD_otc".to_string()]
+        );
+
+        handle.unsubscribe("#AAPL_otc".to_string()).await.unwrap();
+        assert_eq!(
+            handle.list_active().await.unwrap(),
+            vec!["EURUSD_otc".to_string()]
+        );
+        module_task.abort();
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_candle_stream_stats() {
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        let state: Arc<State> = Arc::new(StateBuilder::default().ssid(ssid).build().unwrap());
+        let (command_sender, command_receiver) = bounded_async(8);
+        let (response_sender, response_receiver) = bounded_async(8);
+        let (message_sender, message_receiver) = bounded_async(32);
+        let (ws_sender, ws_receiver) = bounded_async(64);
+        let (mut module, handle) = SubscriptionsApiModule::new_combined(
+            state.clone(),
+            command_receiver,
+            command_sender,
+            response_receiver,
+            response_sender,
+            message_receiver,
+            ws_sender,
+        );
+        let module_task = tokio::spawn(async move { module.run().await });
+        assert!(state.candle_stream_stats("EURUSD_otc").is_none());
+
+        // 10 consecutive ticks, then 5 missing seconds before the last one
+        let timestamps = (0..10).chain([15]).map(|i| 1751906100.0 + i as f64);
+        let server = async {
+            ws_receiver.recv().await.unwrap();
+            for timestamp in timestamps {
+                let update = json!([["EURUSD_otc", timestamp, 1.1]]).to_string();
+                message_sender
+                    .send(Arc::new(Message::binary(update.into_bytes())))
+                    .await
+                    .unwrap();
+            }
+        };
+        let (stream, ()) = tokio::join!(
+            handle.subscribe("EURUSD_otc".to_string(), SubscriptionType::none()),
+            server
+        );
+        let stream = stream.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while state
+                .candle_stream_stats("EURUSD_otc")
+                .is_none_or(|stats| stats.candle_count < 11)
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let stats = state.candle_stream_stats("EURUSD_otc").unwrap();
+        assert_eq!(stats.asset, "EURUSD_otc");
+        assert_eq!(stats.candle_count, 11);
+        assert_eq!(stats.gap_count, 1);
+        assert!((stats.tick_rate_per_second - 10.0 / 15.0).abs() < 1e-9);
+        assert_eq!(stats.last_candle_ts.unwrap().timestamp(), 1751906115);
+
+        handle.unsubscribe("EURUSD_otc".to_string()).await.unwrap();
+        assert!(state.candle_stream_stats("EURUSD_otc").is_none());
+        drop(stream);
+        module_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_subscription_info() {
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        let state: Arc<State> = Arc::new(StateBuilder::default().ssid(ssid).build().unwrap());
+        let (command_sender, command_receiver) = bounded_async(8);
+        let (response_sender, response_receiver) = bounded_async(8);
+        let (message_sender, message_receiver) = bounded_async(8);
+        let (ws_sender, ws_receiver) = bounded_async(64);
+        let (mut module, handle) = SubscriptionsApiModule::new_combined(
+            state,
+            command_receiver,
+            command_sender,
+            response_receiver,
+            response_sender,
+            message_receiver,
+            ws_sender,
+        );
+        let module_task = tokio::spawn(async move { module.run().await });
+        assert!(handle.get_subscription_info("EURUSD_otc").await.is_err());
+
+        let server = async {
+            ws_receiver.recv().await.unwrap();
+            for (timestamp, price) in [(1751906100.0, 1.1), (1751906101.0, 1.2)] {
+                let update = json!([["EURUSD_otc", timestamp, price]]).to_string();
+                message_sender
+                    .send(Arc::new(Message::binary(update.into_bytes())))
+                    .await
+                    .unwrap();
+            }
+        };
+        let (stream, ()) = tokio::join!(
+            handle.subscribe("EURUSD_otc".to_string(), SubscriptionType::none()),
+            server
+        );
+        let mut stream = stream.unwrap();
+        stream.receive().await.unwrap();
+        stream.receive().await.unwrap();
+
+        let info = handle.get_subscription_info("EURUSD_otc").await.unwrap();
+        assert_eq!(info.asset, "EURUSD_otc");
+        assert!(info.candle_count >= 2);
+        assert_eq!(info.last_price, Some(1.2));
+        assert_eq!(info.last_tick_ts.unwrap().timestamp(), 1751906101);
+        assert!(info.subscribed_at <= Utc::now());
+
+        handle.unsubscribe("EURUSD_otc".to_string()).await.unwrap();
+        assert!(handle.get_subscription_info("EURUSD_otc").await.is_err());
+        module_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_subscription_timeout() {
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        let state: Arc<State> = Arc::new(
+            StateBuilder::default()
+                .ssid(ssid)
+                .subscription_timeout(Duration::from_millis(100))
+                .build()
+                .unwrap(),
+        );
+        let (command_sender, command_receiver) = bounded_async(8);
+        let (response_sender, response_receiver) = bounded_async(8);
+        let (_message_sender, message_receiver) = bounded_async(8);
+        let (ws_sender, ws_receiver) = bounded_async(64);
+        let (mut module, handle) = SubscriptionsApiModule::new_combined(
+            state,
+            command_receiver,
+            command_sender,
+            response_receiver,
+            response_sender,
+            message_receiver,
+            ws_sender,
+        );
+        let module_task = tokio::spawn(async move { module.run().await });
+
+        // The server ignores the changeSymbol request
+        let started = Instant::now();
+        let result = handle
+            .subscribe("EURUSD_otc".to_string(), SubscriptionType::none())
+            .await;
+        assert!(matches!(
+            result,
+            Err(PocketError::Timeout { ref context, .. }) if context == "EURUSD_otc"
+        ));
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert!(
+            ws_receiver
+                .recv()
+                .await
+                .unwrap()
+                .to_text()
+                .unwrap()
+                .contains("changeSymbol")
+        );
+        assert!(handle.list_active().await.unwrap().is_empty());
+        module_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_buffer_until_ready() {
+        let prices: Vec<f64> = (0..100).map(|i| i as f64).collect();
+        let mut stream = stream_with_prices(&prices).buffer_until_ready(5);
+        assert_eq!(stream.asset(), "EURUSD_otc");
+
+        let mut received = 0;
+        let mut last = None;
+        while let Ok(candle) = stream.receive().await {
+            // Candles are received in order even when some are dropped
+            assert!(last.is_none_or(|last| last < candle.timestamp));
+            last = Some(candle.timestamp);
+            received += 1;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(stream.dropped() > 0);
+        assert_eq!(received + stream.dropped(), 100);
+        assert_eq!(stream.buffered(), 0);
+    }
+
+    #[cfg(feature = "polars")]
+    #[tokio::test]
+    async fn test_to_polars_dataframe() {
+        let prices: Vec<f64> = (0..10).map(|i| 1.0 + i as f64 / 100.0).collect();
+        let mut stream = stream_with_prices(&prices);
+        let df = stream.to_polars_dataframe(5).await.unwrap();
+        assert_eq!(df.height(), 5);
+        assert_eq!(
+            df.get_column_names(),
+            ["open_time", "open", "high", "low", "close"]
+        );
+
+        // The 5 remaining candles are buffered, then the stream ends
+        let stream = stream.buffer_until_ready(10);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while stream.buffered() < 5 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let df = stream.to_polars_dataframe().unwrap();
+        assert_eq!(df.height(), 5);
+        assert_eq!(stream.buffered(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_resubscribed_resets_candle() {
+        let (data_sender, receiver) = bounded_async(8);
+        let (sender, _command_receiver) = bounded_async(8);
+        let mut stream = SubscriptionStream::new(
+            receiver,
+            sender,
+            "EURUSD_otc".to_string(),
+            SubscriptionType::chunk(2),
+        );
+        let update = |price: f64| StreamData::Update {
+            asset: "EURUSD_otc".to_string(),
+            price,
+            timestamp: price,
+        };
+        data_sender.send(update(1.0)).await.unwrap();
+        data_sender.send(StreamData::Resubscribed).await.unwrap();
+        data_sender.send(update(2.0)).await.unwrap();
+        data_sender.send(update(3.0)).await.unwrap();
+
+        // The tick received before the reconnection isn't part of the candle
+        let candle = stream.receive().await.unwrap();
+        assert_eq!(candle.open, Decimal::from(2));
+        assert_eq!(candle.close, Decimal::from(3));
+    }
+
+    #[tokio::test]
+    async fn test_volume_clock() {
+        let prices: Vec<f64> = (0..100).map(|i| i as f64).collect();
+        let stream = stream_with_prices(&prices).with_volume_clock(25);
+        assert_eq!(stream.tick_threshold(), 25);
+        let results: Vec<_> = stream.to_stream().take(5).collect().await;
+        for (i, result) in results[..4].iter().enumerate() {
+            let VolumeClockCandle { candle, tick_count } = result.as_ref().unwrap();
+            assert_eq!(*tick_count, 25);
+            assert_eq!(candle.open, Decimal::from(i * 25));
+            assert_eq!(candle.close, Decimal::from(i * 25 + 24));
+            // PocketOption ticks have no volume
+            assert_eq!(candle.volume, None);
+        }
+        // The 4 candles used every tick, the stream then ends
+        assert!(results[4].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deadline() {
+        let (data_sender, receiver) = bounded_async(8);
+        let (sender, _command_receiver) = bounded_async(8);
+        let stream = SubscriptionStream::new(
+            receiver,
+            sender,
+            "EURUSD_otc".to_string(),
+            SubscriptionType::none(),
+        );
+        let started = Instant::now();
+        let mut stream = stream
+            .deadline(Utc::now() + chrono::Duration::milliseconds(100))
+            .to_stream();
+
+        for i in 0..3 {
+            data_sender
+                .send(StreamData::Update {
+                    asset: "EURUSD_otc".to_string(),
+                    price: i as f64,
+                    timestamp: i as f64,
+                })
+                .await
+                .unwrap();
+            assert!(stream.next().await.unwrap().is_ok());
+        }
+        // The sender is still open, only the deadline ends the stream
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(PocketError::DeadlinePassed))
+        ));
+        assert!(started.elapsed() >= Duration::from_millis(90));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_deadline_in_the_past() {
+        let mut stream = stream_with_prices(&[1.0])
+            .deadline(Utc::now() - chrono::Duration::seconds(1))
+            .to_stream();
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(PocketError::DeadlinePassed))
+        ));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[test]
+    fn test_dead_letter_queue_evicts_oldest() {
+        let queue = DeadLetterQueue::new(3);
+        for i in 0..5 {
+            queue.push("EURUSD_otc".to_string(), i as f64, i as f64);
+        }
+        assert_eq!(queue.dropped_count(), 2);
+        let prices: Vec<f64> = queue
+            .drain()
+            .into_iter()
+            .map(|letter| match letter {
+                StreamData::Update { price, .. } => price,
+                other => panic!("Unexpected dead letter: {other:?}"),
             })
-            .inspect_err(|e| {
-                warn!(target: "SubscriptionStream", "Failed to send unsubscribe command: {}", e);
-            });
+            .collect();
+        assert_eq!(prices, vec![2.0, 3.0, 4.0]);
+        assert!(queue.is_empty());
     }
 }
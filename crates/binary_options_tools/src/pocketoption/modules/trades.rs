@@ -2,7 +2,7 @@ use std::{fmt::Debug, sync::Arc};
 
 use async_trait::async_trait;
 use binary_options_tools_core_pre::{
-    error::{CoreError, CoreResult},
+    error::CoreResult,
     reimports::{AsyncReceiver, AsyncSender, Message},
     traits::{ApiModule, Rule},
 };
@@ -16,8 +16,11 @@ use crate::pocketoption::{
     state::State,
     types::{Action, Deal, FailOpenOrder, MultiPatternRule, OpenOrder},
 };
+use crate::utils::command_bus::{self, CommandBus};
 
 /// Command enum for the `TradesApiModule`.
+///
+/// The id of the command is used as the request id of the order sent to the server.
 #[derive(Debug)]
 pub enum Command {
     /// Command to place a new trade.
@@ -26,7 +29,6 @@ pub enum Command {
         action: Action,
         amount: f64,
         time: u32,
-        req_id: Uuid,
     },
 }
 
@@ -34,10 +36,7 @@ pub enum Command {
 #[derive(Debug)]
 pub enum CommandResponse {
     /// Response for an `OpenOrder` command.
-    Success {
-        req_id: Uuid,
-        deal: Box<Deal>,
-    },
+    Success(Box<Deal>),
     Error(Box<FailOpenOrder>),
 }
 
@@ -49,10 +48,12 @@ enum ServerResponse {
 }
 
 /// Handle for interacting with the `TradesApiModule`.
+///
+/// Clones of the handle can place trades concurrently, every response goes to the trade it
+/// belongs to.
 #[derive(Clone)]
 pub struct TradesHandle {
-    sender: AsyncSender<Command>,
-    receiver: AsyncReceiver<CommandResponse>,
+    bus: CommandBus<Command, CommandResponse>,
 }
 
 impl TradesHandle {
@@ -64,40 +65,19 @@ impl TradesHandle {
         amount: f64,
         time: u32,
     ) -> PocketResult<Deal> {
-        // let order = OpenOrder::new(amount, asset, action, time, demo)
-        // Implement logic to create an OpenOrder and send the command.
-        // 1. Send `Command::OpenOrder`.
-        // 2. Await and return `CommandResponse::OpenOrder`.
-        let id = Uuid::new_v4(); // Generate a unique request ID for this order
-        self.sender
-            .send(Command::OpenOrder {
-                asset,
-                action,
-                amount,
-                time,
-                req_id: id,
-            })
-            .await
-            .map_err(CoreError::from)?;
-        loop {
-            match self.receiver.recv().await {
-                Ok(CommandResponse::Success { req_id, deal }) => {
-                    if req_id == id {
-                        return Ok(*deal);
-                    } else {
-                        // If the request ID does not match, continue waiting for the correct response
-                        continue;
-                    }
-                }
-                Ok(CommandResponse::Error(fail)) => {
-                    return Err(PocketError::FailOpenOrder {
-                        error: fail.error,
-                        amount: fail.amount,
-                        asset: fail.asset,
-                    });
-                }
-                Err(e) => return Err(CoreError::from(e).into()),
-            }
+        let command = Command::OpenOrder {
+            asset,
+            action,
+            amount,
+            time,
+        };
+        match self.bus.send(command).await? {
+            CommandResponse::Success(deal) => Ok(*deal),
+            CommandResponse::Error(fail) => Err(PocketError::FailOpenOrder {
+                error: fail.error,
+                amount: fail.amount,
+                asset: fail.asset,
+            }),
         }
     }
 
@@ -115,16 +95,38 @@ impl TradesHandle {
 /// The API module for handling all trade-related operations.
 pub struct TradesApiModule {
     state: Arc<State>,
-    command_receiver: AsyncReceiver<Command>,
-    command_responder: AsyncSender<CommandResponse>,
+    command_receiver: AsyncReceiver<command_bus::Command<Command>>,
+    command_responder: AsyncSender<command_bus::Command<CommandResponse>>,
     message_receiver: AsyncReceiver<Arc<Message>>,
     to_ws_sender: AsyncSender<Message>,
+    /// Orders waiting for the server answer as (request id, asset, amount), oldest first
+    pending_orders: Vec<(Uuid, String, f64)>,
+}
+
+impl TradesApiModule {
+    async fn respond(&self, request_id: Uuid, response: CommandResponse) -> CoreResult<()> {
+        self.command_responder
+            .send(command_bus::Command::from_id(request_id, response, None))
+            .await?;
+        Ok(())
+    }
+
+    /// Removes the pending order a `failopenOrder` answers. The server doesn't send the request
+    /// id back, so the oldest order with the same asset and amount is picked.
+    fn take_failed_order(&mut self, fail: &FailOpenOrder) -> Option<Uuid> {
+        let index = self
+            .pending_orders
+            .iter()
+            .position(|(_, asset, amount)| *asset == fail.asset && *amount == fail.amount)
+            .or_else(|| (!self.pending_orders.is_empty()).then_some(0))?;
+        Some(self.pending_orders.remove(index).0)
+    }
 }
 
 #[async_trait]
 impl ApiModule<State> for TradesApiModule {
-    type Command = Command;
-    type CommandResponse = CommandResponse;
+    type Command = command_bus::Command<Command>;
+    type CommandResponse = command_bus::Command<CommandResponse>;
     type Handle = TradesHandle;
 
     fn new(
@@ -140,6 +142,7 @@ impl ApiModule<State> for TradesApiModule {
             command_responder,
             message_receiver,
             to_ws_sender,
+            pending_orders: Vec::new(),
         }
     }
 
@@ -147,26 +150,23 @@ impl ApiModule<State> for TradesApiModule {
         sender: AsyncSender<Self::Command>,
         receiver: AsyncReceiver<Self::CommandResponse>,
     ) -> Self::Handle {
-        TradesHandle { sender, receiver }
+        TradesHandle {
+            bus: CommandBus::new(sender, receiver),
+        }
     }
 
     async fn run(&mut self) -> CoreResult<()> {
-        // TODO: Implement the main run loop.
-        // This loop should handle both incoming commands from the handle
-        // and incoming WebSocket messages for trade responses.
-        //
         loop {
             select! {
               Ok(cmd) = self.command_receiver.recv() => {
-                  match cmd {
-                      Command::OpenOrder { asset, action, amount, time, req_id } => {
-                      // Create OpenOrder and send to WebSocket.
-                      let order = OpenOrder::new(amount, asset, action, time, self.state.is_demo() as u32, req_id);
-                      self.to_ws_sender.send(Message::text(order.to_string())).await?;
+                  let command_id = cmd.id();
+                  match cmd.into_data() {
+                      Command::OpenOrder { asset, action, amount, time } => {
+                          self.pending_orders.push((command_id, asset.clone(), amount));
+                          let order = OpenOrder::new(amount, asset, action, time, self.state.is_demo() as u32, command_id);
+                          self.to_ws_sender.send(Message::text(order.to_string())).await?;
                       }
                   }
-                // Handle OpenOrder: send to websocket.
-                // Handle CheckResult: check state, maybe wait for update.
               },
               Ok(msg) = self.message_receiver.recv() => {
                   if let Message::Binary(data) = &*msg {
@@ -174,19 +174,18 @@ impl ApiModule<State> for TradesApiModule {
                       if let Ok(response) = serde_json::from_slice::<ServerResponse>(data) {
                           match response {
                               ServerResponse::Success(deal) => {
-                                  // Handle successopenOrder.
-                                  // Send CommandResponse::Success to command_responder.
                                   self.state.trade_state.add_opened_deal(*deal.clone()).await;
                                   info!(target: "TradesApiModule", "Trade opened: {}", deal.id);
-                                  self.command_responder.send(CommandResponse::Success {
-                                      req_id: deal.request_id.unwrap_or_default(), // A request should always have a request_id, only for when returning updateOpenedDeals or updateClosedDeals it can not have any
-                                      deal,
-                                  }).await?;
+                                  // A request should always have a request_id, only for when returning updateOpenedDeals or updateClosedDeals it can not have any
+                                  let request_id = deal.request_id.unwrap_or_default();
+                                  self.pending_orders.retain(|(id, _, _)| *id != request_id);
+                                  self.respond(request_id, CommandResponse::Success(deal)).await?;
                               }
                               ServerResponse::Fail(fail) => {
-                                  // Handle failopenOrder.
-                                  // Send CommandResponse::Error to command_responder.
-                                  self.command_responder.send(CommandResponse::Error(fail)).await?;
+                                  match self.take_failed_order(&fail) {
+                                      Some(request_id) => self.respond(request_id, CommandResponse::Error(fail)).await?,
+                                      None => warn!(target: "TradesApiModule", "Received a failed order without pending order: {}", fail.error),
+                                  }
                               }
                           }
                       } else {
@@ -194,8 +193,6 @@ impl ApiModule<State> for TradesApiModule {
                           warn!(target: "TradesApiModule", "Received unrecognized message: {:?}", msg);
                       }
                   }
-                // Handle successopenOrder/failopenOrder.
-                // Find the corresponding pending request and send response via command_responder.
               }
             }
         }
@@ -211,3 +208,16 @@ impl ApiModule<State> for TradesApiModule {
         ]))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_handle_send_sync() {
+        // This will fail to compile if the handle is not Send + Sync
+        assert_send_sync::<<TradesApiModule as ApiModule<State>>::Handle>();
+    }
+}
@@ -1,24 +1,36 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
 
 use binary_options_tools_core_pre::{
     builder::ClientBuilder,
     client::Client,
+    connector::ConnectorError,
+    middleware::StatisticsMiddleware,
+    reimports::bounded_async,
+    statistics::{ConnectionEventType, ConnectionStats},
     testing::{TestingWrapper, TestingWrapperBuilder},
     traits::ApiModule,
 };
 use chrono::{DateTime, Utc};
+use futures_util::stream::unfold;
+use tokio::task::JoinSet;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
 use uuid::Uuid;
 
+#[cfg(feature = "trade_simulation")]
+use crate::pocketoption::simulation::run_expiration_checker;
 use crate::{
     error::BinaryOptionsError,
     pocketoption::{
         candle::{Candle, SubscriptionType},
         connect::PocketConnect,
         error::{PocketError, PocketResult},
+        guards::{MaxDailyLossGuard, TradeGuard},
         modules::{
             assets::AssetsModule,
             balance::BalanceModule,
-            deals::DealsApiModule,
+            correlation::{CorrelationMatrix, CorrelationModule},
+            deals::{DealEvent, DealsApiModule, WinRateSnapshot},
             get_candles::GetCandlesApiModule,
             keep_alive::{InitModule, KeepAliveModule},
             raw::{
@@ -30,19 +42,32 @@ use crate::{
         },
         ssid::Ssid,
         state::{State, StateBuilder},
-        types::{Action, Assets, Deal},
+        types::{
+            Action, AssetInfo, Assets, BatchHistoryResult, CandleStreamStats, ConnectionInfo, Deal,
+            HealthStatus, SubscriptionInfo,
+        },
+        utils::{fetch_certificate_fingerprint, write_deals_csv},
     },
     utils::print_handler,
 };
 
 const MINIMUM_TRADE_AMOUNT: f64 = 1.0;
 const MAXIMUM_TRADE_AMOUNT: f64 = 20000.0;
+/// Maximum time `PocketOption::new` waits for the assets to be loaded.
+const ASSETS_LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+/// Time `PocketOption::batch_history` waits for each history.
+const BATCH_HISTORY_TIMEOUT: Duration = Duration::from_secs(30);
+/// Environment variable read by `PocketOption::new_from_env`.
+pub const SSID_ENV_VAR: &str = "POCKET_OPTION_SSID";
 
 /// PocketOption client for interacting with the PocketOption trading platform.
 ///
 /// This client provides methods for trading, checking balances, subscribing to
 /// asset updates, and managing the connection to the PocketOption platform.
 ///
+/// The client is cheap to clone: clones share the connection, the state and the module handles,
+/// so they can be moved to other tasks to trade or subscribe concurrently.
+///
 /// # Example
 /// ```
 /// use binary_options_tools_pocketoption::PocketOption;
@@ -63,34 +88,109 @@ pub struct PocketOption {
 }
 
 impl PocketOption {
-    fn builder(ssid: impl ToString) -> PocketResult<ClientBuilder<State>> {
-        let state = StateBuilder::default().ssid(Ssid::parse(ssid)?).build()?;
-
-        Ok(ClientBuilder::new(PocketConnect, state)
-            .with_lightweight_module::<KeepAliveModule>()
+    fn builder(state: State) -> ClientBuilder<State> {
+        let statistics = state.statistics.clone();
+        let connection_timeout = state.connection_timeout;
+        ClientBuilder::new(PocketConnect, state)
+            .with_connection_timeout(connection_timeout)
+            .with_middleware(Box::new(StatisticsMiddleware::new(statistics)))
+            .with_timed_lightweight_module::<KeepAliveModule>()
             .with_lightweight_module::<InitModule>()
             .with_lightweight_module::<BalanceModule>()
             .with_lightweight_module::<ServerTimeModule>()
             .with_lightweight_module::<AssetsModule>()
+            .with_lightweight_module::<CorrelationModule>()
             .with_module::<TradesApiModule>()
             .with_module::<DealsApiModule>()
             .with_module::<SubscriptionsApiModule>()
             .with_module::<GetCandlesApiModule>()
             .with_module::<RawApiModule>()
-            .with_lightweight_handler(|msg, _, _| Box::pin(print_handler(msg))))
+            .with_lightweight_handler(|msg, _, _| Box::pin(print_handler(msg)))
     }
 
     pub async fn new(ssid: impl ToString) -> PocketResult<Self> {
-        let builder = Self::builder(ssid)?;
+        let state = StateBuilder::default().ssid(Ssid::parse(ssid)?).build()?;
+        Self::new_with_state(state).await
+    }
+
+    /// Creates a new client with the SSID read from the `POCKET_OPTION_SSID` environment variable.
+    pub async fn new_from_env() -> PocketResult<Self> {
+        Self::new_from_env_with(env_var).await
+    }
+
+    /// Same as [`new_from_env`](Self::new_from_env) but the variables are read with `lookup`,
+    /// which returns the value of the variable it is given or `None` if it isn't set.
+    pub async fn new_from_env_with(
+        lookup: impl FnOnce(&str) -> Option<String>,
+    ) -> PocketResult<Self> {
+        Self::new(ssid_from_env(lookup)?).await
+    }
+
+    /// Creates a new client with the SSID read from the `POCKET_OPTION_SSID` environment variable
+    /// and the settings of a TOML configuration file, see `StateBuilder::config_file`.
+    pub async fn new_from_env_with_config(config_path: &Path) -> PocketResult<Self> {
+        Self::new_from_env_with_config_and(env_var, config_path).await
+    }
+
+    /// Same as [`new_from_env_with_config`](Self::new_from_env_with_config) but the variables are
+    /// read with `lookup`, see [`new_from_env_with`](Self::new_from_env_with).
+    pub async fn new_from_env_with_config_and(
+        lookup: impl FnOnce(&str) -> Option<String>,
+        config_path: &Path,
+    ) -> PocketResult<Self> {
+        let state = StateBuilder::default()
+            .ssid(Ssid::parse(ssid_from_env(lookup)?)?)
+            .config_file(config_path)?
+            .build()?;
+        Self::new_with_state(state).await
+    }
+
+    /// Creates a new client from a custom `State`, see `StateBuilder` for the available options.
+    ///
+    /// Waits for the connection and, unless disabled with `StateBuilder::wait_for_assets(false)`,
+    /// for the assets to be loaded. Fails with `PocketError::CertificatePinningFailure` if the
+    /// server certificate doesn't match the pinned ones, and with `PocketError::ConnectionTimeout`
+    /// if the first connection attempt takes longer than `StateBuilder::connection_timeout`.
+    pub async fn new_with_state(state: State) -> PocketResult<Self> {
+        let wait_for_assets = state.wait_for_assets;
+        let builder = Self::builder(state);
         let (client, mut runner) = builder.build().await?;
 
         let _runner = tokio::spawn(async move { runner.run().await });
-        client.wait_connected().await;
+        if let Err(e) = Self::wait_first_connection(&client).await {
+            if !_runner.is_finished() {
+                let _ = client.shutdown().await;
+            }
+            return Err(e);
+        }
 
-        Ok(Self {
+        let pocket_option = Self {
             client,
             _runner: Arc::new(_runner),
-        })
+        };
+        if wait_for_assets {
+            pocket_option
+                .wait_for_asset_data(ASSETS_LOAD_TIMEOUT)
+                .await?;
+        }
+        Ok(pocket_option)
+    }
+
+    /// Waits until the client is connected, failing on the connection errors that are not retried.
+    async fn wait_first_connection(client: &Client<State>) -> PocketResult<()> {
+        let mut failures = client.connection_failure_watch();
+        loop {
+            tokio::select! {
+                _ = client.wait_connected() => return Ok(()),
+                Ok(()) = failures.changed() => {
+                    let failure = failures.borrow_and_update().clone();
+                    let timeout = client.state.connection_timeout;
+                    if let Some(error) = failure.as_deref().and_then(|e| connection_error(e, timeout)) {
+                        return Err(error);
+                    }
+                }
+            }
+        }
     }
 
     pub async fn new_with_url(ssid: impl ToString, url: String) -> PocketResult<Self> {
@@ -98,19 +198,7 @@ impl PocketOption {
             .ssid(Ssid::parse(ssid)?)
             .default_connection_url(url)
             .build()?;
-        let builder = ClientBuilder::new(PocketConnect, state)
-            .with_lightweight_handler(|msg, _, _| Box::pin(print_handler(msg)))
-            .with_lightweight_module::<KeepAliveModule>()
-            .with_lightweight_module::<InitModule>()
-            .with_lightweight_module::<BalanceModule>()
-            .with_lightweight_module::<ServerTimeModule>()
-            .with_lightweight_module::<AssetsModule>()
-            .with_module::<TradesApiModule>()
-            .with_module::<DealsApiModule>()
-            .with_module::<SubscriptionsApiModule>()
-            .with_module::<GetCandlesApiModule>()
-            .with_module::<RawApiModule>()
-            .with_lightweight_handler(|msg, _, _| Box::pin(print_handler(msg)));
+        let builder = Self::builder(state);
         let (client, mut runner) = builder.build().await?;
 
         let _runner = tokio::spawn(async move { runner.run().await });
@@ -159,11 +247,57 @@ impl PocketOption {
     }
 
     pub fn is_demo(&self) -> bool {
-        let state = &self.client.state;
-        state.ssid.demo()
+        self.client.state.is_demo()
+    }
+
+    /// Switches to the demo account of `ssid` and reconnects with it.
+    ///
+    /// Returns `PocketError::SsidDemoMismatch` without touching the current session if `ssid`
+    /// is a real account session.
+    pub async fn set_demo(&self, ssid: impl ToString) -> PocketResult<()> {
+        self.switch_ssid(ssid, true).await
+    }
+
+    /// Switches to the real account of `ssid` and reconnects with it.
+    ///
+    /// Returns `PocketError::SsidDemoMismatch` without touching the current session if `ssid`
+    /// is a demo account session.
+    pub async fn set_live(&self, ssid: impl ToString) -> PocketResult<()> {
+        self.switch_ssid(ssid, false).await
+    }
+
+    async fn switch_ssid(&self, ssid: impl ToString, demo: bool) -> PocketResult<()> {
+        let ssid = Ssid::parse(ssid)?;
+        if ssid.demo() != demo {
+            return Err(PocketError::SsidDemoMismatch {
+                expected: demo,
+                actual: ssid.demo(),
+            });
+        }
+        self.client.state.set_ssid(ssid);
+        self.reconnect().await
+    }
+
+    /// Registers a risk rule checked before every trade, see `guards` for the built-in ones.
+    /// Guards are checked in registration order and the first rejection cancels the trade.
+    pub fn set_trade_guard(&self, guard: impl TradeGuard + 'static) {
+        self.client.state.add_trade_guard(Arc::new(guard));
+    }
+
+    /// Registers a [`MaxDailyLossGuard`] rejecting the trades that could make the net loss of the
+    /// day exceed `limit_usd`, the deals already closed today are counted. The day follows the
+    /// server clock.
+    pub async fn set_daily_loss_limit(&self, limit_usd: f64) {
+        let guard = Arc::new(MaxDailyLossGuard::new(limit_usd).with_state(&self.client.state));
+        self.client.state.add_trade_guard(guard.clone());
+        guard.load_closed_deals(&self.client.state).await;
     }
 
     /// Executes a trade on the specified asset.
+    ///
+    /// The trade is checked by the guards registered with `set_trade_guard` first, it fails with
+    /// `PocketError::TradeRejected` if any of them refuses it. While guards are registered the
+    /// trades are placed one at a time, so each check sees the trades opened before it.
     /// # Arguments
     /// * `asset` - The asset to trade.
     /// * `action` - The action to perform (Call or Put).
@@ -191,11 +325,31 @@ impl PocketOption {
                     "Amount must be at most {MAXIMUM_TRADE_AMOUNT}"
                 )));
             }
+            // Held until the guards are notified of the opened trade
+            let _guards_lock = self.client.state.lock_trade_guards().await;
+            self.client
+                .state
+                .check_trade_guards(&asset.to_string(), action.clone(), amount, time)
+                .await?;
+            #[cfg(feature = "trade_simulation")]
+            if self.client.state.simulation.is_enabled() {
+                let payout = assets.get(&asset.to_string()).map_or(0, |a| a.payout);
+                let open_time = self.server_time().await;
+                let deal = self
+                    .client
+                    .state
+                    .simulation
+                    .open_deal(asset.to_string(), action, amount, time, payout, open_time)
+                    .await?;
+                self.client.state.notify_trade_opened(&deal).await;
+                return Ok((deal.id, deal));
+            }
             if let Some(handle) = self.client.get_handle::<TradesApiModule>().await {
-                handle
+                let deal = handle
                     .trade(asset.to_string(), action, amount, time)
-                    .await
-                    .map(|d| (d.id, d))
+                    .await?;
+                self.client.state.notify_trade_opened(&deal).await;
+                Ok((deal.id, deal))
             } else {
                 Err(BinaryOptionsError::General("TradesApiModule not found".into()).into())
             }
@@ -238,12 +392,224 @@ impl PocketOption {
         self.trade(asset, Action::Put, time, amount).await
     }
 
+    /// Places several buy trades concurrently.
+    /// # Arguments
+    /// * `orders` - The trades to place, as (asset, time, amount).
+    /// # Returns
+    /// The result of every trade in the order of `orders`, each trade is checked by the trade
+    /// guards on its own.
+    pub async fn multi_buy(
+        &self,
+        orders: Vec<(String, u32, f64)>,
+    ) -> Vec<PocketResult<(Uuid, Deal)>> {
+        self.multi_trade(orders, Action::Call).await
+    }
+
+    /// Places several sell trades concurrently.
+    /// # Arguments
+    /// * `orders` - The trades to place, as (asset, time, amount).
+    /// # Returns
+    /// The result of every trade in the order of `orders`, each trade is checked by the trade
+    /// guards on its own.
+    pub async fn multi_sell(
+        &self,
+        orders: Vec<(String, u32, f64)>,
+    ) -> Vec<PocketResult<(Uuid, Deal)>> {
+        self.multi_trade(orders, Action::Put).await
+    }
+
+    async fn multi_trade(
+        &self,
+        orders: Vec<(String, u32, f64)>,
+        action: Action,
+    ) -> Vec<PocketResult<(Uuid, Deal)>> {
+        let mut tasks = JoinSet::new();
+        let mut results: Vec<PocketResult<(Uuid, Deal)>> = Vec::with_capacity(orders.len());
+        for (index, (asset, time, amount)) in orders.into_iter().enumerate() {
+            results.push(Err(PocketError::General(format!(
+                "The task placing the trade on '{asset}' failed"
+            ))));
+            let client = self.clone();
+            let action = action.clone();
+            tasks.spawn(async move { (index, client.trade(asset, action, time, amount).await) });
+        }
+        while let Some(task) = tasks.join_next().await {
+            match task {
+                Ok((index, result)) => results[index] = result,
+                Err(e) => warn!("Trade task failed: {e}"),
+            }
+        }
+        results
+    }
+
+    /// Trades with Martingale position sizing.
+    ///
+    /// Places a trade of `initial_amount` and waits for its result, after every loss the amount
+    /// is multiplied by `multiplier` and the trade repeated, for at most `max_attempts` trades.
+    /// Every trade goes through `trade`, so the trade guards are checked at each step.
+    /// # Arguments
+    /// * `asset` - The asset to trade.
+    /// * `action` - The action to perform (Call or Put).
+    /// * `time` - The time to trade.
+    /// * `initial_amount` - The amount of the first trade.
+    /// * `multiplier` - The factor applied to the amount after a loss.
+    /// * `max_attempts` - The maximum number of trades placed.
+    /// # Returns
+    /// The first deal that isn't a loss, or the last losing deal once `max_attempts` trades
+    /// were placed. Fails with `PocketError::MaxTradeAmountExceeded` before trading an amount
+    /// above the maximum.
+    pub async fn trade_with_martingale(
+        &self,
+        asset: impl ToString,
+        action: Action,
+        time: u32,
+        initial_amount: f64,
+        multiplier: f64,
+        max_attempts: u32,
+    ) -> PocketResult<Deal> {
+        if max_attempts == 0 {
+            return Err(PocketError::General(
+                "max_attempts must be at least 1".to_string(),
+            ));
+        }
+        let asset = asset.to_string();
+        let mut amount = initial_amount;
+        let mut attempt = 1;
+        loop {
+            if amount > MAXIMUM_TRADE_AMOUNT {
+                return Err(PocketError::MaxTradeAmountExceeded {
+                    amount,
+                    max: MAXIMUM_TRADE_AMOUNT,
+                });
+            }
+            let (id, _) = self.trade(&asset, action.clone(), time, amount).await?;
+            let deal = self.result(id).await?;
+            if deal.profit >= 0.0 || attempt >= max_attempts {
+                return Ok(deal);
+            }
+            debug!(target: "PocketOption", "Martingale trade {attempt} on {asset} lost, trading again");
+            // Keep the amounts in cents, as accepted by the server
+            amount = (amount * multiplier * 100.0).round() / 100.0;
+            attempt += 1;
+        }
+    }
+
+    /// Cancels an open trade before its expiry.
+    ///
+    /// PocketOption's protocol has no message to cancel an open trade, so this always returns
+    /// `PocketError::NotSupported` without contacting the server.
+    pub async fn cancel_order(&self, _id: Uuid) -> PocketResult<bool> {
+        Err(PocketError::NotSupported("Order cancellation".into()))
+    }
+
+    /// Enables the paper trading mode: `trade`, `buy` and `sell` stop sending orders to the
+    /// server and open simulated deals at the last price received for the asset instead.
+    /// Simulated deals are closed when the server time reaches their expiration and can be
+    /// checked with `result`.
+    #[cfg(feature = "trade_simulation")]
+    pub fn enable_simulation_mode(&self) {
+        let state = &self.client.state;
+        state.simulation.enable();
+        if state.simulation.start_checker() {
+            tokio::spawn(run_expiration_checker(Arc::downgrade(state)));
+        }
+    }
+
+    /// Disables the paper trading mode, pending simulated deals are still closed.
+    #[cfg(feature = "trade_simulation")]
+    pub fn disable_simulation_mode(&self) {
+        self.client.state.simulation.disable();
+    }
+
+    /// Checks if the paper trading mode is enabled.
+    #[cfg(feature = "trade_simulation")]
+    pub fn is_simulation_mode(&self) -> bool {
+        self.client.state.simulation.is_enabled()
+    }
+
     /// Gets the current server time.
     /// If the server time is not set, it returns None.
     pub async fn server_time(&self) -> DateTime<Utc> {
         self.client.state.get_server_datetime().await
     }
 
+    /// Returns basic diagnostics about the current connection: the server host, when the
+    /// connection was established and the average keep-alive round trip.
+    pub fn connection_info(&self) -> ConnectionInfo {
+        self.client.state.connection_info()
+    }
+
+    /// Returns the connection state, the subscription count, the server clock drift, the last
+    /// connection error and the balance in a single call, e.g. for monitoring.
+    pub async fn health_check(&self) -> HealthStatus {
+        let state = &self.client.state;
+        let active_subscriptions = match self.client.get_handle::<SubscriptionsApiModule>().await {
+            Some(handle) => handle.get_active_subscriptions_count().await.unwrap_or(0),
+            None => 0,
+        };
+        let last_error = state
+            .statistics
+            .get_stats()
+            .await
+            .connection_history
+            .into_iter()
+            .rev()
+            .filter(|event| {
+                matches!(
+                    event.event_type,
+                    ConnectionEventType::ConnectionFailure | ConnectionEventType::Disconnection
+                )
+            })
+            .find_map(|event| event.reason);
+        let server_time_drift_ms = state.server_time.read().await.offset.num_milliseconds();
+        HealthStatus {
+            is_connected: self.client.is_connected(),
+            active_subscriptions,
+            server_time_drift_ms,
+            last_error,
+            balance: self.balance().await,
+        }
+    }
+
+    /// Opens a new connection to the server and returns the SHA-256 fingerprint of its certificate.
+    ///
+    /// Useful to get the value to pass to `StateBuilder::with_pinned_certificates`.
+    /// Uses the default connection url if one was set, otherwise the first server of the SSID.
+    pub async fn fetch_server_certificate_fingerprint(&self) -> PocketResult<String> {
+        let state = &self.client.state;
+        let url = match state.default_connection_url.clone() {
+            Some(url) => url,
+            None => state
+                .ssid()
+                .servers()
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| PocketError::General("No server available".into()))?,
+        };
+        let headers = PocketConnect::custom_headers(state);
+        fetch_certificate_fingerprint(
+            url,
+            &headers,
+            state.proxy.as_ref(),
+            state.connection_timeout,
+        )
+        .await
+    }
+
+    /// Waits until the assets sent by the server after authentication are loaded.
+    ///
+    /// Returns `PocketError::Timeout` if they are not received within `timeout`.
+    pub async fn wait_for_asset_data(&self, timeout: Duration) -> PocketResult<()> {
+        tokio::time::timeout(timeout, self.client.state.wait_for_assets())
+            .await
+            .map_err(|_| PocketError::Timeout {
+                task: "wait_for_asset_data".to_string(),
+                context: "waiting for the assets to be loaded".to_string(),
+                duration: timeout,
+            })
+    }
+
     /// Gets the current assets.
     pub async fn assets(&self) -> Option<Assets> {
         let state = &self.client.state;
@@ -254,12 +620,144 @@ impl PocketOption {
         None
     }
 
+    /// Returns the active assets sorted by symbol, waiting for the server to send them first.
+    ///
+    /// Unlike `assets()`, the inactive assets are left out and the assets not being loaded yet
+    /// isn't an error, unless they aren't received within 30 seconds.
+    pub async fn available_assets(&self) -> PocketResult<Vec<AssetInfo>> {
+        self.wait_for_asset_data(ASSETS_LOAD_TIMEOUT).await?;
+        let assets = self
+            .assets()
+            .await
+            .ok_or_else(|| PocketError::General("Assets not loaded".to_string()))?;
+        let mut available: Vec<AssetInfo> = assets
+            .0
+            .values()
+            .filter(|asset| asset.is_active())
+            .map(|asset| AssetInfo::new(asset, MINIMUM_TRADE_AMOUNT))
+            .collect();
+        available.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Ok(available)
+    }
+
+    /// Same as `available_assets`, keeping only the assets paying at least `min_payout` percent.
+    pub async fn available_assets_by_payout(
+        &self,
+        min_payout: i32,
+    ) -> PocketResult<Vec<AssetInfo>> {
+        let mut available = self.available_assets().await?;
+        available.retain(|asset| asset.payout >= min_payout);
+        Ok(available)
+    }
+
+    /// Returns the expiry times in seconds `trade` accepts for `asset`, sorted.
+    ///
+    /// Fails if the assets aren't loaded yet or if the asset is unknown or inactive.
+    pub async fn get_asset_expiry_options(&self, asset: &str) -> PocketResult<Vec<u32>> {
+        let assets = self
+            .assets()
+            .await
+            .ok_or_else(|| PocketError::General("Assets not loaded".to_string()))?;
+        let asset = assets.get(asset).ok_or_else(|| {
+            PocketError::InvalidAsset(format!("Asset with symbol `{asset}` not found"))
+        })?;
+        if !asset.is_active() {
+            return Err(PocketError::InvalidAsset("Asset is not active".into()));
+        }
+        Ok(asset.expiry_options())
+    }
+
+    /// Returns a stream yielding the payout of every active asset each time the server
+    /// changes any of them. The current payouts are not replayed, use `assets()` for those.
+    pub fn payout_stream(
+        &self,
+    ) -> impl futures_util::Stream<Item = HashMap<String, i32>> + Send + 'static {
+        let receiver = self.client.state.subscribe_payouts();
+        Box::pin(unfold(receiver, |receiver| async move {
+            let assets = receiver.recv().await.ok()?;
+            Some((assets.payouts(), receiver))
+        }))
+    }
+
+    /// Returns the win rate, profit factor and streaks of the deals closed since the client
+    /// was created.
+    pub fn win_rate_stats(&self) -> WinRateSnapshot {
+        self.client.state.win_rate.snapshot()
+    }
+
+    /// Returns Pearson's correlation of the recent closes of two subscribed assets, `None` until
+    /// they share at least 3 candles. See `StateBuilder::correlation_window` for the window.
+    pub fn correlation(&self, asset_a: &str, asset_b: &str) -> Option<f64> {
+        self.client.state.correlation.correlation(asset_a, asset_b)
+    }
+
+    /// Returns the correlation of every pair of subscribed assets, keyed by the names of the
+    /// assets sorted alphabetically.
+    pub fn all_correlations(&self) -> HashMap<(String, String), f64> {
+        self.client.state.correlation.all_correlations()
+    }
+
+    /// Same as `all_correlations` as a square matrix, easier to serialize or export to CSV.
+    pub fn correlation_matrix(&self) -> CorrelationMatrix {
+        CorrelationMatrix::from_map(&self.all_correlations())
+    }
+
+    /// Returns the tick rate, the number of gaps and the last update of a subscribed asset,
+    /// `None` if it isn't subscribed. Useful to check the quality of a stream while debugging.
+    pub fn candle_stream_stats(&self, asset: &str) -> Option<CandleStreamStats> {
+        self.client.state.candle_stream_stats(asset)
+    }
+
+    /// Returns the payout changes of `asset` received during the last `hours`, oldest first.
+    /// Only the changes of the last 24 hours are kept.
+    pub fn payout_history(&self, asset: &str, hours: u32) -> Vec<(DateTime<Utc>, i32)> {
+        self.client.state.payout_history(asset, hours)
+    }
+
+    /// Returns a stream yielding the server time every time a price update moves it forward,
+    /// the times are never decreasing.
+    pub fn watch_server_time(
+        &self,
+    ) -> impl futures_util::Stream<Item = DateTime<Utc>> + Send + 'static {
+        let receiver = self.client.state.subscribe_server_time();
+        Box::pin(unfold(receiver, |receiver| async move {
+            let time = receiver.recv().await.ok()?;
+            Some((time, receiver))
+        }))
+    }
+
+    /// Returns a stream yielding the server time every `interval`, even when no price update
+    /// arrives, for clocks needing a steady update rate. The first time is yielded right away
+    /// and the stream ends once the client is disconnected.
+    pub fn server_time_poll(
+        &self,
+        interval: Duration,
+    ) -> impl futures_util::Stream<Item = DateTime<Utc>> + Send + 'static {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Box::pin(unfold(
+            (self.client.clone(), ticker),
+            |(client, mut ticker)| async move {
+                ticker.tick().await;
+                if !client.is_connected() {
+                    return None;
+                }
+                let time = client.state.get_server_datetime().await;
+                Some((time, (client, ticker)))
+            },
+        ))
+    }
+
     /// Checks the result of a trade by its ID.
     /// # Arguments
     /// * `id` - The ID of the trade to check.
     /// # Returns
     /// A `PocketResult` containing the `Deal` if successful, or an error if the trade fails.
     pub async fn result(&self, id: Uuid) -> PocketResult<Deal> {
+        #[cfg(feature = "trade_simulation")]
+        if self.client.state.simulation.contains(id).await {
+            return self.client.state.simulation.result(id).await;
+        }
         if let Some(handle) = self.client.get_handle::<DealsApiModule>().await {
             handle.check_result(id).await
         } else {
@@ -274,6 +772,16 @@ impl PocketOption {
     /// # Returns
     /// A `PocketResult` containing the `Deal` if successful, or an error if the trade fails.
     pub async fn result_with_timeout(&self, id: Uuid, timeout: Duration) -> PocketResult<Deal> {
+        #[cfg(feature = "trade_simulation")]
+        if self.client.state.simulation.contains(id).await {
+            return tokio::time::timeout(timeout, self.client.state.simulation.result(id))
+                .await
+                .map_err(|_| PocketError::Timeout {
+                    task: "check_result".to_string(),
+                    context: format!("Waiting for simulated trade '{id}' result"),
+                    duration: timeout,
+                })?;
+        }
         if let Some(handle) = self.client.get_handle::<DealsApiModule>().await {
             handle.check_result_with_timeout(id, timeout).await
         } else {
@@ -281,15 +789,135 @@ impl PocketOption {
         }
     }
 
+    /// Waits for the results of several trades concurrently.
+    /// # Arguments
+    /// * `ids` - The IDs of the trades to check.
+    /// * `timeout` - The duration to wait for all the trades, shared by all of them.
+    /// # Returns
+    /// The result of every trade in the order of `ids`, the trades that didn't close in time
+    /// return a `PocketError::Timeout`.
+    pub async fn result_many(&self, ids: Vec<Uuid>, timeout: Duration) -> Vec<PocketResult<Deal>> {
+        let mut tasks = JoinSet::new();
+        for (index, id) in ids.iter().copied().enumerate() {
+            let client = self.clone();
+            tasks.spawn(async move { (index, client.result_with_timeout(id, timeout).await) });
+        }
+        let mut results: Vec<PocketResult<Deal>> = ids
+            .iter()
+            .map(|id| {
+                Err(PocketError::General(format!(
+                    "The task checking trade '{id}' failed"
+                )))
+            })
+            .collect();
+        while let Some(task) = tasks.join_next().await {
+            match task {
+                Ok((index, result)) => results[index] = result,
+                Err(e) => warn!("Trade result task failed: {e}"),
+            }
+        }
+        results
+    }
+
+    /// Streams the results of several trades as they close, unlike `result_many` which waits
+    /// for all of them.
+    /// # Arguments
+    /// * `ids` - The IDs of the trades to check.
+    /// * `timeout` - The duration to wait for all the trades, shared by all of them.
+    /// # Returns
+    /// A stream of `(id, deal)` in closing order, ending once every trade closed or the timeout
+    /// elapsed. The trades that can't be checked, like unknown ones, are skipped.
+    pub fn result_stream(
+        &self,
+        ids: Vec<Uuid>,
+        timeout: Duration,
+    ) -> impl futures_util::Stream<Item = (Uuid, Deal)> + Send + 'static {
+        let (sender, receiver) = bounded_async(ids.len().max(1));
+        let client = self.clone();
+        tokio::spawn(async move {
+            let mut tasks = JoinSet::new();
+            for id in ids {
+                let client = client.clone();
+                tasks.spawn(async move { (id, client.result(id).await) });
+            }
+            let deadline = tokio::time::sleep(timeout);
+            tokio::pin!(deadline);
+            // Dropping the tasks on return stops waiting for the remaining trades
+            loop {
+                tokio::select! {
+                    _ = &mut deadline => {
+                        warn!("{} trades didn't close in {:?}", tasks.len(), timeout);
+                        return;
+                    }
+                    task = tasks.join_next() => match task {
+                        Some(Ok((id, Ok(deal)))) => {
+                            if sender.send((id, deal)).await.is_err() {
+                                return;
+                            }
+                        }
+                        Some(Ok((id, Err(e)))) => warn!("Failed to check trade '{id}': {e}"),
+                        Some(Err(e)) => warn!("Trade result task failed: {e}"),
+                        None => return,
+                    }
+                }
+            }
+        });
+        Box::pin(unfold(receiver, |receiver| async move {
+            let result = receiver.recv().await.ok()?;
+            Some((result, receiver))
+        }))
+    }
+
+    /// Streams the deals as they are opened and closed, unlike `get_opened_deals` which is a snapshot.
+    /// # Returns
+    /// A `PocketResult` containing a stream of `DealEvent`, slow consumers miss the events that
+    /// don't fit in the buffer.
+    pub async fn deal_events(
+        &self,
+    ) -> PocketResult<impl futures_util::Stream<Item = DealEvent> + Send + 'static> {
+        if let Some(handle) = self.client.get_handle::<DealsApiModule>().await {
+            let receiver = handle.watch_open().await?;
+            Ok(Box::pin(unfold(receiver, |receiver| async move {
+                let event = receiver.recv().await.ok()?;
+                Some((event, receiver))
+            })))
+        } else {
+            Err(BinaryOptionsError::General("DealsApiModule not found".into()).into())
+        }
+    }
+
     /// Gets the currently opened deals.
     pub async fn get_opened_deals(&self) -> HashMap<Uuid, Deal> {
         self.client.state.trade_state.get_opened_deals().await
     }
 
+    /// Gets the connection statistics of this client: attempts, disconnections, messages and bytes.
+    pub async fn get_stats(&self) -> ConnectionStats {
+        self.client.state.statistics.get_stats().await
+    }
+
+    /// Exports the connection statistics as JSON, to be aggregated with the ones of other
+    /// processes using `ConnectionStats::from_json`.
+    pub async fn get_stats_json(&self) -> String {
+        self.client.state.statistics.export_json().await
+    }
+
     /// Gets the currently closed deals.
     pub async fn get_closed_deals(&self) -> HashMap<Uuid, Deal> {
         self.client.state.trade_state.get_closed_deals().await
     }
+
+    /// Exports the closed deals to a CSV file at `path`, sorted by close time.
+    /// # Arguments
+    /// * `path` - The file to write, it will be created or truncated.
+    /// # Returns
+    /// A `PocketResult` containing the number of deals written.
+    pub async fn export_deals_csv(&self, path: &Path) -> PocketResult<usize> {
+        let deals = self.get_closed_deals().await.into_values().collect();
+        let file = std::fs::File::create(path)?;
+        write_deals_csv(file, deals)
+    }
+
     /// Clears the currently closed deals.
     pub async fn clear_closed_deals(&self) {
         self.client.state.trade_state.clear_closed_deals().await
@@ -324,6 +952,57 @@ impl PocketOption {
         }
     }
 
+    /// Subscribes to the order book depth of an asset.
+    ///
+    /// Not live: the PocketOption protocol only streams prices, without any bid/ask or depth
+    /// data, so this always returns `PocketError::NotSupported`.
+    pub async fn subscribe_orderbook(&self, _asset: &str) -> PocketResult<()> {
+        Err(PocketError::NotSupported("Order book data".into()))
+    }
+
+    /// Subscribes to an asset's updates and gets its recent candles at the same time, to seed
+    /// indicators before trading on the live stream.
+    ///
+    /// The history and the subscription are requested together, so the last history candle
+    /// should be at most `history_periods` seconds older than the stream. If the gap is larger
+    /// (plus `StateBuilder::history_gap_threshold`) a `PocketError::HistoryGap` warning is logged.
+    /// # Arguments
+    /// * `asset` - The asset to subscribe to.
+    /// * `sub_type` - How the stream candles are aggregated.
+    /// * `history_periods` - The period of the history candles, same as in `history`.
+    /// # Returns
+    /// The history candles, oldest first, and the live stream.
+    pub async fn subscribe_with_initial_history(
+        &self,
+        asset: impl ToString,
+        sub_type: SubscriptionType,
+        history_periods: u32,
+    ) -> PocketResult<(Vec<Candle>, SubscriptionStream)> {
+        let asset = asset.to_string();
+        let handle = self
+            .client
+            .get_handle::<SubscriptionsApiModule>()
+            .await
+            .ok_or_else(|| {
+                BinaryOptionsError::General("SubscriptionsApiModule not found".into())
+            })?;
+        if let Some(assets) = self.assets().await
+            && assets.get(&asset).is_none()
+        {
+            return Err(PocketError::InvalidAsset(asset));
+        }
+        let (history, stream) = handle
+            .subscribe_with_history(asset.clone(), history_periods, sub_type)
+            .await?;
+        let server_time = self.client.state.get_server_time().await;
+        let max_gap =
+            Duration::from_secs(history_periods as u64) + self.client.state.history_gap_threshold;
+        if let Some(gap) = history_gap(&asset, &history, server_time, max_gap) {
+            warn!(target: "PocketOption", "{gap}");
+        }
+        Ok((history, stream))
+    }
+
     pub async fn unsubscribe(&self, asset: impl ToString) -> PocketResult<()> {
         if let Some(handle) = self.client.get_handle::<SubscriptionsApiModule>().await
             && let Some(assets) = self.assets().await
@@ -338,6 +1017,24 @@ impl PocketOption {
         }
     }
 
+    /// Returns the assets currently subscribed, sorted alphabetically.
+    pub async fn active_subscriptions(&self) -> PocketResult<Vec<String>> {
+        if let Some(handle) = self.client.get_handle::<SubscriptionsApiModule>().await {
+            handle.list_active().await
+        } else {
+            Err(BinaryOptionsError::General("SubscriptionsApiModule not found".into()).into())
+        }
+    }
+
+    /// Returns when `asset` was subscribed, how many updates were received and the last one.
+    pub async fn subscription_info(&self, asset: &str) -> PocketResult<SubscriptionInfo> {
+        if let Some(handle) = self.client.get_handle::<SubscriptionsApiModule>().await {
+            handle.get_subscription_info(asset).await
+        } else {
+            Err(BinaryOptionsError::General("SubscriptionsApiModule not found".into()).into())
+        }
+    }
+
     /// Gets historical candle data for a specific asset.
     ///
     /// # Arguments
@@ -416,6 +1113,35 @@ impl PocketOption {
         }
     }
 
+    /// Gets the candles between `from` and `to`, splitting the range in several requests if needed.
+    ///
+    /// # Arguments
+    /// * `asset` - Trading symbol (e.g., "EURUSD_otc")
+    /// * `period` - Time period for each candle in seconds
+    /// * `from` - Start of the range
+    /// * `to` - End of the range
+    ///
+    /// # Returns
+    /// The candles inside the range sorted by timestamp
+    pub async fn get_candles_range(
+        &self,
+        asset: impl ToString,
+        period: i64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> PocketResult<Vec<Candle>> {
+        if let Some(handle) = self.client.get_handle::<GetCandlesApiModule>().await {
+            if let Some(assets) = self.assets().await
+                && assets.get(&asset.to_string()).is_none()
+            {
+                return Err(PocketError::InvalidAsset(asset.to_string()));
+            }
+            handle.get_candles_range(asset, period, from, to).await
+        } else {
+            Err(BinaryOptionsError::General("GetCandlesApiModule not found".into()).into())
+        }
+    }
+
     /// Gets historical candle data for a specific asset and period.
     /// # Arguments
     /// * `asset` - The asset to get historical data for.
@@ -439,27 +1165,137 @@ impl PocketOption {
         }
     }
 
-    pub async fn get_handle<M: ApiModule<State>>(&self) -> Option<M::Handle> {
-        self.client.get_handle::<M>().await
-    }
-
-    /// Disconnects the client while keeping the configuration intact.
-    /// The connection can be re-established later using `connect()`.
-    /// This is useful for temporarily closing the connection without losing credentials or settings.
-    pub async fn disconnect(&self) -> PocketResult<()> {
-        self.client.disconnect().await.map_err(PocketError::from)
-    }
-
-    /// Establishes a connection after a manual disconnect.
-    /// This will reconnect using the same configuration and credentials.
-    pub async fn connect(&self) -> PocketResult<()> {
-        self.client.reconnect().await.map_err(PocketError::from)
+    /// Gets the history of several assets concurrently.
+    /// # Arguments
+    /// * `requests` - The (asset, period) pairs to fetch, duplicates are only requested once.
+    /// # Returns
+    /// The candles of the successful requests and the error of the failed ones, both keyed by
+    /// `"{asset}_{period}"`.
+    pub async fn batch_history(
+        &self,
+        requests: Vec<(String, u32)>,
+    ) -> PocketResult<BatchHistoryResult> {
+        self.batch_history_with_timeout(requests, BATCH_HISTORY_TIMEOUT)
+            .await
     }
 
-    /// Disconnects and reconnects the client.
-    pub async fn reconnect(&self) -> PocketResult<()> {
-        self.client.reconnect().await.map_err(PocketError::from)
-    }
+    /// Gets the history of several assets concurrently, waiting at most `timeout` for each one.
+    /// # Arguments
+    /// * `requests` - The (asset, period) pairs to fetch, duplicates are only requested once.
+    /// * `timeout` - The duration to wait for every history.
+    /// # Returns
+    /// The candles of the successful requests and the error of the failed ones, both keyed by
+    /// `"{asset}_{period}"`. The histories that didn't arrive in time return a
+    /// `PocketError::Timeout`.
+    pub async fn batch_history_with_timeout(
+        &self,
+        requests: Vec<(String, u32)>,
+        timeout: Duration,
+    ) -> PocketResult<BatchHistoryResult> {
+        let handle = self
+            .client
+            .get_handle::<SubscriptionsApiModule>()
+            .await
+            .ok_or_else(|| {
+                BinaryOptionsError::General("SubscriptionsApiModule not found".into())
+            })?;
+        let assets = self.assets().await;
+        let mut result = BatchHistoryResult::default();
+        let mut valid: Vec<(String, u32)> = Vec::with_capacity(requests.len());
+        for (asset, period) in requests {
+            if valid.contains(&(asset.clone(), period)) {
+                continue;
+            }
+            // Like `history`, the asset is only checked once the assets are loaded
+            if let Some(assets) = &assets
+                && assets.get(&asset).is_none()
+            {
+                result.errors.insert(
+                    format!("{asset}_{period}"),
+                    PocketError::InvalidAsset(asset),
+                );
+                continue;
+            }
+            valid.push((asset, period));
+        }
+        // Every request is sent before waiting for the responses so the server handles them
+        // concurrently
+        let mut tasks = JoinSet::new();
+        let mut keys = HashMap::new();
+        for (asset, period) in valid {
+            let handle = handle.clone();
+            let key = format!("{asset}_{period}");
+            let task_key = key.clone();
+            let task = tasks.spawn(async move {
+                let history = tokio::time::timeout(timeout, handle.history(asset, period))
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(PocketError::Timeout {
+                            task: "history".to_string(),
+                            context: format!("Waiting for the history of '{task_key}'"),
+                            duration: timeout,
+                        })
+                    });
+                (task_key, history)
+            });
+            keys.insert(task.id(), key);
+        }
+        while let Some(task) = tasks.join_next().await {
+            match task {
+                Ok((key, Ok(candles))) => {
+                    result.ok.insert(key, candles);
+                }
+                Ok((key, Err(e))) => {
+                    result.errors.insert(key, e);
+                }
+                Err(e) => {
+                    let key = keys.remove(&e.id()).unwrap_or_default();
+                    result.errors.insert(
+                        key,
+                        PocketError::General(format!("The history task failed: {e}")),
+                    );
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    pub async fn get_handle<M: ApiModule<State>>(&self) -> Option<M::Handle> {
+        self.client.get_handle::<M>().await
+    }
+
+    /// Disconnects the client while keeping the configuration intact.
+    /// The connection can be re-established later using `connect()`.
+    /// This is useful for temporarily closing the connection without losing credentials or settings.
+    pub async fn disconnect(&self) -> PocketResult<()> {
+        self.client.disconnect().await.map_err(PocketError::from)
+    }
+
+    /// Establishes a connection after a manual disconnect.
+    /// This will reconnect using the same configuration and credentials.
+    pub async fn connect(&self) -> PocketResult<()> {
+        self.client.reconnect().await.map_err(PocketError::from)
+    }
+
+    /// Disconnects and reconnects the client.
+    pub async fn reconnect(&self) -> PocketResult<()> {
+        self.client.reconnect().await.map_err(PocketError::from)
+    }
+
+    /// Reconnects if no message was received from the server for more than `threshold`,
+    /// or if none was received at all.
+    ///
+    /// Returns `true` if a reconnection was requested, `false` if the connection is fresh.
+    pub async fn reconnect_if_stale(&self, threshold: Duration) -> PocketResult<bool> {
+        let stale = self
+            .client
+            .last_message_received()
+            .is_none_or(|last| last.elapsed() > threshold);
+        if stale {
+            self.reconnect().await?;
+        }
+        Ok(stale)
+    }
 
     /// Shuts down the client and stops the runner.
     pub async fn shutdown(self) -> PocketResult<()> {
@@ -467,7 +1303,8 @@ impl PocketOption {
     }
 
     pub async fn new_testing_wrapper(ssid: impl ToString) -> PocketResult<TestingWrapper<State>> {
-        let pocket_builder = Self::builder(ssid)?;
+        let state = StateBuilder::default().ssid(Ssid::parse(ssid)?).build()?;
+        let pocket_builder = Self::builder(state);
         let builder = TestingWrapperBuilder::new()
             .with_stats_interval(Duration::from_secs(10))
             .with_log_stats(true)
@@ -483,13 +1320,1474 @@ impl PocketOption {
     }
 }
 
+/// Error returned to the caller for a failed first connection attempt, `None` if it is retried.
+fn connection_error(error: &ConnectorError, timeout: Duration) -> Option<PocketError> {
+    match error {
+        ConnectorError::Timeout => Some(PocketError::ConnectionTimeout(timeout)),
+        ConnectorError::CertificatePinning(fingerprint) => {
+            Some(PocketError::CertificatePinningFailure(fingerprint.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Value of the environment variable `key`, `None` if it isn't set or isn't unicode.
+fn env_var(key: &str) -> Option<String> {
+    std::env::var(key).ok()
+}
+
+/// Reads the SSID from the `POCKET_OPTION_SSID` variable with `lookup`.
+fn ssid_from_env(lookup: impl FnOnce(&str) -> Option<String>) -> PocketResult<String> {
+    lookup(SSID_ENV_VAR).ok_or_else(|| PocketError::MissingEnvVar(SSID_ENV_VAR.to_string()))
+}
+
+/// Returns a `PocketError::HistoryGap` if the last candle of `history` is older than `max_gap`.
+fn history_gap(
+    asset: &str,
+    history: &[Candle],
+    server_time: f64,
+    max_gap: Duration,
+) -> Option<PocketError> {
+    let last = history.last()?;
+    let gap = Duration::from_secs_f64((server_time - last.timestamp).max(0.0));
+    (gap > max_gap).then(|| PocketError::HistoryGap {
+        asset: asset.to_string(),
+        gap,
+        max_gap,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::pocketoption::candle::SubscriptionType;
+    use binary_options_tools_core_pre::{
+        connector::ConnectorError, reimports::Message, statistics::ConnectionStats,
+        testing::MockWebSocketServer,
+    };
     use core::time::Duration;
     use futures_util::StreamExt;
+    use serde_json::{Value, json};
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+
+    use super::{
+        DealEvent, PocketError, PocketOption, SSID_ENV_VAR, SubscriptionsApiModule,
+        connection_error, history_gap,
+    };
+    use crate::pocketoption::{
+        candle::Candle,
+        guards::MaxConcurrentTradesGuard,
+        proxy::ProxyConfig,
+        ssid::Ssid,
+        state::StateBuilder,
+        types::{Action, Deal},
+    };
+
+    const MOCK_SSID: &str = r#"42["auth",{"session":"mocksession","isDemo":1,"uid":1,"platform":2,"isFastHistory":true,"isOptimized":true}]"#;
+
+    /// Starts a mock server that performs the socket.io handshake and authentication.
+    async fn mock_server() -> MockWebSocketServer {
+        let server = MockWebSocketServer::bind().await.unwrap();
+        server
+            .respond_with(Message::text(r#"0{"sid":"mock"}"#))
+            .expect_send("40")
+            .respond_with(Message::text(r#"40{"sid":"mock"}"#))
+            .expect_send(r#"42["auth""#)
+            .respond_with(Message::text(
+                r#"451-["successauth",{"_placeholder":true,"num":0}]"#,
+            ))
+            .respond_with(Message::binary(r#"{"id":"mock"}"#.as_bytes().to_vec()));
+        server
+    }
+
+    /// Queues an `updateAssets` event carrying `assets` on `server`.
+    fn respond_with_assets<'a>(
+        server: &'a MockWebSocketServer,
+        assets: &Value,
+    ) -> &'a MockWebSocketServer {
+        server
+            .respond_with(Message::text(
+                r#"451-["updateAssets",{"_placeholder":true,"num":0}]"#,
+            ))
+            .respond_with(Message::binary(assets.to_string().into_bytes()))
+    }
+
+    /// Same as `mock_server`, followed by the assets update with an open `EURUSD_otc` asset
+    /// paying 92% on 60 seconds trades.
+    async fn mock_server_with_assets() -> MockWebSocketServer {
+        let server = mock_server().await;
+        let assets = json!([[
+            1, "EURUSD_otc", "EUR/USD OTC", "currency", 2, 92, 60, 30, 3, 1, 170, 0, [],
+            1751906100, true, [{"time": 60}], -1, 60, 1751906100
+        ]]);
+        respond_with_assets(&server, &assets);
+        server
+    }
+
+    /// Deal sent by the mock server for `order`, the payload of an `openOrder` message.
+    fn mock_deal(id: &str, order: &Value, profit: f64) -> Value {
+        let mut deal = Deal::mock_json(profit);
+        deal["id"] = json!(id);
+        deal["requestId"] = order["requestId"].clone();
+        deal["amount"] = order["amount"].clone();
+        deal["asset"] = order["asset"].clone();
+        deal["closePrice"] = json!(0.0);
+        deal
+    }
+
+    async fn wait_until<F, Fut>(condition: F)
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = bool>,
+    {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !condition().await {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("Condition not met in time");
+    }
+
+    #[tokio::test]
+    async fn test_mock_authentication() {
+        let server = mock_server().await;
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        let auth = server
+            .wait_for_send(r#"42["auth""#, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(auth.to_text().unwrap().contains("mocksession"));
+        server
+            .wait_for_send("indicator/load", Duration::from_secs(5))
+            .await
+            .unwrap();
+        server.verify().unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_new_from_env() {
+        let env = |key: &str| (key == SSID_ENV_VAR).then(|| MOCK_SSID.to_string());
+        match PocketOption::new_from_env_with(|_| None).await {
+            Err(PocketError::MissingEnvVar(var)) => assert_eq!(var, SSID_ENV_VAR),
+            Err(e) => panic!("Unexpected error: {e}"),
+            Ok(_) => panic!("Client created without the environment variable"),
+        }
+
+        let server = mock_server().await;
+        let config = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            config.path(),
+            format!(
+                "url = \"{}\"\nwait_for_assets = false\ndefault_symbol = \"AUDUSD_otc\"\n",
+                server.url()
+            ),
+        )
+        .unwrap();
+        let api = PocketOption::new_from_env_with_config_and(env, config.path())
+            .await
+            .unwrap();
+
+        let auth = server
+            .wait_for_send(r#"42["auth""#, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(auth.to_text().unwrap().contains("mocksession"));
+        assert_eq!(api.client.state.default_symbol, "AUDUSD_otc");
+        api.shutdown().await.unwrap();
+
+        std::fs::write(config.path(), "wait_for_assets = \"no\"\n").unwrap();
+        let result = PocketOption::new_from_env_with_config_and(env, config.path()).await;
+        assert!(matches!(result, Err(PocketError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mock_custom_headers() {
+        let server = mock_server().await;
+        let state = StateBuilder::default()
+            .ssid(Ssid::parse(MOCK_SSID).unwrap())
+            .default_connection_url(server.url())
+            .with_header("User-Agent", "TestAgent/1.0")
+            .with_header("Sec-WebSocket-Protocol", "pocket-v1")
+            .with_header("X-Region", "eu")
+            .wait_for_assets(false)
+            .build()
+            .unwrap();
+        let api = PocketOption::new_with_state(state).await.unwrap();
+
+        let headers = server.handshake_headers();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0]["user-agent"], "TestAgent/1.0");
+        assert_eq!(headers[0]["sec-websocket-protocol"], "pocket-v1");
+        assert_eq!(headers[0]["x-region"], "eu");
+        assert_eq!(headers[0]["origin"], "https://pocketoption.com");
+        assert_eq!(headers[0].get_all("user-agent").iter().count(), 1);
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_default_user_agent() {
+        let server = mock_server().await;
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+        server
+            .wait_for_send(r#"42["auth""#, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let user_agent = server.handshake_headers()[0]["user-agent"].clone();
+        assert!(user_agent.to_str().unwrap().starts_with("Mozilla/5.0"));
+        api.shutdown().await.unwrap();
+    }
+
+    /// Starts an HTTP proxy accepting a single `CONNECT` tunnel, returns its url and the
+    /// requested target.
+    async fn connect_proxy() -> (String, tokio::task::JoinHandle<String>) {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let task = tokio::spawn(async move {
+            let (mut client, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(&mut client);
+            let mut request = String::new();
+            reader.read_line(&mut request).await.unwrap();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let target = request.split_whitespace().nth(1).unwrap().to_string();
+            let mut server = tokio::net::TcpStream::connect(&target).await.unwrap();
+            client
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await
+                .unwrap();
+            tokio::spawn(async move {
+                let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+            });
+            target
+        });
+        (url, task)
+    }
+
+    #[tokio::test]
+    async fn test_mock_http_proxy() {
+        let server = mock_server().await;
+        let (proxy_url, proxy) = connect_proxy().await;
+        let state = StateBuilder::default()
+            .ssid(Ssid::parse(MOCK_SSID).unwrap())
+            .default_connection_url(server.url())
+            .with_proxy(ProxyConfig::Http(proxy_url.parse().unwrap()))
+            .wait_for_assets(false)
+            .build()
+            .unwrap();
+        let api = PocketOption::new_with_state(state).await.unwrap();
+
+        assert_eq!(proxy.await.unwrap(), server.addr().to_string());
+        let auth = server
+            .wait_for_send(r#"42["auth""#, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(auth.to_text().unwrap().contains("mocksession"));
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_connection_info() {
+        let server = mock_server().await;
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        api.client.wait_connected().await;
+        let info = api.connection_info();
+        assert!(info.connected_since.is_some());
+        assert_eq!(info.region.as_deref(), Some("127.0.0.1"));
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_stats_json() {
+        let server = mock_server().await;
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+        wait_until(|| async { api.get_stats().await.successful_connections == 1 }).await;
+
+        let stats = ConnectionStats::from_json(&api.get_stats_json().await).unwrap();
+        assert_eq!(stats.connection_attempts, 1);
+        assert_eq!(stats.successful_connections, 1);
+        assert_eq!(stats.failed_connections, 0);
+        assert!(stats.messages_sent >= 2);
+        assert!(stats.bytes_received > 0);
+        assert!(stats.is_connected);
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_balance_update() {
+        let server = mock_server().await;
+        server
+            .respond_with(Message::text(
+                r#"451-["successupdateBalance",{"_placeholder":true,"num":0}]"#,
+            ))
+            .respond_with(Message::binary(
+                r#"{"isDemo":1,"balance":1234.5}"#.as_bytes().to_vec(),
+            ));
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        wait_until(|| async { api.balance().await > 0.0 }).await;
+        assert_eq!(api.balance().await, 1234.5);
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_wait_for_asset_data() {
+        let server = mock_server_with_assets().await;
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        api.wait_for_asset_data(Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(api.assets().await.is_some());
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_wait_for_asset_data_timeout() {
+        let server = mock_server().await;
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        let result = api.wait_for_asset_data(Duration::from_millis(200)).await;
+        assert!(matches!(result, Err(PocketError::Timeout { .. })));
+        assert!(api.assets().await.is_none());
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_payout_stream() {
+        let server = mock_server().await;
+        let assets = |payout: i32| {
+            json!([[
+                1, "EURUSD_otc", "EUR/USD OTC", "currency", 2, payout, 60, 30, 3, 1, 170, 0, [],
+                1751906100, true, [{"time": 60}], -1, 60, 1751906100
+            ]])
+        };
+        server.expect_send("payout-stream-ready");
+        respond_with_assets(&server, &assets(80));
+        respond_with_assets(&server, &assets(92));
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        let mut stream = api.payout_stream();
+        // Only trigger the updates once the handshake is over, so the trigger isn't consumed by it.
+        server
+            .wait_for_send("subfor", Duration::from_secs(5))
+            .await
+            .unwrap();
+        api.client
+            .send_text("payout-stream-ready".to_string())
+            .await
+            .unwrap();
+
+        let first = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.get("EURUSD_otc"), Some(&80));
+        let second = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.get("EURUSD_otc"), Some(&92));
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_payout_history() {
+        let server = mock_server().await;
+        let assets = |payout: i32| {
+            json!([
+                [
+                    1, "EURUSD_otc", "EUR/USD OTC", "currency", 2, payout, 60, 30, 3, 1, 170, 0, [],
+                    1751906100, true, [{"time": 60}], -1, 60, 1751906100
+                ],
+                [
+                    2, "AUDUSD_otc", "AUD/USD OTC", "currency", 2, 70, 60, 30, 3, 1, 170, 0, [],
+                    1751906100, true, [{"time": 60}], -1, 60, 1751906100
+                ]
+            ])
+        };
+        for payout in [80, 85, 92] {
+            respond_with_assets(&server, &assets(payout));
+        }
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        wait_until(|| async { api.payout_history("EURUSD_otc", 1).len() == 3 }).await;
+        let history = api.payout_history("EURUSD_otc", 1);
+        let payouts: Vec<_> = history.iter().map(|(_, payout)| *payout).collect();
+        assert_eq!(payouts, vec![80, 85, 92]);
+        assert!(history.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        // The unchanged payout is only recorded once
+        let other: Vec<_> = api
+            .payout_history("AUDUSD_otc", 1)
+            .into_iter()
+            .map(|(_, payout)| payout)
+            .collect();
+        assert_eq!(other, vec![70]);
+        assert!(api.payout_history("GBPUSD_otc", 1).is_empty());
+        // Windows past the retention don't overflow the date range
+        assert_eq!(api.payout_history("EURUSD_otc", u32::MAX).len(), 3);
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_trade_execution() {
+        let server = mock_server_with_assets().await;
+        server.expect_send(r#"42["openOrder""#).reply_with(|msg| {
+            let text = msg.to_text().unwrap();
+            let order: Value = serde_json::from_str(&text[2..]).unwrap();
+            let deal = mock_deal("6f2c5f0e-5a4b-4c1f-9d3e-2b1a0c9d8e7f", &order[1], 0.0);
+            vec![
+                Message::text(r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#),
+                Message::binary(deal.to_string().into_bytes()),
+            ]
+        });
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        wait_until(|| async { api.assets().await.is_some() }).await;
+        let (id, deal) = api.buy("EURUSD_otc", 60, 1.0).await.unwrap();
+        assert_eq!(deal.asset, "EURUSD_otc");
+        assert_eq!(deal.amount, 1.0);
+        assert!(api.get_opened_deal(id).await.is_some());
+        server.verify().unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_hard_disconnect_clears_opened_deals() {
+        let server = mock_server_with_assets().await;
+        server.expect_send(r#"42["openOrder""#).reply_with(|msg| {
+            let text = msg.to_text().unwrap();
+            let order: Value = serde_json::from_str(&text[2..]).unwrap();
+            let deal = mock_deal("6f2c5f0e-5a4b-4c1f-9d3e-2b1a0c9d8e7f", &order[1], 0.0);
+            vec![
+                Message::text(r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#),
+                Message::binary(deal.to_string().into_bytes()),
+            ]
+        });
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        wait_until(|| async { api.assets().await.is_some() }).await;
+        api.buy("EURUSD_otc", 60, 1.0).await.unwrap();
+        assert_eq!(api.get_opened_deals().await.len(), 1);
+        api.client
+            .state
+            .correlation
+            .update("EURUSD_otc", 1751906100.0, 1.1);
+        let ahead = chrono::Utc::now().timestamp() as f64 + 5.0;
+        api.client.state.update_server_time(ahead).await;
+        assert_ne!(
+            api.client.state.server_time.read().await.offset,
+            chrono::Duration::zero()
+        );
+
+        api.disconnect().await.unwrap();
+        api.connect().await.unwrap();
+        wait_until(|| async { server.handshake_headers().len() >= 2 }).await;
+        assert!(api.get_opened_deals().await.is_empty());
+        assert!(api.all_correlations().is_empty());
+        assert_eq!(
+            api.client.state.server_time.read().await.offset,
+            chrono::Duration::zero()
+        );
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_max_concurrent_trades_guard_multi_buy() {
+        let server = mock_server_with_assets().await;
+        server.expect_send(r#"42["openOrder""#).reply_with(|msg| {
+            let text = msg.to_text().unwrap();
+            let order: Value = serde_json::from_str(&text[2..]).unwrap();
+            let deal = mock_deal("6f2c5f0e-5a4b-4c1f-9d3e-2b1a0c9d8e7f", &order[1], 0.0);
+            vec![
+                Message::text(r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#),
+                Message::binary(deal.to_string().into_bytes()),
+            ]
+        });
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+        api.set_trade_guard(MaxConcurrentTradesGuard::new(1));
+
+        wait_until(|| async { api.assets().await.is_some() }).await;
+        // Without the guard the server would never answer the extra orders
+        let results = tokio::time::timeout(
+            Duration::from_secs(5),
+            api.multi_buy(vec![("EURUSD_otc".to_string(), 60, 1.0); 3]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+        assert_eq!(
+            results
+                .iter()
+                .filter(|result| matches!(result, Err(PocketError::TradeRejected(_))))
+                .count(),
+            2
+        );
+        let orders = server
+            .received()
+            .await
+            .into_iter()
+            .filter(|msg| msg.to_text().is_ok_and(|t| t.contains("openOrder")))
+            .count();
+        assert_eq!(orders, 1);
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_max_concurrent_trades_guard() {
+        let server = mock_server_with_assets().await;
+        server.expect_send(r#"42["openOrder""#).reply_with(|msg| {
+            let text = msg.to_text().unwrap();
+            let order: Value = serde_json::from_str(&text[2..]).unwrap();
+            let deal = mock_deal("6f2c5f0e-5a4b-4c1f-9d3e-2b1a0c9d8e7f", &order[1], 0.0);
+            vec![
+                Message::text(r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#),
+                Message::binary(deal.to_string().into_bytes()),
+            ]
+        });
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+        api.set_trade_guard(MaxConcurrentTradesGuard::new(1));
+
+        wait_until(|| async { api.assets().await.is_some() }).await;
+        api.buy("EURUSD_otc", 60, 1.0).await.unwrap();
+        assert!(matches!(
+            api.sell("EURUSD_otc", 60, 1.0).await,
+            Err(PocketError::TradeRejected(_))
+        ));
+        // The second order never reached the server
+        let orders = server
+            .received()
+            .await
+            .into_iter()
+            .filter(|msg| msg.to_text().is_ok_and(|t| t.contains("openOrder")))
+            .count();
+        assert_eq!(orders, 1);
+        api.shutdown().await.unwrap();
+    }
 
-    use super::PocketOption;
+    #[tokio::test]
+    async fn test_mock_trade_with_martingale() {
+        let server = mock_server_with_assets().await;
+        // Two losses then a win, each deal closes right after being opened
+        for profit in [-1.0, -1.0, 0.92] {
+            server
+                .expect_send(r#"42["openOrder""#)
+                .reply_with(move |msg| {
+                    let text = msg.to_text().unwrap();
+                    let order: Value = serde_json::from_str(&text[2..]).unwrap();
+                    let id = Uuid::new_v4().to_string();
+                    let amount = order[1]["amount"].as_f64().unwrap();
+                    let opened = mock_deal(&id, &order[1], 0.0);
+                    let closed = mock_deal(&id, &order[1], profit * amount);
+                    vec![
+                        Message::text(r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#),
+                        Message::binary(opened.to_string().into_bytes()),
+                        Message::text(r#"451-["updateClosedDeals",{"_placeholder":true,"num":0}]"#),
+                        Message::binary(json!([closed]).to_string().into_bytes()),
+                    ]
+                });
+        }
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        wait_until(|| async { api.assets().await.is_some() }).await;
+        let deal = api
+            .trade_with_martingale("EURUSD_otc", Action::Call, 60, 1.0, 2.0, 5)
+            .await
+            .unwrap();
+        assert_eq!(deal.amount, 4.0);
+        assert!(deal.profit > 0.0);
+
+        let amounts: Vec<f64> = server
+            .received()
+            .await
+            .into_iter()
+            .filter_map(|msg| {
+                let text = msg.to_text().ok()?.strip_prefix("42")?.to_string();
+                let order: Value = serde_json::from_str(&text).ok()?;
+                (order[0] == "openOrder").then(|| order[1]["amount"].as_f64().unwrap())
+            })
+            .collect();
+        assert_eq!(amounts, vec![1.0, 2.0, 4.0]);
+        server.verify().unwrap();
+
+        assert!(matches!(
+            api.trade_with_martingale("EURUSD_otc", Action::Call, 60, 30000.0, 2.0, 5)
+                .await,
+            Err(PocketError::MaxTradeAmountExceeded { .. })
+        ));
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_multi_buy() {
+        let server = mock_server_with_assets().await;
+        for _ in 0..3 {
+            server.expect_send(r#"42["openOrder""#).reply_with(|msg| {
+                let order: Value = serde_json::from_str(&msg.to_text().unwrap()[2..]).unwrap();
+                let deal = mock_deal(&Uuid::new_v4().to_string(), &order[1], 0.0);
+                vec![
+                    Message::text(r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#),
+                    Message::binary(deal.to_string().into_bytes()),
+                ]
+            });
+        }
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        wait_until(|| async { api.assets().await.is_some() }).await;
+        let results = api
+            .multi_buy(vec![
+                ("EURUSD_otc".to_string(), 60, 1.0),
+                ("EURUSD_otc".to_string(), 60, 2.0),
+                ("EURUSD_otc".to_string(), 60, 3.0),
+            ])
+            .await;
+        let deals: Vec<_> = results.into_iter().map(Result::unwrap).collect();
+        // Every order gets its own deal, in the order of the orders
+        let amounts: Vec<_> = deals.iter().map(|(_, deal)| deal.amount).collect();
+        assert_eq!(amounts, vec![1.0, 2.0, 3.0]);
+        let ids: std::collections::HashSet<_> = deals.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids.len(), 3);
+        server.verify().unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_asset_expiry_options() {
+        let server = mock_server().await;
+        let assets = json!([[
+            1, "EURUSD_otc", "EUR/USD OTC", "currency", 2, 92, 60, 30, 3, 1, 170, 0, [],
+            1751906100, true, [{"time": 60}, {"time": 120}], -1, 60, 1751906100
+        ]]);
+        respond_with_assets(&server, &assets);
+        // The default expiry times and the 120 seconds one of the asset
+        for _ in 0..10 {
+            server.expect_send(r#"42["openOrder""#).reply_with(|msg| {
+                let order: Value = serde_json::from_str(&msg.to_text().unwrap()[2..]).unwrap();
+                let deal = mock_deal(&Uuid::new_v4().to_string(), &order[1], 0.0);
+                vec![
+                    Message::text(r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#),
+                    Message::binary(deal.to_string().into_bytes()),
+                ]
+            });
+        }
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        wait_until(|| async { api.assets().await.is_some() }).await;
+        let options = api.get_asset_expiry_options("EURUSD_otc").await.unwrap();
+        assert_eq!(options.len(), 10);
+        assert!(options.contains(&120));
+        assert!(matches!(
+            api.get_asset_expiry_options("UNKNOWN").await,
+            Err(PocketError::InvalidAsset(_))
+        ));
+
+        for time in options {
+            api.buy("EURUSD_otc", time, 1.0).await.unwrap();
+        }
+        server.verify().unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_available_assets() {
+        let server = mock_server().await;
+        let assets = json!([
+            [
+                1, "EURUSD_otc", "EUR/USD OTC", "currency", 2, 92, 60, 30, 3, 1, 170, 0, [],
+                1751906100, true, [{"time": 60}, {"time": 120}], -1, 60, 1751906100
+            ],
+            [
+                2, "#AAPL", "Apple", "stock", 2, 70, 60, 30, 3, 0, 170, 0, [],
+                1751906100, true, [], -1, 60, 1751906100
+            ],
+            [
+                3, "GBPUSD", "GBP/USD", "currency", 2, 85, 60, 30, 3, 0, 170, 0, [],
+                1751906100, false, [], -1, 60, 1751906100
+            ]
+        ]);
+        respond_with_assets(&server, &assets);
+        let state = StateBuilder::default()
+            .ssid(Ssid::parse(MOCK_SSID).unwrap())
+            .default_connection_url(server.url())
+            .wait_for_assets(false)
+            .build()
+            .unwrap();
+        let api = PocketOption::new_with_state(state).await.unwrap();
+
+        // Waits for the assets instead of failing like `assets()` may
+        let available = api.available_assets().await.unwrap();
+        assert!(!available.is_empty());
+        let all_assets = api.assets().await.unwrap();
+        assert!(
+            available
+                .iter()
+                .all(|info| all_assets.get(&info.symbol).unwrap().is_active())
+        );
+        let symbols: Vec<&str> = available.iter().map(|info| info.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["#AAPL", "EURUSD_otc"]);
+        let eurusd = &available[1];
+        assert_eq!(eurusd.payout, 92);
+        assert!(eurusd.is_otc);
+        assert!(eurusd.expiry_times.contains(&120));
+        assert_eq!(eurusd.min_amount, super::MINIMUM_TRADE_AMOUNT);
+
+        let high_payout = api.available_assets_by_payout(80).await.unwrap();
+        assert_eq!(high_payout.len(), 1);
+        assert_eq!(high_payout[0].symbol, "EURUSD_otc");
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_cloned_client_shares_handles() {
+        let server = mock_server().await;
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        let client = api.client.clone();
+        assert!(Arc::ptr_eq(
+            &client.module_handles,
+            &api.client.module_handles
+        ));
+        let original = api
+            .client
+            .get_handle::<SubscriptionsApiModule>()
+            .await
+            .unwrap();
+        let cloned = client.get_handle::<SubscriptionsApiModule>().await.unwrap();
+        assert_eq!(cloned.get_active_subscriptions_count().await.unwrap(), 0);
+        assert_eq!(original.get_active_subscriptions_count().await.unwrap(), 0);
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_health_check() {
+        let server = mock_server_with_assets().await;
+        server
+            .expect_send(r#"42["changeSymbol",{"asset":"EURUSD_otc","period":1}]"#)
+            .respond_with(Message::text(
+                r#"451-["updateStream",{"_placeholder":true,"num":0}]"#,
+            ))
+            .respond_with(Message::binary(
+                r#"[["EURUSD_otc",1751906100.0,1.1]]"#.as_bytes().to_vec(),
+            ));
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+        api.wait_for_asset_data(Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let health = api.health_check().await;
+        assert!(health.is_connected);
+        assert_eq!(health.active_subscriptions, 0);
+        assert_eq!(health.last_error, None);
+        assert_eq!(health.balance, -1.0);
+        let json = serde_json::to_value(&health).unwrap();
+        for field in [
+            "is_connected",
+            "active_subscriptions",
+            "server_time_drift_ms",
+            "last_error",
+            "balance",
+        ] {
+            assert!(json.get(field).is_some(), "missing {field}");
+        }
+
+        // The health check runs while the subscription waits for the server
+        let (stream, _) = tokio::join!(
+            api.subscribe("EURUSD_otc", SubscriptionType::none()),
+            api.health_check()
+        );
+        let stream = stream.unwrap();
+        assert_eq!(api.health_check().await.active_subscriptions, 1);
+        api.unsubscribe("EURUSD_otc").await.unwrap();
+        assert_eq!(api.health_check().await.active_subscriptions, 0);
+        drop(stream);
+
+        // Without the server the client can't reconnect after the disconnection
+        drop(server);
+        api.disconnect().await.unwrap();
+        wait_until(|| async { api.health_check().await.last_error.is_some() }).await;
+        assert!(!api.health_check().await.is_connected);
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_reconnect_if_stale() {
+        let server = mock_server().await;
+        let state = StateBuilder::default()
+            .ssid(Ssid::parse(MOCK_SSID).unwrap())
+            .default_connection_url(server.url())
+            .wait_for_assets(false)
+            .build()
+            .unwrap();
+        let api = PocketOption::new_with_state(state).await.unwrap();
+        wait_until(|| async { api.client.last_message_received().is_some() }).await;
+
+        assert!(
+            !api.reconnect_if_stale(Duration::from_secs(60))
+                .await
+                .unwrap()
+        );
+        tokio::time::pause();
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(
+            api.reconnect_if_stale(Duration::from_secs(60))
+                .await
+                .unwrap()
+        );
+        tokio::time::resume();
+
+        // The reconnection performs a new handshake with the server
+        wait_until(|| async { server.handshake_headers().len() == 2 }).await;
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_set_demo_set_live() {
+        let server = mock_server_with_assets().await;
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        let err = api.set_live(MOCK_SSID).await.unwrap_err();
+        assert!(matches!(
+            err,
+            PocketError::SsidDemoMismatch {
+                expected: false,
+                actual: true
+            }
+        ));
+        assert!(api.is_demo());
+
+        let new_ssid = MOCK_SSID.replace("mocksession", "newsession");
+        api.set_demo(&new_ssid).await.unwrap();
+        server
+            .wait_for_send("newsession", Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(api.is_demo());
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_cancel_order() {
+        let server = mock_server().await;
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            api.cancel_order(Uuid::new_v4()).await,
+            Err(PocketError::NotSupported(_))
+        ));
+        assert!(matches!(
+            api.subscribe_orderbook("EURUSD_otc").await,
+            Err(PocketError::NotSupported(_))
+        ));
+        // Nothing is sent to the server
+        assert!(
+            !server
+                .received()
+                .await
+                .iter()
+                .any(|msg| msg.to_text().is_ok_and(|t| t.contains("cancelOrder")))
+        );
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_result_many() {
+        const TRADES: usize = 4;
+        const CLOSE_DELAY: Duration = Duration::from_millis(300);
+        let server = mock_server_with_assets().await;
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        for _ in 0..TRADES {
+            let opened = opened.clone();
+            server
+                .expect_send(r#"42["openOrder""#)
+                .reply_with(move |msg| {
+                    let text = msg.to_text().unwrap();
+                    let order: Value = serde_json::from_str(&text[2..]).unwrap();
+                    let deal = mock_deal(&Uuid::new_v4().to_string(), &order[1], 0.0);
+                    opened.lock().unwrap().push(deal.clone());
+                    vec![
+                        Message::text(r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#),
+                        Message::binary(deal.to_string().into_bytes()),
+                    ]
+                });
+        }
+        server.expect_send("close-deals").reply_with(move |_| {
+            let closed: Vec<Value> = opened
+                .lock()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(i, deal)| {
+                    let mut deal = deal.clone();
+                    deal["profit"] = json!(i as f64);
+                    deal
+                })
+                .collect();
+            vec![
+                Message::text(r#"451-["updateClosedDeals",{"_placeholder":true,"num":0}]"#),
+                Message::binary(json!(closed).to_string().into_bytes()),
+            ]
+        });
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        wait_until(|| async { api.assets().await.is_some() }).await;
+        let mut ids = Vec::new();
+        for _ in 0..TRADES {
+            ids.push(api.buy("EURUSD_otc", 60, 1.0).await.unwrap().0);
+        }
+        let unknown = Uuid::new_v4();
+        ids.push(unknown);
+
+        let start = std::time::Instant::now();
+        let closer = api.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(CLOSE_DELAY).await;
+            closer.client.send_text("close-deals".to_string()).await
+        });
+        let results = api.result_many(ids.clone(), Duration::from_secs(5)).await;
+        let elapsed = start.elapsed();
+
+        assert_eq!(results.len(), TRADES + 1);
+        for (i, (id, result)) in ids.iter().zip(&results).take(TRADES).enumerate() {
+            let deal = result.as_ref().unwrap();
+            assert_eq!(deal.id, *id);
+            assert_eq!(deal.profit, i as f64);
+        }
+        assert!(matches!(
+            results[TRADES],
+            Err(PocketError::DealNotFound(id)) if id == unknown
+        ));
+        // Waited once for all the deals instead of once per deal
+        assert!(elapsed < CLOSE_DELAY * 2, "took {elapsed:?}");
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_result_stream() {
+        use futures_util::StreamExt;
+
+        const TRADES: usize = 3;
+        let server = mock_server_with_assets().await;
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        for _ in 0..TRADES {
+            let opened = opened.clone();
+            server
+                .expect_send(r#"42["openOrder""#)
+                .reply_with(move |msg| {
+                    let text = msg.to_text().unwrap();
+                    let order: Value = serde_json::from_str(&text[2..]).unwrap();
+                    let deal = mock_deal(&Uuid::new_v4().to_string(), &order[1], 0.0);
+                    opened.lock().unwrap().push(deal.clone());
+                    vec![
+                        Message::text(r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#),
+                        Message::binary(deal.to_string().into_bytes()),
+                    ]
+                });
+        }
+        // The deals close in the order 2, 0, 1
+        for index in [2, 0, 1] {
+            let opened = opened.clone();
+            server
+                .expect_send(&format!("close-{index}"))
+                .reply_with(move |_| {
+                    let deal = opened.lock().unwrap()[index].clone();
+                    vec![
+                        Message::text(r#"451-["updateClosedDeals",{"_placeholder":true,"num":0}]"#),
+                        Message::binary(json!([deal]).to_string().into_bytes()),
+                    ]
+                });
+        }
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        wait_until(|| async { api.assets().await.is_some() }).await;
+        let mut ids = Vec::new();
+        for _ in 0..TRADES {
+            ids.push(api.buy("EURUSD_otc", 60, 1.0).await.unwrap().0);
+        }
+        let mut requested = ids.clone();
+        requested.push(Uuid::new_v4());
+
+        let stream = api.result_stream(requested, Duration::from_secs(5));
+        let closer = api.clone();
+        tokio::spawn(async move {
+            for index in [2, 0, 1] {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                closer
+                    .client
+                    .send_text(format!("close-{index}"))
+                    .await
+                    .unwrap();
+            }
+        });
+        let results: Vec<_> = tokio::time::timeout(Duration::from_secs(5), stream.collect())
+            .await
+            .expect("The stream didn't end once every trade closed");
+
+        // The unknown trade is skipped
+        let order: Vec<Uuid> = results.iter().map(|(id, _)| *id).collect();
+        assert_eq!(order, vec![ids[2], ids[0], ids[1]]);
+        assert!(results.iter().all(|(id, deal)| deal.id == *id));
+        server.verify().unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_deal_events() {
+        let server = mock_server_with_assets().await;
+        let deal = |order: &Value, profit: f64| {
+            mock_deal("6f2c5f0e-5a4b-4c1f-9d3e-2b1a0c9d8e7f", order, profit)
+        };
+        let order = Arc::new(Mutex::new(Value::Null));
+        let opened = order.clone();
+        let closed = order.clone();
+        server
+            .expect_send(r#"42["openOrder""#)
+            .reply_with(move |msg| {
+                let text = msg.to_text().unwrap();
+                let order: Value = serde_json::from_str(&text[2..]).unwrap();
+                let deal = deal(&order[1], 0.0);
+                *opened.lock().unwrap() = order[1].clone();
+                vec![
+                    Message::text(r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#),
+                    Message::binary(deal.to_string().into_bytes()),
+                    Message::text(r#"451-["updateOpenedDeals",{"_placeholder":true,"num":0}]"#),
+                    Message::binary(json!([deal]).to_string().into_bytes()),
+                ]
+            })
+            .expect_send("close-deal")
+            .reply_with(move |_| {
+                let deal = deal(&closed.lock().unwrap(), 0.92);
+                vec![
+                    Message::text(r#"451-["updateClosedDeals",{"_placeholder":true,"num":0}]"#),
+                    Message::binary(json!([deal]).to_string().into_bytes()),
+                ]
+            });
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        wait_until(|| async { api.assets().await.is_some() }).await;
+        let mut events = api.deal_events().await.unwrap();
+        let (id, _) = api.buy("EURUSD_otc", 60, 1.0).await.unwrap();
+        api.client
+            .send_text("close-deal".to_string())
+            .await
+            .unwrap();
+
+        let first = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap()
+            .unwrap();
+        match first {
+            DealEvent::Opened(opened_id, deal) => {
+                assert_eq!(opened_id, id);
+                assert_eq!(deal.profit, 0.0);
+            }
+            event => panic!("Expected an opened deal, got {event:?}"),
+        }
+        let second = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap()
+            .unwrap();
+        match second {
+            DealEvent::Closed(closed_id, deal) => {
+                assert_eq!(closed_id, id);
+                assert_eq!(deal.profit, 0.92);
+            }
+            event => panic!("Expected a closed deal, got {event:?}"),
+        }
+        server.verify().unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_batch_history() {
+        let server = mock_server().await;
+        let assets = json!([
+            [
+                1, "EURUSD_otc", "EUR/USD OTC", "currency", 2, 92, 60, 30, 3, 1, 170, 0, [],
+                1751906100, true, [{"time": 60}], -1, 60, 1751906100
+            ],
+            [
+                2, "GBPUSD_otc", "GBP/USD OTC", "currency", 2, 90, 60, 30, 3, 1, 170, 0, [],
+                1751906100, true, [{"time": 60}], -1, 60, 1751906100
+            ],
+            [
+                3, "AUDUSD_otc", "AUD/USD OTC", "currency", 2, 88, 60, 30, 3, 1, 170, 0, [],
+                1751906100, true, [{"time": 60}], -1, 60, 1751906100
+            ]
+        ]);
+        let history = |asset: &str, candles: Value| {
+            json!({"asset": asset, "period": 60, "candles": candles, "history": []})
+                .to_string()
+                .into_bytes()
+        };
+        // The requests are sent concurrently in any order, the history of AUDUSD_otc never comes
+        respond_with_assets(&server, &assets)
+            .expect_send(r#"42["changeSymbol""#)
+            .expect_send(r#"42["changeSymbol""#)
+            .expect_send(r#"42["changeSymbol""#)
+            .respond_with(Message::text(
+                r#"451-["updateHistoryNewFast",{"_placeholder":true,"num":0}]"#,
+            ))
+            .respond_with(Message::binary(history(
+                "GBPUSD_otc",
+                json!([[1751906040.0, 1.3, 1.32, 1.29, 1.31]]),
+            )))
+            .respond_with(Message::text(
+                r#"451-["updateHistoryNewFast",{"_placeholder":true,"num":0}]"#,
+            ))
+            .respond_with(Message::binary(history(
+                "EURUSD_otc",
+                json!([
+                    [1751905980.0, 1.1, 1.12, 1.09, 1.13],
+                    [1751906040.0, 1.12, 1.11, 1.1, 1.14]
+                ]),
+            )));
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+        api.wait_for_asset_data(Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            api.batch_history_with_timeout(
+                vec![
+                    ("EURUSD_otc".to_string(), 60),
+                    ("GBPUSD_otc".to_string(), 60),
+                    ("AUDUSD_otc".to_string(), 60),
+                    ("UNKNOWN_otc".to_string(), 60),
+                ],
+                Duration::from_millis(500),
+            ),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(result.ok.len(), 2);
+        assert_eq!(result.ok["EURUSD_otc_60"].len(), 2);
+        assert_eq!(result.ok["GBPUSD_otc_60"].len(), 1);
+        assert_eq!(result.ok["GBPUSD_otc_60"][0].symbol, "GBPUSD_otc");
+        assert!(matches!(
+            result.errors.get("UNKNOWN_otc_60"),
+            Some(PocketError::InvalidAsset(_))
+        ));
+        assert!(matches!(
+            result.errors.get("AUDUSD_otc_60"),
+            Some(PocketError::Timeout { .. })
+        ));
+        assert_eq!(result.errors.len(), 2);
+        server.verify().unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_subscribe_with_initial_history() {
+        let server = mock_server_with_assets().await;
+        let now = chrono::Utc::now().timestamp() as f64;
+        let history = json!({
+            "asset": "EURUSD_otc",
+            "period": 60,
+            "candles": [[now - 120.0, 1.1, 1.12, 1.09, 1.13], [now - 60.0, 1.12, 1.11, 1.1, 1.14]],
+            "history": []
+        });
+        server
+            .expect_send(r#"42["changeSymbol",{"asset":"EURUSD_otc","period":60}]"#)
+            .respond_with(Message::text(
+                r#"451-["updateHistoryNewFast",{"_placeholder":true,"num":0}]"#,
+            ))
+            .respond_with(Message::binary(history.to_string().into_bytes()))
+            .respond_with(Message::text(
+                r#"451-["updateStream",{"_placeholder":true,"num":0}]"#,
+            ))
+            .respond_with(Message::binary(
+                json!([["EURUSD_otc", now, 1.115]]).to_string().into_bytes(),
+            ));
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        api.wait_for_asset_data(Duration::from_secs(5))
+            .await
+            .unwrap();
+        let (candles, mut stream) = api
+            .subscribe_with_initial_history("EURUSD_otc", SubscriptionType::none(), 60)
+            .await
+            .unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[1].timestamp, now - 60.0);
+        let live = tokio::time::timeout(Duration::from_secs(5), stream.receive())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(live.timestamp, now);
+        assert!(live.timestamp - candles[1].timestamp <= 60.0);
+        server.verify().unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_fast_history_update() {
+        let server = mock_server_with_assets().await;
+        let now = chrono::Utc::now().timestamp() as f64;
+        // Fast history without candles, the ticks are not in order
+        let history = json!({
+            "asset": "EURUSD_otc",
+            "period": 1,
+            "history": [[now - 1.0, 1.1], [now, 1.12], [now - 2.0, 1.09]]
+        });
+        server
+            .expect_send(r#"42["changeSymbol",{"asset":"EURUSD_otc","period":1}]"#)
+            .respond_with(Message::text(
+                r#"451-["updateHistoryNewFast",{"_placeholder":true,"num":0}]"#,
+            ))
+            .respond_with(Message::binary(history.to_string().into_bytes()));
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        api.wait_for_asset_data(Duration::from_secs(5))
+            .await
+            .unwrap();
+        let mut stream = api
+            .subscribe("EURUSD_otc", SubscriptionType::none())
+            .await
+            .unwrap();
+        let candle = tokio::time::timeout(Duration::from_secs(5), stream.receive())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(candle.timestamp, now);
+        assert_eq!(candle.close, rust_decimal::dec!(1.12));
+        server.verify().unwrap();
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_watch_server_time() {
+        let server = mock_server().await;
+        server.expect_send("watch-server-time");
+        // The third update is late, it must not move the time backwards
+        for (symbol, timestamp) in [
+            ("EURUSD_otc", 1751906100.0),
+            ("EURUSD_otc", 1751906101.5),
+            ("AUDUSD_otc", 1751906101.0),
+            ("EURUSD_otc", 1751906102.0),
+        ] {
+            server
+                .respond_with(Message::text(
+                    r#"451-["updateStream",{"_placeholder":true,"num":0}]"#,
+                ))
+                .respond_with(Message::binary(
+                    json!([[symbol, timestamp, 1.1]]).to_string().into_bytes(),
+                ));
+        }
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+
+        let stream = api.watch_server_time();
+        server
+            .wait_for_send("subfor", Duration::from_secs(5))
+            .await
+            .unwrap();
+        api.client
+            .send_text("watch-server-time".to_string())
+            .await
+            .unwrap();
+        let times: Vec<_> = tokio::time::timeout(Duration::from_secs(5), stream.take(3).collect())
+            .await
+            .unwrap();
+        let millis: Vec<_> = times.iter().map(|time| time.timestamp_millis()).collect();
+        assert_eq!(millis, vec![1751906100000, 1751906101500, 1751906102000]);
+        assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+        api.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_server_time_poll() {
+        let server = mock_server().await;
+        let state = StateBuilder::default()
+            .ssid(Ssid::parse(MOCK_SSID).unwrap())
+            .default_connection_url(server.url())
+            .wait_for_assets(false)
+            .build()
+            .unwrap();
+        let api = PocketOption::new_with_state(state).await.unwrap();
+        api.client.wait_connected().await;
+
+        // No price update is sent, the times are still yielded on schedule
+        let interval = Duration::from_millis(100);
+        let mut stream = api.server_time_poll(interval);
+        let start = std::time::Instant::now();
+        let mut received = Vec::new();
+        while start.elapsed() < interval * 3 {
+            stream.next().await.unwrap();
+            received.push(start.elapsed());
+        }
+        assert!(received.len() >= 3);
+        for pair in received.windows(2) {
+            let gap = pair[1] - pair[0];
+            assert!(
+                gap.abs_diff(interval) <= Duration::from_millis(10),
+                "{gap:?} between two times"
+            );
+        }
+
+        // The stream keeps its own handle on the client, it ends once the runner is gone
+        api.shutdown().await.unwrap();
+        let end = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap();
+        assert!(end.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mock_clock_offset() {
+        let server = mock_server().await;
+        // The server clock is 5 seconds ahead of the local one
+        let server_now = chrono::Utc::now().timestamp_millis() as f64 / 1000.0 + 5.0;
+        server
+            .respond_with(Message::text(
+                r#"451-["updateStream",{"_placeholder":true,"num":0}]"#,
+            ))
+            .respond_with(Message::binary(
+                json!([["EURUSD_otc", server_now, 1.1]])
+                    .to_string()
+                    .into_bytes(),
+            ));
+        let api = PocketOption::new_with_url(MOCK_SSID, server.url())
+            .await
+            .unwrap();
+        let state = &api.client.state;
+        wait_until(|| async {
+            state.server_time.read().await.offset > chrono::Duration::seconds(4)
+        })
+        .await;
+
+        let offset = (state.corrected_now().await - chrono::Utc::now()).num_milliseconds();
+        assert!((4500..=5500).contains(&offset), "offset: {offset}ms");
+        // A deal expiring in 3 seconds of local time is already closed for the server
+        let close_timestamp = chrono::Utc::now() + chrono::Duration::seconds(3);
+        assert!(close_timestamp > chrono::Utc::now());
+        assert!(close_timestamp <= state.corrected_now().await);
+        api.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn test_history_gap() {
+        let candle = |timestamp: f64| Candle {
+            timestamp,
+            ..Default::default()
+        };
+        let max_gap = Duration::from_secs(65);
+        assert!(history_gap("EURUSD_otc", &[], 1000.0, max_gap).is_none());
+        assert!(history_gap("EURUSD_otc", &[candle(940.0)], 1000.0, max_gap).is_none());
+        assert!(matches!(
+            history_gap("EURUSD_otc", &[candle(900.0)], 1000.0, max_gap),
+            Some(PocketError::HistoryGap { gap, .. }) if gap == Duration::from_secs(100)
+        ));
+    }
+
+    #[test]
+    fn test_connection_error() {
+        let timeout = Duration::from_secs(10);
+        let pinning = ConnectorError::CertificatePinning("abcd".to_string());
+        assert!(matches!(
+            connection_error(&pinning, timeout),
+            Some(PocketError::CertificatePinningFailure(fingerprint)) if fingerprint == "abcd"
+        ));
+        assert!(matches!(
+            connection_error(&ConnectorError::Timeout, timeout),
+            Some(PocketError::ConnectionTimeout(t)) if t == timeout
+        ));
+        // Retried by the runner
+        let refused = ConnectorError::Custom("refused".to_string());
+        assert!(connection_error(&refused, timeout).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_new_connection_timeout() {
+        // Accepts the TCP connections but never answers the handshake
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                sockets.push(socket);
+            }
+        });
+        let timeout = Duration::from_millis(200);
+        let state = StateBuilder::default()
+            .ssid(Ssid::parse(MOCK_SSID).unwrap())
+            .default_connection_url(url)
+            .connection_timeout(timeout)
+            .build()
+            .unwrap();
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(2), PocketOption::new_with_state(state))
+                .await
+                .expect("The connection timeout was not reported");
+        assert!(matches!(result, Err(PocketError::ConnectionTimeout(t)) if t == timeout));
+        server.abort();
+    }
 
     #[tokio::test]
     async fn test_pocket_option_tester() {
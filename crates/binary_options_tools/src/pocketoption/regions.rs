@@ -61,4 +61,23 @@ mod tests {
         dbg!(server);
         Ok(())
     }
+
+    #[test]
+    fn test_all_regions() {
+        let all = Regions::all_regions();
+        assert_eq!(all.len(), 17);
+        assert_eq!(Regions::region_count(), 17);
+        assert_eq!(
+            all.len(),
+            Regions::regions().len() + Regions::demo_regions().len()
+        );
+        let urls: std::collections::HashSet<&str> = all.iter().map(|(url, _, _)| *url).collect();
+        assert_eq!(urls.len(), all.len());
+        for region in Regions::regions()
+            .iter()
+            .chain(Regions::demo_regions().iter())
+        {
+            assert_eq!(all.iter().filter(|r| *r == region).count(), 1);
+        }
+    }
 }
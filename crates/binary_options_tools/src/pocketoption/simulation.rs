@@ -0,0 +1,315 @@
+//! Paper trading support, only available with the `trade_simulation` feature.
+//!
+//! While the simulation mode is enabled, trades are not sent to PocketOption. They are stored in
+//! [`SimulatedTradeState`] and closed with the last price received from the server once they expire.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        RwLock as SyncRwLock, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::{Notify, RwLock};
+use uuid::Uuid;
+
+use crate::pocketoption::{
+    error::{PocketError, PocketResult},
+    state::State,
+    types::{Action, Deal},
+};
+
+/// How often the simulated deals are checked for expiration.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Holds the simulated deals and the last known price of each asset.
+#[derive(Debug, Default)]
+pub struct SimulatedTradeState {
+    enabled: AtomicBool,
+    checker_started: AtomicBool,
+    last_prices: SyncRwLock<HashMap<String, f64>>,
+    opened: RwLock<HashMap<Uuid, Deal>>,
+    closed: RwLock<HashMap<Uuid, Deal>>,
+    closed_notify: Notify,
+}
+
+impl SimulatedTradeState {
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Marks the expiration checker as started, returns `false` if it was already running.
+    pub(crate) fn start_checker(&self) -> bool {
+        !self.checker_started.swap(true, Ordering::SeqCst)
+    }
+
+    /// Stores the last price received for `symbol`.
+    pub fn update_price(&self, symbol: &str, price: f64) {
+        self.last_prices
+            .write()
+            .expect("Failed to acquire last prices lock")
+            .insert(symbol.to_string(), price);
+    }
+
+    /// Returns the last price received for `symbol`, if any.
+    pub fn last_price(&self, symbol: &str) -> Option<f64> {
+        self.last_prices
+            .read()
+            .expect("Failed to acquire last prices lock")
+            .get(symbol)
+            .copied()
+    }
+
+    /// Opens a simulated deal at the last known price of `asset`.
+    pub async fn open_deal(
+        &self,
+        asset: String,
+        action: Action,
+        amount: f64,
+        time: u32,
+        payout: i32,
+        open_timestamp: DateTime<Utc>,
+    ) -> PocketResult<Deal> {
+        let open_price = self.last_price(&asset).ok_or_else(|| {
+            PocketError::General(format!(
+                "No price received for '{asset}', subscribe to it before simulating trades"
+            ))
+        })?;
+        let close_timestamp = open_timestamp + chrono::Duration::seconds(time as i64);
+        let id = Uuid::new_v4();
+        let deal = Deal {
+            id,
+            open_time: open_timestamp.format(TIME_FORMAT).to_string(),
+            close_time: close_timestamp.format(TIME_FORMAT).to_string(),
+            open_timestamp,
+            close_timestamp,
+            refund_time: None,
+            refund_timestamp: None,
+            uid: 0,
+            request_id: Some(id),
+            amount,
+            profit: 0.0,
+            percent_profit: payout,
+            percent_loss: 100,
+            open_price,
+            close_price: 0.0,
+            command: match action {
+                Action::Call => 0,
+                Action::Put => 1,
+            },
+            asset,
+            is_demo: 1,
+            copy_ticket: String::new(),
+            open_ms: 0,
+            close_ms: None,
+            option_type: 100,
+            is_rollover: None,
+            is_copy_signal: None,
+            is_ai: None,
+            currency: String::new(),
+            amount_usd: None,
+            amount_usd2: None,
+        };
+        self.opened.write().await.insert(id, deal.clone());
+        Ok(deal)
+    }
+
+    /// Closes every deal that expired at `now` using the last known prices and returns them.
+    pub async fn close_expired(&self, now: DateTime<Utc>) -> Vec<Deal> {
+        let mut opened = self.opened.write().await;
+        let expired: Vec<Uuid> = opened
+            .values()
+            .filter(|deal| deal.close_timestamp <= now)
+            .map(|deal| deal.id)
+            .collect();
+        let mut closed = Vec::with_capacity(expired.len());
+        for id in expired {
+            if let Some(mut deal) = opened.remove(&id) {
+                deal.close_price = self.last_price(&deal.asset).unwrap_or(deal.open_price);
+                deal.profit = simulated_profit(&deal);
+                closed.push(deal);
+            }
+        }
+        drop(opened);
+        if !closed.is_empty() {
+            self.closed
+                .write()
+                .await
+                .extend(closed.iter().map(|deal| (deal.id, deal.clone())));
+            self.closed_notify.notify_waiters();
+        }
+        closed
+    }
+
+    /// Checks if `id` belongs to a simulated deal.
+    pub async fn contains(&self, id: Uuid) -> bool {
+        self.opened.read().await.contains_key(&id) || self.closed.read().await.contains_key(&id)
+    }
+
+    pub async fn get_opened_deals(&self) -> HashMap<Uuid, Deal> {
+        self.opened.read().await.clone()
+    }
+
+    pub async fn get_closed_deals(&self) -> HashMap<Uuid, Deal> {
+        self.closed.read().await.clone()
+    }
+
+    /// Waits until the simulated deal `id` is closed.
+    pub async fn result(&self, id: Uuid) -> PocketResult<Deal> {
+        loop {
+            let notified = self.closed_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if let Some(deal) = self.closed.read().await.get(&id) {
+                return Ok(deal.clone());
+            }
+            if !self.opened.read().await.contains_key(&id) {
+                return Err(PocketError::DealNotFound(id));
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Profit of a closed deal, following the server convention: the payout on a win, the full
+/// amount lost on a loss and zero on a draw.
+fn simulated_profit(deal: &Deal) -> f64 {
+    let difference = deal.close_price - deal.open_price;
+    let won = match deal.command {
+        0 => difference > 0.0,
+        _ => difference < 0.0,
+    };
+    if difference == 0.0 {
+        0.0
+    } else if won {
+        deal.amount * deal.percent_profit as f64 / 100.0
+    } else {
+        -deal.amount * deal.percent_loss as f64 / 100.0
+    }
+}
+
+/// Closes the simulated deals once the server time reaches their expiration.
+/// Stops when the client state is dropped.
+pub(crate) async fn run_expiration_checker(state: Weak<State>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let Some(state) = state.upgrade() else {
+            break;
+        };
+        let now = state.get_server_datetime().await;
+        for deal in state.simulation.close_expired(now).await {
+            state.notify_trade_closed(&deal).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_time() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_simulated_buy_wins_when_price_rises() {
+        let state = SimulatedTradeState::default();
+        state.update_price("EURUSD_otc", 1.1);
+        let deal = state
+            .open_deal(
+                "EURUSD_otc".to_string(),
+                Action::Call,
+                10.0,
+                60,
+                92,
+                open_time(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(deal.open_price, 1.1);
+
+        state.update_price("EURUSD_otc", 1.2);
+        assert!(
+            state
+                .close_expired(open_time() + chrono::Duration::seconds(30))
+                .await
+                .is_empty()
+        );
+        let closed = state
+            .close_expired(open_time() + chrono::Duration::seconds(60))
+            .await;
+        assert_eq!(closed.len(), 1);
+
+        let result = state.result(deal.id).await.unwrap();
+        assert_eq!(result.close_price, 1.2);
+        assert!((result.profit - 9.2).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_simulated_sell_loses_when_price_rises() {
+        let state = SimulatedTradeState::default();
+        state.update_price("EURUSD_otc", 1.1);
+        let deal = state
+            .open_deal(
+                "EURUSD_otc".to_string(),
+                Action::Put,
+                10.0,
+                60,
+                92,
+                open_time(),
+            )
+            .await
+            .unwrap();
+        let waiter = tokio::spawn({
+            let state = std::sync::Arc::new(state);
+            let id = deal.id;
+            async move {
+                let result = state.result(id);
+                let closer = async {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    state.update_price("EURUSD_otc", 1.15);
+                    state
+                        .close_expired(open_time() + chrono::Duration::seconds(60))
+                        .await;
+                };
+                let (result, _) = tokio::join!(result, closer);
+                result
+            }
+        });
+        let result = waiter.await.unwrap().unwrap();
+        assert_eq!(result.profit, -10.0);
+    }
+
+    #[tokio::test]
+    async fn test_simulated_trade_requires_price() {
+        let state = SimulatedTradeState::default();
+        let result = state
+            .open_deal(
+                "EURUSD_otc".to_string(),
+                Action::Call,
+                10.0,
+                60,
+                92,
+                open_time(),
+            )
+            .await;
+        assert!(result.is_err());
+        assert!(matches!(
+            state.result(Uuid::new_v4()).await,
+            Err(PocketError::DealNotFound(_))
+        ));
+    }
+}
@@ -196,4 +196,22 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_parse_malformed_ssid() {
+        // Seeds of the `fuzz_target_ssid_parse` fuzzer, they must fail without panicking
+        let ssids = [
+            "",
+            "   ",
+            r#"42["auth","#,
+            r#"42["auth",]"#,
+            r#"42["auth",{"session":"abc","isDemo":1}]"#,
+            r#"42["auth",{"session":"a:4:{s:999:\"session_id","isDemo":0,"uid":1,"platform":2}]"#,
+            r#"42["auth",{"session":"a:-1:{}","isDemo":0,"uid":1,"platform":2}]"#,
+            "42[\"auth\",{\"session\":\"\u{0}\",\"isDemo\":0,\"uid\":1,\"platform\":2}]",
+        ];
+        for ssid in ssids {
+            assert!(Ssid::parse(ssid).is_err(), "{ssid:?} was parsed");
+        }
+    }
 }
@@ -1,22 +1,59 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock as SyncRwLock},
+    collections::{HashMap, VecDeque},
+    path::Path,
+    sync::{Arc, Mutex, RwLock as SyncRwLock},
+    time::Duration,
 };
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard, Notify, RwLock};
+use toml_edit::Document;
 use uuid::Uuid;
 
-use binary_options_tools_core_pre::traits::AppState;
+use binary_options_tools_core_pre::{
+    reimports::{AsyncReceiver, AsyncSender, bounded_async},
+    statistics::StatisticsTracker,
+    traits::AppState,
+};
 
+use crate::pocketoption::guards::TradeGuard;
+use crate::pocketoption::modules::correlation::CorrelationTracker;
+use crate::pocketoption::modules::deals::WinRateTracker;
+use crate::pocketoption::proxy::ProxyConfig;
+#[cfg(feature = "trade_simulation")]
+use crate::pocketoption::simulation::SimulatedTradeState;
 use crate::pocketoption::types::ServerTimeState;
-use crate::pocketoption::types::{Assets, Deal};
+use crate::pocketoption::types::{
+    Action, Assets, CandleStreamStats, ConnectionInfo, Deal, PayoutRecord, StreamStats,
+};
+use crate::pocketoption::utils::normalize_fingerprint;
 use crate::pocketoption::{
     error::{PocketError, PocketResult},
     ssid::Ssid,
 };
 use crate::validator::Validator;
 
+/// Default extra delay tolerated between the history and the live stream.
+const DEFAULT_HISTORY_GAP_THRESHOLD: Duration = Duration::from_secs(5);
+
+/// Default number of undelivered price updates kept by the subscriptions module.
+const DEFAULT_DEAD_LETTER_CAPACITY: usize = 1024;
+
+/// Default number of candles used to compute the correlation between assets.
+const DEFAULT_CORRELATION_WINDOW: usize = 120;
+
+/// Default duration of the candles used to compute the correlation between assets.
+const DEFAULT_CORRELATION_PERIOD: Duration = Duration::from_secs(5);
+
+/// Default maximum duration of the connection handshake with a server.
+const DEFAULT_CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default time the server has to confirm a subscription.
+const DEFAULT_SUBSCRIPTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long the payout changes are kept in the payout history.
+const PAYOUT_HISTORY_RETENTION: chrono::Duration = chrono::Duration::hours(24);
+
 /// Application state for PocketOption client
 ///
 /// This structure holds all the shared state for the PocketOption client,
@@ -29,8 +66,9 @@ use crate::validator::Validator;
 /// from multiple modules and tasks.
 pub struct State {
     /// Unique identifier for the session.
-    /// This is used to identify the session across different operations.
-    pub ssid: Ssid,
+    /// This is used to identify the session across different operations,
+    /// it's replaced when switching between the demo and the real account.
+    pub ssid: SyncRwLock<Ssid>,
     /// Default connection URL, if none is specified.
     pub default_connection_url: Option<String>,
     /// Default symbol to use if none is specified.
@@ -41,10 +79,52 @@ pub struct State {
     pub server_time: ServerTimeState,
     /// Assets information
     pub assets: RwLock<Option<Assets>>,
+    /// Notified every time the assets are updated.
+    pub(crate) assets_notify: Notify,
+    /// Whether `PocketOption::new` waits for the assets to be loaded before returning.
+    pub wait_for_assets: bool,
+    /// Extra delay tolerated between the last history candle and the live stream.
+    pub history_gap_threshold: Duration,
+    /// Maximum number of undelivered price updates kept by the subscriptions module.
+    pub dead_letter_capacity: usize,
+    /// SHA-256 fingerprints (lowercase hex) accepted for the server certificate, any certificate is accepted if empty.
+    pub pinned_certificates: Vec<String>,
+    /// Proxy used to reach the server, the connection is direct if `None`.
+    pub proxy: Option<ProxyConfig>,
+    /// Extra headers sent with the WebSocket upgrade request, they replace the default ones.
+    pub headers: HashMap<String, String>,
+    /// Maximum duration of the TCP, TLS and WebSocket handshakes with a server.
+    pub connection_timeout: Duration,
+    /// Maximum time the server has to send the first update of a new subscription.
+    pub subscription_timeout: Duration,
+    /// Receive the full assets every time a payout changes.
+    pub(crate) payout_subscribers: Mutex<Vec<AsyncSender<Assets>>>,
+    /// Receive the server time every time it moves forward.
+    pub(crate) server_time_subscribers: Mutex<Vec<AsyncSender<DateTime<Utc>>>>,
+    /// Payout changes of the last 24 hours, oldest first.
+    pub payout_history: SyncRwLock<VecDeque<PayoutRecord>>,
+    /// Connection and message counters, kept across reconnections.
+    pub statistics: Arc<StatisticsTracker>,
+    /// Basic diagnostics about the current connection.
+    pub connection_info: SyncRwLock<ConnectionInfo>,
     /// Holds the state for all trading-related data.
     pub trade_state: Arc<TradeState>,
+    /// Win rate statistics of the closed deals.
+    pub win_rate: WinRateTracker,
+    /// Recent closes of the subscribed assets, used to compute their correlation.
+    pub correlation: CorrelationTracker,
+    /// Tick counters of the active subscriptions, keyed by asset.
+    pub(crate) stream_stats: SyncRwLock<HashMap<String, StreamStats>>,
+    /// Risk rules checked before every trade, in registration order.
+    pub trade_guards: SyncRwLock<Vec<Arc<dyn TradeGuard>>>,
+    /// Held from the guards check of a trade until they are notified of its opening, so
+    /// concurrent trades can't all pass the check before any of them is counted.
+    trade_guards_lock: AsyncMutex<()>,
     /// Holds the current validators for the raw module keyed by ID
     pub raw_validators: SyncRwLock<HashMap<Uuid, Validator>>,
+    /// Simulated deals and last known prices, used while the simulation mode is enabled.
+    #[cfg(feature = "trade_simulation")]
+    pub simulation: SimulatedTradeState,
 }
 
 /// Builder pattern for creating State instances
@@ -56,6 +136,15 @@ pub struct StateBuilder {
     ssid: Option<Ssid>,
     default_connection_url: Option<String>,
     default_symbol: Option<String>,
+    wait_for_assets: Option<bool>,
+    history_gap_threshold: Option<Duration>,
+    dead_letter_capacity: Option<usize>,
+    pinned_certificates: Vec<String>,
+    proxy: Option<ProxyConfig>,
+    headers: HashMap<String, String>,
+    connection_timeout: Option<Duration>,
+    subscription_timeout: Option<Duration>,
+    correlation_window: Option<(usize, Duration)>,
 }
 
 impl StateBuilder {
@@ -86,15 +175,158 @@ impl StateBuilder {
         self
     }
 
+    /// Set whether the client waits for the assets to be loaded when created
+    ///
+    /// # Arguments
+    /// * `wait` - `true` (the default) to wait for the assets before returning from `PocketOption::new`
+    pub fn wait_for_assets(mut self, wait: bool) -> Self {
+        self.wait_for_assets = Some(wait);
+        self
+    }
+
+    /// Set the delay tolerated between the last history candle and the live stream
+    ///
+    /// # Arguments
+    /// * `threshold` - Extra delay allowed on top of the history period before
+    ///   `PocketOption::subscribe_with_initial_history` warns about a gap (5 seconds by default)
+    pub fn history_gap_threshold(mut self, threshold: Duration) -> Self {
+        self.history_gap_threshold = Some(threshold);
+        self
+    }
+
+    /// Set how many undelivered price updates are kept for `SubscriptionsHandle::drain_dead_letters`
+    ///
+    /// # Arguments
+    /// * `capacity` - Maximum size of the queue, the oldest updates are dropped once it is full (1024 by default)
+    pub fn dead_letter_capacity(mut self, capacity: usize) -> Self {
+        self.dead_letter_capacity = Some(capacity);
+        self
+    }
+
+    /// Set the rolling window used by `PocketOption::correlation`
+    ///
+    /// # Arguments
+    /// * `window` - Number of candles kept for every subscribed asset (120 by default)
+    /// * `period` - Duration of the candles, their close is the last price received (5 seconds by default)
+    pub fn correlation_window(mut self, window: usize, period: Duration) -> Self {
+        self.correlation_window = Some((window, period));
+        self
+    }
+
+    /// Only accept servers whose certificate matches one of the given fingerprints
+    ///
+    /// # Arguments
+    /// * `fingerprints` - SHA-256 fingerprints of the DER encoded certificates as hex strings,
+    ///   colons are allowed (`PocketOption::fetch_server_certificate_fingerprint` returns the current one)
+    pub fn with_pinned_certificates(mut self, fingerprints: Vec<String>) -> Self {
+        self.pinned_certificates = fingerprints
+            .iter()
+            .map(|fingerprint| normalize_fingerprint(fingerprint))
+            .collect();
+        self
+    }
+
+    /// Connect to the server through a proxy
+    ///
+    /// # Arguments
+    /// * `proxy` - HTTP (`CONNECT` tunnel) or SOCKS5 proxy
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Send an extra header with the WebSocket upgrade request
+    ///
+    /// # Arguments
+    /// * `name` - Header name, a default header with the same name (like `User-Agent`) is replaced
+    /// * `value` - Header value
+    pub fn with_header(mut self, name: impl ToString, value: impl ToString) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Give up a connection attempt when the handshake with the server takes too long, the
+    /// client then fails with `PocketError::ConnectionTimeout` if it never connected before
+    ///
+    /// # Arguments
+    /// * `timeout` - Maximum duration of the TCP, TLS and WebSocket handshakes (10 seconds by default)
+    pub fn connection_timeout(mut self, timeout: Duration) -> Self {
+        self.connection_timeout = Some(timeout);
+        self
+    }
+
+    /// Fail a subscription when the server doesn't answer the `changeSymbol` request in time
+    ///
+    /// # Arguments
+    /// * `timeout` - Maximum time to wait for the first update of the asset (10 seconds by default)
+    pub fn subscription_timeout(mut self, timeout: Duration) -> Self {
+        self.subscription_timeout = Some(timeout);
+        self
+    }
+
+    /// Apply the settings of a TOML configuration file
+    ///
+    /// # Arguments
+    /// * `path` - File with any of the `url`, `default_symbol`, `wait_for_assets`,
+    ///   `history_gap_threshold` (in seconds) and `dead_letter_capacity` keys
+    pub fn config_file(mut self, path: &Path) -> PocketResult<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let document: Document<String> = content
+            .parse()
+            .map_err(|e| PocketError::Config(format!("{}: {e}", path.display())))?;
+        let invalid = |key: &str, expected: &str| {
+            PocketError::Config(format!("{}: `{key}` must be {expected}", path.display()))
+        };
+        for (key, value) in document.iter() {
+            match key {
+                "url" => {
+                    let url = value.as_str().ok_or_else(|| invalid(key, "a string"))?;
+                    self.default_connection_url = Some(url.to_string());
+                }
+                "default_symbol" => {
+                    let symbol = value.as_str().ok_or_else(|| invalid(key, "a string"))?;
+                    self.default_symbol = Some(symbol.to_string());
+                }
+                "wait_for_assets" => {
+                    let wait = value.as_bool().ok_or_else(|| invalid(key, "a boolean"))?;
+                    self.wait_for_assets = Some(wait);
+                }
+                "history_gap_threshold" => {
+                    let secs = value
+                        .as_float()
+                        .or_else(|| value.as_integer().map(|secs| secs as f64))
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                        .ok_or_else(|| invalid(key, "a positive number of seconds"))?;
+                    self.history_gap_threshold = Some(secs);
+                }
+                "dead_letter_capacity" => {
+                    let capacity = value
+                        .as_integer()
+                        .and_then(|capacity| usize::try_from(capacity).ok())
+                        .ok_or_else(|| invalid(key, "a positive integer"))?;
+                    self.dead_letter_capacity = Some(capacity);
+                }
+                _ => {
+                    return Err(PocketError::Config(format!(
+                        "{}: unknown key `{key}`",
+                        path.display()
+                    )));
+                }
+            }
+        }
+        Ok(self)
+    }
+
     /// Build the final State instance
     ///
     /// # Returns
     /// Result containing the State or an error if required fields are missing
     pub fn build(self) -> PocketResult<State> {
         Ok(State {
-            ssid: self
-                .ssid
-                .ok_or(PocketError::StateBuilder("SSID is required".into()))?,
+            ssid: SyncRwLock::new(
+                self.ssid
+                    .ok_or(PocketError::StateBuilder("SSID is required".into()))?,
+            ),
             default_connection_url: self.default_connection_url,
             default_symbol: self
                 .default_symbol
@@ -102,8 +334,42 @@ impl StateBuilder {
             balance: RwLock::new(None),
             server_time: ServerTimeState::default(),
             assets: RwLock::new(None),
+            assets_notify: Notify::new(),
+            wait_for_assets: self.wait_for_assets.unwrap_or(true),
+            history_gap_threshold: self
+                .history_gap_threshold
+                .unwrap_or(DEFAULT_HISTORY_GAP_THRESHOLD),
+            dead_letter_capacity: self
+                .dead_letter_capacity
+                .unwrap_or(DEFAULT_DEAD_LETTER_CAPACITY),
+            pinned_certificates: self.pinned_certificates,
+            proxy: self.proxy,
+            headers: self.headers,
+            connection_timeout: self
+                .connection_timeout
+                .unwrap_or(DEFAULT_CONNECTION_TIMEOUT),
+            subscription_timeout: self
+                .subscription_timeout
+                .unwrap_or(DEFAULT_SUBSCRIPTION_TIMEOUT),
+            payout_subscribers: Mutex::new(Vec::new()),
+            server_time_subscribers: Mutex::new(Vec::new()),
+            payout_history: SyncRwLock::new(VecDeque::new()),
+            statistics: Arc::new(StatisticsTracker::new()),
+            connection_info: SyncRwLock::new(ConnectionInfo::default()),
             trade_state: Arc::new(TradeState::default()),
+            win_rate: WinRateTracker::default(),
+            correlation: {
+                let (window, period) = self
+                    .correlation_window
+                    .unwrap_or((DEFAULT_CORRELATION_WINDOW, DEFAULT_CORRELATION_PERIOD));
+                CorrelationTracker::new(window, period)
+            },
+            stream_stats: SyncRwLock::new(HashMap::new()),
+            trade_guards: SyncRwLock::new(Vec::new()),
+            trade_guards_lock: AsyncMutex::new(()),
             raw_validators: SyncRwLock::new(HashMap::new()),
+            #[cfg(feature = "trade_simulation")]
+            simulation: SimulatedTradeState::default(),
         })
     }
 }
@@ -111,15 +377,28 @@ impl StateBuilder {
 #[async_trait]
 impl AppState for State {
     async fn clear_temporal_data(&self) {
-        // Clear any temporary data associated with the state
-        let mut balance = self.balance.write().await;
-        *balance = None; // Clear balance
-        // Note: We don't clear server time as it's useful to maintain
-        // time synchronization across reconnections
+        self.on_hard_disconnect().await;
     }
 }
 
 impl State {
+    /// Clears the data of the session that ended with a manual disconnect.
+    ///
+    /// The balance, the opened deals (they are sent again by the server after connecting), the
+    /// recent closes used for the correlation and the clock offset are reset.
+    pub async fn on_hard_disconnect(&self) {
+        *self.balance.write().await = None;
+        self.connection_info
+            .write()
+            .expect("Failed to acquire connection info lock")
+            .connected_since = None;
+        self.trade_state.clear_opened_deals().await;
+        self.correlation.clear();
+        // Note: We keep the last server time as it's useful to maintain time synchronization
+        // across reconnections, only the clock offset is recomputed with the new stream
+        self.server_time.write().await.offset = chrono::Duration::zero();
+    }
+
     /// Sets the current balance.
     /// This method updates the balance in a thread-safe manner.
     ///
@@ -147,7 +426,20 @@ impl State {
     /// # Returns
     /// True if using demo account, false for real account
     pub fn is_demo(&self) -> bool {
-        self.ssid.demo()
+        self.ssid().demo()
+    }
+
+    /// Current session, used by the next connection.
+    pub fn ssid(&self) -> Ssid {
+        self.ssid
+            .read()
+            .expect("Failed to acquire ssid lock")
+            .clone()
+    }
+
+    /// Replaces the session, it's only sent to the server on the next connection.
+    pub fn set_ssid(&self, ssid: Ssid) {
+        *self.ssid.write().expect("Failed to acquire ssid lock") = ssid;
     }
 
     /// Get current server time
@@ -166,6 +458,40 @@ impl State {
         self.server_time.write().await.update(timestamp);
     }
 
+    /// Registers a new receiver for the server time updates.
+    pub fn subscribe_server_time(&self) -> AsyncReceiver<DateTime<Utc>> {
+        let (sender, receiver) = bounded_async(64);
+        self.server_time_subscribers
+            .lock()
+            .expect("Failed to acquire server time subscribers lock")
+            .push(sender);
+        receiver
+    }
+
+    /// Current time corrected with the offset of the server clock, use it instead of
+    /// `Utc::now()` when comparing with server timestamps.
+    pub async fn corrected_now(&self) -> DateTime<Utc> {
+        Utc::now() + self.server_time.read().await.offset
+    }
+
+    /// Sends the server time to every subscriber, dropping the ones that are gone.
+    /// Slow subscribers with a full buffer miss the update instead of blocking the caller.
+    pub fn notify_server_time_subscribers(&self, time: DateTime<Utc>) {
+        self.server_time_subscribers
+            .lock()
+            .expect("Failed to acquire server time subscribers lock")
+            .retain(|sender| sender.try_send(time).is_ok());
+    }
+
+    /// Tick statistics of `asset`, `None` if it isn't subscribed.
+    pub fn candle_stream_stats(&self, asset: &str) -> Option<CandleStreamStats> {
+        self.stream_stats
+            .read()
+            .expect("Failed to acquire stream stats lock")
+            .get(asset)
+            .map(|stats| stats.to_stats(asset))
+    }
+
     /// Check if server time data is stale
     ///
     /// # Returns
@@ -217,6 +543,173 @@ impl State {
     pub async fn set_assets(&self, assets: Assets) {
         let mut state = self.assets.write().await;
         *state = Some(assets);
+        self.assets_notify.notify_waiters();
+    }
+
+    /// Registers a guard checked before every trade, after the ones already registered.
+    pub fn add_trade_guard(&self, guard: Arc<dyn TradeGuard>) {
+        self.trade_guards
+            .write()
+            .expect("Failed to acquire trade guards lock")
+            .push(guard);
+    }
+
+    fn trade_guards(&self) -> Vec<Arc<dyn TradeGuard>> {
+        self.trade_guards
+            .read()
+            .expect("Failed to acquire trade guards lock")
+            .clone()
+    }
+
+    /// Locks the trades until the returned guard is dropped, `None` if no trade guard is
+    /// registered since there is nothing to keep consistent then.
+    pub async fn lock_trade_guards(&self) -> Option<AsyncMutexGuard<'_, ()>> {
+        if self
+            .trade_guards
+            .read()
+            .expect("Failed to acquire trade guards lock")
+            .is_empty()
+        {
+            None
+        } else {
+            Some(self.trade_guards_lock.lock().await)
+        }
+    }
+
+    /// Checks the trade against every guard, stopping at the first rejection.
+    pub async fn check_trade_guards(
+        &self,
+        asset: &str,
+        action: Action,
+        amount: f64,
+        time: u32,
+    ) -> PocketResult<()> {
+        for guard in self.trade_guards() {
+            guard
+                .allow_trade(asset, action.clone(), amount, time)
+                .await
+                .map_err(PocketError::TradeRejected)?;
+        }
+        Ok(())
+    }
+
+    /// Notifies the guards that a trade placed by the client was opened.
+    pub async fn notify_trade_opened(&self, deal: &Deal) {
+        for guard in self.trade_guards() {
+            guard.on_trade_opened(deal).await;
+        }
+    }
+
+    /// Notifies the guards that the server reported a closed deal and counts it in the win rate.
+    /// Returns false, without notifying anything, if the deal was already reported.
+    pub async fn notify_trade_closed(&self, deal: &Deal) -> bool {
+        if !self.win_rate.record(deal) {
+            return false;
+        }
+        for guard in self.trade_guards() {
+            guard.on_trade_closed(deal).await;
+        }
+        true
+    }
+
+    /// Registers a new receiver for the assets sent each time a payout changes.
+    pub fn subscribe_payouts(&self) -> AsyncReceiver<Assets> {
+        let (sender, receiver) = bounded_async(16);
+        self.payout_subscribers
+            .lock()
+            .expect("Failed to acquire payout subscribers lock")
+            .push(sender);
+        receiver
+    }
+
+    /// Sends the assets to every payout subscriber, dropping the ones that are gone.
+    /// Slow subscribers with a full buffer miss the update instead of blocking the caller.
+    pub fn notify_payout_subscribers(&self, assets: &Assets) {
+        self.payout_subscribers
+            .lock()
+            .expect("Failed to acquire payout subscribers lock")
+            .retain(|sender| sender.try_send(assets.clone()).is_ok());
+    }
+
+    /// Records the payouts of `assets` that differ from `previous`, every payout is recorded
+    /// if there are no previous assets. Changes older than 24 hours are dropped.
+    pub fn record_payout_changes(&self, previous: Option<&Assets>, assets: &Assets) {
+        let now = Utc::now();
+        let previous = previous.map(Assets::payouts).unwrap_or_default();
+        let mut history = self
+            .payout_history
+            .write()
+            .expect("Failed to acquire payout history lock");
+        for (asset, payout) in assets.payouts() {
+            if previous.get(&asset) != Some(&payout) {
+                history.push_back(PayoutRecord {
+                    time: now,
+                    asset,
+                    payout,
+                });
+            }
+        }
+        while history
+            .front()
+            .is_some_and(|record| now - record.time > PAYOUT_HISTORY_RETENTION)
+        {
+            history.pop_front();
+        }
+    }
+
+    /// Returns the payout changes of `asset` received during the last `hours`, oldest first.
+    /// Nothing older than the 24 hours retention is kept, so larger windows are clamped to it.
+    pub fn payout_history(&self, asset: &str, hours: u32) -> Vec<(DateTime<Utc>, i32)> {
+        let window = chrono::Duration::hours(hours.into()).min(PAYOUT_HISTORY_RETENTION);
+        let since = Utc::now() - window;
+        self.payout_history
+            .read()
+            .expect("Failed to acquire payout history lock")
+            .iter()
+            .filter(|record| record.asset == asset && record.time >= since)
+            .map(|record| (record.time, record.payout))
+            .collect()
+    }
+
+    /// Waits until the assets are loaded, returns immediately if they already are.
+    pub async fn wait_for_assets(&self) {
+        loop {
+            // Create the future before checking so an update in between is not missed.
+            let notified = self.assets_notify.notified();
+            if self.assets.read().await.is_some() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Records a new connection to the server at `url`.
+    pub fn set_connected_to(&self, url: &str) {
+        let region = url::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string));
+        let mut info = self
+            .connection_info
+            .write()
+            .expect("Failed to acquire connection info lock");
+        info.region = region;
+        info.connected_since = Some(Utc::now());
+    }
+
+    /// Updates the average keep-alive round trip, in milliseconds.
+    pub fn set_avg_round_trip(&self, avg_round_trip_ms: f64) {
+        self.connection_info
+            .write()
+            .expect("Failed to acquire connection info lock")
+            .avg_round_trip_ms = Some(avg_round_trip_ms);
+    }
+
+    /// Returns a snapshot of the current connection diagnostics.
+    pub fn connection_info(&self) -> ConnectionInfo {
+        self.connection_info
+            .read()
+            .expect("Failed to acquire connection info lock")
+            .clone()
     }
 
     /// Adds or replaces a validator in the list of raw validators.
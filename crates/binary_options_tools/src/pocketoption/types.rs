@@ -11,6 +11,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 use uuid::Uuid;
 
+use crate::pocketoption::candle::Candle;
 use crate::pocketoption::error::{PocketError, PocketResult};
 use crate::pocketoption::utils::float_time;
 /// Server time management structure for synchronizing with PocketOption servers
@@ -48,7 +49,7 @@ impl ServerTime {
     /// * `server_timestamp` - Unix timestamp from the server as f64
     pub fn update(&mut self, server_timestamp: f64) {
         let now = Utc::now();
-        let local_timestamp = now.timestamp() as f64;
+        let local_timestamp = now.timestamp_millis() as f64 / 1000.0;
 
         self.last_server_time = server_timestamp;
         self.last_updated = now;
@@ -358,6 +359,19 @@ impl Asset {
         &self.allowed_candles
     }
 
+    /// Expiry times in seconds accepted by `validate`, sorted.
+    pub fn expiry_options(&self) -> Vec<u32> {
+        let mut options: Vec<u32> = self
+            .allowed_candles
+            .iter()
+            .chain(Self::DEFAULT_CANDLE_LENGTHS.iter())
+            .map(CandleLength::duration)
+            .collect();
+        options.sort_unstable();
+        options.dedup();
+        options
+    }
+
     /// Validates if the asset can be used for trading at the given time
     /// It checks, if the time is in the allowed candle durations
     /// and also if the asset is active.
@@ -424,6 +438,30 @@ impl<'de> Deserialize<'de> for Asset {
     }
 }
 
+/// Trading details of an active asset, returned by `PocketOption::available_assets`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetInfo {
+    pub symbol: String,
+    pub payout: i32,
+    /// Smallest amount accepted for a trade
+    pub min_amount: f64,
+    /// Expiry times in seconds accepted for a trade, sorted
+    pub expiry_times: Vec<u32>,
+    pub is_otc: bool,
+}
+
+impl AssetInfo {
+    pub(crate) fn new(asset: &Asset, min_amount: f64) -> Self {
+        Self {
+            symbol: asset.symbol.clone(),
+            payout: asset.payout,
+            min_amount,
+            expiry_times: asset.expiry_options(),
+            is_otc: asset.is_otc,
+        }
+    }
+}
+
 /// Wrapper around HashMap<String, Asset>
 #[derive(Debug, Default, Clone)]
 pub struct Assets(pub HashMap<String, Asset>);
@@ -446,6 +484,15 @@ impl Assets {
     pub fn names(&self) -> Vec<&str> {
         self.0.values().map(|a| a.name.as_str()).collect()
     }
+
+    /// Payout of every active asset, keyed by symbol.
+    pub fn payouts(&self) -> HashMap<String, i32> {
+        self.0
+            .iter()
+            .filter(|(_, asset)| asset.is_active)
+            .map(|(symbol, asset)| (symbol.clone(), asset.payout))
+            .collect()
+    }
 }
 
 impl<'de> Deserialize<'de> for Assets {
@@ -459,6 +506,151 @@ impl<'de> Deserialize<'de> for Assets {
     }
 }
 
+/// Basic diagnostics about the current connection.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+    /// Host of the server the client is connected to.
+    pub region: Option<String>,
+    /// When the current connection was established.
+    pub connected_since: Option<DateTime<Utc>>,
+    /// Rolling average of the last keep-alive round trips, in milliseconds.
+    pub avg_round_trip_ms: Option<f64>,
+}
+
+/// Snapshot of the client health, returned by `PocketOption::health_check`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthStatus {
+    pub is_connected: bool,
+    /// Number of assets currently subscribed.
+    pub active_subscriptions: u32,
+    /// Difference between the server clock and the local one, in milliseconds.
+    pub server_time_drift_ms: i64,
+    /// Reason of the last connection failure or disconnection, if any.
+    pub last_error: Option<String>,
+    /// Current balance, `-1` if it wasn't received yet.
+    pub balance: f64,
+}
+
+/// Histories returned by `PocketOption::batch_history`, keyed by `"{asset}_{period}"`.
+#[derive(Debug, Default)]
+pub struct BatchHistoryResult {
+    /// Candles of the requests that succeeded.
+    pub ok: HashMap<String, Vec<Candle>>,
+    /// Error of the requests that failed.
+    pub errors: HashMap<String, PocketError>,
+}
+
+/// Tick statistics of a subscribed asset, returned by `PocketOption::candle_stream_stats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandleStreamStats {
+    pub asset: String,
+    /// Average number of price updates per second of server time since the subscription.
+    pub tick_rate_per_second: f64,
+    /// Number of price updates received since the subscription.
+    pub candle_count: u64,
+    /// Number of times two consecutive updates were more than two periods apart.
+    pub gap_count: u64,
+    /// Server timestamp of the newest update.
+    pub last_candle_ts: Option<DateTime<Utc>>,
+}
+
+/// Metadata of an active subscription, returned by `PocketOption::subscription_info`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionInfo {
+    pub asset: String,
+    /// When the subscription was requested
+    pub subscribed_at: DateTime<Utc>,
+    /// Number of price updates forwarded to the stream
+    pub candle_count: u64,
+    /// Price of the newest update, `None` until the first one
+    pub last_price: Option<f64>,
+    /// Timestamp of the newest update, `None` until the first one
+    pub last_tick_ts: Option<DateTime<Utc>>,
+}
+
+/// Running counters of a subscription, turned into `CandleStreamStats` or `SubscriptionInfo`
+/// when requested.
+#[derive(Debug, Clone)]
+pub(crate) struct StreamStats {
+    /// Period requested to the server with `changeSymbol`, in seconds.
+    period: f64,
+    subscribed_at: DateTime<Utc>,
+    first_timestamp: Option<f64>,
+    last_timestamp: Option<f64>,
+    last_price: Option<f64>,
+    count: u64,
+    gaps: u64,
+}
+
+impl StreamStats {
+    pub(crate) fn new(period: u32) -> Self {
+        Self {
+            period: period.max(1) as f64,
+            subscribed_at: Utc::now(),
+            first_timestamp: None,
+            last_timestamp: None,
+            last_price: None,
+            count: 0,
+            gaps: 0,
+        }
+    }
+
+    /// Records a price update, late updates are counted without moving the last timestamp.
+    pub(crate) fn record(&mut self, price: f64, timestamp: f64) {
+        self.count += 1;
+        self.first_timestamp.get_or_insert(timestamp);
+        match self.last_timestamp {
+            Some(last) if timestamp <= last => return,
+            Some(last) if timestamp - last > 2.0 * self.period => self.gaps += 1,
+            _ => {}
+        }
+        self.last_timestamp = Some(timestamp);
+        self.last_price = Some(price);
+    }
+
+    fn last_datetime(&self) -> Option<DateTime<Utc>> {
+        self.last_timestamp
+            .and_then(|timestamp| DateTime::from_timestamp_millis((timestamp * 1000.0) as i64))
+    }
+
+    pub(crate) fn to_info(&self, asset: &str) -> SubscriptionInfo {
+        SubscriptionInfo {
+            asset: asset.to_string(),
+            subscribed_at: self.subscribed_at,
+            candle_count: self.count,
+            last_price: self.last_price,
+            last_tick_ts: self.last_datetime(),
+        }
+    }
+
+    pub(crate) fn to_stats(&self, asset: &str) -> CandleStreamStats {
+        let span = match (self.first_timestamp, self.last_timestamp) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        };
+        CandleStreamStats {
+            asset: asset.to_string(),
+            tick_rate_per_second: if span > 0.0 {
+                self.count.saturating_sub(1) as f64 / span
+            } else {
+                0.0
+            },
+            candle_count: self.count,
+            gap_count: self.gaps,
+            last_candle_ts: self.last_datetime(),
+        }
+    }
+}
+
+/// A payout change of a single asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayoutRecord {
+    /// When the change was received.
+    pub time: DateTime<Utc>,
+    pub asset: String,
+    pub payout: i32,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum Action {
@@ -576,6 +768,39 @@ impl fmt::Display for OpenOrder {
     }
 }
 
+#[cfg(test)]
+impl Deal {
+    /// Payload of a closed 1$ call on `EURUSD_otc`, as sent by the server, used as a fixture by the tests.
+    pub(crate) fn mock_json(profit: f64) -> Value {
+        serde_json::json!({
+            "id": Uuid::new_v4(),
+            "openTime": "2025-01-01 00:00:00",
+            "closeTime": "2025-01-01 00:01:00",
+            "openTimestamp": 1735689600,
+            "closeTimestamp": 1735689660,
+            "uid": 1,
+            "amount": 1.0,
+            "profit": profit,
+            "percentProfit": 92,
+            "percentLoss": 100,
+            "openPrice": 1.1,
+            "closePrice": 1.2,
+            "command": 0,
+            "asset": "EURUSD_otc",
+            "isDemo": 1,
+            "copyTicket": "",
+            "openMs": 0,
+            "optionType": 100,
+            "currency": "USD"
+        })
+    }
+
+    /// Same as `mock_json`, parsed.
+    pub(crate) fn mock(profit: f64) -> Self {
+        serde_json::from_value(Self::mock_json(profit)).unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
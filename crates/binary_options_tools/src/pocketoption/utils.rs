@@ -1,14 +1,20 @@
+use std::collections::HashMap;
+
 use binary_options_tools_core_pre::connector::{ConnectorError, ConnectorResult};
+use binary_options_tools_core_pre::error::CoreError;
 use binary_options_tools_core_pre::reimports::{
-    Connector, MaybeTlsStream, Request, WebSocketStream, connect_async_tls_with_config,
-    generate_key,
+    HeaderName, HeaderValue, MaybeTlsStream, Request, WebSocketStream,
+    client_async_tls_with_config, connect_async_tls_with_config, generate_key,
 };
 use chrono::{Duration, Utc};
 use rand::{Rng, rng};
+use ring::digest::{SHA256, digest};
+use tracing::debug;
 
 use crate::pocketoption::{
     error::{PocketError, PocketResult},
-    ssid::Ssid,
+    proxy::ProxyConfig,
+    types::Deal,
 };
 use serde_json::Value;
 use tokio::net::TcpStream;
@@ -56,26 +62,125 @@ pub async fn get_public_ip() -> PocketResult<String> {
     Ok(json["ip"].as_str().unwrap().to_string())
 }
 
+/// Connects to `url`, through `proxy` if set, rejecting the server if `pinned_certificates` is
+/// not empty and its certificate doesn't match any of them.
+///
+/// The certificate is checked before the SSID is sent, so it is never leaked to an untrusted server.
+/// Returns `ConnectorError::CertificatePinning` if it doesn't match, and `ConnectorError::Timeout`
+/// if the handshake takes longer than `timeout`.
 pub async fn try_connect(
-    ssid: Ssid,
     url: String,
+    headers: &HashMap<String, String>,
+    pinned_certificates: &[String],
+    proxy: Option<&ProxyConfig>,
+    timeout: std::time::Duration,
 ) -> ConnectorResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
-    let tls_connector: native_tls::TlsConnector = native_tls::TlsConnector::builder()
-        .build()
-        .map_err(|e| ConnectorError::Tls(e.to_string()))?;
+    let ws = connect(url, headers, proxy, timeout).await?;
+    if !pinned_certificates.is_empty() {
+        let der = peer_certificate_der(&ws).map_err(|e| ConnectorError::Tls(e.to_string()))?;
+        verify_pinned_certificate(&der, pinned_certificates).map_err(|e| match e {
+            PocketError::CertificatePinningFailure(fingerprint) => {
+                ConnectorError::CertificatePinning(fingerprint)
+            }
+            e => ConnectorError::Tls(e.to_string()),
+        })?;
+    }
+    Ok(ws)
+}
 
-    let connector = Connector::NativeTls(tls_connector);
+/// Connects to `url` and returns the SHA-256 fingerprint of the server certificate.
+pub async fn fetch_certificate_fingerprint(
+    url: String,
+    headers: &HashMap<String, String>,
+    proxy: Option<&ProxyConfig>,
+    timeout: std::time::Duration,
+) -> PocketResult<String> {
+    let mut ws = connect(url, headers, proxy, timeout)
+        .await
+        .map_err(|e| match e {
+            ConnectorError::Timeout => PocketError::ConnectionTimeout(timeout),
+            e => CoreError::from(e).into(),
+        })?;
+    let der = peer_certificate_der(&ws)?;
+    if let Err(e) = ws.close(None).await {
+        debug!(target: "PocketConnect", "Failed to close connection after fetching the certificate: {e}");
+    }
+    Ok(certificate_fingerprint(&der))
+}
+
+/// SHA-256 of a DER encoded certificate as a lowercase hex string.
+pub fn certificate_fingerprint(der: &[u8]) -> String {
+    digest(&SHA256, der)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Lowercases a hex fingerprint and removes the separators (`AB:CD` becomes `abcd`).
+pub fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(char::is_ascii_hexdigit)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
 
-    let user_agent = ssid.user_agent();
+/// Checks that the DER encoded certificate matches one of the pinned fingerprints.
+pub fn verify_pinned_certificate(der: &[u8], pinned_certificates: &[String]) -> PocketResult<()> {
+    let fingerprint = certificate_fingerprint(der);
+    if pinned_certificates
+        .iter()
+        .any(|pinned| normalize_fingerprint(pinned) == fingerprint)
+    {
+        Ok(())
+    } else {
+        Err(PocketError::CertificatePinningFailure(fingerprint))
+    }
+}
+
+fn peer_certificate_der(ws: &WebSocketStream<MaybeTlsStream<TcpStream>>) -> PocketResult<Vec<u8>> {
+    let der = match ws.get_ref() {
+        #[cfg(feature = "native-tls")]
+        MaybeTlsStream::NativeTls(stream) => stream
+            .get_ref()
+            .peer_certificate()
+            .and_then(|cert| cert.map(|cert| cert.to_der()).transpose())
+            .map_err(|e| {
+                PocketError::General(format!("Failed to read the server certificate: {e}"))
+            })?,
+        #[cfg(feature = "rustls")]
+        MaybeTlsStream::Rustls(stream) => stream
+            .get_ref()
+            .1
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .map(|cert| cert.to_vec()),
+        _ => {
+            return Err(PocketError::General(
+                "The connection is not using TLS, there is no certificate to check".into(),
+            ));
+        }
+    };
+    der.ok_or_else(|| PocketError::General("The server didn't send any certificate".into()))
+}
+
+/// Opens the WebSocket connection, `headers` are added to the upgrade request and replace the
+/// default ones with the same name.
+async fn connect(
+    url: String,
+    headers: &HashMap<String, String>,
+    proxy: Option<&ProxyConfig>,
+    timeout: std::time::Duration,
+) -> ConnectorResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
     let t_url = Url::parse(&url).map_err(|e| ConnectorError::UrlParsing(e.to_string()))?;
     let host = t_url
         .host_str()
         .ok_or(ConnectorError::UrlParsing("Host not found".into()))?;
-    let request = Request::builder()
+    let mut request = Request::builder()
         .uri(t_url.to_string())
         .header("Origin", "https://pocketoption.com")
         .header("Cache-Control", "no-cache")
-        .header("User-Agent", user_agent)
         .header("Upgrade", "websocket")
         .header("Connection", "upgrade")
         .header("Sec-Websocket-Key", generate_key())
@@ -83,13 +188,83 @@ pub async fn try_connect(
         .header("Host", host)
         .body(())
         .map_err(|e| ConnectorError::HttpRequestBuild(e.to_string()))?;
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| ConnectorError::HttpRequestBuild(format!("{name}: {e}")))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| ConnectorError::HttpRequestBuild(format!("{name}: {e}")))?;
+        request.headers_mut().insert(name, value);
+    }
 
-    let (ws, _) = connect_async_tls_with_config(request, None, false, Some(connector))
+    // No explicit connector: the TLS backend selected through the `native-tls` / `rustls`
+    // features is used for `wss://` urls
+    let handshake = async {
+        match proxy {
+            Some(proxy) => {
+                let port = t_url
+                    .port_or_known_default()
+                    .ok_or(ConnectorError::UrlParsing("Port not found".into()))?;
+                let tunnel = proxy.connect(host, port).await?;
+                client_async_tls_with_config(request, tunnel, None, None).await
+            }
+            None => connect_async_tls_with_config(request, None, false, None).await,
+        }
+        .map_err(|e| ConnectorError::Custom(e.to_string()))
+    };
+    let (ws, _) = tokio::time::timeout(timeout, handshake)
         .await
-        .map_err(|e| ConnectorError::Custom(e.to_string()))?;
+        .map_err(|_| ConnectorError::Timeout)??;
     Ok(ws)
 }
 
+/// Writes the given deals as CSV rows into `writer`, sorted by `close_timestamp` ascending.
+///
+/// The header row is `id,asset,action,amount,open_price,close_price,open_time,close_time,result,pnl`,
+/// timestamps are written as RFC 3339 strings and `result` is one of `win`, `loss` or `draw`.
+/// Returns the number of deal rows written (the header is not counted).
+pub fn write_deals_csv<W: std::io::Write>(writer: W, deals: Vec<Deal>) -> PocketResult<usize> {
+    let mut deals = deals;
+    deals.sort_by_key(|d| d.close_timestamp);
+
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record([
+        "id",
+        "asset",
+        "action",
+        "amount",
+        "open_price",
+        "close_price",
+        "open_time",
+        "close_time",
+        "result",
+        "pnl",
+    ])?;
+    for deal in deals.iter() {
+        let action = if deal.command == 0 { "call" } else { "put" };
+        let result = if deal.profit > 0.0 {
+            "win"
+        } else if deal.profit < 0.0 {
+            "loss"
+        } else {
+            "draw"
+        };
+        writer.write_record([
+            deal.id.to_string(),
+            deal.asset.clone(),
+            action.to_string(),
+            deal.amount.to_string(),
+            deal.open_price.to_string(),
+            deal.close_price.to_string(),
+            deal.open_timestamp.to_rfc3339(),
+            deal.close_timestamp.to_rfc3339(),
+            result.to_string(),
+            deal.profit.to_string(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(deals.len())
+}
+
 pub mod float_time {
     use chrono::{DateTime, Utc};
     use serde::{Deserialize, Deserializer, Serializer};
@@ -134,3 +309,119 @@ pub mod float_time {
             .ok_or(serde::de::Error::custom("Error parsing ints to time"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine, engine::general_purpose::STANDARD};
+    use chrono::DateTime;
+
+    use std::{
+        collections::HashMap,
+        time::{Duration, Instant},
+    };
+
+    use binary_options_tools_core_pre::connector::ConnectorError;
+
+    use super::{
+        certificate_fingerprint, fetch_certificate_fingerprint, try_connect,
+        verify_pinned_certificate, write_deals_csv,
+    };
+    use crate::pocketoption::{error::PocketError, types::Deal};
+
+    /// Self-signed certificate for `localhost`
+    const SELF_SIGNED_CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIBgDCCASWgAwIBAgIUHwwyroDgXTA2ToWb+A05byQI4JgwCgYIKoZIzj0EAwIw
+FDESMBAGA1UEAwwJbG9jYWxob3N0MCAXDTI2MTAxNzAzMTI1N1oYDzIxMjYwOTIz
+MDMxMjU3WjAUMRIwEAYDVQQDDAlsb2NhbGhvc3QwWTATBgcqhkjOPQIBBggqhkjO
+PQMBBwNCAATw2YiNKfJDYZzX8AhsEmLvzWvUF1YoaSW7M2gaMRMeu7NJnImBmoaZ
+pXpf7PXb9WKHmaIELTNSauj2fMj2hf2bo1MwUTAdBgNVHQ4EFgQUngCQS8PbunGC
+L0LGhl5CB9+CDaYwHwYDVR0jBBgwFoAUngCQS8PbunGCL0LGhl5CB9+CDaYwDwYD
+VR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNJADBGAiEAyPYJ95e5/mzqOKPfcxzS
+4NsO0Pbr7ERRTPCsO6y6nh8CIQD/s5FmxTzdP894P6OaVUhf62+FljyRlEKwk01B
+ZPEAxA==
+-----END CERTIFICATE-----";
+    const SELF_SIGNED_FINGERPRINT: &str =
+        "4dc577c38a04fb51db9e7d2583995337e8aab1cec5d3e34f2389533d99e1817c";
+
+    #[test]
+    fn test_certificate_pinning() {
+        let pem_body: String = SELF_SIGNED_CERT
+            .lines()
+            .filter(|line| !line.starts_with("-----"))
+            .collect();
+        let der = STANDARD.decode(pem_body).unwrap();
+        assert_eq!(certificate_fingerprint(&der), SELF_SIGNED_FINGERPRINT);
+
+        let pinned = vec!["00".repeat(32)];
+        match verify_pinned_certificate(&der, &pinned) {
+            Err(PocketError::CertificatePinningFailure(actual)) => {
+                assert_eq!(actual, SELF_SIGNED_FINGERPRINT)
+            }
+            other => panic!("Expected a pinning failure, got {other:?}"),
+        }
+
+        // Uppercase fingerprints with separators are accepted too
+        let colons = SELF_SIGNED_FINGERPRINT
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| std::str::from_utf8(pair).unwrap().to_uppercase())
+            .collect::<Vec<_>>()
+            .join(":");
+        assert!(verify_pinned_certificate(&der, &[colons]).is_ok());
+    }
+
+    fn deal(close: i64, command: i32, profit: f64) -> Deal {
+        Deal {
+            open_timestamp: DateTime::from_timestamp(close - 60, 0).unwrap(),
+            close_timestamp: DateTime::from_timestamp(close, 0).unwrap(),
+            command,
+            ..Deal::mock(profit)
+        }
+    }
+
+    #[test]
+    fn test_write_deals_csv() {
+        let deals = vec![deal(2000, 1, -1.0), deal(1000, 0, 0.92)];
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let rows = write_deals_csv(file.as_file_mut(), deals.clone()).unwrap();
+        assert_eq!(rows, 2);
+
+        let content = std::fs::read_to_string(file.path()).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "id,asset,action,amount,open_price,close_price,open_time,close_time,result,pnl"
+        );
+        assert!(lines[1].starts_with(&format!("{},EURUSD_otc,call,", deals[1].id)));
+        assert!(lines[1].ends_with(",win,0.92"));
+        assert!(lines[2].starts_with(&format!("{},EURUSD_otc,put,", deals[0].id)));
+        assert!(lines[2].ends_with(",loss,-1"));
+    }
+
+    #[tokio::test]
+    async fn test_connection_timeout() {
+        // Accepts the TCP connections but never answers the handshake
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                sockets.push(socket);
+            }
+        });
+        let timeout = Duration::from_millis(200);
+        let headers = HashMap::new();
+
+        let start = Instant::now();
+        let result = try_connect(url.clone(), &headers, &[], None, timeout).await;
+        assert!(matches!(result, Err(ConnectorError::Timeout)));
+        assert!(start.elapsed() < timeout * 5);
+
+        let start = Instant::now();
+        let result = fetch_certificate_fingerprint(url, &headers, None, timeout).await;
+        assert!(matches!(result, Err(PocketError::ConnectionTimeout(t)) if t == timeout));
+        assert!(start.elapsed() < timeout * 5);
+        server.abort();
+    }
+}
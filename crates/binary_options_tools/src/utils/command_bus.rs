@@ -0,0 +1,340 @@
+//! Request/response helper for the API module handles.
+//!
+//! Every command sent to a module is wrapped in a [`Command`] with a random id, the module answers
+//! with a [`Command`] carrying the same id. [`CommandBus`] hides the matching loop: responses that
+//! belong to another request (sent from a clone of the same handle) are kept until their owner
+//! asks for them instead of being dropped. Responses arriving after their caller stopped waiting,
+//! e.g. on timeout, are discarded.
+//!
+//! Commands also carry the tracing span they were created in, the modules process them inside of
+//! it so their events are attached to the request of the caller.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use binary_options_tools_core_pre::{
+    error::{CoreError, CoreResult},
+    reimports::{AsyncReceiver, AsyncSender},
+};
+use tokio::sync::{Mutex as AsyncMutex, Notify};
+use tracing::{Span, debug};
+use uuid::Uuid;
+
+/// Data sent to or received from a module, tagged with the id of the request.
+#[derive(Debug)]
+pub struct Command<T> {
+    id: Uuid,
+    data: T,
+    span: Span,
+}
+
+impl<T> Command<T> {
+    /// Creates a command with a new id in the current span.
+    pub fn new(data: T) -> (Uuid, Self) {
+        let id = Uuid::new_v4();
+        (id, Self::from_id(id, data, None))
+    }
+
+    /// Creates a command with the given id, in the current span if `span` is `None`.
+    pub fn from_id(id: Uuid, data: T, span: Option<Span>) -> Self {
+        Command {
+            id,
+            data,
+            span: span.unwrap_or_else(Span::current),
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Span the command was created in.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn into_data(self) -> T {
+        self.data
+    }
+}
+
+/// Sends commands to a module and waits for the response with the same id.
+pub struct CommandBus<Cmd, Resp> {
+    sender: AsyncSender<Command<Cmd>>,
+    receiver: AsyncReceiver<Command<Resp>>,
+    /// Requests still waiting for their response, with the response once it was received while
+    /// waiting for another request. Shared by all the clones
+    pending: Arc<Mutex<HashMap<Uuid, Option<Resp>>>>,
+    unclaimed_notify: Arc<Notify>,
+    /// Held by the waiter currently reading the responses
+    reading: Arc<AsyncMutex<()>>,
+    timeout: Option<Duration>,
+    cancel: Option<fn(Uuid) -> Cmd>,
+}
+
+impl<Cmd, Resp> Clone for CommandBus<Cmd, Resp> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+            pending: self.pending.clone(),
+            unclaimed_notify: self.unclaimed_notify.clone(),
+            reading: self.reading.clone(),
+            timeout: self.timeout,
+            cancel: self.cancel,
+        }
+    }
+}
+
+impl<Cmd, Resp> CommandBus<Cmd, Resp> {
+    /// Creates a bus without timeout, `send` waits until the module answers.
+    pub fn new(sender: AsyncSender<Command<Cmd>>, receiver: AsyncReceiver<Command<Resp>>) -> Self {
+        Self {
+            sender,
+            receiver,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            unclaimed_notify: Arc::new(Notify::new()),
+            reading: Arc::new(AsyncMutex::new(())),
+            timeout: None,
+            cancel: None,
+        }
+    }
+
+    /// Fails the requests that are not answered within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sends `cancel(id)` to the module when the caller of the request `id` stops waiting before
+    /// the response, so the module can forget the request. The module must not answer it.
+    pub fn with_cancel(mut self, cancel: fn(Uuid) -> Cmd) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Sends `cmd` and returns the matching response.
+    pub async fn send(&self, cmd: Cmd) -> CoreResult<Resp> {
+        self.request(cmd).await.map(|(_, response)| response)
+    }
+
+    /// Sends `cmd` and returns the id given to the request with the matching response.
+    pub async fn request(&self, cmd: Cmd) -> CoreResult<(Uuid, Resp)> {
+        let (id, cmd) = Command::new(cmd);
+        let mut pending = PendingRequest::new(self, id)?;
+        self.sender.send(cmd).await?;
+        let response = match self.timeout {
+            Some(duration) => tokio::time::timeout(duration, self.wait_for(id))
+                .await
+                .map_err(|_| CoreError::TimeoutError {
+                    task: std::any::type_name::<Cmd>().to_string(),
+                    duration,
+                })??,
+            None => self.wait_for(id).await?,
+        };
+        pending.answered = true;
+        Ok((id, response))
+    }
+
+    async fn wait_for(&self, id: Uuid) -> CoreResult<Resp> {
+        loop {
+            let notified = self.unclaimed_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if let Some(response) = self.take_unclaimed(id)? {
+                return Ok(response);
+            }
+            // A single waiter reads the channel at a time, the receive future is never cancelled
+            // by another branch so no response can be lost
+            let result = {
+                let _reading = match self.reading.try_lock() {
+                    Ok(reading) => reading,
+                    // Either a response was stashed or the reader is done, possibly because its
+                    // request timed out without notifying
+                    Err(_) => tokio::select! {
+                        _ = &mut notified => continue,
+                        reading = self.reading.lock() => reading,
+                    },
+                };
+                // Responses are stashed while holding the lock, the previous reader may have
+                // stashed this one after the check above
+                if let Some(response) = self.take_unclaimed(id)? {
+                    return Ok(response);
+                }
+                match self.receiver.recv().await {
+                    Ok(response) if response.id() == id => Some(Ok(response.into_data())),
+                    Ok(response) => {
+                        let response_id = response.id();
+                        match self
+                            .pending
+                            .lock()
+                            .map_err(|e| CoreError::Poison(e.to_string()))?
+                            .get_mut(&response_id)
+                        {
+                            Some(slot) => *slot = Some(response.into_data()),
+                            None => debug!(
+                                target: "CommandBus",
+                                "Discarding the response of the abandoned request {response_id}"
+                            ),
+                        }
+                        None
+                    }
+                    Err(e) => Some(self.take_unclaimed(id)?.ok_or_else(|| e.into())),
+                }
+            };
+            // Wakes up the other waiters, either to claim their response or to start reading
+            self.unclaimed_notify.notify_waiters();
+            if let Some(result) = result {
+                return result;
+            }
+        }
+    }
+
+    fn take_unclaimed(&self, id: Uuid) -> CoreResult<Option<Resp>> {
+        Ok(self
+            .pending
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))?
+            .get_mut(&id)
+            .and_then(Option::take))
+    }
+}
+
+/// Registers a request as pending for as long as its caller waits for the response.
+///
+/// Dropping it, once answered, on timeout or when the caller drops the future, removes the request
+/// and its stashed response from the bus.
+struct PendingRequest<'a, Cmd, Resp> {
+    bus: &'a CommandBus<Cmd, Resp>,
+    id: Uuid,
+    answered: bool,
+}
+
+impl<'a, Cmd, Resp> PendingRequest<'a, Cmd, Resp> {
+    fn new(bus: &'a CommandBus<Cmd, Resp>, id: Uuid) -> CoreResult<Self> {
+        bus.pending
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))?
+            .insert(id, None);
+        Ok(Self {
+            bus,
+            id,
+            answered: false,
+        })
+    }
+}
+
+impl<Cmd, Resp> Drop for PendingRequest<'_, Cmd, Resp> {
+    fn drop(&mut self) {
+        if let Ok(mut pending) = self.bus.pending.lock() {
+            pending.remove(&self.id);
+        }
+        if !self.answered
+            && let Some(cancel) = self.bus.cancel
+        {
+            // Best effort, the module only loses the chance to clean up early
+            let _ =
+                self.bus
+                    .sender
+                    .try_send(Command::from_id(Uuid::new_v4(), cancel(self.id), None));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use binary_options_tools_core_pre::reimports::bounded_async;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_out_of_order_responses() {
+        let (cmd_sender, cmd_receiver) = bounded_async::<Command<u32>>(8);
+        let (resp_sender, resp_receiver) = bounded_async(8);
+        let bus = CommandBus::new(cmd_sender, resp_receiver);
+
+        // Answers both requests once they arrived, in reverse order
+        let module = tokio::spawn(async move {
+            let first = cmd_receiver.recv().await.unwrap();
+            let second = cmd_receiver.recv().await.unwrap();
+            for cmd in [second, first] {
+                let response = Command::from_id(cmd.id(), cmd.data() * 10, None);
+                resp_sender.send(response).await.unwrap();
+            }
+        });
+
+        let other = bus.clone();
+        let (a, b) = tokio::join!(bus.send(1), other.send(2));
+        assert_eq!(a.unwrap(), 10);
+        assert_eq!(b.unwrap(), 20);
+        module.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_many_concurrent_requests() {
+        let (cmd_sender, cmd_receiver) = bounded_async::<Command<u32>>(16);
+        let (resp_sender, resp_receiver) = bounded_async(16);
+        let bus = CommandBus::new(cmd_sender, resp_receiver);
+
+        // Answers all the requests at once, in reverse order
+        let module = tokio::spawn(async move {
+            let mut commands = Vec::new();
+            for _ in 0..8 {
+                commands.push(cmd_receiver.recv().await.unwrap());
+            }
+            for cmd in commands.into_iter().rev() {
+                let response = Command::from_id(cmd.id(), cmd.data() * 10, None);
+                resp_sender.send(response).await.unwrap();
+            }
+        });
+
+        let requests = (0..8).map(|i| {
+            let bus = bus.clone();
+            tokio::spawn(async move { bus.send(i).await })
+        });
+        for (i, request) in requests.collect::<Vec<_>>().into_iter().enumerate() {
+            let response = tokio::time::timeout(Duration::from_secs(5), request)
+                .await
+                .expect("A response was lost")
+                .unwrap();
+            assert_eq!(response.unwrap(), i as u32 * 10);
+        }
+        module.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let (cmd_sender, cmd_receiver) = bounded_async::<Command<u32>>(8);
+        let (resp_sender, resp_receiver) = bounded_async::<Command<u32>>(8);
+        let bus = CommandBus::new(cmd_sender, resp_receiver)
+            .with_timeout(Duration::from_millis(50))
+            .with_cancel(|_| 0);
+        assert!(matches!(
+            bus.send(1).await,
+            Err(CoreError::TimeoutError { .. })
+        ));
+        // The module is told the caller gave up
+        let request = cmd_receiver.recv().await.unwrap();
+        assert_eq!(*request.data(), 1);
+        assert_eq!(*cmd_receiver.recv().await.unwrap().data(), 0);
+
+        // The late response isn't kept once read by the next request
+        resp_sender
+            .send(Command::from_id(request.id(), 10, None))
+            .await
+            .unwrap();
+        let module = tokio::spawn(async move {
+            let cmd = cmd_receiver.recv().await.unwrap();
+            let response = Command::from_id(cmd.id(), cmd.data() * 10, None);
+            resp_sender.send(response).await.unwrap();
+        });
+        assert_eq!(bus.send(2).await.unwrap(), 20);
+        assert!(bus.pending.lock().unwrap().is_empty());
+        module.await.unwrap();
+    }
+}
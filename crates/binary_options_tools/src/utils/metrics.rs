@@ -0,0 +1,250 @@
+//! Prometheus metrics for the WebSocket client.
+//!
+//! [`PrometheusMiddleware`] registers its collectors on a user provided registry and keeps them
+//! updated from the middleware hooks. Only available with the `prometheus` feature.
+
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+use std::time::Instant;
+
+use binary_options_tools_core_pre::{
+    error::{CoreError, CoreResult},
+    middleware::{MiddlewareContext, WebSocketMiddleware},
+    reimports::Message,
+    traits::AppState,
+};
+use prometheus::{Encoder, Histogram, HistogramOpts, IntCounter, Registry, TextEncoder};
+
+/// Middleware exporting connection, message and latency metrics to a Prometheus registry.
+///
+/// Registered metrics:
+/// - `ws_messages_sent_total`
+/// - `ws_messages_received_total`
+/// - `ws_reconnections_total`
+/// - `ws_errors_total`
+/// - `ws_connection_duration_seconds` (histogram)
+pub struct PrometheusMiddleware<S: AppState> {
+    registry: Arc<Registry>,
+    messages_sent: IntCounter,
+    messages_received: IntCounter,
+    reconnections: IntCounter,
+    errors: IntCounter,
+    connection_duration: Histogram,
+    connected_at: Mutex<Option<Instant>>,
+    has_connected: AtomicBool,
+    _phantom: std::marker::PhantomData<S>,
+}
+
+impl<S: AppState> PrometheusMiddleware<S> {
+    /// Creates the middleware and registers its metrics on `registry`.
+    pub fn new(registry: Arc<Registry>) -> CoreResult<Self> {
+        let messages_sent = IntCounter::new(
+            "ws_messages_sent_total",
+            "Total number of messages sent to the websocket",
+        )
+        .map_err(prometheus_error)?;
+        let messages_received = IntCounter::new(
+            "ws_messages_received_total",
+            "Total number of messages received from the websocket",
+        )
+        .map_err(prometheus_error)?;
+        let reconnections = IntCounter::new(
+            "ws_reconnections_total",
+            "Total number of reconnections to the websocket",
+        )
+        .map_err(prometheus_error)?;
+        let errors = IntCounter::new("ws_errors_total", "Total number of connection errors")
+            .map_err(prometheus_error)?;
+        let connection_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "ws_connection_duration_seconds",
+                "Duration of each websocket connection in seconds",
+            )
+            .buckets(vec![
+                1.0, 10.0, 60.0, 300.0, 900.0, 1800.0, 3600.0, 21600.0, 86400.0,
+            ]),
+        )
+        .map_err(prometheus_error)?;
+
+        registry
+            .register(Box::new(messages_sent.clone()))
+            .map_err(prometheus_error)?;
+        registry
+            .register(Box::new(messages_received.clone()))
+            .map_err(prometheus_error)?;
+        registry
+            .register(Box::new(reconnections.clone()))
+            .map_err(prometheus_error)?;
+        registry
+            .register(Box::new(errors.clone()))
+            .map_err(prometheus_error)?;
+        registry
+            .register(Box::new(connection_duration.clone()))
+            .map_err(prometheus_error)?;
+
+        Ok(Self {
+            registry,
+            messages_sent,
+            messages_received,
+            reconnections,
+            errors,
+            connection_duration,
+            connected_at: Mutex::new(None),
+            has_connected: AtomicBool::new(false),
+            _phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// Returns a handler that serializes the registry in the Prometheus text format.
+    ///
+    /// The handler returns the `Content-Type` header value and the body, so it can be
+    /// plugged into any HTTP server (warp, axum, hyper...) to expose a `/metrics` endpoint.
+    pub fn metrics_handler(
+        &self,
+    ) -> impl Fn() -> CoreResult<(String, String)> + Clone + Send + Sync + 'static {
+        let registry = self.registry.clone();
+        move || {
+            let encoder = TextEncoder::new();
+            let mut buffer = Vec::new();
+            encoder
+                .encode(&registry.gather(), &mut buffer)
+                .map_err(prometheus_error)?;
+            let body = String::from_utf8(buffer).map_err(|e| CoreError::Other(e.to_string()))?;
+            Ok((encoder.format_type().to_string(), body))
+        }
+    }
+
+    fn finish_connection(&self) -> CoreResult<()> {
+        let connected_at = self
+            .connected_at
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))?
+            .take();
+        if let Some(start) = connected_at {
+            self.connection_duration
+                .observe(start.elapsed().as_secs_f64());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: AppState> WebSocketMiddleware<S> for PrometheusMiddleware<S> {
+    async fn on_send(&self, _message: &Message, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        self.messages_sent.inc();
+        Ok(())
+    }
+
+    async fn on_receive(
+        &self,
+        _message: &Message,
+        _context: &MiddlewareContext<S>,
+    ) -> CoreResult<()> {
+        self.messages_received.inc();
+        Ok(())
+    }
+
+    async fn on_connect(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        if self.has_connected.swap(true, Ordering::SeqCst) {
+            self.reconnections.inc();
+        }
+        self.finish_connection()?;
+        *self
+            .connected_at
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))? = Some(Instant::now());
+        Ok(())
+    }
+
+    async fn on_disconnect(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        self.finish_connection()
+    }
+
+    async fn on_connection_failure(
+        &self,
+        _context: &MiddlewareContext<S>,
+        _reason: Option<String>,
+    ) -> CoreResult<()> {
+        self.errors.inc();
+        Ok(())
+    }
+}
+
+fn prometheus_error(error: impl std::fmt::Display) -> CoreError {
+    CoreError::Other(format!("Prometheus error: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use binary_options_tools_core_pre::{
+        middleware::{MiddlewareContext, WebSocketMiddleware},
+        reimports::{Message, bounded_async},
+    };
+    use prometheus::Registry;
+
+    use super::PrometheusMiddleware;
+    use crate::pocketoption::{ssid::Ssid, state::StateBuilder};
+
+    fn metric_value(registry: &Registry, name: &str) -> f64 {
+        let family = registry
+            .gather()
+            .into_iter()
+            .find(|f| f.name() == name)
+            .unwrap();
+        let metric = &family.get_metric()[0];
+        if name.ends_with("_seconds") {
+            metric.get_histogram().get_sample_count() as f64
+        } else {
+            metric.get_counter().value()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_middleware_counters() {
+        let registry = Arc::new(Registry::new());
+        let middleware = PrometheusMiddleware::new(registry.clone()).unwrap();
+
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        let state = Arc::new(StateBuilder::default().ssid(ssid).build().unwrap());
+        let (sender, _receiver) = bounded_async(8);
+        let context = MiddlewareContext::new(state, sender);
+
+        middleware.on_connect(&context).await.unwrap();
+        middleware
+            .on_send(&Message::text("42[\"ps\"]"), &context)
+            .await
+            .unwrap();
+        middleware
+            .on_receive(&Message::text("2"), &context)
+            .await
+            .unwrap();
+        middleware
+            .on_receive(&Message::text("2"), &context)
+            .await
+            .unwrap();
+        middleware.on_disconnect(&context).await.unwrap();
+        middleware
+            .on_connection_failure(&context, Some("refused".into()))
+            .await
+            .unwrap();
+        middleware.on_connect(&context).await.unwrap();
+
+        assert_eq!(metric_value(&registry, "ws_messages_sent_total"), 1.0);
+        assert_eq!(metric_value(&registry, "ws_messages_received_total"), 2.0);
+        assert_eq!(metric_value(&registry, "ws_reconnections_total"), 1.0);
+        assert_eq!(metric_value(&registry, "ws_errors_total"), 1.0);
+        assert_eq!(
+            metric_value(&registry, "ws_connection_duration_seconds"),
+            1.0
+        );
+
+        let (content_type, body) = middleware.metrics_handler()().unwrap();
+        assert!(content_type.starts_with("text/plain"));
+        assert!(body.contains("ws_messages_sent_total 1"));
+    }
+}
@@ -7,8 +7,15 @@ use binary_options_tools_core_pre::{
     traits::AppState,
 };
 
+pub mod command_bus;
+pub mod recorder;
 pub mod serialize;
 
+#[cfg(feature = "prometheus")]
+pub mod metrics;
+#[cfg(feature = "otel")]
+pub mod otel;
+
 /// Lightweight message printer for debugging purposes
 ///
 /// This handler logs all incoming WebSocket messages for debugging
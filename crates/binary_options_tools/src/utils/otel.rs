@@ -0,0 +1,163 @@
+//! OpenTelemetry integration for the WebSocket client.
+//!
+//! [`OtelMiddleware`] opens a `websocket.connect` span for every connection and records a
+//! `websocket.send` child span for each outgoing message, so trade requests can be correlated
+//! with the session they were sent on. Only available with the `otel` feature.
+
+use std::sync::{Arc, Mutex};
+
+use binary_options_tools_core_pre::{
+    builder::ClientBuilder,
+    error::{CoreError, CoreResult},
+    middleware::{MiddlewareContext, WebSocketMiddleware},
+    reimports::Message,
+    traits::AppState,
+};
+use opentelemetry::{
+    Context, KeyValue,
+    global::BoxedTracer,
+    trace::{Span, TraceContextExt, Tracer},
+};
+
+/// Middleware exporting OpenTelemetry spans for the connection lifecycle and outgoing messages.
+pub struct OtelMiddleware<S: AppState> {
+    tracer: Arc<BoxedTracer>,
+    /// Context holding the `websocket.connect` span of the current connection.
+    connection: Mutex<Option<Context>>,
+    _phantom: std::marker::PhantomData<S>,
+}
+
+impl<S: AppState> OtelMiddleware<S> {
+    pub fn new(tracer: Arc<BoxedTracer>) -> Self {
+        Self {
+            tracer,
+            connection: Mutex::new(None),
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    fn end_connection_span(&self) -> CoreResult<()> {
+        let connection = self
+            .connection
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))?
+            .take();
+        if let Some(cx) = connection {
+            cx.span().end();
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: AppState> WebSocketMiddleware<S> for OtelMiddleware<S> {
+    async fn on_connect(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        // A reconnection without a disconnect event still closes the previous span.
+        self.end_connection_span()?;
+        let span = self.tracer.start("websocket.connect");
+        *self
+            .connection
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))? =
+            Some(Context::current_with_span(span));
+        Ok(())
+    }
+
+    async fn on_send(&self, message: &Message, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        let parent = self
+            .connection
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))?
+            .clone()
+            .unwrap_or_default();
+        let mut span = self.tracer.start_with_context("websocket.send", &parent);
+        span.set_attribute(KeyValue::new("message.size", message_size(message) as i64));
+        span.end();
+        Ok(())
+    }
+
+    async fn on_disconnect(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        self.end_connection_span()
+    }
+}
+
+/// Extension trait to register the [`OtelMiddleware`] on a [`ClientBuilder`].
+pub trait OtelClientBuilderExt {
+    /// Adds an [`OtelMiddleware`] using `tracer` to the builder.
+    fn with_otel_middleware(self, tracer: Arc<BoxedTracer>) -> Self;
+}
+
+impl<S: AppState> OtelClientBuilderExt for ClientBuilder<S> {
+    fn with_otel_middleware(self, tracer: Arc<BoxedTracer>) -> Self {
+        self.with_middleware(Box::new(OtelMiddleware::new(tracer)))
+    }
+}
+
+fn message_size(message: &Message) -> usize {
+    match message {
+        Message::Text(text) => text.len(),
+        Message::Binary(data) => data.len(),
+        Message::Ping(data) => data.len(),
+        Message::Pong(data) => data.len(),
+        Message::Close(_) => 0,
+        Message::Frame(frame) => frame.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use binary_options_tools_core_pre::{
+        middleware::{MiddlewareContext, WebSocketMiddleware},
+        reimports::{Message, bounded_async},
+    };
+    use opentelemetry::{
+        Value,
+        global::BoxedTracer,
+        trace::{SpanId, TracerProvider},
+    };
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+
+    use super::OtelMiddleware;
+    use crate::pocketoption::{ssid::Ssid, state::StateBuilder};
+
+    #[tokio::test]
+    async fn test_otel_middleware_spans() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let tracer = Arc::new(BoxedTracer::new(Box::new(provider.tracer("test"))));
+        let middleware = OtelMiddleware::new(tracer);
+
+        let ssid = Ssid::parse(r#"42["auth",{"session":"mock","isDemo":1,"uid":1,"platform":2}]"#)
+            .unwrap();
+        let state = Arc::new(StateBuilder::default().ssid(ssid).build().unwrap());
+        let (sender, _receiver) = bounded_async(8);
+        let context = MiddlewareContext::new(state, sender);
+
+        middleware.on_connect(&context).await.unwrap();
+        middleware
+            .on_send(&Message::text("42[\"ps\"]"), &context)
+            .await
+            .unwrap();
+        middleware.on_disconnect(&context).await.unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 2);
+        let send = spans.iter().find(|s| s.name == "websocket.send").unwrap();
+        let connect = spans
+            .iter()
+            .find(|s| s.name == "websocket.connect")
+            .unwrap();
+        assert_eq!(connect.parent_span_id, SpanId::INVALID);
+        assert_eq!(send.parent_span_id, connect.span_context.span_id());
+        let size = send
+            .attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == "message.size")
+            .unwrap();
+        assert_eq!(size.value, Value::I64(8));
+    }
+}
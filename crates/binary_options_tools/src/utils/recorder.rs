@@ -0,0 +1,338 @@
+//! Recording and replaying of WebSocket sessions.
+//!
+//! [`MessageRecorder`] writes every message going through the client as a JSON line
+//! (`{"direction":"IN","timestamp":..,"kind":"text","data":..}`) and [`MessageReplayer`]
+//! reads those files back, which is useful to debug live trading issues offline.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use binary_options_tools_core_pre::{
+    error::{CoreError, CoreResult},
+    middleware::{MiddlewareContext, WebSocketMiddleware},
+    reimports::Message,
+    traits::AppState,
+};
+use chrono::Utc;
+use futures_util::{Stream, stream::unfold};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tracing::warn;
+
+/// Direction of a recorded message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    /// Received from the server
+    #[serde(rename = "IN")]
+    In,
+    /// Sent to the server
+    #[serde(rename = "OUT")]
+    Out,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "lowercase")]
+enum RecordedPayload {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    Close,
+}
+
+/// A single line of a recording.
+#[derive(Serialize, Deserialize)]
+struct RecordedMessage {
+    direction: Direction,
+    /// Unix timestamp in milliseconds
+    timestamp: i64,
+    #[serde(flatten)]
+    payload: RecordedPayload,
+}
+
+impl RecordedPayload {
+    fn from_message(message: &Message) -> Option<Self> {
+        match message {
+            Message::Text(text) => Some(Self::Text(text.to_string())),
+            Message::Binary(data) => Some(Self::Binary(data.to_vec())),
+            Message::Ping(data) => Some(Self::Ping(data.to_vec())),
+            Message::Pong(data) => Some(Self::Pong(data.to_vec())),
+            Message::Close(_) => Some(Self::Close),
+            // Raw frames are never produced when reading messages
+            Message::Frame(_) => None,
+        }
+    }
+
+    fn into_message(self) -> Message {
+        match self {
+            Self::Text(text) => Message::text(text),
+            Self::Binary(data) => Message::binary(data),
+            Self::Ping(data) => Message::Ping(data.into()),
+            Self::Pong(data) => Message::Pong(data.into()),
+            Self::Close => Message::Close(None),
+        }
+    }
+}
+
+/// Where the recordings are written.
+enum Target {
+    /// Always append to the same file
+    File(PathBuf),
+    /// Create a new file in `dir` every time the current one exceeds `max_size` bytes
+    Rotated { dir: PathBuf, max_size: u64 },
+}
+
+struct RecorderInner {
+    target: Target,
+    writer: Option<BufWriter<File>>,
+    /// Bytes written to the current file
+    written: u64,
+    /// Number of files created by a rotated recorder
+    files: u64,
+}
+
+impl RecorderInner {
+    fn open(&mut self) -> CoreResult<&mut BufWriter<File>> {
+        if let Target::Rotated { max_size, .. } = self.target
+            && self.written >= max_size
+            && let Some(mut writer) = self.writer.take()
+        {
+            writer.flush()?;
+        }
+        if self.writer.is_none() {
+            let path = match &self.target {
+                Target::File(path) => path.clone(),
+                Target::Rotated { dir, .. } => {
+                    self.files += 1;
+                    dir.join(format!(
+                        "session-{}-{}.jsonl",
+                        Utc::now().timestamp_millis(),
+                        self.files
+                    ))
+                }
+            };
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            self.written = file.metadata()?.len();
+            self.writer = Some(BufWriter::new(file));
+        }
+        // The writer was just set if it was missing
+        self.writer
+            .as_mut()
+            .ok_or_else(|| CoreError::Other("Recorder file is not open".into()))
+    }
+
+    fn write(&mut self, record: &RecordedMessage) -> CoreResult<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        self.open()?.write_all(&line)?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    fn close(&mut self) -> CoreResult<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Middleware saving every incoming and outgoing message to a file for later replay.
+///
+/// The file is flushed and closed on disconnection and opened again when the next message arrives.
+pub struct MessageRecorder {
+    inner: Mutex<RecorderInner>,
+}
+
+impl MessageRecorder {
+    /// Records all the messages to `path`, appending to it if it already exists.
+    pub fn new(path: PathBuf) -> CoreResult<Self> {
+        Ok(Self::with_target(Target::File(path)))
+    }
+
+    /// Records the messages to files inside `dir`, starting a new file once the current one
+    /// exceeds `max_size_mb` megabytes.
+    pub fn new_rotated(dir: PathBuf, max_size_mb: u64) -> CoreResult<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self::with_target(Target::Rotated {
+            dir,
+            max_size: max_size_mb * 1024 * 1024,
+        }))
+    }
+
+    fn with_target(target: Target) -> Self {
+        Self {
+            inner: Mutex::new(RecorderInner {
+                target,
+                writer: None,
+                written: 0,
+                files: 0,
+            }),
+        }
+    }
+
+    fn record(&self, direction: Direction, message: &Message) -> CoreResult<()> {
+        let Some(payload) = RecordedPayload::from_message(message) else {
+            return Ok(());
+        };
+        let record = RecordedMessage {
+            direction,
+            timestamp: Utc::now().timestamp_millis(),
+            payload,
+        };
+        self.inner
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))?
+            .write(&record)
+    }
+
+    /// Flushes and closes the current file.
+    pub fn close(&self) -> CoreResult<()> {
+        self.inner
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))?
+            .close()
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: AppState> WebSocketMiddleware<S> for MessageRecorder {
+    async fn on_send(&self, message: &Message, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        self.record(Direction::Out, message)
+    }
+
+    async fn on_receive(
+        &self,
+        message: &Message,
+        _context: &MiddlewareContext<S>,
+    ) -> CoreResult<()> {
+        self.record(Direction::In, message)
+    }
+
+    async fn on_disconnect(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        self.close()
+    }
+}
+
+impl Drop for MessageRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            warn!(target: "MessageRecorder", "Failed to flush recording: {e}");
+        }
+    }
+}
+
+/// Loads sessions saved by [`MessageRecorder`].
+pub struct MessageReplayer;
+
+impl MessageReplayer {
+    /// Returns a stream with the messages recorded in `path`, in the order they were recorded.
+    ///
+    /// Lines that can't be parsed are skipped.
+    pub async fn from_file(
+        path: impl AsRef<Path>,
+    ) -> CoreResult<impl Stream<Item = (Direction, Message)> + Send + 'static> {
+        let file = tokio::fs::File::open(path).await?;
+        let lines = BufReader::new(file).lines();
+        Ok(unfold(lines, |mut lines| async move {
+            loop {
+                match lines.next_line().await {
+                    Ok(Some(line)) if line.trim().is_empty() => continue,
+                    Ok(Some(line)) => match serde_json::from_str::<RecordedMessage>(&line) {
+                        Ok(record) => {
+                            return Some((
+                                (record.direction, record.payload.into_message()),
+                                lines,
+                            ));
+                        }
+                        Err(e) => {
+                            warn!(target: "MessageReplayer", "Skipping invalid recorded message: {e}");
+                        }
+                    },
+                    Ok(None) => return None,
+                    Err(e) => {
+                        warn!(target: "MessageReplayer", "Failed to read recording: {e}");
+                        return None;
+                    }
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use binary_options_tools_core_pre::reimports::bounded_async;
+    use futures_util::StreamExt;
+
+    use super::*;
+
+    struct TestState;
+
+    #[async_trait::async_trait]
+    impl AppState for TestState {
+        async fn clear_temporal_data(&self) {}
+    }
+
+    fn context() -> MiddlewareContext<TestState> {
+        let (sender, _) = bounded_async(1);
+        MiddlewareContext::new(Arc::new(TestState), sender)
+    }
+
+    fn message(i: usize) -> Message {
+        if i.is_multiple_of(2) {
+            Message::text(format!("42[\"message\",{i}]"))
+        } else {
+            Message::binary(format!("{{\"index\":{i}}}").into_bytes())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let recorder = MessageRecorder::new(path.clone()).unwrap();
+        let context = context();
+        for i in 0..10 {
+            if i % 3 == 0 {
+                recorder.on_send(&message(i), &context).await.unwrap();
+            } else {
+                recorder.on_receive(&message(i), &context).await.unwrap();
+            }
+        }
+        recorder.on_disconnect(&context).await.unwrap();
+
+        let replayed: Vec<_> = MessageReplayer::from_file(&path)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(replayed.len(), 10);
+        for (i, (direction, msg)) in replayed.into_iter().enumerate() {
+            let expected = if i % 3 == 0 {
+                Direction::Out
+            } else {
+                Direction::In
+            };
+            assert_eq!(direction, expected);
+            assert_eq!(msg, message(i));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rotated_recorder() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = MessageRecorder::new_rotated(dir.path().to_path_buf(), 0).unwrap();
+        let context = context();
+        for i in 0..3 {
+            recorder.on_receive(&message(i), &context).await.unwrap();
+        }
+        recorder.close().unwrap();
+        // With a limit of 0 bytes every message goes to its own file.
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+}
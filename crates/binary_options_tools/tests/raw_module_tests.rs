@@ -146,11 +146,13 @@ mod tests {
         let command_id = Uuid::new_v4();
         let id = Uuid::new_v4();
         let (_tx, rx) = bounded_async(10);
+        let (fan_out, _) = tokio::sync::broadcast::channel(10);
 
         let created_resp = CommandResponse::Created {
             command_id,
             id,
             stream_receiver: rx,
+            fan_out,
         };
 
         let removed_resp = CommandResponse::Removed {
@@ -164,6 +166,7 @@ mod tests {
                 command_id: cid,
                 id: i,
                 stream_receiver: _,
+                fan_out: _,
             } => {
                 assert_eq!(cid, command_id);
                 assert_eq!(i, id);
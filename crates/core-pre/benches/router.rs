@@ -0,0 +1,119 @@
+//! Benchmarks of the message routing overhead.
+//!
+//! Run with `cargo bench --bench router`.
+
+use std::hint::black_box;
+use std::sync::Arc;
+
+use binary_options_tools_core_pre::client::Router;
+use binary_options_tools_core_pre::statistics::StatisticsTracker;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::runtime::Runtime;
+use tokio_tungstenite::tungstenite::Message;
+
+const MESSAGES: usize = 1000;
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build the tokio runtime")
+}
+
+fn event(rule: usize) -> String {
+    format!("42[\"event_{rule}\"")
+}
+
+// Builds a router with `rules` lightweight rules, each matching the messages starting with its
+// own event name, the messages matched are drained by tasks of the runtime.
+fn router(runtime: &Runtime, rules: usize) -> Router<()> {
+    let mut router = Router::new(Arc::new(()));
+    for rule in 0..rules {
+        let prefix = event(rule);
+        let (tx, rx) = kanal::unbounded_async();
+        router.add_lightweight_rule(
+            format!("rule_{rule}"),
+            Box::new(move |msg: &Message| {
+                matches!(msg, Message::Text(text) if text.starts_with(&prefix))
+            }),
+            tx,
+        );
+        runtime.spawn(async move { while rx.recv().await.is_ok() {} });
+    }
+    router
+}
+
+// Half of the messages match one of the `rules` rules, the other half match none of them.
+fn messages(rules: usize) -> Vec<Arc<Message>> {
+    let mut rng = StdRng::seed_from_u64(42);
+    (0..MESSAGES)
+        .map(|_| {
+            let price: f64 = rng.random_range(1.0..2.0);
+            let text = if rng.random_bool(0.5) {
+                format!(
+                    "{},{{\"price\":{price}}}]",
+                    event(rng.random_range(0..rules))
+                )
+            } else {
+                format!("42[\"unknown\",{{\"price\":{price}}}]")
+            };
+            Arc::new(Message::text(text))
+        })
+        .collect()
+}
+
+fn bench_route(c: &mut Criterion) {
+    let runtime = runtime();
+    let (sender, _receiver) = kanal::unbounded_async();
+    let mut group = c.benchmark_group("router_route");
+    group.throughput(Throughput::Elements(MESSAGES as u64));
+    for rules in [1, 10, 50] {
+        let router = router(&runtime, rules);
+        let messages = messages(rules);
+        group.bench_with_input(BenchmarkId::from_parameter(rules), &rules, |b, _| {
+            b.to_async(&runtime).iter(|| async {
+                for message in &messages {
+                    router
+                        .route(black_box(Arc::clone(message)), &sender)
+                        .await
+                        .expect("Failed to route the message");
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_record_message_sent(c: &mut Criterion) {
+    let runtime = runtime();
+    let message = Message::text("42[\"event_0\",{\"price\":1.5}]");
+    let mut group = c.benchmark_group("statistics_record_message_sent");
+    group.throughput(Throughput::Elements(MESSAGES as u64));
+    for tasks in [1, 4, 10] {
+        let stats = Arc::new(StatisticsTracker::new());
+        group.bench_with_input(BenchmarkId::from_parameter(tasks), &tasks, |b, &tasks| {
+            b.to_async(&runtime).iter(|| async {
+                let handles: Vec<_> = (0..tasks)
+                    .map(|_| {
+                        let stats = Arc::clone(&stats);
+                        let message = message.clone();
+                        tokio::spawn(async move {
+                            for _ in 0..MESSAGES / tasks {
+                                stats.record_message_sent(black_box(&message)).await;
+                            }
+                        })
+                    })
+                    .collect();
+                for handle in handles {
+                    handle.await.expect("Recording task panicked");
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_route, bench_record_message_sent);
+criterion_main!(benches);
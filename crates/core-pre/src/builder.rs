@@ -11,13 +11,16 @@ use tokio::task::JoinSet;
 use tokio_tungstenite::tungstenite::Message;
 use tracing::{error, info, warn};
 
-use crate::callback::{ConnectionCallback, ReconnectCallbackStack};
-use crate::client::{Client, ClientRunner, LightweightHandler, Router};
-use crate::connector::Connector;
+use crate::callback::ConnectionCallbackBuilder;
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+use crate::client::{Client, ClientRunner, InactivityWatchdog, LightweightHandler, Router};
+use crate::connector::{ArcConnector, Connector};
 use crate::error::{CoreError, CoreResult};
 use crate::middleware::{MiddlewareStack, WebSocketMiddleware};
 use crate::signals::Signals;
-use crate::traits::{ApiModule, AppState, LightweightModule, ReconnectCallback};
+use crate::traits::{
+    ApiModule, AppState, LightweightModule, ReconnectCallback, TimedLightweightModule,
+};
 
 type HandlerMap = Arc<RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync>>>>;
 type HandlersFn<S> = Box<
@@ -26,23 +29,31 @@ type HandlersFn<S> = Box<
             &mut JoinSet<()>,
             HandlerMap,
             AsyncSender<Message>,
-            &mut ReconnectCallbackStack<S>,
+            &mut ConnectionCallbackBuilder<S>,
         ) + Send
         + Sync,
 >;
 
 type LightweightHandlersFn<S> = Box<dyn FnOnce(&mut Router<S>, AsyncSender<Message>) + Send + Sync>;
 
+/// Default capacity of the queue of the outgoing messages.
+const DEFAULT_MESSAGE_BUFFER_SIZE: usize = 256;
+
 pub struct ClientBuilder<S: AppState> {
     state: Arc<S>,
     connector: Arc<dyn Connector<S>>,
-    connection_callback: ConnectionCallback<S>,
+    connection_callback: ConnectionCallbackBuilder<S>,
     lightweight_handlers: Vec<LightweightHandler<S>>,
     // Stores functions that know how to create and register each module.
     module_factories: Vec<HandlersFn<S>>,
     lightweight_factories: Vec<LightweightHandlersFn<S>>,
     // Middleware stack for WebSocket message processing
     middleware_stack: MiddlewareStack<S>,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+    inactivity_watchdog: Option<InactivityWatchdog>,
+    connection_timeout: Option<Duration>,
+    max_message_size: Option<usize>,
+    message_buffer_size: usize,
 }
 
 impl<S: AppState> ClientBuilder<S> {
@@ -52,17 +63,25 @@ impl<S: AppState> ClientBuilder<S> {
             state: Arc::new(state),
             connector: Arc::new(connector),
             // Provide empty default callbacks.
-            connection_callback: ConnectionCallback {
-                on_connect: Box::new(|_, _| Box::pin(async { Ok(()) })),
-                on_reconnect: ReconnectCallbackStack::default(),
-            },
+            connection_callback: ConnectionCallbackBuilder::new(),
             lightweight_handlers: Vec::new(),
             module_factories: Vec::new(),
             lightweight_factories: Vec::new(),
             middleware_stack: MiddlewareStack::new(),
+            circuit_breaker: None,
+            inactivity_watchdog: None,
+            connection_timeout: None,
+            max_message_size: None,
+            message_buffer_size: DEFAULT_MESSAGE_BUFFER_SIZE,
         }
     }
 
+    /// Replaces the connector given to [`ClientBuilder::new`] with a shared one.
+    pub fn with_connector(mut self, connector: ArcConnector<S>) -> Self {
+        self.connector = connector.0;
+        self
+    }
+
     /// Sets the callback for the initial connection.
     pub fn on_connect(
         mut self,
@@ -74,7 +93,7 @@ impl<S: AppState> ClientBuilder<S> {
         + Sync
         + 'static,
     ) -> Self {
-        self.connection_callback.on_connect = Box::new(callback);
+        self.connection_callback.set_on_connect(Box::new(callback));
         self
     }
 
@@ -83,7 +102,28 @@ impl<S: AppState> ClientBuilder<S> {
         mut self,
         callback: Box<dyn ReconnectCallback<S> + Send + Sync + 'static>,
     ) -> Self {
-        self.connection_callback.on_reconnect.add_layer(callback);
+        self.connection_callback.add_reconnect(callback);
+        self
+    }
+
+    /// Sets the callback for the initial connection, see [`ConnectionCallbackBuilder::on_connect`].
+    pub fn with_on_connect(
+        self,
+        callback: impl Fn(
+            Arc<S>,
+            &AsyncSender<Message>,
+        ) -> futures_util::future::BoxFuture<'static, CoreResult<()>>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        self.on_connect(callback)
+    }
+
+    /// Adds a callback for subsequent reconnections, see
+    /// [`ConnectionCallbackBuilder::on_reconnect`].
+    pub fn with_on_reconnect(mut self, callback: impl ReconnectCallback<S> + 'static) -> Self {
+        self.connection_callback = self.connection_callback.on_reconnect(callback);
         self
     }
 
@@ -148,7 +188,51 @@ impl<S: AppState> ClientBuilder<S> {
                     }
                 }
             });
-            router.add_lightweight_rule(M::rule(), msg_tx);
+            router.add_lightweight_rule(type_name::<M>(), M::rule(), msg_tx);
+        };
+
+        self.lightweight_factories.push(Box::new(factory));
+        self
+    }
+
+    /// Registers a timed lightweight module.
+    ///
+    /// The module's `tick` runs every `M::interval()` and `on_message` runs for every message
+    /// matching `M::rule()`, both from the same task.
+    pub fn with_timed_lightweight_module<M: TimedLightweightModule<S>>(mut self) -> Self {
+        let factory = |router: &mut Router<S>, to_ws_tx: AsyncSender<Message>| {
+            let (msg_tx, msg_rx) = bounded_async(256);
+
+            let state = router.state.clone();
+            router.spawn_lightweight_module(async move {
+                let mut module = M::new(state.clone(), to_ws_tx.clone());
+                let period = M::interval();
+                let mut interval =
+                    tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+                loop {
+                    tokio::select! {
+                        _ = interval.tick() => {
+                            if let Err(e) = module.tick(state.clone(), &to_ws_tx).await {
+                                warn!(target: "TimedLightweightModule", "[Timed {}] tick error: {:?}", type_name::<M>(), e);
+                            }
+                        }
+                        msg = msg_rx.recv() => match msg {
+                            Ok(msg) => {
+                                if let Err(e) = module.on_message(msg).await {
+                                    warn!(target: "TimedLightweightModule", "[Timed {}] message error: {:?}", type_name::<M>(), e);
+                                }
+                            }
+                            Err(_) => {
+                                info!(target: "TimedLightweightModule", "[Timed {}] message channel closed, exiting", type_name::<M>());
+                                break;
+                            }
+                        }
+                    }
+                }
+            });
+            router.add_lightweight_rule(type_name::<M>(), M::rule(), msg_tx);
         };
 
         self.lightweight_factories.push(Box::new(factory));
@@ -162,7 +246,7 @@ impl<S: AppState> ClientBuilder<S> {
              join_set: &mut JoinSet<()>,
              handles: Arc<RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync>>>>,
              to_ws_tx: AsyncSender<Message>,
-             reconnect_callback_stack: &mut ReconnectCallbackStack<S>| {
+             connection_callback: &mut ConnectionCallbackBuilder<S>| {
                 let (cmd_tx, cmd_rx) = bounded_async(32);
                 let (cmd_ret_tx, cmd_ret_rx) = bounded_async(32);
                 let (msg_tx, msg_rx) = bounded_async(256);
@@ -187,7 +271,7 @@ impl<S: AppState> ClientBuilder<S> {
                 );
                 match m_temp.callback() {
                     Ok(Some(callback)) => {
-                        reconnect_callback_stack.add_layer(callback);
+                        connection_callback.add_reconnect(callback);
                     }
                     Ok(None) => {
                         // No callback needed, continue.
@@ -235,16 +319,17 @@ impl<S: AppState> ClientBuilder<S> {
                 }
             });
 
-                router.add_module_rule(M::rule(state_clone), msg_tx);
+                router.add_module_rule(type_name::<M>(), M::rule(state_clone), msg_tx);
             };
 
         self.module_factories.push(Box::new(factory));
         self
     }
 
-    /// Adds a middleware layer to the client.
+    /// Adds a middleware layer to the client with the default priority `0`.
     ///
-    /// Middleware will be executed in the order they are added.
+    /// Middleware with the same priority will be executed in the order they are added,
+    /// see [`with_middleware_prioritized`](Self::with_middleware_prioritized).
     /// They will be called for all WebSocket messages sent and received.
     ///
     /// # Example
@@ -281,6 +366,21 @@ impl<S: AppState> ClientBuilder<S> {
         self
     }
 
+    /// Adds a middleware layer to the client with the given priority.
+    ///
+    /// Lower priorities run first, so a layer registered with `-5` sees every message
+    /// before the layers registered with [`with_middleware`](Self::with_middleware).
+    /// Layers with the same priority keep their insertion order.
+    pub fn with_middleware_prioritized(
+        mut self,
+        middleware: Box<dyn WebSocketMiddleware<S>>,
+        priority: i32,
+    ) -> Self {
+        self.middleware_stack
+            .add_layer_prioritized(middleware, priority);
+        self
+    }
+
     /// Adds multiple middleware layers at once.
     ///
     /// This is a convenience method for adding multiple middleware layers.
@@ -368,17 +468,57 @@ impl<S: AppState> ClientBuilder<S> {
         self
     }
 
+    /// Enables a circuit breaker that pauses reconnection attempts when too many of them fail
+    /// within the configured window. See [`CircuitBreakerConfig`].
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Reconnects automatically when no message is received for `timeout`.
+    /// See [`InactivityWatchdog`].
+    pub fn with_inactivity_watchdog(mut self, timeout: Duration) -> Self {
+        self.inactivity_watchdog = Some(InactivityWatchdog { timeout });
+        self
+    }
+
+    /// Fails a connection attempt with `ConnectorError::Timeout` when the connector doesn't
+    /// return a stream within `timeout`, the runner then retries like for any failed attempt.
+    pub fn with_connection_timeout(mut self, timeout: Duration) -> Self {
+        self.connection_timeout = Some(timeout);
+        self
+    }
+
+    /// Drops the received messages larger than `limit` bytes before they're routed, protecting
+    /// the handlers from oversized frames. The middleware `on_message_size_exceeded` hook is
+    /// called for each of them, see [`MaxMessageSizeMiddleware`](crate::middleware::MaxMessageSizeMiddleware).
+    pub fn with_max_message_size(mut self, limit: usize) -> Self {
+        self.max_message_size = Some(limit);
+        self
+    }
+
+    /// Sets how many outgoing messages can be queued for the WebSocket writer, once the queue is
+    /// full the senders wait for the writer to catch up. Defaults to 256.
+    pub fn with_message_buffer_size(mut self, size: usize) -> Self {
+        self.message_buffer_size = size;
+        self
+    }
+
     /// Assembles and returns the final `Client` handle and its `ClientRunner`.
     pub async fn build(self) -> CoreResult<(Client<S>, ClientRunner<S>)> {
         let (runner_cmd_tx, runner_cmd_rx) = bounded_async(8);
-        let (to_ws_tx, to_ws_rx) = bounded_async(256);
+        let (to_ws_tx, to_ws_rx) = bounded_async(self.message_buffer_size);
         let signals = Signals::default();
-        let client = Client::new(
+        let circuit_breaker = self
+            .circuit_breaker
+            .map(|config| Arc::new(CircuitBreaker::new(config)));
+        let mut client = Client::new(
             signals.clone(),
-            runner_cmd_tx,
+            runner_cmd_tx.clone(),
             self.state.clone(),
             to_ws_tx.clone(),
         );
+        client.circuit_breaker = circuit_breaker.clone();
 
         let mut router = Router::new(self.state.clone());
         router.lightweight_handlers = self.lightweight_handlers;
@@ -393,13 +533,14 @@ impl<S: AppState> ClientBuilder<S> {
                 &mut join_set,
                 client.module_handles.clone(),
                 to_ws_tx.clone(),
-                &mut connection_callback.on_reconnect,
+                &mut connection_callback,
             );
         }
 
         for factory in self.lightweight_factories {
             factory(&mut router, to_ws_tx.clone());
         }
+        client.router_metrics = Arc::clone(&router.metrics);
 
         // Wait for all the handles to be added to the handles hashmap.
         while let Some(h) = join_set.join_next().await {
@@ -422,7 +563,12 @@ impl<S: AppState> ClientBuilder<S> {
             to_ws_sender: to_ws_tx,
             to_ws_receiver: to_ws_rx,
             runner_command_rx: runner_cmd_rx,
-            connection_callback,
+            runner_command_tx: runner_cmd_tx,
+            connection_callback: connection_callback.build(),
+            circuit_breaker,
+            inactivity_watchdog: self.inactivity_watchdog,
+            connection_timeout: self.connection_timeout,
+            max_message_size: self.max_message_size,
         };
 
         Ok((client, runner))
@@ -441,4 +587,93 @@ mod tests {
         // This will fail to compile if ClientBuilder is not Send + Sync
         assert_send_sync::<ClientBuilder<()>>();
     }
+
+    use crate::connector::{ConnectorError, ConnectorResult, WsStream};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct NoopConnector;
+
+    #[async_trait]
+    impl Connector<TickState> for NoopConnector {
+        async fn connect(&self, _: Arc<TickState>) -> ConnectorResult<WsStream> {
+            Err(ConnectorError::Custom("not used".into()))
+        }
+
+        async fn disconnect(&self) -> ConnectorResult<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct TickState {
+        ticks: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl AppState for TickState {
+        async fn clear_temporal_data(&self) {}
+    }
+
+    struct TickModule;
+
+    #[async_trait]
+    impl TimedLightweightModule<TickState> for TickModule {
+        fn new(_: Arc<TickState>, _: AsyncSender<Message>) -> Self {
+            Self
+        }
+
+        fn interval() -> Duration {
+            Duration::from_millis(50)
+        }
+
+        async fn tick(
+            &mut self,
+            state: Arc<TickState>,
+            sender: &AsyncSender<Message>,
+        ) -> CoreResult<()> {
+            state.ticks.fetch_add(1, Ordering::SeqCst);
+            sender.send(Message::text("tick")).await?;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timed_lightweight_module_ticks() {
+        let (client, _runner) = ClientBuilder::new(NoopConnector, TickState::default())
+            .with_timed_lightweight_module::<TickModule>()
+            .build()
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(2 * 50 + 25)).await;
+        assert!(client.state.ticks.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_blocks_reconnect() {
+        let (client, mut runner) = ClientBuilder::new(NoopConnector, TickState::default())
+            .with_circuit_breaker(CircuitBreakerConfig {
+                window: Duration::from_secs(10),
+                failure_threshold: 1,
+                open_duration: Duration::from_secs(60),
+            })
+            .build()
+            .await
+            .unwrap();
+        let runner_task = tokio::spawn(async move { runner.run().await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(matches!(
+            client.reconnect().await,
+            Err(CoreError::CircuitBreakerOpen(_))
+        ));
+
+        // Shutdown is still honoured while the circuit is open.
+        client.shutdown().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(1), runner_task)
+            .await
+            .expect("runner should stop while the circuit is open")
+            .unwrap();
+    }
 }
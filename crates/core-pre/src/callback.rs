@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures_util::future::BoxFuture;
 use kanal::AsyncSender;
 use tokio_tungstenite::tungstenite::Message;
 
@@ -14,15 +15,74 @@ pub struct ConnectionCallback<S: AppState> {
     pub on_reconnect: ReconnectCallbackStack<S>,
 }
 
-// --- Callbacks and Lightweight Handlers ---
-pub type OnConnectCallback<S> = Box<
-    dyn Fn(
-            Arc<S>,
-            &AsyncSender<Message>,
-        ) -> futures_util::future::BoxFuture<'static, CoreResult<()>>
+impl<S: AppState> Default for ConnectionCallback<S> {
+    fn default() -> Self {
+        ConnectionCallbackBuilder::new().build()
+    }
+}
+
+/// Builds a [`ConnectionCallback`], `on_connect` and `on_reconnect` can be set separately.
+///
+/// Unset parts do nothing, every `on_reconnect` call adds a layer run in insertion order.
+pub struct ConnectionCallbackBuilder<S: AppState> {
+    on_connect: Option<OnConnectCallback<S>>,
+    on_reconnect: ReconnectCallbackStack<S>,
+}
+
+impl<S: AppState> Default for ConnectionCallbackBuilder<S> {
+    fn default() -> Self {
+        Self {
+            on_connect: None,
+            on_reconnect: ReconnectCallbackStack::default(),
+        }
+    }
+}
+
+impl<S: AppState> ConnectionCallbackBuilder<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the callback for the initial connection, replacing the previous one.
+    pub fn on_connect(
+        mut self,
+        callback: impl Fn(Arc<S>, &AsyncSender<Message>) -> BoxFuture<'static, CoreResult<()>>
         + Send
-        + Sync,
->;
+        + Sync
+        + 'static,
+    ) -> Self {
+        self.on_connect = Some(Box::new(callback));
+        self
+    }
+
+    /// Adds a callback for subsequent reconnections, either a closure with the same signature
+    /// as `on_connect` or any [`ReconnectCallback`] such as an `Arc<dyn ReconnectCallback<S>>`.
+    pub fn on_reconnect(mut self, callback: impl ReconnectCallback<S> + 'static) -> Self {
+        self.add_reconnect(Box::new(callback));
+        self
+    }
+
+    pub(crate) fn set_on_connect(&mut self, callback: OnConnectCallback<S>) {
+        self.on_connect = Some(callback);
+    }
+
+    pub(crate) fn add_reconnect(&mut self, callback: Box<dyn ReconnectCallback<S>>) {
+        self.on_reconnect.add_layer(callback);
+    }
+
+    pub fn build(self) -> ConnectionCallback<S> {
+        ConnectionCallback {
+            on_connect: self
+                .on_connect
+                .unwrap_or_else(|| Box::new(|_, _| Box::pin(async { Ok(()) }))),
+            on_reconnect: self.on_reconnect,
+        }
+    }
+}
+
+// --- Callbacks and Lightweight Handlers ---
+pub type OnConnectCallback<S> =
+    Box<dyn Fn(Arc<S>, &AsyncSender<Message>) -> BoxFuture<'static, CoreResult<()>> + Send + Sync>;
 
 pub struct ReconnectCallbackStack<S: AppState> {
     pub layers: Vec<Box<dyn ReconnectCallback<S>>>,
@@ -49,3 +109,21 @@ impl<S: AppState> ReconnectCallback<S> for ReconnectCallbackStack<S> {
         Ok(())
     }
 }
+
+#[async_trait]
+impl<S: AppState, T: ReconnectCallback<S> + ?Sized> ReconnectCallback<S> for Arc<T> {
+    async fn call(&self, state: Arc<S>, sender: &AsyncSender<Message>) -> CoreResult<()> {
+        (**self).call(state, sender).await
+    }
+}
+
+#[async_trait]
+impl<S, F> ReconnectCallback<S> for F
+where
+    S: AppState,
+    F: Fn(Arc<S>, &AsyncSender<Message>) -> BoxFuture<'static, CoreResult<()>> + Send + Sync,
+{
+    async fn call(&self, state: Arc<S>, sender: &AsyncSender<Message>) -> CoreResult<()> {
+        self(state, sender).await
+    }
+}
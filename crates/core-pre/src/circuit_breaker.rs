@@ -0,0 +1,216 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Configuration for the connection circuit breaker.
+///
+/// When `failure_threshold` connection failures happen within `window`, the circuit opens and
+/// no reconnection is attempted for `open_duration`. After that a single attempt is allowed
+/// (half-open); if it succeeds the circuit closes again, otherwise it reopens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    pub window: Duration,
+    pub failure_threshold: u32,
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60),
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(300),
+        }
+    }
+}
+
+/// The state of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Connection attempts are allowed.
+    Closed,
+    /// Too many failures, connection attempts are blocked until the open duration elapses.
+    Open,
+    /// The open duration elapsed, a single connection attempt is allowed.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct CircuitBreakerInner {
+    failures: VecDeque<Instant>,
+    opened_at: Option<Instant>,
+    half_open: bool,
+}
+
+/// Sliding-window circuit breaker guarding the reconnection loop of the `ClientRunner`.
+///
+/// It is shared between the runner and the `Client`, so the client can refuse manual
+/// reconnections while the circuit is open.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Mutex<CircuitBreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            inner: Mutex::new(CircuitBreakerInner {
+                failures: VecDeque::new(),
+                opened_at: None,
+                half_open: false,
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    /// Returns the current state, moving from `Open` to `HalfOpen` once the open duration elapsed.
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.lock();
+        self.refresh(&mut inner)
+    }
+
+    /// Returns `true` while connection attempts are blocked.
+    pub fn is_open(&self) -> bool {
+        self.state() == CircuitState::Open
+    }
+
+    /// Time left before the circuit becomes half-open, `None` if it is not open.
+    pub fn remaining_open_duration(&self) -> Option<Duration> {
+        let mut inner = self.lock();
+        match self.refresh(&mut inner) {
+            CircuitState::Open => inner
+                .opened_at
+                .map(|opened| self.config.open_duration.saturating_sub(opened.elapsed())),
+            _ => None,
+        }
+    }
+
+    /// Records a failed connection attempt and returns the resulting state.
+    pub fn record_failure(&self) -> CircuitState {
+        let mut inner = self.lock();
+        let now = Instant::now();
+        match self.refresh(&mut inner) {
+            CircuitState::Open => return CircuitState::Open,
+            CircuitState::HalfOpen => {
+                // The trial attempt failed, open the circuit again.
+                inner.half_open = false;
+                inner.opened_at = Some(now);
+                return CircuitState::Open;
+            }
+            CircuitState::Closed => {}
+        }
+
+        inner.failures.push_back(now);
+        while let Some(first) = inner.failures.front() {
+            if now.duration_since(*first) > self.config.window {
+                inner.failures.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if inner.failures.len() >= self.config.failure_threshold as usize {
+            inner.failures.clear();
+            inner.opened_at = Some(now);
+            CircuitState::Open
+        } else {
+            CircuitState::Closed
+        }
+    }
+
+    /// Records a successful connection, closing the circuit.
+    pub fn record_success(&self) {
+        let mut inner = self.lock();
+        inner.failures.clear();
+        inner.opened_at = None;
+        inner.half_open = false;
+    }
+
+    fn refresh(&self, inner: &mut CircuitBreakerInner) -> CircuitState {
+        if inner.half_open {
+            return CircuitState::HalfOpen;
+        }
+        match inner.opened_at {
+            Some(opened) if opened.elapsed() >= self.config.open_duration => {
+                inner.opened_at = None;
+                inner.half_open = true;
+                CircuitState::HalfOpen
+            }
+            Some(_) => CircuitState::Open,
+            None => CircuitState::Closed,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CircuitBreakerInner> {
+        // The inner data is always left consistent, so a poisoned lock can be reused.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(open_duration: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            window: Duration::from_secs(10),
+            failure_threshold: 3,
+            open_duration,
+        })
+    }
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = breaker(Duration::from_secs(60));
+        assert_eq!(breaker.record_failure(), CircuitState::Closed);
+        assert_eq!(breaker.record_failure(), CircuitState::Closed);
+        assert_eq!(breaker.record_failure(), CircuitState::Open);
+        assert!(breaker.is_open());
+        assert!(breaker.remaining_open_duration().unwrap() <= Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_failures_outside_window_are_forgotten() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            window: Duration::from_millis(20),
+            failure_threshold: 2,
+            open_duration: Duration::from_secs(60),
+        });
+        assert_eq!(breaker.record_failure(), CircuitState::Closed);
+        std::thread::sleep(Duration::from_millis(40));
+        assert_eq!(breaker.record_failure(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_open_half_open_closed() {
+        let breaker = breaker(Duration::from_millis(20));
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        std::thread::sleep(Duration::from_millis(40));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert_eq!(breaker.remaining_open_duration(), None);
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_failure_reopens() {
+        let breaker = breaker(Duration::from_millis(20));
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        std::thread::sleep(Duration::from_millis(40));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert_eq!(breaker.record_failure(), CircuitState::Open);
+        assert!(breaker.is_open());
+    }
+}
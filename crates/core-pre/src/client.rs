@@ -1,15 +1,18 @@
 use crate::callback::ConnectionCallback;
-use crate::connector::Connector;
-use crate::error::CoreResult;
+use crate::circuit_breaker::{CircuitBreaker, CircuitState};
+use crate::connector::{Connector, ConnectorError};
+use crate::error::{CoreError, CoreResult};
 use crate::middleware::{MiddlewareContext, MiddlewareStack};
 use crate::signals::Signals;
 use crate::traits::{ApiModule, AppState, ReconnectCallback, Rule};
-use futures_util::{SinkExt, stream::StreamExt};
+use futures_util::stream::StreamExt;
 use kanal::{AsyncReceiver, AsyncSender};
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{RwLock, watch};
 use tokio::task::JoinSet;
 use tokio_tungstenite::tungstenite::Message;
 use tracing::{debug, error, info, warn};
@@ -31,7 +34,16 @@ pub type LightweightHandler<S> = Box<
         + Sync,
 >;
 
-type RuleTp = (Box<dyn Rule + Send + Sync>, AsyncSender<Arc<Message>>);
+type RuleTp = (
+    String,
+    Box<dyn Rule + Send + Sync>,
+    AsyncSender<Arc<Message>>,
+);
+
+/// Longest time spent sending the queued messages on disconnect or shutdown.
+const WRITER_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the runner waits for the server to answer the close frame before dropping the connection.
+const CLOSE_FRAME_TIMEOUT: Duration = Duration::from_millis(500);
 // --- Control Commands for the Runner ---
 
 #[derive(Debug)]
@@ -43,6 +55,124 @@ pub enum RunnerCommand {
     // You can add more commands like Shutdown in the future
 }
 
+/// Reconnects when no message is received for `timeout`.
+///
+/// Silent TCP drops may keep the read stream open for minutes, the watchdog detects them by
+/// the absence of incoming messages, so `timeout` must be longer than the interval of the
+/// keep-alive messages of the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InactivityWatchdog {
+    pub timeout: Duration,
+}
+
+impl InactivityWatchdog {
+    /// Sends `RunnerCommand::Reconnect` once no message was received during `timeout`.
+    async fn watch(
+        self,
+        last_message: Arc<Mutex<tokio::time::Instant>>,
+        runner_command_tx: AsyncSender<RunnerCommand>,
+    ) {
+        loop {
+            let deadline = match last_message.lock() {
+                Ok(last_message) => *last_message + self.timeout,
+                Err(_) => return,
+            };
+            tokio::time::sleep_until(deadline).await;
+            let idle = match last_message.lock() {
+                Ok(last_message) => last_message.elapsed(),
+                Err(_) => return,
+            };
+            if idle >= self.timeout {
+                warn!(target: "Runner", "No message received for {idle:?}, reconnecting.");
+                let _ = runner_command_tx.send(RunnerCommand::Reconnect).await;
+                return;
+            }
+        }
+    }
+}
+
+/// Writer task of a session, sending the queued messages to the WebSocket.
+struct WriterTask {
+    handle: tokio::task::JoinHandle<()>,
+    flush: tokio::sync::oneshot::Sender<()>,
+}
+
+/// Queues a close frame behind the pending messages, so the writer sends it last before stopping.
+async fn send_close_frame(sender: &AsyncSender<Message>, timeout: Duration) {
+    match tokio::time::timeout(timeout, sender.send(Message::Close(None))).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => warn!(target: "Runner", "Failed to queue the close frame: {e}"),
+        Err(_) => warn!(target: "Runner", "Close frame not queued after {timeout:?}."),
+    }
+}
+
+impl WriterTask {
+    /// Sends the messages still queued then stops the task, it's aborted once `timeout` elapsed.
+    async fn flush(mut self, timeout: Duration) {
+        // The writer only stops once the queue is empty
+        let _ = self.flush.send(());
+        if tokio::time::timeout(timeout, &mut self.handle)
+            .await
+            .is_err()
+        {
+            warn!(target: "Runner", "Queued messages not sent after {timeout:?}, dropping them.");
+            self.handle.abort();
+        }
+    }
+
+    fn abort(self) {
+        self.handle.abort();
+    }
+}
+
+/// Dispatch counters of the `Router`, used to diagnose the routing overhead.
+///
+/// Rules are keyed by the type name of the module they belong to.
+#[derive(Debug, Default)]
+pub struct RouterMetrics {
+    /// Number of messages matched by each rule.
+    pub rule_match_counts: HashMap<String, AtomicU64>,
+    /// Number of messages routed.
+    pub total_routed: AtomicU64,
+    /// Number of messages no rule matched.
+    pub unmatched: AtomicU64,
+    /// When the last message was routed, `None` until the first one.
+    pub last_routed: Mutex<Option<tokio::time::Instant>>,
+}
+
+/// Point-in-time copy of the [`RouterMetrics`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RouterMetricsSnapshot {
+    pub rule_match_counts: HashMap<String, u64>,
+    pub total_routed: u64,
+    pub unmatched: u64,
+}
+
+impl RouterMetrics {
+    pub fn snapshot(&self) -> RouterMetricsSnapshot {
+        RouterMetricsSnapshot {
+            rule_match_counts: self
+                .rule_match_counts
+                .iter()
+                .map(|(name, count)| (name.clone(), count.load(Ordering::Relaxed)))
+                .collect(),
+            total_routed: self.total_routed.load(Ordering::Relaxed),
+            unmatched: self.unmatched.load(Ordering::Relaxed),
+        }
+    }
+
+    /// When the last message was routed, `None` if no message was received yet.
+    pub fn last_routed(&self) -> Option<tokio::time::Instant> {
+        self.last_routed.lock().ok().and_then(|last| *last)
+    }
+
+    fn record_match(&self, name: &str) {
+        if let Some(count) = self.rule_match_counts.get(name) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
 // --- Internal Router ---
 pub struct Router<S: AppState> {
     pub(crate) state: Arc<S>,
@@ -52,6 +182,7 @@ pub struct Router<S: AppState> {
     pub(crate) lightweight_handlers: Vec<LightweightHandler<S>>,
     pub(crate) lightweight_set: JoinSet<()>,
     pub(crate) middleware_stack: MiddlewareStack<S>,
+    pub(crate) metrics: Arc<RouterMetrics>,
 }
 
 impl<S: AppState> Router<S> {
@@ -64,6 +195,7 @@ impl<S: AppState> Router<S> {
             lightweight_handlers: Vec::new(),
             lightweight_set: JoinSet::new(),
             middleware_stack: MiddlewareStack::new(),
+            metrics: Arc::default(),
         }
     }
 
@@ -71,20 +203,48 @@ impl<S: AppState> Router<S> {
         self.module_set.spawn(task);
     }
 
+    /// Adds the rule of a module, `name` identifies it in the [`RouterMetrics`].
     pub fn add_module_rule(
         &mut self,
+        name: impl ToString,
         rule: Box<dyn Rule + Send + Sync>,
         sender: AsyncSender<Arc<Message>>,
     ) {
-        self.module_rules.push((rule, sender));
+        let name = name.to_string();
+        self.register_rule_metrics(&name);
+        self.module_rules.push((name, rule, sender));
     }
 
+    /// Adds the rule of a lightweight module, `name` identifies it in the [`RouterMetrics`].
     pub fn add_lightweight_rule(
         &mut self,
+        name: impl ToString,
         rule: Box<dyn Rule + Send + Sync>,
         sender: AsyncSender<Arc<Message>>,
     ) {
-        self.lightweight_rules.push((rule, sender));
+        let name = name.to_string();
+        self.register_rule_metrics(&name);
+        self.lightweight_rules.push((name, rule, sender));
+    }
+
+    fn register_rule_metrics(&mut self, name: &str) {
+        // The metrics are only shared once the client is built, after every rule was added
+        match Arc::get_mut(&mut self.metrics) {
+            Some(metrics) => {
+                metrics
+                    .rule_match_counts
+                    .entry(name.to_string())
+                    .or_default();
+            }
+            None => {
+                warn!(target: "Router", "Rule '{name}' added after the metrics were shared, it won't be counted.")
+            }
+        }
+    }
+
+    /// Returns the number of messages routed and matched by each rule so far.
+    pub fn metrics(&self) -> RouterMetricsSnapshot {
+        self.metrics.snapshot()
     }
 
     pub fn add_lightweight_handler(&mut self, handler: LightweightHandler<S>) {
@@ -106,12 +266,16 @@ impl<S: AppState> Router<S> {
     /// # Middleware Integration
     /// The `on_receive` middleware hook is called at the beginning of message processing,
     /// allowing middleware to observe, log, or transform incoming messages before they
-    /// reach the application logic.
+    /// reach the application logic. A message dropped by a middleware isn't routed at all.
     ///
     /// # Arguments
     /// - `message`: The incoming WebSocket message wrapped in Arc for sharing
     /// - `sender`: Channel for sending outgoing messages
-    async fn route(&self, message: Arc<Message>, sender: &AsyncSender<Message>) -> CoreResult<()> {
+    pub async fn route(
+        &self,
+        message: Arc<Message>,
+        sender: &AsyncSender<Message>,
+    ) -> CoreResult<()> {
         // Route to all lightweight handlers first
         debug!(target: "Router", "Routing message: {message:?}");
 
@@ -120,9 +284,13 @@ impl<S: AppState> Router<S> {
 
         // 🎯 MIDDLEWARE HOOK: on_receive - called for ALL incoming messages
         // This is where middleware can observe, log, or process incoming messages
-        self.middleware_stack
+        if !self
+            .middleware_stack
             .on_receive(&message, &middleware_context)
-            .await;
+            .await
+        {
+            return Ok(());
+        }
 
         for handler in &self.lightweight_handlers {
             if let Err(err) = handler(Arc::clone(&message), Arc::clone(&self.state), sender).await {
@@ -131,19 +299,35 @@ impl<S: AppState> Router<S> {
                 );
             }
         }
-        for (rule, sender) in &self.lightweight_rules {
+        self.metrics.total_routed.fetch_add(1, Ordering::Relaxed);
+        let mut matched = false;
+        for (name, rule, sender) in &self.lightweight_rules {
             // If the rule matches, send the message to the lightweight handler
-            if rule.call(&message) && sender.send(message.clone()).await.is_err() {
-                error!(target: "Router", "A lightweight module has shut down and its channel is closed.");
+            if rule.call(&message) {
+                matched = true;
+                self.metrics.record_match(name);
+                if sender.send(message.clone()).await.is_err() {
+                    error!(target: "Router", "A lightweight module has shut down and its channel is closed.");
+                }
             }
         }
 
         // Route to the first matching API module
-        for (rule, sender) in &self.module_rules {
-            if rule.call(&message) && sender.send(message.clone()).await.is_err() {
-                error!(target: "Router", "A module has shut down and its channel is closed.");
+        for (name, rule, sender) in &self.module_rules {
+            if rule.call(&message) {
+                matched = true;
+                self.metrics.record_match(name);
+                if sender.send(message.clone()).await.is_err() {
+                    error!(target: "Router", "A module has shut down and its channel is closed.");
+                }
             }
         }
+        if !matched {
+            self.metrics.unmatched.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Ok(mut last_routed) = self.metrics.last_routed.lock() {
+            *last_routed = Some(tokio::time::Instant::now());
+        }
         Ok(())
     }
 }
@@ -158,8 +342,12 @@ pub struct Client<S: AppState> {
     pub to_ws_sender: AsyncSender<Message>,
 
     runner_command_tx: AsyncSender<RunnerCommand>,
+    pub(crate) circuit_breaker: Option<Arc<CircuitBreaker>>,
+    pub(crate) router_metrics: Arc<RouterMetrics>,
 }
 
+/// Clones share the state, the module handles and the channels to the runner and the connection,
+/// so a module handle retrieved from a clone talks to the same module as one from the original.
 impl<S: AppState> Clone for Client<S> {
     fn clone(&self) -> Self {
         Self {
@@ -168,6 +356,8 @@ impl<S: AppState> Clone for Client<S> {
             module_handles: Arc::clone(&self.module_handles),
             runner_command_tx: self.runner_command_tx.clone(),
             to_ws_sender: self.to_ws_sender.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            router_metrics: Arc::clone(&self.router_metrics),
         }
     }
 }
@@ -186,9 +376,22 @@ impl<S: AppState> Client<S> {
             module_handles: Arc::new(RwLock::new(HashMap::new())),
             runner_command_tx,
             to_ws_sender: sender,
+            circuit_breaker: None,
+            router_metrics: Arc::default(),
         }
     }
 
+    /// Returns the number of messages routed and matched by each module rule so far.
+    pub fn router_metrics(&self) -> RouterMetricsSnapshot {
+        self.router_metrics.snapshot()
+    }
+
+    /// Returns when the last message from the server was routed, `None` if no message was
+    /// received yet. Messages dropped by a middleware don't count.
+    pub fn last_message_received(&self) -> Option<tokio::time::Instant> {
+        self.router_metrics.last_routed()
+    }
+
     /// Waits until the client is connected to the WebSocket server.
     /// This method will block until the connection is established.
     /// It is useful for ensuring that the client is ready to send and receive messages.
@@ -196,11 +399,32 @@ impl<S: AppState> Client<S> {
         self.signal.wait_connected().await
     }
 
+    /// Waits until the client is disconnected from the WebSocket server.
+    /// Returns immediately if the client is not connected.
+    pub async fn wait_disconnected(&self) {
+        self.signal.wait_disconnected().await
+    }
+
+    /// Waits for the next connection state change and returns `true` if the client is now connected.
+    pub async fn wait_for_state_change(&self) -> bool {
+        self.signal.wait_for_state_change().await
+    }
+
     /// Checks if the client is connected to the WebSocket server.
     pub fn is_connected(&self) -> bool {
         self.signal.is_connected()
     }
 
+    /// Returns a `watch` receiver tracking whether the client is connected.
+    pub fn connection_watch(&self) -> watch::Receiver<bool> {
+        self.signal.connection_watch()
+    }
+
+    /// Returns a `watch` receiver tracking the error of the last failed connection attempt.
+    pub fn connection_failure_watch(&self) -> watch::Receiver<Option<Arc<ConnectorError>>> {
+        self.signal.connection_failure_watch()
+    }
+
     /// Retrieves a clonable, typed handle to an already-registered module.
     pub async fn get_handle<M: ApiModule<S>>(&self) -> Option<M::Handle> {
         let handles = self.module_handles.read().await;
@@ -219,7 +443,16 @@ impl<S: AppState> Client<S> {
     }
 
     /// Commands the runner to disconnect, and perform a "soft" reconnect.
+    ///
+    /// Fails with `CoreError::CircuitBreakerOpen` while the circuit breaker is open.
     pub async fn reconnect(&self) -> CoreResult<()> {
+        if let Some(remaining) = self
+            .circuit_breaker
+            .as_ref()
+            .and_then(|breaker| breaker.remaining_open_duration())
+        {
+            return Err(CoreError::CircuitBreakerOpen(remaining));
+        }
         Ok(self
             .runner_command_tx
             .send(RunnerCommand::Reconnect)
@@ -308,6 +541,16 @@ pub struct ClientRunner<S: AppState> {
     pub(crate) to_ws_sender: AsyncSender<Message>,
     pub(crate) to_ws_receiver: AsyncReceiver<Message>,
     pub(crate) runner_command_rx: AsyncReceiver<RunnerCommand>,
+    /// Used by the background tasks of a session to command the runner.
+    pub(crate) runner_command_tx: AsyncSender<RunnerCommand>,
+    /// Stops reconnection attempts when too many of them fail in a short time.
+    pub(crate) circuit_breaker: Option<Arc<CircuitBreaker>>,
+    /// Reconnects when the connection stays silent for too long.
+    pub(crate) inactivity_watchdog: Option<InactivityWatchdog>,
+    /// Maximum duration of a connection attempt, unbounded if `None`.
+    pub(crate) connection_timeout: Option<Duration>,
+    /// Received messages larger than this are dropped before being routed.
+    pub(crate) max_message_size: Option<usize>,
 }
 
 impl<S: AppState> ClientRunner<S> {
@@ -317,9 +560,12 @@ impl<S: AppState> ClientRunner<S> {
     ///
     /// This method integrates middleware at four key points:
     ///
-    /// 1. **Connection Establishment** (`on_connect`): Called after successful connection
-    /// 2. **Message Sending** (`on_send`): Called before each message is sent to WebSocket
-    /// 3. **Message Receiving** (`on_receive`): Called for each incoming message (in Router::route)
+    /// 1. **Connection Establishment** (`on_connection_attempt`, `on_connect`, `on_connection_failure`):
+    ///    Called before each connection attempt, then after it succeeds or fails
+    /// 2. **Message Sending** (`on_send`, `on_send_error`): Called before each message is sent to
+    ///    WebSocket, then after every failed attempt to decide whether it's sent again
+    /// 3. **Message Receiving** (`on_receive`, `on_message_size_exceeded`): Called for each incoming
+    ///    message (in Router::route), or instead of routing it when it's larger than the size limit
     /// 4. **Disconnection** (`on_disconnect`): Called on manual disconnect, shutdown, or connection loss
     ///
     /// # Connection Lifecycle
@@ -343,16 +589,41 @@ impl<S: AppState> ClientRunner<S> {
                 .await;
 
             // Use the correct connection method based on the flag.
-            let stream_result = if self.is_hard_disconnect {
-                self.connector.connect(self.state.clone()).await
+            let attempt = if self.is_hard_disconnect {
+                self.connector.connect(self.state.clone())
             } else {
-                self.connector.reconnect(self.state.clone()).await
+                self.connector.reconnect(self.state.clone())
+            };
+            let stream_result = match self.connection_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, attempt)
+                    .await
+                    .unwrap_or(Err(ConnectorError::Timeout)),
+                None => attempt.await,
             };
 
             let ws_stream = match stream_result {
                 Ok(stream) => stream,
                 Err(e) => {
-                    warn!(target: "Runner", "Connection failed: {e}. Retrying in 5s...");
+                    self.router
+                        .middleware_stack
+                        .record_connection_failure(&middleware_context, Some(e.to_string()))
+                        .await;
+                    if e.is_fatal() {
+                        error!(target: "Runner", "Connection failed: {e}. Not retrying.");
+                        self.signal.set_connection_failure(e);
+                        self.shutdown_requested = true;
+                        continue;
+                    }
+                    let reason = e.to_string();
+                    self.signal.set_connection_failure(e);
+                    if let Some(breaker) = self.circuit_breaker.clone()
+                        && breaker.record_failure() == CircuitState::Open
+                    {
+                        self.wait_circuit_breaker(&breaker).await;
+                        self.is_hard_disconnect = false;
+                        continue;
+                    }
+                    warn!(target: "Runner", "Connection failed: {reason}. Retrying in 5s...");
                     tokio::time::sleep(std::time::Duration::from_secs(5)).await;
                     // On failure, the next attempt is a reconnect, not a hard connect.
                     self.is_hard_disconnect = false;
@@ -363,6 +634,9 @@ impl<S: AppState> ClientRunner<S> {
             // 🎯 MIDDLEWARE HOOK: on_connect - called after successful connection
             // Location: After WebSocket connection is established
             info!(target: "Runner", "Connection successful.");
+            if let Some(breaker) = &self.circuit_breaker {
+                breaker.record_success();
+            }
             self.signal.set_connected();
             self.router
                 .middleware_stack
@@ -402,6 +676,7 @@ impl<S: AppState> ClientRunner<S> {
             let (mut ws_writer, mut ws_reader) = ws_stream.split();
 
             // 🎯 MIDDLEWARE HOOK: on_send - called in writer task for outgoing messages
+            let (flush_tx, mut flush_rx) = tokio::sync::oneshot::channel::<()>();
             let writer_task = tokio::spawn({
                 let to_ws_rx = self.to_ws_receiver.clone();
                 let router = Arc::clone(&self.router);
@@ -409,25 +684,50 @@ impl<S: AppState> ClientRunner<S> {
                 let to_ws_sender = self.to_ws_sender.clone();
                 async move {
                     let middleware_context = MiddlewareContext::new(state, to_ws_sender);
-                    while let Ok(msg) = to_ws_rx.recv().await {
-                        // Execute middleware on_send hook
-                        router
+                    loop {
+                        // Queued messages go first, so a flush request only stops an idle writer
+                        let msg = tokio::select! {
+                            biased;
+                            msg = to_ws_rx.recv() => match msg {
+                                Ok(msg) => msg,
+                                Err(_) => break,
+                            },
+                            _ = &mut flush_rx => break,
+                        };
+                        // Execute middleware on_send hook, and on_send_error for every failed attempt
+                        if let Err(e) = router
                             .middleware_stack
-                            .on_send(&msg, &middleware_context)
-                            .await;
-                        if ws_writer.send(msg).await.is_err() {
-                            error!(target: "Runner", "WebSocket writer task failed to send message.");
+                            .send(&mut ws_writer, msg, &middleware_context)
+                            .await
+                        {
+                            error!(target: "Runner", "WebSocket writer task failed to send message: {e}");
                             break;
                         }
                     }
                 }
             });
 
+            let last_message = Arc::new(Mutex::new(tokio::time::Instant::now()));
             let reader_task = tokio::spawn({
                 let to_ws_sender = self.to_ws_sender.clone();
                 let router = Arc::clone(&self.router); // Use Arc for sharing
+                let last_message = Arc::clone(&last_message);
+                let state = Arc::clone(&self.state);
+                let max_message_size = self.max_message_size;
                 async move {
                     while let Some(Ok(msg)) = ws_reader.next().await {
+                        if let Ok(mut last_message) = last_message.lock() {
+                            *last_message = tokio::time::Instant::now();
+                        }
+                        if let Some(limit) = max_message_size.filter(|limit| msg.len() > *limit) {
+                            let middleware_context =
+                                MiddlewareContext::new(Arc::clone(&state), to_ws_sender.clone());
+                            router
+                                .middleware_stack
+                                .on_message_size_exceeded(msg.len(), limit, &middleware_context)
+                                .await;
+                            continue;
+                        }
                         if let Err(e) = router.route(Arc::new(msg), &to_ws_sender).await {
                             warn!(target: "Router", "Error routing message: {:?}", e);
                         }
@@ -435,9 +735,16 @@ impl<S: AppState> ClientRunner<S> {
                 }
             });
 
+            let watchdog_task = self.inactivity_watchdog.map(|watchdog| {
+                tokio::spawn(watchdog.watch(last_message, self.runner_command_tx.clone()))
+            });
+
             // --- Active Session Loop ---
             // This loop runs as long as the connection is stable or no commands are received.
-            let mut writer_task_opt = Some(writer_task);
+            let mut writer_task_opt = Some(WriterTask {
+                handle: writer_task,
+                flush: flush_tx,
+            });
             let mut reader_task_opt: Option<tokio::task::JoinHandle<()>> = Some(reader_task);
 
             let mut session_active = true;
@@ -455,6 +762,16 @@ impl<S: AppState> ClientRunner<S> {
 
                                 info!(target: "Runner", "Disconnect command received.");
 
+                                // Send the queued messages then the close frame while the connection is still open
+                                send_close_frame(&self.to_ws_sender, CLOSE_FRAME_TIMEOUT).await;
+                                if let Some(writer_task) = writer_task_opt.take() {
+                                    writer_task.flush(WRITER_FLUSH_TIMEOUT).await;
+                                }
+                                // The reader stops once the server echoed the close frame
+                                if let Some(reader_task) = &mut reader_task_opt {
+                                    let _ = tokio::time::timeout(CLOSE_FRAME_TIMEOUT, reader_task).await;
+                                }
+
                                 // Execute middleware on_disconnect hook
                                 let middleware_context = MiddlewareContext::new(Arc::clone(&self.state), self.to_ws_sender.clone());
                                 self.router.middleware_stack.on_disconnect(&middleware_context).await;
@@ -467,13 +784,9 @@ impl<S: AppState> ClientRunner<S> {
 
                                 self.state.clear_temporal_data().await;
                                 self.is_hard_disconnect = true;
-                                if let Some(writer_task) = writer_task_opt.take() {
-                                    writer_task.abort();
-                                }
                                 if let Some(reader_task) = reader_task_opt.take() {
                                     reader_task.abort();
                                 }
-                                self.signal.set_disconnected();
                                 session_active = false;
                             },
                             RunnerCommand::Shutdown => {
@@ -481,6 +794,16 @@ impl<S: AppState> ClientRunner<S> {
 
                                 info!(target: "Runner", "Shutdown command received.");
 
+                                // Send the queued messages then the close frame while the connection is still open
+                                send_close_frame(&self.to_ws_sender, CLOSE_FRAME_TIMEOUT).await;
+                                if let Some(writer_task) = writer_task_opt.take() {
+                                    writer_task.flush(WRITER_FLUSH_TIMEOUT).await;
+                                }
+                                // The reader stops once the server echoed the close frame
+                                if let Some(reader_task) = &mut reader_task_opt {
+                                    let _ = tokio::time::timeout(CLOSE_FRAME_TIMEOUT, reader_task).await;
+                                }
+
                                 // Execute middleware on_disconnect hook
                                 let middleware_context = MiddlewareContext::new(Arc::clone(&self.state), self.to_ws_sender.clone());
                                 self.router.middleware_stack.on_disconnect(&middleware_context).await;
@@ -491,13 +814,25 @@ impl<S: AppState> ClientRunner<S> {
                                 }
 
                                 self.shutdown_requested = true;
+                                if let Some(reader_task) = reader_task_opt.take() {
+                                    reader_task.abort();
+                                }
+                                session_active = false;
+                            }
+                            RunnerCommand::Reconnect => {
+                                // 🎯 MIDDLEWARE HOOK: on_disconnect - reconnect requested
+                                info!(target: "Runner", "Reconnect command received.");
+
+                                let middleware_context = MiddlewareContext::new(Arc::clone(&self.state), self.to_ws_sender.clone());
+                                self.router.middleware_stack.on_disconnect(&middleware_context).await;
+
+                                // The connector disconnects by itself before reconnecting.
                                 if let Some(writer_task) = writer_task_opt.take() {
                                     writer_task.abort();
                                 }
                                 if let Some(reader_task) = reader_task_opt.take() {
                                     reader_task.abort();
                                 }
-                                self.signal.set_disconnected();
                                 session_active = false;
                             }
                             _ => {}
@@ -522,16 +857,52 @@ impl<S: AppState> ClientRunner<S> {
                             // Already finished, but abort for completeness
                             reader_task.abort();
                         }
-                        self.signal.set_disconnected();
                         session_active = false;
                         // panic!("Connection lost unexpectedly, exiting session loop. Duration: {:?}", temporal_timer.elapsed());
                     }
                 }
             }
+            if let Some(watchdog_task) = watchdog_task {
+                watchdog_task.abort();
+            }
+            // Every way out of the session loop ends the session, so signal it exactly once here.
+            self.signal.set_disconnected();
         }
 
         info!(target: "Runner", "Shutdown complete.");
     }
+
+    /// Keeps the runner idle while the circuit breaker is open.
+    /// Only a shutdown command can interrupt the wait, other commands are ignored.
+    async fn wait_circuit_breaker(&mut self, breaker: &CircuitBreaker) {
+        let open_for = breaker
+            .remaining_open_duration()
+            .unwrap_or(breaker.config().open_duration);
+        error!(target: "Runner", "Too many connection failures, circuit breaker open for {open_for:?}.");
+        self.signal.set_disconnected();
+
+        let sleep = tokio::time::sleep(open_for);
+        tokio::pin!(sleep);
+        loop {
+            tokio::select! {
+                _ = &mut sleep => break,
+                cmd = self.runner_command_rx.recv() => match cmd {
+                    Ok(RunnerCommand::Shutdown) => {
+                        info!(target: "Runner", "Shutdown requested while the circuit breaker is open.");
+                        self.shutdown_requested = true;
+                        break;
+                    }
+                    Ok(_) => {}
+                    Err(_) => {
+                        // No client left to send commands, just wait for the circuit to half-open.
+                        (&mut sleep).await;
+                        break;
+                    }
+                }
+            }
+        }
+        info!(target: "Runner", "Circuit breaker half-open, allowing one connection attempt.");
+    }
 }
 
 // A proper builder would be used here to configure and create the Client and ClientRunner
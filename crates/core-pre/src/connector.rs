@@ -1,9 +1,11 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use thiserror::Error;
 use tokio::net::TcpStream;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use url::Url;
 
 use crate::traits::AppState;
 
@@ -29,6 +31,16 @@ pub enum ConnectorError {
     HttpRequestBuild(String),
     #[error("Core error: {0}")]
     Core(String),
+    /// The server certificate doesn't match any of the pinned fingerprints.
+    #[error("Certificate pinning failure, server certificate fingerprint: {0}")]
+    CertificatePinning(String),
+}
+
+impl ConnectorError {
+    /// Returns true if connecting again can't succeed, the runner stops instead of retrying.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::CertificatePinning(_))
+    }
 }
 
 pub type ConnectorResult<T> = std::result::Result<T, ConnectorError>;
@@ -50,3 +62,99 @@ pub trait Connector<S: AppState>: Send + Sync {
         self.connect(state).await
     }
 }
+
+/// Type-erased connector sharing a `dyn Connector<S>`, cheap to clone.
+///
+/// Lets the connection strategy be picked at runtime, clients built with
+/// [`ClientBuilder::with_connector`](crate::builder::ClientBuilder::with_connector)
+/// use the shared connector as is instead of wrapping it again.
+pub struct ArcConnector<S: AppState>(pub(crate) Arc<dyn Connector<S>>);
+
+impl<S: AppState> ArcConnector<S> {
+    /// Wraps `connector` in a shareable connector.
+    pub fn new(connector: impl Connector<S> + 'static) -> Self {
+        Self(Arc::new(connector))
+    }
+}
+
+impl<S: AppState> Clone for ArcConnector<S> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<S: AppState> From<Arc<dyn Connector<S>>> for ArcConnector<S> {
+    fn from(connector: Arc<dyn Connector<S>>) -> Self {
+        Self(connector)
+    }
+}
+
+#[async_trait]
+impl<S: AppState> Connector<S> for ArcConnector<S> {
+    async fn connect(&self, state: Arc<S>) -> ConnectorResult<WsStream> {
+        self.0.connect(state).await
+    }
+
+    async fn disconnect(&self) -> ConnectorResult<()> {
+        self.0.disconnect().await
+    }
+
+    async fn reconnect(&self, state: Arc<S>) -> ConnectorResult<WsStream> {
+        self.0.reconnect(state).await
+    }
+}
+
+/// Opens `samples` sequential TCP connections to the host of `url` and returns the median of
+/// their connect time, to compare the latency of several servers before connecting to one.
+///
+/// The port defaults to the one of the scheme (443 for `wss`). Attempts failing or taking
+/// longer than `timeout` are ignored, returns `None` if none succeeded.
+pub async fn probe_latency(url: &Url, samples: u8, timeout: Duration) -> Option<Duration> {
+    let host = url.host_str()?;
+    let port = url.port_or_known_default()?;
+    let mut latencies = Vec::with_capacity(samples as usize);
+    for _ in 0..samples {
+        let start = Instant::now();
+        if let Ok(Ok(_stream)) =
+            tokio::time::timeout(timeout, TcpStream::connect((host, port))).await
+        {
+            latencies.push(start.elapsed());
+        }
+    }
+    latencies.sort();
+    match latencies.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((latencies[len / 2 - 1] + latencies[len / 2]) / 2),
+        len => Some(latencies[len / 2]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_probe_latency() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("ws://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move { while listener.accept().await.is_ok() {} });
+
+        let latency = probe_latency(&url, 5, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert!(latency <= Duration::from_millis(10), "latency: {latency:?}");
+    }
+
+    #[tokio::test]
+    async fn test_probe_latency_unreachable() {
+        // Bind then drop the listener so nothing listens on the port
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("ws://{}", listener.local_addr().unwrap())).unwrap();
+        drop(listener);
+
+        assert_eq!(
+            probe_latency(&url, 3, Duration::from_millis(200)).await,
+            None
+        );
+    }
+}
@@ -44,6 +44,19 @@ pub enum CoreError {
 
     #[error("Failed to execute '{task}' task before the maximum allowed time of '{duration:?}'")]
     TimeoutError { task: String, duration: Duration },
+
+    #[error("Circuit breaker is open, reconnections are blocked for another {0:?}")]
+    CircuitBreakerOpen(Duration),
+
+    /// Returned by a middleware's `on_receive` to stop the message from being routed.
+    #[error("Message dropped by middleware: {0}")]
+    MessageDropped(String),
 }
 
 pub type CoreResult<T> = std::result::Result<T, CoreError>;
+
+impl From<tokio_tungstenite::tungstenite::Error> for CoreError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        CoreError::WebSocket(Box::new(error))
+    }
+}
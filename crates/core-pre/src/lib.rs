@@ -4,6 +4,7 @@
 //!
 //! # Modules
 //! - `builder`: Utilities for constructing core objects.
+//! - `circuit_breaker`: Circuit breaker guarding the reconnection loop.
 //! - `client`: Client-side logic and abstractions.
 //! - `connector`: Connection management and protocols.
 //! - `error`: Error types and handling utilities.
@@ -18,6 +19,7 @@
 //! This crate is intended for internal use by higher-level application crates.
 pub mod builder;
 pub mod callback;
+pub mod circuit_breaker;
 pub mod client;
 pub mod connector;
 pub mod error;
@@ -48,13 +48,21 @@
 //! }
 //! ```
 
-use crate::error::CoreResult;
+use crate::error::{CoreError, CoreResult};
+use crate::statistics::{MessageSize, StatisticsTracker};
 use crate::traits::AppState;
 use async_trait::async_trait;
+use futures_util::{Sink, SinkExt};
 use kanal::AsyncSender;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio_tungstenite::tungstenite::Message;
-use tracing::{error, warn};
+use tracing::{debug, error, warn};
+use xxhash_rust::xxh3::xxh3_64;
 
 /// Context information passed to middleware during message processing.
 ///
@@ -88,11 +96,14 @@ impl<S: AppState> MiddlewareContext<S> {
 /// - [`on_receive`]: Called after a message is received from the WebSocket
 /// - [`on_connect`]: Called when a WebSocket connection is established
 /// - [`on_disconnect`]: Called when a WebSocket connection is lost
+/// - [`on_send_error`]: Called when a message couldn't be sent, to retry it
+/// - [`on_message_size_exceeded`]: Called when a received message is larger than the limit
 ///
 /// # Error Handling
 /// Middleware should be designed to be resilient. If middleware returns an error,
 /// it will be logged but will not prevent the message from being processed or
-/// other middleware from running.
+/// other middleware from running. The only exception is [`CoreError::MessageDropped`]
+/// returned by [`on_receive`], which stops the message before it reaches the handlers.
 #[async_trait]
 pub trait WebSocketMiddleware<S: AppState>: Send + Sync + 'static {
     /// Called before a message is sent to the WebSocket.
@@ -118,6 +129,7 @@ pub trait WebSocketMiddleware<S: AppState>: Send + Sync + 'static {
     ///
     /// # Returns
     /// - `Ok(())` if the middleware processed successfully
+    /// - `Err(CoreError::MessageDropped(_))` to drop the message, it won't be routed
     /// - `Err(_)` if an error occurred (will be logged but not block processing)
     async fn on_receive(
         &self,
@@ -157,6 +169,48 @@ pub trait WebSocketMiddleware<S: AppState>: Send + Sync + 'static {
         Ok(())
     }
 
+    /// Called when a message couldn't be sent to the WebSocket.
+    ///
+    /// # Arguments
+    /// - `message`: The message that failed to be sent
+    /// - `error`: The error returned by the WebSocket
+    /// - `attempt`: Number of failed attempts for this message, starting at 1
+    /// - `context`: Context information including state and sender
+    ///
+    /// # Returns
+    /// - `Some(delay)` to send the message again after `delay`
+    /// - `None` to give up, the message is dropped if no middleware retries it
+    async fn on_send_error(
+        &self,
+        message: &Message,
+        error: &str,
+        attempt: u32,
+        context: &MiddlewareContext<S>,
+    ) -> Option<Duration> {
+        // Default implementation never retries
+        let _ = (message, error, attempt, context);
+        None
+    }
+
+    /// Called when a received message is larger than the limit set with
+    /// [`ClientBuilder::with_max_message_size`](crate::builder::ClientBuilder::with_max_message_size).
+    /// The message is dropped without being routed, [`on_receive`] isn't called for it.
+    ///
+    /// # Arguments
+    /// - `size`: Size of the message in bytes
+    /// - `limit`: The maximum size allowed
+    /// - `context`: Context information including state and sender
+    async fn on_message_size_exceeded(
+        &self,
+        size: usize,
+        limit: usize,
+        context: &MiddlewareContext<S>,
+    ) -> CoreResult<()> {
+        // Default implementation does nothing
+        let _ = (size, limit, context);
+        Ok(())
+    }
+
     /// Called when a connection attempt is made (before actual connection)
     async fn on_connection_attempt(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
         Ok(())
@@ -175,7 +229,7 @@ pub trait WebSocketMiddleware<S: AppState>: Send + Sync + 'static {
 /// A composable stack of middleware layers.
 ///
 /// This struct holds a collection of middleware that will be executed in order.
-/// Middleware are executed in the order they are added to the stack.
+/// Middleware are executed by ascending priority, then in the order they are added to the stack.
 ///
 /// # Example
 /// ```rust,no_run
@@ -203,7 +257,8 @@ pub trait WebSocketMiddleware<S: AppState>: Send + Sync + 'static {
 /// stack.add_layer(Box::new(StatisticsMiddleware::new()));
 /// ```
 pub struct MiddlewareStack<S: AppState> {
-    layers: Vec<Box<dyn WebSocketMiddleware<S> + Send + Sync>>,
+    /// Layers with their priority, kept sorted by ascending priority.
+    layers: Vec<(i32, Box<dyn WebSocketMiddleware<S> + Send + Sync>)>,
 }
 
 impl<S: AppState> MiddlewareStack<S> {
@@ -212,11 +267,23 @@ impl<S: AppState> MiddlewareStack<S> {
         Self { layers: Vec::new() }
     }
 
-    /// Adds a middleware layer to the stack.
+    /// Adds a middleware layer to the stack with the default priority `0`.
     ///
-    /// Middleware will be executed in the order they are added.
+    /// Middleware with the same priority will be executed in the order they are added.
     pub fn add_layer(&mut self, middleware: Box<dyn WebSocketMiddleware<S> + Send + Sync>) {
-        self.layers.push(middleware);
+        self.add_layer_prioritized(middleware, 0);
+    }
+
+    /// Adds a middleware layer to the stack with the given priority.
+    ///
+    /// Lower priorities run first, layers with the same priority keep their insertion order.
+    pub fn add_layer_prioritized(
+        &mut self,
+        middleware: Box<dyn WebSocketMiddleware<S> + Send + Sync>,
+        priority: i32,
+    ) {
+        let index = self.layers.partition_point(|(p, _)| *p <= priority);
+        self.layers.insert(index, (priority, middleware));
     }
 
     /// Executes all middleware for an outgoing message.
@@ -229,7 +296,7 @@ impl<S: AppState> MiddlewareStack<S> {
     /// All middleware will be executed even if some fail. Errors are logged but
     /// do not prevent other middleware from running.
     pub async fn on_send(&self, message: &Message, context: &MiddlewareContext<S>) {
-        for (index, middleware) in self.layers.iter().enumerate() {
+        for (index, (_, middleware)) in self.layers.iter().enumerate() {
             if let Err(e) = middleware.on_send(message, context).await {
                 error!(
                     target: "Middleware",
@@ -246,19 +313,35 @@ impl<S: AppState> MiddlewareStack<S> {
     /// - `message`: The message that was received
     /// - `context`: Context information
     ///
+    /// # Returns
+    /// `false` if a layer dropped the message, in which case it must not be routed.
+    ///
     /// # Behavior
     /// All middleware will be executed even if some fail. Errors are logged but
-    /// do not prevent other middleware from running.
-    pub async fn on_receive(&self, message: &Message, context: &MiddlewareContext<S>) {
-        for (index, middleware) in self.layers.iter().enumerate() {
-            if let Err(e) = middleware.on_receive(message, context).await {
-                error!(
-                    target: "Middleware",
-                    "Error in middleware layer {} on_receive: {:?}",
-                    index, e
-                );
+    /// do not prevent other middleware from running, except for
+    /// [`CoreError::MessageDropped`] which skips the following layers.
+    pub async fn on_receive(&self, message: &Message, context: &MiddlewareContext<S>) -> bool {
+        for (index, (_, middleware)) in self.layers.iter().enumerate() {
+            match middleware.on_receive(message, context).await {
+                Ok(()) => {}
+                Err(CoreError::MessageDropped(reason)) => {
+                    debug!(
+                        target: "Middleware",
+                        "Middleware layer {} dropped the message: {}",
+                        index, reason
+                    );
+                    return false;
+                }
+                Err(e) => {
+                    error!(
+                        target: "Middleware",
+                        "Error in middleware layer {} on_receive: {:?}",
+                        index, e
+                    );
+                }
             }
         }
+        true
     }
 
     /// Executes all middleware for connection establishment.
@@ -270,7 +353,7 @@ impl<S: AppState> MiddlewareStack<S> {
     /// All middleware will be executed even if some fail. Errors are logged but
     /// do not prevent other middleware from running.
     pub async fn on_connect(&self, context: &MiddlewareContext<S>) {
-        for (index, middleware) in self.layers.iter().enumerate() {
+        for (index, (_, middleware)) in self.layers.iter().enumerate() {
             if let Err(e) = middleware.on_connect(context).await {
                 error!(
                     target: "Middleware",
@@ -290,7 +373,7 @@ impl<S: AppState> MiddlewareStack<S> {
     /// All middleware will be executed even if some fail. Errors are logged but
     /// do not prevent other middleware from running.
     pub async fn on_disconnect(&self, context: &MiddlewareContext<S>) {
-        for (index, middleware) in self.layers.iter().enumerate() {
+        for (index, (_, middleware)) in self.layers.iter().enumerate() {
             if let Err(e) = middleware.on_disconnect(context).await {
                 warn!(
                     target: "Middleware",
@@ -301,9 +384,87 @@ impl<S: AppState> MiddlewareStack<S> {
         }
     }
 
+    /// Executes all middleware for a message that couldn't be sent.
+    ///
+    /// # Returns
+    /// The delay asked by the first middleware retrying the message, `None` if none does.
+    pub async fn on_send_error(
+        &self,
+        message: &Message,
+        error: &str,
+        attempt: u32,
+        context: &MiddlewareContext<S>,
+    ) -> Option<Duration> {
+        let mut retry = None;
+        for (_, middleware) in &self.layers {
+            let delay = middleware
+                .on_send_error(message, error, attempt, context)
+                .await;
+            retry = retry.or(delay);
+        }
+        retry
+    }
+
+    /// Executes all middleware for a received message larger than the limit.
+    ///
+    /// # Behavior
+    /// All middleware will be executed even if some fail. Errors are logged but
+    /// do not prevent other middleware from running.
+    pub async fn on_message_size_exceeded(
+        &self,
+        size: usize,
+        limit: usize,
+        context: &MiddlewareContext<S>,
+    ) {
+        for (index, (_, middleware)) in self.layers.iter().enumerate() {
+            if let Err(e) = middleware
+                .on_message_size_exceeded(size, limit, context)
+                .await
+            {
+                error!(
+                    target: "Middleware",
+                    "Error in middleware layer {} on_message_size_exceeded: {:?}",
+                    index, e
+                );
+            }
+        }
+    }
+
+    /// Sends `message` through `sink`, running the `on_send` hooks once and the
+    /// `on_send_error` hooks after every failed attempt.
+    ///
+    /// # Returns
+    /// The error of the last attempt if no middleware retries the message anymore.
+    pub async fn send<W>(
+        &self,
+        sink: &mut W,
+        message: Message,
+        context: &MiddlewareContext<S>,
+    ) -> Result<(), W::Error>
+    where
+        W: Sink<Message> + Unpin,
+        W::Error: Display,
+    {
+        self.on_send(&message, context).await;
+        let mut attempt = 0;
+        loop {
+            let Err(e) = sink.send(message.clone()).await else {
+                return Ok(());
+            };
+            attempt += 1;
+            match self
+                .on_send_error(&message, &e.to_string(), attempt, context)
+                .await
+            {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(e),
+            }
+        }
+    }
+
     /// Record a connection attempt across all middleware
     pub async fn record_connection_attempt(&self, context: &MiddlewareContext<S>) {
-        for (index, middleware) in self.layers.iter().enumerate() {
+        for (index, (_, middleware)) in self.layers.iter().enumerate() {
             if let Err(e) = middleware.on_connection_attempt(context).await {
                 warn!(
                     target: "Middleware",
@@ -320,7 +481,7 @@ impl<S: AppState> MiddlewareStack<S> {
         context: &MiddlewareContext<S>,
         reason: Option<String>,
     ) {
-        for (index, middleware) in self.layers.iter().enumerate() {
+        for (index, (_, middleware)) in self.layers.iter().enumerate() {
             if let Err(e) = middleware
                 .on_connection_failure(context, reason.clone())
                 .await
@@ -399,6 +560,16 @@ impl<S: AppState> MiddlewareStackBuilder<S> {
         self
     }
 
+    /// Adds a middleware layer to the stack with the given priority, lower priorities run first.
+    pub fn layer_prioritized(
+        mut self,
+        middleware: Box<dyn WebSocketMiddleware<S>>,
+        priority: i32,
+    ) -> Self {
+        self.stack.add_layer_prioritized(middleware, priority);
+        self
+    }
+
     /// Builds and returns the middleware stack.
     pub fn build(self) -> MiddlewareStack<S> {
         self.stack
@@ -411,10 +582,341 @@ impl<S: AppState> Default for MiddlewareStackBuilder<S> {
     }
 }
 
+/// Middleware retrying the messages that couldn't be sent, with an exponential backoff.
+///
+/// The first retry waits `base_delay`, every following one waits twice as long as the
+/// previous one, up to `max_delay`. Once `max_retries` retries failed the error is propagated
+/// as if there was no retry.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use binary_options_tools_core_pre::middleware::RetryMiddleware;
+/// # use binary_options_tools_core_pre::traits::AppState;
+/// # #[derive(Debug)]
+/// # struct MyState;
+/// # #[async_trait::async_trait]
+/// # impl AppState for MyState {
+/// #     async fn clear_temporal_data(&self) {}
+/// # }
+///
+/// let retry = RetryMiddleware::<MyState>::new(3, Duration::from_millis(100))
+///     .with_max_delay(Duration::from_secs(1));
+/// // builder.with_middleware(Box::new(retry))
+/// ```
+pub struct RetryMiddleware<S: AppState> {
+    max_retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    /// Messages waiting to be sent again
+    queue: Mutex<VecDeque<Message>>,
+    _phantom: PhantomData<S>,
+}
+
+impl<S: AppState> RetryMiddleware<S> {
+    /// Creates a middleware retrying every message up to `max_retries` times.
+    pub fn new(max_retries: u32, base_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            base_delay,
+            max_delay: Duration::from_secs(30),
+            queue: Mutex::new(VecDeque::new()),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Sets the longest delay between two attempts, 30 seconds by default.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Delay before the retry following the failed `attempt`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Number of messages waiting to be sent again.
+    pub fn pending(&self) -> usize {
+        self.queue
+            .lock()
+            .map(|queue| queue.len())
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl<S: AppState> WebSocketMiddleware<S> for RetryMiddleware<S> {
+    async fn on_send(&self, _message: &Message, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        // Messages are sent one at a time, the previous ones were either delivered or dropped
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.clear();
+        }
+        Ok(())
+    }
+
+    async fn on_send_error(
+        &self,
+        message: &Message,
+        error: &str,
+        attempt: u32,
+        _context: &MiddlewareContext<S>,
+    ) -> Option<Duration> {
+        let mut queue = self.queue.lock().ok()?;
+        if attempt > self.max_retries {
+            warn!(target: "RetryMiddleware", "Giving up sending message after {} attempts: {}", attempt, error);
+            queue.retain(|queued| queued != message);
+            return None;
+        }
+        if !queue.contains(message) {
+            queue.push_back(message.clone());
+        }
+        let delay = self.delay(attempt);
+        debug!(target: "RetryMiddleware", "Failed to send message ({}), retrying in {:?}", error, delay);
+        Some(delay)
+    }
+}
+
+/// Middleware dropping the messages already received within a sliding time window.
+///
+/// Messages are identified by the xxHash of their content, a text or binary message with the
+/// same content as one first received less than `window` ago never reaches the handlers.
+/// Control frames are never dropped.
+///
+/// Socket.io binary placeholder headers (`451-["event",{"_placeholder":true,"num":0}]`) are
+/// never deduplicated either: servers like PocketOption send byte-identical headers before
+/// every binary payload, and dropping one would detach its payload from the event name.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use binary_options_tools_core_pre::middleware::MessageDeduplicator;
+/// # use binary_options_tools_core_pre::traits::AppState;
+/// # #[derive(Debug)]
+/// # struct MyState;
+/// # #[async_trait::async_trait]
+/// # impl AppState for MyState {
+/// #     async fn clear_temporal_data(&self) {}
+/// # }
+///
+/// let deduplicator = MessageDeduplicator::<MyState>::new(Duration::from_secs(1));
+/// let dropped = deduplicator.counter();
+/// // builder.with_middleware(Box::new(deduplicator))
+/// ```
+pub struct MessageDeduplicator<S: AppState> {
+    window: Duration,
+    /// Hash of the recent messages and the first time they were received
+    seen: Mutex<HashMap<u64, Instant>>,
+    duplicates_dropped: Arc<AtomicU64>,
+    _phantom: PhantomData<S>,
+}
+
+impl<S: AppState> MessageDeduplicator<S> {
+    /// Creates a middleware dropping the messages repeated within `window`.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: Mutex::new(HashMap::new()),
+            duplicates_dropped: Arc::new(AtomicU64::new(0)),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Number of messages dropped so far.
+    pub fn duplicates_dropped(&self) -> u64 {
+        self.duplicates_dropped.load(Ordering::Relaxed)
+    }
+
+    /// Shared handle to the dropped messages counter, still readable once the middleware
+    /// was moved into the client.
+    pub fn counter(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.duplicates_dropped)
+    }
+}
+
+#[async_trait]
+impl<S: AppState> WebSocketMiddleware<S> for MessageDeduplicator<S> {
+    async fn on_receive(
+        &self,
+        message: &Message,
+        _context: &MiddlewareContext<S>,
+    ) -> CoreResult<()> {
+        let hash = match message {
+            Message::Text(text) if text.contains(r#""_placeholder":true"#) => return Ok(()),
+            Message::Text(text) => xxh3_64(text.as_bytes()),
+            Message::Binary(data) => xxh3_64(data),
+            _ => return Ok(()),
+        };
+        let now = Instant::now();
+        let mut seen = self
+            .seen
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))?;
+        seen.retain(|_, received| now.duration_since(*received) < self.window);
+        // Keep the first-seen time, so a message repeated more often than `window` still
+        // gets through once per window
+        if *seen.entry(hash).or_insert(now) != now {
+            self.duplicates_dropped.fetch_add(1, Ordering::Relaxed);
+            return Err(CoreError::MessageDropped(format!(
+                "duplicate received within {:?}",
+                self.window
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Middleware logging the messages dropped for being larger than the limit set with
+/// [`ClientBuilder::with_max_message_size`](crate::builder::ClientBuilder::with_max_message_size).
+///
+/// The limit itself is enforced by the client before routing, this middleware only reports
+/// the oversized frames, which usually come from a malformed or malicious server.
+///
+/// # Example
+/// ```rust,no_run
+/// use binary_options_tools_core_pre::middleware::MaxMessageSizeMiddleware;
+/// # use binary_options_tools_core_pre::traits::AppState;
+/// # #[derive(Debug)]
+/// # struct MyState;
+/// # #[async_trait::async_trait]
+/// # impl AppState for MyState {
+/// #     async fn clear_temporal_data(&self) {}
+/// # }
+///
+/// let middleware = MaxMessageSizeMiddleware::<MyState>::new();
+/// let dropped = middleware.counter();
+/// // builder.with_max_message_size(1024 * 1024).with_middleware(Box::new(middleware))
+/// ```
+pub struct MaxMessageSizeMiddleware<S: AppState> {
+    oversized_messages: Arc<AtomicU64>,
+    _phantom: PhantomData<S>,
+}
+
+impl<S: AppState> MaxMessageSizeMiddleware<S> {
+    /// Creates a middleware counting the oversized messages.
+    pub fn new() -> Self {
+        Self {
+            oversized_messages: Arc::new(AtomicU64::new(0)),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Number of oversized messages dropped so far.
+    pub fn oversized_messages(&self) -> u64 {
+        self.oversized_messages.load(Ordering::Relaxed)
+    }
+
+    /// Shared handle to the oversized messages counter, still readable once the middleware
+    /// was moved into the client.
+    pub fn counter(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.oversized_messages)
+    }
+}
+
+impl<S: AppState> Default for MaxMessageSizeMiddleware<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<S: AppState> WebSocketMiddleware<S> for MaxMessageSizeMiddleware<S> {
+    async fn on_message_size_exceeded(
+        &self,
+        size: usize,
+        limit: usize,
+        _context: &MiddlewareContext<S>,
+    ) -> CoreResult<()> {
+        self.oversized_messages.fetch_add(1, Ordering::Relaxed);
+        warn!(
+            target: "MaxMessageSizeMiddleware",
+            "Dropped a {} bytes message, larger than the {} bytes limit", size, limit
+        );
+        Ok(())
+    }
+}
+
+/// Records the connections and the messages of the client in a shared [`StatisticsTracker`].
+pub struct StatisticsMiddleware<S: AppState> {
+    stats: Arc<StatisticsTracker>,
+    _phantom: PhantomData<S>,
+}
+
+impl<S: AppState> StatisticsMiddleware<S> {
+    /// Creates a middleware recording into `stats`.
+    pub fn new(stats: Arc<StatisticsTracker>) -> Self {
+        Self {
+            stats,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<S: AppState> WebSocketMiddleware<S> for StatisticsMiddleware<S> {
+    async fn on_connection_attempt(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        self.stats.record_connection_attempt().await;
+        debug!(target: "StatisticsMiddleware", "Connection attempt recorded");
+        Ok(())
+    }
+
+    async fn on_connection_failure(
+        &self,
+        _context: &MiddlewareContext<S>,
+        reason: Option<String>,
+    ) -> CoreResult<()> {
+        self.stats.record_connection_failure(reason).await;
+        debug!(target: "StatisticsMiddleware", "Connection failure recorded");
+        Ok(())
+    }
+
+    async fn on_connect(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        self.stats.record_connection_success().await;
+        debug!(target: "StatisticsMiddleware", "Connection established");
+        Ok(())
+    }
+
+    async fn on_disconnect(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        self.stats
+            .record_disconnection(Some("Connection lost".to_string()))
+            .await;
+        debug!(target: "StatisticsMiddleware", "Connection lost");
+        Ok(())
+    }
+
+    async fn on_send(&self, message: &Message, _context: &MiddlewareContext<S>) -> CoreResult<()> {
+        self.stats.record_message_sent(message).await;
+        debug!(target: "StatisticsMiddleware", "Message sent: {} bytes", message.size_bytes());
+        Ok(())
+    }
+
+    async fn on_receive(
+        &self,
+        message: &Message,
+        _context: &MiddlewareContext<S>,
+    ) -> CoreResult<()> {
+        self.stats.record_message_received(message).await;
+        debug!(target: "StatisticsMiddleware", "Message received: {} bytes", message.size_bytes());
+        Ok(())
+    }
+
+    async fn on_message_size_exceeded(
+        &self,
+        size: usize,
+        _limit: usize,
+        _context: &MiddlewareContext<S>,
+    ) -> CoreResult<()> {
+        self.stats.record_oversized_message();
+        debug!(target: "StatisticsMiddleware", "Oversized message dropped: {} bytes", size);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::atomic::{AtomicU64, Ordering};
+    use crate::client::Router;
 
     #[derive(Debug)]
     struct TestState;
@@ -496,4 +998,69 @@ mod tests {
 
         assert_eq!(stack.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_message_deduplicator() {
+        let (sender, _receiver) = kanal::unbounded_async();
+        let (tx, rx) = kanal::unbounded_async();
+        let deduplicator = MessageDeduplicator::<TestState>::new(Duration::from_secs(10));
+        let dropped = deduplicator.counter();
+
+        let mut router = Router::new(Arc::new(TestState));
+        router.middleware_stack.add_layer(Box::new(deduplicator));
+        router.add_lightweight_rule("all", Box::new(|_: &Message| true), tx);
+
+        let messages: Vec<_> = (0..100)
+            .map(|i| Arc::new(Message::text(format!("42[\"tick\",{{\"id\":{i}}}]"))))
+            .collect();
+        for _ in 0..2 {
+            for message in &messages {
+                router.route(Arc::clone(message), &sender).await.unwrap();
+            }
+        }
+
+        assert_eq!(dropped.load(Ordering::Relaxed), 100);
+        assert_eq!(rx.len(), 100);
+        assert_eq!(router.metrics().total_routed, 100);
+    }
+
+    #[tokio::test]
+    async fn test_message_deduplicator_window() {
+        let (sender, _receiver) = kanal::bounded_async(10);
+        let context = MiddlewareContext::new(Arc::new(TestState), sender);
+        let deduplicator = MessageDeduplicator::<TestState>::new(Duration::from_millis(50));
+        let message = Message::text("duplicate");
+
+        assert!(deduplicator.on_receive(&message, &context).await.is_ok());
+        assert!(matches!(
+            deduplicator.on_receive(&message, &context).await,
+            Err(CoreError::MessageDropped(_))
+        ));
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(deduplicator.on_receive(&message, &context).await.is_ok());
+        assert_eq!(deduplicator.duplicates_dropped(), 1);
+
+        // Repeating faster than the window doesn't keep the message dropped forever
+        let deduplicator = MessageDeduplicator::<TestState>::new(Duration::from_millis(100));
+        assert!(deduplicator.on_receive(&message, &context).await.is_ok());
+        for _ in 0..2 {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            assert!(deduplicator.on_receive(&message, &context).await.is_err());
+        }
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(deduplicator.on_receive(&message, &context).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_message_deduplicator_keeps_placeholders() {
+        let (sender, _receiver) = kanal::bounded_async(10);
+        let context = MiddlewareContext::new(Arc::new(TestState), sender);
+        let deduplicator = MessageDeduplicator::<TestState>::new(Duration::from_secs(10));
+        let header = Message::text(r#"451-["updateStream",{"_placeholder":true,"num":0}]"#);
+
+        for _ in 0..3 {
+            assert!(deduplicator.on_receive(&header, &context).await.is_ok());
+        }
+        assert_eq!(deduplicator.duplicates_dropped(), 0);
+    }
 }
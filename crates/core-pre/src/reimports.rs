@@ -1,6 +1,11 @@
 pub use tokio_tungstenite::{
-    Connector, MaybeTlsStream, WebSocketStream, connect_async_tls_with_config,
-    tungstenite::{Bytes, Message, handshake::client::generate_key, http::Request},
+    Connector, MaybeTlsStream, WebSocketStream, client_async_tls_with_config,
+    connect_async_tls_with_config,
+    tungstenite::{
+        Bytes, Message,
+        handshake::client::generate_key,
+        http::{HeaderMap, HeaderName, HeaderValue, Request},
+    },
 };
 
 pub use kanal::{AsyncReceiver, AsyncSender, bounded_async};
@@ -1,45 +1,95 @@
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use tokio::sync::Notify;
+use tokio::sync::watch;
 
-#[derive(Clone, Default, Debug)]
+use crate::connector::ConnectorError;
+
+#[derive(Clone, Debug)]
 pub struct Signals {
-    is_connected: Arc<AtomicBool>,
-    connected_notify: Arc<Notify>,
-    disconnected_notify: Arc<Notify>,
+    connection: Arc<watch::Sender<bool>>,
+    /// Error of the last failed connection attempt, cleared once connected.
+    connection_failure: Arc<watch::Sender<Option<Arc<ConnectorError>>>>,
+}
+
+impl Default for Signals {
+    fn default() -> Self {
+        Self {
+            connection: Arc::new(watch::Sender::new(false)),
+            connection_failure: Arc::new(watch::Sender::new(None)),
+        }
+    }
 }
 
 impl Signals {
     /// Call this when a connection is established.
     pub fn set_connected(&self) {
-        self.is_connected.store(true, Ordering::SeqCst);
-        self.connected_notify.notify_waiters();
+        self.connection_failure.send_replace(None);
+        self.set_state(true);
+    }
+
+    /// Call this when a connection attempt fails.
+    pub fn set_connection_failure(&self, error: ConnectorError) {
+        self.connection_failure.send_replace(Some(Arc::new(error)));
+    }
+
+    /// Returns the error of the last failed connection attempt, `None` once connected.
+    pub fn connection_failure(&self) -> Option<Arc<ConnectorError>> {
+        self.connection_failure.borrow().clone()
+    }
+
+    /// Returns a receiver tracking the failed connection attempts.
+    /// `changed()` resolves on every failed attempt and once the connection is established.
+    pub fn connection_failure_watch(&self) -> watch::Receiver<Option<Arc<ConnectorError>>> {
+        self.connection_failure.subscribe()
     }
 
     /// Call this when a disconnection occurs.
+    /// Only the transition from connected to disconnected wakes up the waiters.
     pub fn set_disconnected(&self) {
-        self.is_connected.store(false, Ordering::SeqCst);
-        self.disconnected_notify.notify_waiters();
+        self.set_state(false);
+    }
+
+    /// Updates the connection state, receivers are only notified if it actually changed.
+    fn set_state(&self, connected: bool) {
+        self.connection.send_if_modified(|current| {
+            let changed = *current != connected;
+            *current = connected;
+            changed
+        });
     }
 
     /// Check current connection state.
     pub fn is_connected(&self) -> bool {
-        self.is_connected.load(Ordering::SeqCst)
+        *self.connection.borrow()
+    }
+
+    /// Returns a receiver tracking the connection state.
+    /// The current value is `true` while connected, `changed()` resolves on every connect or disconnect.
+    pub fn connection_watch(&self) -> watch::Receiver<bool> {
+        self.connection.subscribe()
     }
 
     /// Wait for the next connection event.
     pub async fn wait_connected(&self) {
-        // Only wait if not already connected
-        if !self.is_connected() {
-            self.connected_notify.notified().await;
-        }
+        // The sender lives as long as `self`, so waiting can't fail
+        let _ = self
+            .connection_watch()
+            .wait_for(|connected| *connected)
+            .await;
     }
 
     /// Wait for the next disconnection event.
     pub async fn wait_disconnected(&self) {
-        // Only wait if currently connected
-        if self.is_connected() {
-            self.disconnected_notify.notified().await;
-        }
+        let _ = self
+            .connection_watch()
+            .wait_for(|connected| !*connected)
+            .await;
+    }
+
+    /// Wait for the next connect or disconnect event.
+    /// Returns `true` if the new state is connected.
+    pub async fn wait_for_state_change(&self) -> bool {
+        let mut receiver = self.connection_watch();
+        let _ = receiver.changed().await;
+        *receiver.borrow_and_update()
     }
 }
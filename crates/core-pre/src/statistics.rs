@@ -6,8 +6,34 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tokio_tungstenite::tungstenite::Message;
 
+use crate::error::CoreResult;
+
+/// Size in bytes of the payload of a message, as counted in `bytes_sent` and `bytes_received`.
+pub trait MessageSize {
+    fn size_bytes(&self) -> u64;
+}
+
+impl MessageSize for Message {
+    fn size_bytes(&self) -> u64 {
+        match self {
+            Message::Text(text) => text.len() as u64,
+            Message::Binary(data) => data.len() as u64,
+            Message::Ping(data) => data.len() as u64,
+            Message::Pong(data) => data.len() as u64,
+            Message::Close(_) => 0,
+            Message::Frame(_) => 0,
+        }
+    }
+}
+
+impl<T: MessageSize + ?Sized> MessageSize for Arc<T> {
+    fn size_bytes(&self) -> u64 {
+        self.as_ref().size_bytes()
+    }
+}
+
 /// Comprehensive connection statistics for WebSocket testing
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ConnectionStats {
     /// Total number of connection attempts
     pub connection_attempts: u64,
@@ -45,13 +71,16 @@ pub struct ConnectionStats {
     pub avg_bytes_sent_per_second: f64,
     /// Average bytes per second (received)
     pub avg_bytes_received_per_second: f64,
+    /// Received messages dropped for being larger than the size limit
+    #[serde(default)]
+    pub oversized_messages_dropped: u64,
     /// Is currently connected
     pub is_connected: bool,
     /// Connection history (last 10 connections)
     pub connection_history: Vec<ConnectionEvent>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ConnectionEvent {
     pub event_type: ConnectionEventType,
     pub timestamp: u64,           // Unix timestamp in milliseconds
@@ -59,7 +88,7 @@ pub struct ConnectionEvent {
     pub reason: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ConnectionEventType {
     ConnectionAttempt,
     ConnectionSuccess,
@@ -91,12 +120,46 @@ impl Default for ConnectionStats {
             avg_messages_received_per_second: 0.0,
             avg_bytes_sent_per_second: 0.0,
             avg_bytes_received_per_second: 0.0,
+            oversized_messages_dropped: 0,
             is_connected: false,
             connection_history: Vec::new(),
         }
     }
 }
 
+/// Merges every statistics of the iterator with [`ConnectionStats::merge`], the default
+/// statistics are returned for an empty iterator.
+impl FromIterator<ConnectionStats> for ConnectionStats {
+    fn from_iter<I: IntoIterator<Item = ConnectionStats>>(iter: I) -> Self {
+        iter.into_iter()
+            .reduce(|merged, stats| merged.merge(&stats))
+            .unwrap_or_default()
+    }
+}
+
+/// Average of `a` and `b` weighted by the number of samples behind each.
+fn weighted_average(a: f64, a_weight: u64, b: f64, b_weight: u64) -> f64 {
+    let total = a_weight + b_weight;
+    if total == 0 {
+        (a + b) / 2.0
+    } else {
+        (a * a_weight as f64 + b * b_weight as f64) / total as f64
+    }
+}
+
+/// Rate of `a_count + b_count` events over the time both rates were measured on.
+fn combined_rate(a_count: u64, a_rate: f64, b_count: u64, b_rate: f64) -> f64 {
+    let seconds = |count: u64, rate: f64| {
+        if rate > 0.0 { count as f64 / rate } else { 0.0 }
+    };
+    let total_seconds = seconds(a_count, a_rate) + seconds(b_count, b_rate);
+    if total_seconds > 0.0 {
+        (a_count + b_count) as f64 / total_seconds
+    } else {
+        0.0
+    }
+}
+
 /// Internal statistics tracker with atomic operations for performance
 pub struct StatisticsTracker {
     // Atomic counters for thread-safe access
@@ -109,6 +172,7 @@ pub struct StatisticsTracker {
     messages_received: AtomicU64,
     bytes_sent: AtomicU64,
     bytes_received: AtomicU64,
+    oversized_messages_dropped: AtomicU64,
 
     // Connection timing
     start_time: Instant,
@@ -127,7 +191,93 @@ pub struct StatisticsTracker {
     event_history: RwLock<Vec<ConnectionEvent>>,
 }
 
+/// Maximum number of events kept in the history of merged statistics, like the tracker does.
+const MAX_MERGED_EVENTS: usize = 100;
+
 impl ConnectionStats {
+    /// Parses statistics exported with [`StatisticsTracker::export_json`], e.g. by another process.
+    pub fn from_json(s: &str) -> CoreResult<Self> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// Combines the statistics of two processes or sessions.
+    ///
+    /// Counters are summed, the average latency is weighted by the successful connections and
+    /// the rates are the combined counts over the combined observation time. The longest
+    /// uptimes and the most recent disconnection are kept, and the result is connected if
+    /// either side is. The histories are interleaved by timestamp, keeping the last 100 events.
+    pub fn merge(&self, other: &ConnectionStats) -> ConnectionStats {
+        let latest =
+            |stats: &ConnectionStats| stats.connection_history.last().map(|event| event.timestamp);
+        let mut connection_history: Vec<ConnectionEvent> = self
+            .connection_history
+            .iter()
+            .chain(&other.connection_history)
+            .cloned()
+            .collect();
+        connection_history.sort_by_key(|event| event.timestamp);
+        let excess = connection_history.len().saturating_sub(MAX_MERGED_EVENTS);
+        connection_history.drain(..excess);
+
+        ConnectionStats {
+            connection_attempts: self.connection_attempts + other.connection_attempts,
+            successful_connections: self.successful_connections + other.successful_connections,
+            failed_connections: self.failed_connections + other.failed_connections,
+            disconnections: self.disconnections + other.disconnections,
+            reconnections: self.reconnections + other.reconnections,
+            avg_connection_latency_ms: weighted_average(
+                self.avg_connection_latency_ms,
+                self.successful_connections,
+                other.avg_connection_latency_ms,
+                other.successful_connections,
+            ),
+            last_connection_latency_ms: if latest(other) > latest(self) {
+                other.last_connection_latency_ms
+            } else {
+                self.last_connection_latency_ms
+            },
+            total_uptime_seconds: self.total_uptime_seconds.max(other.total_uptime_seconds),
+            current_uptime_seconds: self
+                .current_uptime_seconds
+                .max(other.current_uptime_seconds),
+            time_since_last_disconnection_seconds: self
+                .time_since_last_disconnection_seconds
+                .min(other.time_since_last_disconnection_seconds),
+            messages_sent: self.messages_sent + other.messages_sent,
+            messages_received: self.messages_received + other.messages_received,
+            bytes_sent: self.bytes_sent + other.bytes_sent,
+            bytes_received: self.bytes_received + other.bytes_received,
+            avg_messages_sent_per_second: combined_rate(
+                self.messages_sent,
+                self.avg_messages_sent_per_second,
+                other.messages_sent,
+                other.avg_messages_sent_per_second,
+            ),
+            avg_messages_received_per_second: combined_rate(
+                self.messages_received,
+                self.avg_messages_received_per_second,
+                other.messages_received,
+                other.avg_messages_received_per_second,
+            ),
+            avg_bytes_sent_per_second: combined_rate(
+                self.bytes_sent,
+                self.avg_bytes_sent_per_second,
+                other.bytes_sent,
+                other.avg_bytes_sent_per_second,
+            ),
+            avg_bytes_received_per_second: combined_rate(
+                self.bytes_received,
+                self.avg_bytes_received_per_second,
+                other.bytes_received,
+                other.avg_bytes_received_per_second,
+            ),
+            oversized_messages_dropped: self.oversized_messages_dropped
+                + other.oversized_messages_dropped,
+            is_connected: self.is_connected || other.is_connected,
+            connection_history,
+        }
+    }
+
     /// Generate a comprehensive, user-readable summary of the connection statistics
     pub fn summary(&self) -> String {
         let mut summary = String::new();
@@ -250,6 +400,12 @@ impl ConnectionStats {
                 self.messages_received, self.avg_messages_received_per_second
             )
         ));
+        if self.oversized_messages_dropped > 0 {
+            summary.push_str(&format!(
+                "║   • Oversized Messages Dropped: {:<43} ║\n",
+                self.oversized_messages_dropped
+            ));
+        }
 
         // Data Transfer Statistics
         summary.push_str(
@@ -510,6 +666,7 @@ impl StatisticsTracker {
             messages_received: AtomicU64::new(0),
             bytes_sent: AtomicU64::new(0),
             bytes_received: AtomicU64::new(0),
+            oversized_messages_dropped: AtomicU64::new(0),
             start_time: Instant::now(),
             last_connection_attempt: RwLock::new(None),
             current_connection_start: RwLock::new(None),
@@ -614,7 +771,7 @@ impl StatisticsTracker {
     pub async fn record_message_sent(&self, message: &Message) {
         self.messages_sent.fetch_add(1, Ordering::SeqCst);
         self.bytes_sent
-            .fetch_add(Self::message_size(message), Ordering::SeqCst);
+            .fetch_add(message.size_bytes(), Ordering::SeqCst);
 
         self.add_event(ConnectionEvent {
             event_type: ConnectionEventType::MessageSent,
@@ -628,7 +785,7 @@ impl StatisticsTracker {
     pub async fn record_message_received(&self, message: &Message) {
         self.messages_received.fetch_add(1, Ordering::SeqCst);
         self.bytes_received
-            .fetch_add(Self::message_size(message), Ordering::SeqCst);
+            .fetch_add(message.size_bytes(), Ordering::SeqCst);
 
         self.add_event(ConnectionEvent {
             event_type: ConnectionEventType::MessageReceived,
@@ -639,6 +796,18 @@ impl StatisticsTracker {
         .await;
     }
 
+    pub fn record_oversized_message(&self) {
+        self.oversized_messages_dropped
+            .fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Serializes the current statistics to JSON, they can be read back with
+    /// [`ConnectionStats::from_json`].
+    pub async fn export_json(&self) -> String {
+        serde_json::to_string(&self.get_stats().await)
+            .expect("ConnectionStats only contains serializable fields")
+    }
+
     pub async fn get_stats(&self) -> ConnectionStats {
         let now = Instant::now();
         let elapsed = now.duration_since(self.start_time);
@@ -713,22 +882,12 @@ impl StatisticsTracker {
             } else {
                 0.0
             },
+            oversized_messages_dropped: self.oversized_messages_dropped.load(Ordering::SeqCst),
             is_connected: self.is_connected.load(Ordering::SeqCst),
             connection_history: self.event_history.read().await.clone(),
         }
     }
 
-    fn message_size(message: &Message) -> u64 {
-        match message {
-            Message::Text(text) => text.len() as u64,
-            Message::Binary(data) => data.len() as u64,
-            Message::Ping(data) => data.len() as u64,
-            Message::Pong(data) => data.len() as u64,
-            Message::Close(_) => 0,
-            Message::Frame(_) => 0,
-        }
-    }
-
     fn current_timestamp() -> u64 {
         std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -759,7 +918,7 @@ pub struct TrackedSender<T> {
     stats: Arc<StatisticsTracker>,
 }
 
-impl<T> TrackedSender<T> {
+impl<T: MessageSize> TrackedSender<T> {
     pub fn new(sender: AsyncSender<T>, stats: Arc<StatisticsTracker>) -> Self {
         Self {
             inner: sender,
@@ -768,21 +927,12 @@ impl<T> TrackedSender<T> {
     }
 
     pub async fn send(&self, item: T) -> Result<(), kanal::SendError> {
+        let size = item.size_bytes();
         let result = self.inner.send(item).await;
-
-        // We'll track all sends for now, regardless of type
         if result.is_ok() {
-            // Use tokio::spawn for async operation
-            let stats = self.stats.clone();
-            tokio::spawn(async move {
-                // For now, we'll just track the count without message details
-                // In a real implementation, you might want to have a trait for message sizing
-                stats
-                    .messages_sent
-                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-            });
+            self.stats.messages_sent.fetch_add(1, Ordering::SeqCst);
+            self.stats.bytes_sent.fetch_add(size, Ordering::SeqCst);
         }
-
         result
     }
 }
@@ -793,7 +943,7 @@ pub struct TrackedReceiver<T> {
     stats: Arc<StatisticsTracker>,
 }
 
-impl<T> TrackedReceiver<T> {
+impl<T: MessageSize> TrackedReceiver<T> {
     pub fn new(receiver: AsyncReceiver<T>, stats: Arc<StatisticsTracker>) -> Self {
         Self {
             inner: receiver,
@@ -803,20 +953,203 @@ impl<T> TrackedReceiver<T> {
 
     pub async fn recv(&self) -> Result<T, kanal::ReceiveError> {
         let result = self.inner.recv().await;
+        if let Ok(item) = &result {
+            self.stats.messages_received.fetch_add(1, Ordering::SeqCst);
+            self.stats
+                .bytes_received
+                .fetch_add(item.size_bytes(), Ordering::SeqCst);
+        }
+        result
+    }
+}
 
-        // We'll track all receives for now, regardless of type
-        if result.is_ok() {
-            // Use tokio::spawn for async operation
-            let stats = self.stats.clone();
-            tokio::spawn(async move {
-                // For now, we'll just track the count without message details
-                // In a real implementation, you might want to have a trait for message sizing
-                stats
-                    .messages_received
-                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-            });
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_json_round_trip() {
+        let tracker = StatisticsTracker::new();
+        tracker.record_connection_attempt().await;
+        tracker.record_connection_success().await;
+        tracker
+            .record_message_sent(&Message::text("42[\"ping\"]"))
+            .await;
+        tracker
+            .record_message_received(&Message::binary(vec![0u8; 16]))
+            .await;
+        tracker.record_disconnection(Some("test".into())).await;
+
+        let json = tracker.export_json().await;
+        let stats = ConnectionStats::from_json(&json).unwrap();
+        let expected: ConnectionStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(stats, expected);
+        assert_eq!(stats.connection_attempts, 1);
+        assert_eq!(stats.successful_connections, 1);
+        assert_eq!(stats.disconnections, 1);
+        assert_eq!(stats.messages_sent, 1);
+        assert_eq!(stats.messages_received, 1);
+        assert_eq!(stats.bytes_sent, 10);
+        assert_eq!(stats.bytes_received, 16);
+        assert!(!stats.is_connected);
+        assert!(stats.summary().contains("Total Attempts: 1"));
+    }
+
+    #[tokio::test]
+    async fn test_tracked_sender_counts_bytes() {
+        let stats = Arc::new(StatisticsTracker::new());
+        let (sender, receiver) = kanal::bounded_async(4);
+        let sender = TrackedSender::new(sender, stats.clone());
+        let receiver = TrackedReceiver::new(receiver, stats.clone());
+
+        sender.send(Message::text("hello")).await.unwrap();
+        sender.send(Message::binary(vec![0u8; 1024])).await.unwrap();
+        receiver.recv().await.unwrap();
+
+        let snapshot = stats.get_stats().await;
+        assert_eq!(snapshot.messages_sent, 2);
+        assert_eq!(snapshot.bytes_sent, 5 + 1024);
+        assert_eq!(snapshot.messages_received, 1);
+        assert_eq!(snapshot.bytes_received, 5);
+    }
+
+    fn event(event_type: ConnectionEventType, timestamp: u64) -> ConnectionEvent {
+        ConnectionEvent {
+            event_type,
+            timestamp,
+            duration_ms: None,
+            reason: None,
         }
+    }
 
-        result
+    #[test]
+    fn test_merge() {
+        let first = ConnectionStats {
+            connection_attempts: 3,
+            successful_connections: 2,
+            failed_connections: 1,
+            disconnections: 1,
+            reconnections: 1,
+            avg_connection_latency_ms: 100.0,
+            last_connection_latency_ms: 90.0,
+            total_uptime_seconds: 50.0,
+            current_uptime_seconds: 20.0,
+            time_since_last_disconnection_seconds: 20.0,
+            messages_sent: 100,
+            messages_received: 200,
+            bytes_sent: 1_000,
+            bytes_received: 4_000,
+            avg_messages_sent_per_second: 10.0,
+            avg_messages_received_per_second: 20.0,
+            avg_bytes_sent_per_second: 100.0,
+            avg_bytes_received_per_second: 400.0,
+            oversized_messages_dropped: 0,
+            is_connected: false,
+            connection_history: vec![
+                event(ConnectionEventType::ConnectionAttempt, 1_000),
+                event(ConnectionEventType::ConnectionSuccess, 3_000),
+            ],
+        };
+        let second = ConnectionStats {
+            connection_attempts: 1,
+            successful_connections: 1,
+            failed_connections: 0,
+            disconnections: 0,
+            reconnections: 0,
+            avg_connection_latency_ms: 40.0,
+            last_connection_latency_ms: 40.0,
+            total_uptime_seconds: 30.0,
+            current_uptime_seconds: 30.0,
+            time_since_last_disconnection_seconds: 30.0,
+            messages_sent: 300,
+            messages_received: 0,
+            bytes_sent: 3_000,
+            bytes_received: 0,
+            avg_messages_sent_per_second: 30.0,
+            avg_messages_received_per_second: 0.0,
+            avg_bytes_sent_per_second: 300.0,
+            avg_bytes_received_per_second: 0.0,
+            oversized_messages_dropped: 0,
+            is_connected: true,
+            connection_history: vec![event(ConnectionEventType::ConnectionSuccess, 2_000)],
+        };
+
+        let merged = first.merge(&second);
+        let expected = ConnectionStats {
+            connection_attempts: 4,
+            successful_connections: 3,
+            failed_connections: 1,
+            disconnections: 1,
+            reconnections: 1,
+            avg_connection_latency_ms: 80.0,
+            // The first session has the most recent event
+            last_connection_latency_ms: 90.0,
+            total_uptime_seconds: 50.0,
+            current_uptime_seconds: 30.0,
+            time_since_last_disconnection_seconds: 20.0,
+            messages_sent: 400,
+            messages_received: 200,
+            bytes_sent: 4_000,
+            bytes_received: 4_000,
+            // 400 messages in 10 + 10 seconds
+            avg_messages_sent_per_second: 20.0,
+            avg_messages_received_per_second: 20.0,
+            avg_bytes_sent_per_second: 200.0,
+            avg_bytes_received_per_second: 400.0,
+            oversized_messages_dropped: 0,
+            is_connected: true,
+            connection_history: vec![
+                event(ConnectionEventType::ConnectionAttempt, 1_000),
+                event(ConnectionEventType::ConnectionSuccess, 2_000),
+                event(ConnectionEventType::ConnectionSuccess, 3_000),
+            ],
+        };
+        assert_eq!(merged, expected);
+        assert!(merged.summary().contains("Total Attempts: 4"));
+        assert!(merged.summary().contains("🟢 CONNECTED"));
+
+        let collected: ConnectionStats = [first.clone(), second.clone()].into_iter().collect();
+        assert_eq!(collected, expected);
+        assert_eq!(ConnectionStats::from_iter([first.clone()]), first);
+        assert_eq!(
+            ConnectionStats::from_iter(Vec::new()),
+            ConnectionStats::default()
+        );
+    }
+
+    #[test]
+    fn test_from_json_keeps_all_fields() {
+        let stats = ConnectionStats {
+            connection_attempts: 7,
+            successful_connections: 5,
+            failed_connections: 2,
+            disconnections: 4,
+            reconnections: 3,
+            avg_connection_latency_ms: 12.345678901234567,
+            last_connection_latency_ms: 0.1,
+            total_uptime_seconds: 3600.000001,
+            current_uptime_seconds: 1.0 / 3.0,
+            time_since_last_disconnection_seconds: 42.5,
+            messages_sent: 1_000_000,
+            messages_received: u64::MAX,
+            bytes_sent: 123_456_789,
+            bytes_received: 987_654_321,
+            avg_messages_sent_per_second: 2.0 / 7.0,
+            avg_messages_received_per_second: 1e-9,
+            avg_bytes_sent_per_second: 1e12,
+            avg_bytes_received_per_second: f64::MIN_POSITIVE,
+            oversized_messages_dropped: 0,
+            is_connected: true,
+            connection_history: vec![ConnectionEvent {
+                event_type: ConnectionEventType::ConnectionFailure,
+                timestamp: 1_735_689_600_000,
+                duration_ms: Some(250),
+                reason: Some("timeout".into()),
+            }],
+        };
+        let json = serde_json::to_string(&stats).unwrap();
+        let parsed = ConnectionStats::from_json(&json).unwrap();
+        assert_eq!(parsed, stats);
+        assert_eq!(parsed.summary(), stats.summary());
     }
 }
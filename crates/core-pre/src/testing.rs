@@ -1,14 +1,20 @@
 use crate::builder::ClientBuilder;
 use crate::client::{Client, ClientRunner};
-use crate::connector::Connector;
+use crate::connector::{Connector, ConnectorError, ConnectorResult, WsStream};
 use crate::error::{CoreError, CoreResult};
-use crate::middleware::{MiddlewareContext, WebSocketMiddleware};
+use crate::middleware::StatisticsMiddleware;
 use crate::statistics::{ConnectionStats, StatisticsTracker};
 use crate::traits::AppState;
 use async_trait::async_trait;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::io::AsyncWriteExt;
 use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::http::HeaderMap;
 use tracing::{debug, error, info, warn};
 
 /// Configuration for the testing wrapper
@@ -54,92 +60,12 @@ pub struct TestingWrapper<S: AppState> {
     is_running: Arc<std::sync::atomic::AtomicBool>,
     stats_task: Option<tokio::task::JoinHandle<()>>,
     runner_task: Option<tokio::task::JoinHandle<()>>,
+    /// JSONL file the statistics are appended to every `stats_interval`
+    record_path: Option<PathBuf>,
 }
 
-/// A testing middleware that tracks connection statistics using the shared StatisticsTracker
-pub struct TestingMiddleware<S: AppState> {
-    stats: Arc<StatisticsTracker>,
-    _phantom: std::marker::PhantomData<S>,
-}
-
-impl<S: AppState> TestingMiddleware<S> {
-    /// Create a new testing middleware with the provided StatisticsTracker
-    pub fn new(stats: Arc<StatisticsTracker>) -> Self {
-        Self {
-            stats,
-            _phantom: std::marker::PhantomData,
-        }
-    }
-}
-
-#[async_trait]
-impl<S: AppState> WebSocketMiddleware<S> for TestingMiddleware<S> {
-    async fn on_connection_attempt(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
-        // 🎯 This is the missing piece!
-        self.stats.record_connection_attempt().await;
-        debug!(target: "TestingMiddleware", "Connection attempt recorded");
-        Ok(())
-    }
-
-    async fn on_connection_failure(
-        &self,
-        _context: &MiddlewareContext<S>,
-        reason: Option<String>,
-    ) -> CoreResult<()> {
-        // 🎯 This will give you proper failure tracking
-        self.stats.record_connection_failure(reason).await;
-        debug!(target: "TestingMiddleware", "Connection failure recorded");
-        Ok(())
-    }
-
-    async fn on_connect(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
-        // This calls record_connection_success - already implemented
-        self.stats.record_connection_success().await;
-        debug!(target: "TestingMiddleware", "Connection established");
-        Ok(())
-    }
-
-    async fn on_disconnect(&self, _context: &MiddlewareContext<S>) -> CoreResult<()> {
-        // Record disconnection with reason
-        self.stats
-            .record_disconnection(Some("Connection lost".to_string()))
-            .await;
-        debug!(target: "TestingMiddleware", "Connection lost");
-        Ok(())
-    }
-
-    async fn on_send(&self, message: &Message, _context: &MiddlewareContext<S>) -> CoreResult<()> {
-        // Record message sent with size tracking
-        self.stats.record_message_sent(message).await;
-        debug!(target: "TestingMiddleware", "Message sent: {} bytes", Self::get_message_size(message));
-        Ok(())
-    }
-
-    async fn on_receive(
-        &self,
-        message: &Message,
-        _context: &MiddlewareContext<S>,
-    ) -> CoreResult<()> {
-        // Record message received with size tracking
-        self.stats.record_message_received(message).await;
-        debug!(target: "TestingMiddleware", "Message received: {} bytes", Self::get_message_size(message));
-        Ok(())
-    }
-}
-
-impl<S: AppState> TestingMiddleware<S> {
-    /// Get the size of a message in bytes
-    fn get_message_size(message: &Message) -> usize {
-        match message {
-            Message::Text(text) => text.len(),
-            Message::Binary(data) => data.len(),
-            Message::Ping(data) => data.len(),
-            Message::Pong(data) => data.len(),
-            Message::Close(_) => 0,
-            Message::Frame(_) => 0,
-        }
-    }
-}
+/// The middleware collecting the statistics of the testing wrapper.
+pub type TestingMiddleware<S> = StatisticsMiddleware<S>;
 
 impl<S: AppState> TestingWrapper<S> {
     /// Create a new testing wrapper with the provided client and runner
@@ -154,6 +80,7 @@ impl<S: AppState> TestingWrapper<S> {
             is_running: Arc::new(std::sync::atomic::AtomicBool::new(false)),
             stats_task: None,
             runner_task: None,
+            record_path: None,
         }
     }
 
@@ -173,6 +100,7 @@ impl<S: AppState> TestingWrapper<S> {
             is_running: Arc::new(std::sync::atomic::AtomicBool::new(false)),
             stats_task: None,
             runner_task: None,
+            record_path: None,
         }
     }
 
@@ -181,16 +109,65 @@ impl<S: AppState> TestingWrapper<S> {
         TestingMiddleware::new(Arc::clone(&self.stats))
     }
 
+    /// Append the statistics to a JSONL file every `stats_interval`, one `ConnectionStats` per line.
+    ///
+    /// Must be called before `start`, the file is created if it doesn't exist.
+    pub fn record_to_file(&mut self, path: PathBuf) {
+        self.record_path = Some(path);
+    }
+
+    /// Read back the statistics recorded with `record_to_file`, oldest first.
+    ///
+    /// Lines that aren't valid `ConnectionStats` are skipped, the iterator is empty if the file can't be opened.
+    pub fn replay_stats(path: PathBuf) -> impl Iterator<Item = ConnectionStats> {
+        let lines = match File::open(&path) {
+            Ok(file) => Some(BufReader::new(file).lines()),
+            Err(e) => {
+                warn!("Failed to open stats record {}: {e}", path.display());
+                None
+            }
+        };
+        lines
+            .into_iter()
+            .flatten()
+            .map_while(Result::ok)
+            .filter_map(|line| match serde_json::from_str(&line) {
+                Ok(stats) => Some(stats),
+                Err(e) => {
+                    warn!("Skipping invalid stats record: {e}");
+                    None
+                }
+            })
+    }
+
+    /// Append the statistics as a JSON line to the record file
+    async fn append_record(path: &Path, stats: &ConnectionStats) -> CoreResult<()> {
+        let mut line = serde_json::to_string(stats)
+            .map_err(|e| CoreError::Other(format!("Failed to serialize stats: {e}")))?;
+        line.push('\n');
+        let mut file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)
+            .await
+            .map_err(|e| CoreError::Other(format!("Failed to open stats record: {e}")))?;
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|e| CoreError::Other(format!("Failed to write stats record: {e}")))
+    }
+
     /// Start the testing wrapper, which will run the client and begin collecting statistics
     pub async fn start(&mut self) -> CoreResult<()> {
         self.is_running
             .store(true, std::sync::atomic::Ordering::SeqCst);
 
         // Start statistics collection task
-        if self.config.log_stats {
+        if self.config.log_stats || self.record_path.is_some() {
             let stats = self.stats.clone();
             let interval = self.config.stats_interval;
             let is_running = self.is_running.clone();
+            let log_stats = self.config.log_stats;
+            let record_path = self.record_path.clone();
 
             self.stats_task = Some(tokio::spawn(async move {
                 let mut interval = tokio::time::interval(interval);
@@ -200,7 +177,14 @@ impl<S: AppState> TestingWrapper<S> {
                     interval.tick().await;
 
                     let stats = stats.get_stats().await;
-                    Self::log_statistics(&stats);
+                    if log_stats {
+                        Self::log_statistics(&stats);
+                    }
+                    if let Some(path) = &record_path
+                        && let Err(e) = Self::append_record(path, &stats).await
+                    {
+                        warn!("{e}");
+                    }
                 }
             }));
         }
@@ -608,6 +592,7 @@ impl<S: AppState> TestingWrapperBuilder<S> {
         let middleware = TestingMiddleware::new(Arc::clone(&stats));
         let (client, runner) = builder
             .with_middleware(Box::new(middleware))
+            .with_connection_timeout(self.config.connection_timeout)
             .build()
             .await?;
         let wrapper = TestingWrapper::new_with_stats(client, runner, self.config, stats);
@@ -621,3 +606,339 @@ impl<S: AppState> Default for TestingWrapperBuilder<S> {
         Self::new()
     }
 }
+
+/// Builds the replies for a message matched by [`MockWebSocketServer::expect_send`].
+type MockReply = Arc<dyn Fn(&Message) -> Vec<Message> + Send + Sync>;
+
+/// A single step of the script a [`MockWebSocketServer`] replays on each connection.
+#[derive(Clone)]
+enum MockStep {
+    /// Send a message to the client.
+    Respond(Message),
+    /// Wait until the client sends a message containing the pattern.
+    Expect(String),
+    /// Send the messages produced from the last message matched by an `Expect` step.
+    Reply(MockReply),
+}
+
+/// A local WebSocket server used to run clients fully offline in tests.
+///
+/// The server binds to a random port on `127.0.0.1` and replays the same script on
+/// every accepted connection, so reconnections see the same conversation again.
+/// Every message sent by a client is recorded and can be inspected afterwards.
+///
+/// # Example
+/// ```no_run
+/// use binary_options_tools_core_pre::testing::MockWebSocketServer;
+/// use binary_options_tools_core_pre::reimports::Message;
+///
+/// # async fn example() -> binary_options_tools_core_pre::error::CoreResult<()> {
+/// let server = MockWebSocketServer::bind().await?;
+/// server
+///     .respond_with(Message::text("hello"))
+///     .expect_send("ping")
+///     .respond_with(Message::text("pong"));
+/// // Connect a client to `server.url()`...
+/// server.verify()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct MockWebSocketServer {
+    addr: std::net::SocketAddr,
+    script: Arc<std::sync::Mutex<Vec<MockStep>>>,
+    received: Arc<tokio::sync::Mutex<Vec<Message>>>,
+    received_notify: Arc<tokio::sync::Notify>,
+    /// Number of `expect_send` steps met, for every connection accepted so far
+    expectations_met: Arc<std::sync::Mutex<Vec<usize>>>,
+    handshake_headers: Arc<std::sync::Mutex<Vec<HeaderMap>>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockWebSocketServer {
+    /// Binds the server to a random local port and starts accepting connections.
+    pub async fn bind() -> CoreResult<Self> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let script = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = Arc::new(tokio::sync::Mutex::new(Vec::new()));
+        let received_notify = Arc::new(tokio::sync::Notify::new());
+        let expectations_met = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let handshake_headers = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let task = {
+            let script = script.clone();
+            let received = received.clone();
+            let received_notify = received_notify.clone();
+            let expectations_met = expectations_met.clone();
+            let handshake_headers = handshake_headers.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, peer)) = listener.accept().await {
+                    debug!(target: "MockWebSocketServer", "Accepted connection from {}", peer);
+                    let steps = match script.lock() {
+                        Ok(script) => script.clone(),
+                        Err(e) => {
+                            error!(target: "MockWebSocketServer", "Script lock poisoned: {}", e);
+                            return;
+                        }
+                    };
+                    let index = match expectations_met.lock() {
+                        Ok(mut met) => {
+                            met.push(0);
+                            met.len() - 1
+                        }
+                        Err(e) => {
+                            error!(target: "MockWebSocketServer", "Expectations lock poisoned: {}", e);
+                            return;
+                        }
+                    };
+                    let connection = MockConnection {
+                        index,
+                        received: received.clone(),
+                        received_notify: received_notify.clone(),
+                        expectations_met: expectations_met.clone(),
+                        handshake_headers: handshake_headers.clone(),
+                    };
+                    tokio::spawn(async move {
+                        if let Err(e) = connection.run(stream, steps).await {
+                            warn!(target: "MockWebSocketServer", "Connection closed with error: {}", e);
+                        }
+                    });
+                }
+            })
+        };
+
+        Ok(Self {
+            addr,
+            script,
+            received,
+            received_notify,
+            expectations_met,
+            handshake_headers,
+            task,
+        })
+    }
+
+    /// The local address the server is listening on.
+    pub fn addr(&self) -> std::net::SocketAddr {
+        self.addr
+    }
+
+    /// The `ws://` url clients should connect to.
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Returns a connector opening plain connections to this server.
+    pub fn connector(&self) -> MockServerConnector {
+        MockServerConnector::new(self.url())
+    }
+
+    /// Queues a message to be sent to the client.
+    pub fn respond_with(&self, msg: Message) -> &Self {
+        self.push_step(MockStep::Respond(msg))
+    }
+
+    /// Waits for the client to send a message containing `pattern` before continuing the script.
+    /// Use [`MockWebSocketServer::verify`] to assert every expectation was met.
+    pub fn expect_send(&self, pattern: &str) -> &Self {
+        self.push_step(MockStep::Expect(pattern.to_string()))
+    }
+
+    /// Queues the messages built from the last message matched by [`MockWebSocketServer::expect_send`],
+    /// useful to echo request ids back to the client.
+    pub fn reply_with<F>(&self, reply: F) -> &Self
+    where
+        F: Fn(&Message) -> Vec<Message> + Send + Sync + 'static,
+    {
+        self.push_step(MockStep::Reply(Arc::new(reply)))
+    }
+
+    /// Returns the headers of the upgrade request of every connection accepted so far.
+    pub fn handshake_headers(&self) -> Vec<HeaderMap> {
+        self.handshake_headers
+            .lock()
+            .map(|headers| headers.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns every message received from the clients so far.
+    pub async fn received(&self) -> Vec<Message> {
+        self.received.lock().await.clone()
+    }
+
+    /// Waits until a client sends a message containing `pattern` and returns it.
+    pub async fn wait_for_send(&self, pattern: &str, timeout: Duration) -> CoreResult<Message> {
+        let wait = async {
+            loop {
+                let notified = self.received_notify.notified();
+                if let Some(msg) = self
+                    .received
+                    .lock()
+                    .await
+                    .iter()
+                    .find(|msg| message_contains(msg, pattern))
+                {
+                    return Ok::<_, CoreError>(msg.clone());
+                }
+                notified.await;
+            }
+        };
+        crate::utils::time::timeout(timeout, wait, format!("wait_for_send({pattern})")).await
+    }
+
+    /// Checks that every `expect_send` step of the script has been met by a single connection,
+    /// the steps met by different connections don't add up.
+    pub fn verify(&self) -> CoreResult<()> {
+        let expected = self
+            .script
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))?
+            .iter()
+            .filter(|step| matches!(step, MockStep::Expect(_)))
+            .count();
+        let met = self
+            .expectations_met
+            .lock()
+            .map_err(|e| CoreError::Poison(e.to_string()))?
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0);
+        if met < expected {
+            return Err(CoreError::Other(format!(
+                "Only {met} out of {expected} expected messages were sent on a single connection"
+            )));
+        }
+        Ok(())
+    }
+
+    fn push_step(&self, step: MockStep) -> &Self {
+        match self.script.lock() {
+            Ok(mut script) => script.push(step),
+            Err(e) => error!(target: "MockWebSocketServer", "Script lock poisoned: {}", e),
+        }
+        self
+    }
+}
+
+impl Drop for MockWebSocketServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Connector opening plain WebSocket connections to a URL, usually the one of a
+/// [`MockWebSocketServer`], for any state.
+#[derive(Debug, Clone)]
+pub struct MockServerConnector {
+    url: String,
+}
+
+impl MockServerConnector {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+}
+
+#[async_trait]
+impl<S: AppState> Connector<S> for MockServerConnector {
+    async fn connect(&self, _: Arc<S>) -> ConnectorResult<WsStream> {
+        let (ws, _) = tokio_tungstenite::connect_async(self.url.as_str())
+            .await
+            .map_err(|e| ConnectorError::ConnectionFailed(Box::new(e)))?;
+        Ok(ws)
+    }
+
+    async fn disconnect(&self) -> ConnectorResult<()> {
+        Ok(())
+    }
+}
+
+/// State shared between a [`MockWebSocketServer`] and one of its connections.
+struct MockConnection {
+    /// Position of the connection in the accepted connections
+    index: usize,
+    received: Arc<tokio::sync::Mutex<Vec<Message>>>,
+    received_notify: Arc<tokio::sync::Notify>,
+    expectations_met: Arc<std::sync::Mutex<Vec<usize>>>,
+    handshake_headers: Arc<std::sync::Mutex<Vec<HeaderMap>>>,
+}
+
+impl MockConnection {
+    async fn run(&self, stream: tokio::net::TcpStream, steps: Vec<MockStep>) -> CoreResult<()> {
+        use futures_util::{SinkExt, StreamExt};
+
+        // Records the upgrade request and accepts the first subprotocol asked by the client
+        let handshake_headers = self.handshake_headers.clone();
+        // The error type is imposed by tungstenite
+        #[allow(clippy::result_large_err)]
+        let callback = move |request: &Request, mut response: Response| {
+            if let Ok(mut headers) = handshake_headers.lock() {
+                headers.push(request.headers().clone());
+            }
+            if let Some(protocol) = request
+                .headers()
+                .get("Sec-WebSocket-Protocol")
+                .and_then(|protocols| protocols.to_str().ok())
+                .and_then(|protocols| protocols.split(',').next())
+                .and_then(|protocol| protocol.trim().parse().ok())
+            {
+                response
+                    .headers_mut()
+                    .insert("Sec-WebSocket-Protocol", protocol);
+            }
+            Ok(response)
+        };
+        let mut ws = tokio_tungstenite::accept_hdr_async(stream, callback).await?;
+        let mut last_matched: Option<Message> = None;
+
+        for step in steps {
+            match step {
+                MockStep::Respond(msg) => ws.send(msg).await?,
+                MockStep::Expect(pattern) => loop {
+                    let Some(msg) = ws.next().await.transpose()? else {
+                        return Ok(());
+                    };
+                    self.record(msg.clone()).await;
+                    if message_contains(&msg, &pattern) {
+                        if let Some(met) = self
+                            .expectations_met
+                            .lock()
+                            .map_err(|e| CoreError::Poison(e.to_string()))?
+                            .get_mut(self.index)
+                        {
+                            *met += 1;
+                        }
+                        last_matched = Some(msg);
+                        break;
+                    }
+                },
+                MockStep::Reply(reply) => {
+                    if let Some(msg) = &last_matched {
+                        for response in reply(msg) {
+                            ws.send(response).await?;
+                        }
+                    }
+                }
+            }
+        }
+
+        while let Some(msg) = ws.next().await.transpose()? {
+            self.record(msg).await;
+        }
+        Ok(())
+    }
+
+    async fn record(&self, msg: Message) {
+        self.received.lock().await.push(msg);
+        self.received_notify.notify_waiters();
+    }
+}
+
+fn message_contains(msg: &Message, pattern: &str) -> bool {
+    match msg {
+        Message::Text(text) => text.as_str().contains(pattern),
+        Message::Binary(data) => String::from_utf8_lossy(data).contains(pattern),
+        _ => false,
+    }
+}
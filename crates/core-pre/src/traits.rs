@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use kanal::{AsyncReceiver, AsyncSender};
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_tungstenite::tungstenite::Message;
 
 use crate::error::CoreResult;
@@ -139,6 +140,43 @@ pub trait LightweightModule<S: AppState>: Send + 'static {
     fn rule() -> Box<dyn Rule + Send + Sync>;
 }
 
+/// A lightweight module that is driven by a timer in addition to incoming messages.
+///
+/// Use this for background work that has to happen on an interval, like keep-alive pings or
+/// periodic polling, instead of spawning a `tokio::time::interval` by hand inside a `run` loop.
+/// Register it with `ClientBuilder::with_timed_lightweight_module`, which drives both the timer
+/// and the messages matching `rule` from a single task, so `tick` and `on_message` can share
+/// `&mut self` without any locking.
+#[async_trait]
+pub trait TimedLightweightModule<S: AppState>: Send + 'static {
+    /// Construct the module with the shared app state and a sender for outgoing WS messages.
+    fn new(state: Arc<S>, ws_sender: AsyncSender<Message>) -> Self
+    where
+        Self: Sized;
+
+    /// Time between two consecutive calls to `tick`.
+    fn interval() -> Duration
+    where
+        Self: Sized;
+
+    /// Called once every `interval`, the first call happening one `interval` after startup.
+    async fn tick(&mut self, state: Arc<S>, sender: &AsyncSender<Message>) -> CoreResult<()>;
+
+    /// Called for every incoming message that matches `rule`.
+    async fn on_message(&mut self, _message: Arc<Message>) -> CoreResult<()> {
+        // Default implementation does nothing, most timed modules only care about the timer.
+        Ok(())
+    }
+
+    /// Route only messages for which this returns true. By default no message is routed.
+    fn rule() -> Box<dyn Rule + Send + Sync>
+    where
+        Self: Sized,
+    {
+        Box::new(|_: &Message| false)
+    }
+}
+
 /// Data returned by the rule function of a module.
 /// This trait is used to define the rules that determine whether a message should be processed by a module.
 /// It allows for flexible and reusable rules that can be applied to different modules.
@@ -33,6 +33,21 @@ impl RecieverStream {
         Self { inner, timeout }
     }
 
+    /// Number of messages waiting in the channel.
+    pub fn item_count(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Takes every message already in the channel, in order, without waiting for new ones.
+    /// Stops at the first error, like when the channel is closed.
+    pub fn try_recv_all(&self) -> Vec<Message> {
+        let mut messages = Vec::with_capacity(self.inner.len());
+        while let Ok(Some(msg)) = self.inner.try_recv() {
+            messages.push(msg);
+        }
+        messages
+    }
+
     async fn receive(&self) -> CoreResult<Message> {
         match self.timeout {
             Some(time) => timeout(time, self.inner.recv(), "RecieverStream".to_string()).await,
@@ -113,3 +128,28 @@ impl FilteredRecieverStream {
 fn default_filter() -> Box<dyn Rule + Send + Sync> {
     Box::new(move |_: &Message| true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_recv_all() {
+        let (sender, receiver) = kanal::unbounded_async();
+        let stream = RecieverStream::new(receiver);
+        for i in 0..5 {
+            sender.as_sync().send(Message::text(i.to_string())).unwrap();
+        }
+        assert_eq!(stream.item_count(), 5);
+
+        let messages = stream.try_recv_all();
+        assert_eq!(
+            messages,
+            (0..5)
+                .map(|i| Message::text(i.to_string()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(stream.item_count(), 0);
+        assert!(stream.try_recv_all().is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use binary_options_tools_core_pre::builder::ClientBuilder;
+use binary_options_tools_core_pre::connector::{
+    ArcConnector, Connector, ConnectorError, ConnectorResult, WsStream,
+};
+use binary_options_tools_core_pre::testing::{MockServerConnector, MockWebSocketServer};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+struct CountingConnector {
+    inner: MockServerConnector,
+    connections: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Connector<()> for CountingConnector {
+    async fn connect(&self, state: Arc<()>) -> ConnectorResult<WsStream> {
+        self.connections.fetch_add(1, Ordering::SeqCst);
+        self.inner.connect(state).await
+    }
+
+    async fn disconnect(&self) -> ConnectorResult<()> {
+        Ok(())
+    }
+}
+
+// Never used, replaced by the shared connector
+struct FailingConnector;
+
+#[async_trait]
+impl Connector<()> for FailingConnector {
+    async fn connect(&self, _: Arc<()>) -> ConnectorResult<WsStream> {
+        Err(ConnectorError::Custom("Unreachable".to_string()))
+    }
+
+    async fn disconnect(&self) -> ConnectorResult<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_arc_connector() {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    let connections = Arc::new(AtomicUsize::new(0));
+    let connector = ArcConnector::new(CountingConnector {
+        inner: server.connector(),
+        connections: Arc::clone(&connections),
+    });
+
+    let (client, mut runner) = ClientBuilder::new(FailingConnector, ())
+        .with_connector(connector.clone())
+        .build()
+        .await
+        .expect("Failed to build client");
+    let runner_task = tokio::spawn(async move { runner.run().await });
+    tokio::time::timeout(Duration::from_secs(5), client.wait_connected())
+        .await
+        .expect("Client never connected");
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+    // The same connector can also be used directly as a `Connector`
+    let (other, mut other_runner) = ClientBuilder::new(connector, ())
+        .build()
+        .await
+        .expect("Failed to build client");
+    let other_task = tokio::spawn(async move { other_runner.run().await });
+    tokio::time::timeout(Duration::from_secs(5), other.wait_connected())
+        .await
+        .expect("Client never connected");
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+
+    client.shutdown().await.unwrap();
+    other.shutdown().await.unwrap();
+    for task in [runner_task, other_task] {
+        tokio::time::timeout(Duration::from_secs(5), task)
+            .await
+            .expect("Runner did not stop")
+            .unwrap();
+    }
+}
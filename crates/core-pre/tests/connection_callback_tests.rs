@@ -0,0 +1,90 @@
+use binary_options_tools_core_pre::builder::ClientBuilder;
+use binary_options_tools_core_pre::callback::ConnectionCallbackBuilder;
+use binary_options_tools_core_pre::testing::MockWebSocketServer;
+use binary_options_tools_core_pre::traits::ReconnectCallback;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+async fn wait_for(counter: &AtomicUsize, value: usize) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while counter.load(Ordering::SeqCst) < value {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Callback was never called");
+}
+
+#[tokio::test]
+async fn test_on_connect_then_on_reconnect() {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    let connects = Arc::new(AtomicUsize::new(0));
+    let reconnects = Arc::new(AtomicUsize::new(0));
+    // Number of connects seen by the reconnect callback, to check the order
+    let connects_before_reconnect = Arc::new(AtomicUsize::new(0));
+
+    let (client, mut runner) = ClientBuilder::new(server.connector(), ())
+        .with_on_connect({
+            let connects = connects.clone();
+            move |_, _| {
+                connects.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async { Ok(()) })
+            }
+        })
+        .with_on_reconnect({
+            let connects = connects.clone();
+            let reconnects = reconnects.clone();
+            let seen = connects_before_reconnect.clone();
+            move |_, _: &_| {
+                seen.store(connects.load(Ordering::SeqCst), Ordering::SeqCst);
+                reconnects.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async { Ok(()) }) as futures_util::future::BoxFuture<'static, _>
+            }
+        })
+        .build()
+        .await
+        .expect("Failed to build client");
+    let runner_task = tokio::spawn(async move { runner.run().await });
+
+    wait_for(&connects, 1).await;
+    assert_eq!(reconnects.load(Ordering::SeqCst), 0);
+
+    client.reconnect().await.unwrap();
+    wait_for(&reconnects, 1).await;
+    assert_eq!(connects.load(Ordering::SeqCst), 1);
+    assert_eq!(connects_before_reconnect.load(Ordering::SeqCst), 1);
+
+    client.shutdown().await.unwrap();
+    let _ = tokio::time::timeout(Duration::from_secs(5), runner_task).await;
+}
+
+#[tokio::test]
+async fn test_builder_layers_run_in_order() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let first = Arc::new(AtomicUsize::new(0));
+    let second = Arc::new(AtomicUsize::new(0));
+    let layer = |calls: Arc<AtomicUsize>, slot: Arc<AtomicUsize>| {
+        move |_: Arc<()>, _: &_| {
+            slot.store(calls.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+            Box::pin(async { Ok(()) }) as futures_util::future::BoxFuture<'static, _>
+        }
+    };
+    let callback = ConnectionCallbackBuilder::new()
+        .on_reconnect(layer(calls.clone(), first.clone()))
+        .on_reconnect(layer(calls.clone(), second.clone()))
+        .build();
+
+    let (sender, _receiver) = kanal::bounded_async(1);
+    (callback.on_connect)(Arc::new(()), &sender).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+    callback
+        .on_reconnect
+        .call(Arc::new(()), &sender)
+        .await
+        .unwrap();
+    assert_eq!(first.load(Ordering::SeqCst), 1);
+    assert_eq!(second.load(Ordering::SeqCst), 2);
+}
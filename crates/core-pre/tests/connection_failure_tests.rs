@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use binary_options_tools_core_pre::builder::ClientBuilder;
+use binary_options_tools_core_pre::connector::{
+    Connector, ConnectorError, ConnectorResult, WsStream,
+};
+use binary_options_tools_core_pre::statistics::StatisticsTracker;
+use binary_options_tools_core_pre::testing::{
+    MockServerConnector, MockWebSocketServer, TestingMiddleware, TestingWrapperBuilder,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+// Connector failing its first attempt, then connecting to a local mock server
+struct FailOnceConnector {
+    inner: MockServerConnector,
+    attempts: AtomicUsize,
+}
+
+#[async_trait]
+impl Connector<()> for FailOnceConnector {
+    async fn connect(&self, state: Arc<()>) -> ConnectorResult<WsStream> {
+        if self.attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Err(ConnectorError::Custom("Forced failure".to_string()));
+        }
+        self.inner.connect(state).await
+    }
+
+    async fn disconnect(&self) -> ConnectorResult<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_connection_failure_is_recorded() {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    let stats = Arc::new(StatisticsTracker::new());
+    let connector = FailOnceConnector {
+        inner: server.connector(),
+        attempts: AtomicUsize::new(0),
+    };
+    let (client, mut runner) = ClientBuilder::new(connector, ())
+        .with_middleware(Box::new(TestingMiddleware::new(stats.clone())))
+        .build()
+        .await
+        .expect("Failed to build client");
+    let runner_task = tokio::spawn(async move { runner.run().await });
+
+    // The runner waits 5 seconds after a failed attempt
+    tokio::time::timeout(Duration::from_secs(10), client.wait_connected())
+        .await
+        .expect("Client never connected");
+    let stats = stats.get_stats().await;
+    assert_eq!(stats.connection_attempts, 2);
+    assert_eq!(stats.failed_connections, 1);
+    assert_eq!(stats.successful_connections, 1);
+
+    client.shutdown().await.expect("Failed to send shutdown");
+    tokio::time::timeout(Duration::from_secs(5), runner_task)
+        .await
+        .expect("Runner did not stop")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_connection_timeout() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind listener");
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let server_task = tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+
+    let builder = ClientBuilder::new(MockServerConnector::new(url), ());
+    let mut wrapper = TestingWrapperBuilder::new()
+        .with_connection_timeout(Duration::from_millis(200))
+        .build_with_middleware(builder)
+        .await
+        .expect("Failed to build client");
+    wrapper.start().await.expect("Failed to start wrapper");
+
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while wrapper.get_stats().await.failed_connections == 0 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("Connection attempt never timed out");
+
+    wrapper.stop().await.expect("Failed to stop wrapper");
+    server_task.abort();
+}
+
+// Connector whose server never presents the pinned certificate
+struct PinningFailureConnector {
+    attempts: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Connector<()> for PinningFailureConnector {
+    async fn connect(&self, _: Arc<()>) -> ConnectorResult<WsStream> {
+        self.attempts.fetch_add(1, Ordering::SeqCst);
+        Err(ConnectorError::CertificatePinning("abcd".to_string()))
+    }
+
+    async fn disconnect(&self) -> ConnectorResult<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_fatal_connection_failure_stops_runner() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let connector = PinningFailureConnector {
+        attempts: attempts.clone(),
+    };
+    let (client, mut runner) = ClientBuilder::new(connector, ())
+        .build()
+        .await
+        .expect("Failed to build client");
+    let mut failures = client.connection_failure_watch();
+    let runner_task = tokio::spawn(async move { runner.run().await });
+
+    // No retry, so the runner stops well before the 5 seconds delay between attempts
+    tokio::time::timeout(Duration::from_secs(2), runner_task)
+        .await
+        .expect("Runner kept retrying")
+        .unwrap();
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    assert!(failures.has_changed().unwrap());
+    assert!(matches!(
+        failures.borrow_and_update().as_deref(),
+        Some(ConnectorError::CertificatePinning(fingerprint)) if fingerprint == "abcd"
+    ));
+    assert!(!client.is_connected());
+}
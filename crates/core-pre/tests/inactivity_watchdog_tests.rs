@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use binary_options_tools_core_pre::builder::ClientBuilder;
+use binary_options_tools_core_pre::connector::{Connector, ConnectorResult, WsStream};
+use binary_options_tools_core_pre::reimports::Message;
+use binary_options_tools_core_pre::testing::{MockServerConnector, MockWebSocketServer};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+// Connector that talks to a local mock server and counts the connections
+struct CountingConnector {
+    inner: MockServerConnector,
+    connections: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Connector<()> for CountingConnector {
+    async fn connect(&self, state: Arc<()>) -> ConnectorResult<WsStream> {
+        let ws = self.inner.connect(state).await?;
+        self.connections.fetch_add(1, Ordering::SeqCst);
+        Ok(ws)
+    }
+
+    async fn disconnect(&self) -> ConnectorResult<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_reconnects_after_inactivity() {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    // A single message per connection, then the server stays silent without closing the socket
+    server.respond_with(Message::text("hello"));
+    let connections = Arc::new(AtomicUsize::new(0));
+    let connector = CountingConnector {
+        inner: server.connector(),
+        connections: connections.clone(),
+    };
+    let (client, mut runner) = ClientBuilder::new(connector, ())
+        .with_inactivity_watchdog(Duration::from_millis(200))
+        .build()
+        .await
+        .expect("Failed to build client");
+    let runner_task = tokio::spawn(async move { runner.run().await });
+
+    tokio::time::timeout(Duration::from_secs(5), client.wait_connected())
+        .await
+        .expect("Client never connected");
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+    // Still within the timeout, no reconnection yet
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while connections.load(Ordering::SeqCst) < 2 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("The watchdog never reconnected");
+
+    client.shutdown().await.expect("Failed to send shutdown");
+    tokio::time::timeout(Duration::from_secs(5), runner_task)
+        .await
+        .expect("Runner did not stop")
+        .unwrap();
+}
@@ -0,0 +1,50 @@
+use binary_options_tools_core_pre::builder::ClientBuilder;
+use binary_options_tools_core_pre::middleware::MaxMessageSizeMiddleware;
+use binary_options_tools_core_pre::reimports::Message;
+use binary_options_tools_core_pre::statistics::StatisticsTracker;
+use binary_options_tools_core_pre::testing::{MockWebSocketServer, TestingMiddleware};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+const LIMIT: usize = 1024 * 1024;
+
+#[tokio::test]
+async fn test_oversized_message_is_dropped() {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    server
+        .respond_with(Message::binary(vec![0u8; 10 * LIMIT]))
+        .respond_with(Message::text("small"));
+
+    let middleware = MaxMessageSizeMiddleware::new();
+    let oversized = middleware.counter();
+    let stats = Arc::new(StatisticsTracker::new());
+    let (client, mut runner) = ClientBuilder::new(server.connector(), ())
+        .with_max_message_size(LIMIT)
+        .with_middleware(Box::new(middleware))
+        .with_middleware(Box::new(TestingMiddleware::new(Arc::clone(&stats))))
+        .build()
+        .await
+        .expect("Failed to build client");
+    let runner_task = tokio::spawn(async move { runner.run().await });
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while client.router_metrics().total_routed < 1 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("The small message was never routed");
+
+    // The oversized message comes first, it was handled before the small one was routed
+    assert_eq!(oversized.load(Ordering::Relaxed), 1);
+    assert_eq!(client.router_metrics().total_routed, 1);
+    let stats = stats.get_stats().await;
+    assert_eq!(stats.oversized_messages_dropped, 1);
+    assert_eq!(stats.messages_received, 1);
+
+    client.shutdown().await.unwrap();
+    let _ = tokio::time::timeout(Duration::from_secs(5), runner_task).await;
+}
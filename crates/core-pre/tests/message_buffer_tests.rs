@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use binary_options_tools_core_pre::builder::ClientBuilder;
+use binary_options_tools_core_pre::error::CoreResult;
+use binary_options_tools_core_pre::middleware::{MiddlewareContext, WebSocketMiddleware};
+use binary_options_tools_core_pre::reimports::Message;
+use binary_options_tools_core_pre::testing::MockWebSocketServer;
+use std::time::Duration;
+
+// Slows down the writer so the buffer fills up
+struct SlowSendMiddleware;
+
+#[async_trait]
+impl WebSocketMiddleware<()> for SlowSendMiddleware {
+    async fn on_send(&self, _: &Message, _: &MiddlewareContext<()>) -> CoreResult<()> {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_small_message_buffer_delivers_every_message() {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    let (client, mut runner) = ClientBuilder::new(server.connector(), ())
+        .with_middleware(Box::new(SlowSendMiddleware))
+        .with_message_buffer_size(2)
+        .build()
+        .await
+        .expect("Failed to build client");
+    let runner_task = tokio::spawn(async move { runner.run().await });
+    tokio::time::timeout(Duration::from_secs(5), client.wait_connected())
+        .await
+        .expect("Client never connected");
+
+    // The senders wait for room in the buffer instead of failing
+    let senders: Vec<_> = (0..10)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move { client.send_text(format!("burst-{i}")).await })
+        })
+        .collect();
+    for sender in senders {
+        sender.await.unwrap().expect("Failed to queue message");
+    }
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while server.received().await.len() < 10 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Messages were not delivered");
+    let mut received: Vec<_> = server
+        .received()
+        .await
+        .iter()
+        .filter_map(|msg| msg.to_text().ok().map(str::to_string))
+        .collect();
+    received.sort();
+    let mut expected: Vec<_> = (0..10).map(|i| format!("burst-{i}")).collect();
+    expected.sort();
+    assert_eq!(received, expected);
+
+    client.shutdown().await.expect("Failed to send shutdown");
+    tokio::time::timeout(Duration::from_secs(5), runner_task)
+        .await
+        .expect("Runner did not stop")
+        .unwrap();
+}
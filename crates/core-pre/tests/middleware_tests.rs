@@ -1,11 +1,14 @@
 use async_trait::async_trait;
+use binary_options_tools_core_pre::builder::ClientBuilder;
 use binary_options_tools_core_pre::error::CoreResult;
 use binary_options_tools_core_pre::middleware::{
     MiddlewareContext, MiddlewareStack, WebSocketMiddleware,
 };
+use binary_options_tools_core_pre::testing::MockWebSocketServer;
 use binary_options_tools_core_pre::traits::AppState;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio_tungstenite::tungstenite::Message;
 
 #[derive(Debug)]
@@ -167,3 +170,81 @@ async fn test_middleware_context() {
     let send_result = context.ws_sender.send(test_message).await;
     assert!(send_result.is_ok());
 }
+
+struct OrderMiddleware {
+    name: &'static str,
+    calls: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[async_trait]
+impl WebSocketMiddleware<TestState> for OrderMiddleware {
+    async fn on_receive(
+        &self,
+        _message: &Message,
+        _context: &MiddlewareContext<TestState>,
+    ) -> CoreResult<()> {
+        self.calls.lock().unwrap().push(self.name);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_middleware_stack_priority() {
+    let (sender, _receiver) = kanal::bounded_async(10);
+    let context = MiddlewareContext::new(Arc::new(TestState), sender);
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let layer = |name| {
+        Box::new(OrderMiddleware {
+            name,
+            calls: Arc::clone(&calls),
+        })
+    };
+
+    let mut stack = MiddlewareStack::new();
+    stack.add_layer_prioritized(layer("late"), 10);
+    stack.add_layer(layer("default"));
+    stack.add_layer_prioritized(layer("early"), -5);
+    stack.add_layer_prioritized(layer("default_second"), 0);
+
+    stack.on_receive(&Message::text("test"), &context).await;
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec!["early", "default", "default_second", "late"]
+    );
+}
+
+#[tokio::test]
+async fn test_client_builder_middleware_priority() {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    server.respond_with(Message::text("hello"));
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let layer = |name| {
+        Box::new(OrderMiddleware {
+            name,
+            calls: Arc::clone(&calls),
+        })
+    };
+    let (client, mut runner) = ClientBuilder::new(server.connector(), TestState)
+        .with_middleware_prioritized(layer("ten"), 10)
+        .with_middleware(layer("zero"))
+        .with_middleware_prioritized(layer("minus_five"), -5)
+        .build()
+        .await
+        .expect("Failed to build client");
+    let runner_task = tokio::spawn(async move { runner.run().await });
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while calls.lock().unwrap().len() < 3 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("The message never went through the middleware");
+    assert_eq!(*calls.lock().unwrap(), vec!["minus_five", "zero", "ten"]);
+
+    client.shutdown().await.unwrap();
+    let _ = tokio::time::timeout(Duration::from_secs(5), runner_task).await;
+}
@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use binary_options_tools_core_pre::middleware::{
+    MiddlewareContext, MiddlewareStack, RetryMiddleware,
+};
+use binary_options_tools_core_pre::statistics::StatisticsTracker;
+use binary_options_tools_core_pre::testing::TestingMiddleware;
+use binary_options_tools_core_pre::traits::AppState;
+use futures_util::Sink;
+use tokio_tungstenite::tungstenite::Message;
+
+#[derive(Debug)]
+struct TestState;
+
+#[async_trait]
+impl AppState for TestState {
+    async fn clear_temporal_data(&self) {}
+}
+
+/// Sink failing the first `failures` sends, like a WebSocket with transient errors.
+struct FlakySink {
+    failures: u32,
+    attempts: u32,
+    sent: VecDeque<Message>,
+}
+
+impl FlakySink {
+    fn new(failures: u32) -> Self {
+        Self {
+            failures,
+            attempts: 0,
+            sent: VecDeque::new(),
+        }
+    }
+}
+
+impl Sink<Message> for FlakySink {
+    type Error = String;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), String>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), String> {
+        self.attempts += 1;
+        if self.attempts <= self.failures {
+            return Err(format!("transient failure {}", self.attempts));
+        }
+        self.sent.push_back(item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), String>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), String>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn stack_with_retries(
+    max_retries: u32,
+    stats: &Arc<StatisticsTracker>,
+) -> MiddlewareStack<TestState> {
+    let mut stack = MiddlewareStack::new();
+    stack.add_layer(Box::new(RetryMiddleware::new(
+        max_retries,
+        Duration::from_millis(1),
+    )));
+    stack.add_layer(Box::new(TestingMiddleware::new(stats.clone())));
+    stack
+}
+
+fn context() -> MiddlewareContext<TestState> {
+    let (sender, _receiver) = kanal::bounded_async(10);
+    MiddlewareContext::new(Arc::new(TestState), sender)
+}
+
+#[tokio::test]
+async fn test_message_delivered_on_third_attempt() {
+    let stats = Arc::new(StatisticsTracker::new());
+    let stack = stack_with_retries(3, &stats);
+    let mut sink = FlakySink::new(2);
+
+    stack
+        .send(&mut sink, Message::text("42[\"ping\"]"), &context())
+        .await
+        .unwrap();
+
+    assert_eq!(sink.attempts, 3);
+    assert_eq!(sink.sent, VecDeque::from([Message::text("42[\"ping\"]")]));
+    assert_eq!(stats.get_stats().await.messages_sent, 1);
+}
+
+#[tokio::test]
+async fn test_error_propagated_once_retries_exhausted() {
+    let stats = Arc::new(StatisticsTracker::new());
+    let stack = stack_with_retries(2, &stats);
+    let mut sink = FlakySink::new(5);
+
+    let result = stack
+        .send(&mut sink, Message::text("42[\"ping\"]"), &context())
+        .await;
+
+    assert_eq!(result, Err("transient failure 3".to_string()));
+    assert_eq!(sink.attempts, 3);
+    assert!(sink.sent.is_empty());
+}
+
+#[tokio::test]
+async fn test_no_retry_without_retry_middleware() {
+    let stack = MiddlewareStack::<TestState>::new();
+    let mut sink = FlakySink::new(1);
+
+    let result = stack
+        .send(&mut sink, Message::text("42[\"ping\"]"), &context())
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(sink.attempts, 1);
+}
+
+#[test]
+fn test_exponential_backoff() {
+    let retry = RetryMiddleware::<TestState>::new(10, Duration::from_millis(100))
+        .with_max_delay(Duration::from_millis(500));
+    assert_eq!(retry.delay(1), Duration::from_millis(100));
+    assert_eq!(retry.delay(2), Duration::from_millis(200));
+    assert_eq!(retry.delay(3), Duration::from_millis(400));
+    assert_eq!(retry.delay(4), Duration::from_millis(500));
+    assert_eq!(retry.pending(), 0);
+}
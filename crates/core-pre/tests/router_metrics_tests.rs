@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use binary_options_tools_core_pre::builder::ClientBuilder;
+use binary_options_tools_core_pre::error::CoreResult;
+use binary_options_tools_core_pre::reimports::{AsyncReceiver, AsyncSender, Message};
+use binary_options_tools_core_pre::testing::MockWebSocketServer;
+use binary_options_tools_core_pre::traits::{LightweightModule, Rule};
+use std::any::type_name;
+use std::sync::Arc;
+use std::time::Duration;
+
+struct PingRule;
+
+impl Rule for PingRule {
+    fn call(&self, msg: &Message) -> bool {
+        msg.to_text().is_ok_and(|text| text.starts_with("ping"))
+    }
+
+    fn reset(&self) {}
+}
+
+struct PingModule {
+    receiver: AsyncReceiver<Arc<Message>>,
+}
+
+#[async_trait]
+impl LightweightModule<()> for PingModule {
+    fn new(_: Arc<()>, _: AsyncSender<Message>, receiver: AsyncReceiver<Arc<Message>>) -> Self {
+        Self { receiver }
+    }
+
+    async fn run(&mut self) -> CoreResult<()> {
+        while self.receiver.recv().await.is_ok() {}
+        Ok(())
+    }
+
+    fn rule() -> Box<dyn Rule + Send + Sync> {
+        Box::new(PingRule)
+    }
+}
+
+#[tokio::test]
+async fn test_router_metrics() {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    for i in 0..100 {
+        let text = if i % 4 == 0 { "ping" } else { "other" };
+        server.respond_with(Message::text(format!("{text}-{i}")));
+    }
+    let (client, mut runner) = ClientBuilder::new(server.connector(), ())
+        .with_lightweight_module::<PingModule>()
+        .build()
+        .await
+        .expect("Failed to build client");
+    let runner_task = tokio::spawn(async move { runner.run().await });
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client.router_metrics().total_routed < 100 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Messages were never routed");
+
+    let metrics = client.router_metrics();
+    assert_eq!(metrics.total_routed, 100);
+    assert_eq!(metrics.rule_match_counts[type_name::<PingModule>()], 25);
+    assert_eq!(metrics.unmatched, 75);
+    let last_message = client
+        .last_message_received()
+        .expect("No message was recorded");
+    assert!(last_message.elapsed() < Duration::from_secs(5));
+
+    client.shutdown().await.unwrap();
+    let _ = tokio::time::timeout(Duration::from_secs(5), runner_task).await;
+}
@@ -0,0 +1,113 @@
+use binary_options_tools_core_pre::builder::ClientBuilder;
+use binary_options_tools_core_pre::signals::Signals;
+use binary_options_tools_core_pre::testing::MockWebSocketServer;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_signals_state_change() {
+    let signals = Signals::default();
+    let (connected, _) = tokio::join!(signals.wait_for_state_change(), async {
+        signals.set_connected()
+    });
+    assert!(connected);
+
+    let (connected, _) = tokio::join!(signals.wait_for_state_change(), async {
+        signals.set_disconnected()
+    });
+    assert!(!connected);
+
+    // Already disconnected, should resolve immediately
+    tokio::time::timeout(Duration::from_millis(100), signals.wait_disconnected())
+        .await
+        .expect("wait_disconnected should not block while disconnected");
+}
+
+#[tokio::test]
+async fn test_wait_disconnected_on_shutdown() {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    let (client, mut runner) = ClientBuilder::new(server.connector(), ())
+        .build()
+        .await
+        .expect("Failed to build client");
+
+    let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+    tokio::spawn(async move {
+        runner.run().await;
+        let _ = done_tx.send(());
+    });
+
+    tokio::time::timeout(Duration::from_secs(5), client.wait_connected())
+        .await
+        .expect("Client never connected");
+    assert!(client.is_connected());
+
+    let watcher = client.clone();
+    let (_, shutdown) = tokio::join!(
+        tokio::time::timeout(Duration::from_secs(5), watcher.wait_disconnected()),
+        client.clone().shutdown()
+    );
+    shutdown.expect("Failed to send shutdown");
+    tokio::time::timeout(Duration::from_secs(5), done_rx)
+        .await
+        .expect("Runner did not stop")
+        .expect("Runner task dropped the channel");
+    assert!(!watcher.is_connected());
+}
+
+#[tokio::test]
+async fn test_connection_watch() {
+    let signals = Signals::default();
+    let mut receiver = signals.connection_watch();
+    let mut cloned = receiver.clone();
+    assert!(!*receiver.borrow());
+
+    signals.set_connected();
+    receiver.changed().await.unwrap();
+    assert!(*receiver.borrow_and_update());
+    assert!(signals.is_connected());
+
+    // Setting the same state again is not a change
+    signals.set_connected();
+    assert!(!receiver.has_changed().unwrap());
+
+    signals.set_disconnected();
+    receiver.changed().await.unwrap();
+    assert!(!*receiver.borrow_and_update());
+    assert!(!signals.is_connected());
+
+    // The clone saw neither update, it only observes the latest value
+    cloned.changed().await.unwrap();
+    assert!(!*cloned.borrow_and_update());
+}
+
+#[tokio::test]
+async fn test_client_connection_watch() {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    let (client, mut runner) = ClientBuilder::new(server.connector(), ())
+        .build()
+        .await
+        .expect("Failed to build client");
+    let mut receiver = client.connection_watch();
+    assert!(!*receiver.borrow());
+
+    let runner_task = tokio::spawn(async move { runner.run().await });
+
+    tokio::time::timeout(Duration::from_secs(5), receiver.changed())
+        .await
+        .expect("Client never connected")
+        .unwrap();
+    assert!(*receiver.borrow_and_update());
+    assert!(client.is_connected());
+
+    client.clone().shutdown().await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), receiver.changed())
+        .await
+        .expect("Client never disconnected")
+        .unwrap();
+    assert!(!*receiver.borrow_and_update());
+    let _ = tokio::time::timeout(Duration::from_secs(5), runner_task).await;
+}
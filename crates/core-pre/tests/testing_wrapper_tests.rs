@@ -195,3 +195,113 @@ async fn test_statistics_export() {
     assert!(csv_stats.contains("connection_attempts"));
     assert!(csv_stats.contains("successful_connections"));
 }
+
+#[tokio::test]
+async fn test_mock_websocket_server_replays_script() {
+    use binary_options_tools_core_pre::testing::MockWebSocketServer;
+    use futures_util::{SinkExt, StreamExt};
+
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    server
+        .respond_with(Message::text("hello"))
+        .expect_send("ping")
+        .reply_with(|msg| vec![Message::text(format!("echo {}", msg.to_text().unwrap()))]);
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(server.url())
+        .await
+        .expect("Failed to connect to mock server");
+    assert_eq!(ws.next().await.unwrap().unwrap(), Message::text("hello"));
+    assert!(server.verify().is_err());
+
+    ws.send(Message::text("ping 1")).await.unwrap();
+    assert_eq!(
+        ws.next().await.unwrap().unwrap(),
+        Message::text("echo ping 1")
+    );
+
+    let sent = server
+        .wait_for_send("ping", Duration::from_secs(1))
+        .await
+        .expect("Message was not recorded");
+    assert_eq!(sent, Message::text("ping 1"));
+    assert!(server.verify().is_ok());
+}
+
+#[tokio::test]
+async fn test_mock_websocket_server_expectations_per_connection() {
+    use binary_options_tools_core_pre::testing::MockWebSocketServer;
+    use futures_util::{SinkExt, StreamExt};
+
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    server
+        .expect_send("auth")
+        .respond_with(Message::text("authenticated"))
+        .expect_send("subscribe");
+
+    // Each connection only meets one of the two expectations
+    let (mut first, _) = tokio_tungstenite::connect_async(server.url())
+        .await
+        .expect("Failed to connect to mock server");
+    first.send(Message::text("auth")).await.unwrap();
+    assert_eq!(
+        first.next().await.unwrap().unwrap(),
+        Message::text("authenticated")
+    );
+    first.close(None).await.unwrap();
+
+    let (mut second, _) = tokio_tungstenite::connect_async(server.url())
+        .await
+        .expect("Failed to connect to mock server");
+    second.send(Message::text("subscribe")).await.unwrap();
+    second.send(Message::text("auth")).await.unwrap();
+    assert_eq!(
+        second.next().await.unwrap().unwrap(),
+        Message::text("authenticated")
+    );
+    assert!(server.verify().is_err());
+
+    second.send(Message::text("subscribe")).await.unwrap();
+    server
+        .wait_for_send("subscribe", Duration::from_secs(1))
+        .await
+        .unwrap();
+    // The message is recorded before the expectation is counted
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while server.verify().is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("The second connection met every expectation");
+}
+
+#[tokio::test]
+async fn test_record_stats_to_file() {
+    let (client, runner) = ClientBuilder::new(MockConnector, ())
+        .with_module::<TestModule>()
+        .build()
+        .await
+        .expect("Failed to build client");
+
+    let mut wrapper = TestingWrapperBuilder::new()
+        .with_stats_interval(Duration::from_millis(100))
+        .with_log_stats(false)
+        .with_auto_reconnect(false)
+        .build(client, runner);
+    let path = std::env::temp_dir().join(format!("stats-record-{}.jsonl", std::process::id()));
+    wrapper.record_to_file(path.clone());
+
+    wrapper.start().await.expect("Failed to start wrapper");
+    // Two stats intervals, plus some margin
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    wrapper.stop().await.expect("Failed to stop wrapper");
+
+    let records: Vec<_> = TestingWrapper::<()>::replay_stats(path.clone()).collect();
+    std::fs::remove_file(&path).unwrap();
+    assert!(records.len() >= 2, "Only {} records", records.len());
+    assert!(records.iter().all(|stats| stats.connection_attempts == 1));
+}
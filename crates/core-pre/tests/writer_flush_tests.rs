@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+use binary_options_tools_core_pre::builder::ClientBuilder;
+use binary_options_tools_core_pre::error::CoreResult;
+use binary_options_tools_core_pre::middleware::{MiddlewareContext, WebSocketMiddleware};
+use binary_options_tools_core_pre::reimports::Message;
+use binary_options_tools_core_pre::testing::MockWebSocketServer;
+use std::time::Duration;
+
+// Slows down the writer so the messages are still queued when the command arrives
+struct SlowSendMiddleware;
+
+#[async_trait]
+impl WebSocketMiddleware<()> for SlowSendMiddleware {
+    async fn on_send(&self, _: &Message, _: &MiddlewareContext<()>) -> CoreResult<()> {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(())
+    }
+}
+
+async fn assert_queued_messages_sent(disconnect: bool) {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    let (client, mut runner) = ClientBuilder::new(server.connector(), ())
+        .with_middleware(Box::new(SlowSendMiddleware))
+        .build()
+        .await
+        .expect("Failed to build client");
+    let runner_task = tokio::spawn(async move { runner.run().await });
+    tokio::time::timeout(Duration::from_secs(5), client.wait_connected())
+        .await
+        .expect("Client never connected");
+
+    for i in 0..5 {
+        client.send_text(format!("queued-{i}")).await.unwrap();
+    }
+    if disconnect {
+        client.disconnect().await.unwrap();
+    } else {
+        client.clone().shutdown().await.unwrap();
+    }
+
+    server
+        .wait_for_send("queued-4", Duration::from_secs(5))
+        .await
+        .expect("Queued messages were dropped");
+    let received: Vec<_> = server
+        .received()
+        .await
+        .iter()
+        .filter_map(|msg| msg.to_text().ok().map(str::to_string))
+        .collect();
+    assert_eq!(
+        received,
+        (0..5).map(|i| format!("queued-{i}")).collect::<Vec<_>>()
+    );
+
+    if disconnect {
+        client.shutdown().await.unwrap();
+    }
+    tokio::time::timeout(Duration::from_secs(5), runner_task)
+        .await
+        .expect("Runner did not stop")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_queued_messages_sent_on_disconnect() {
+    assert_queued_messages_sent(true).await;
+}
+
+#[tokio::test]
+async fn test_queued_messages_sent_on_shutdown() {
+    assert_queued_messages_sent(false).await;
+}
+
+async fn assert_close_frame_sent(disconnect: bool) {
+    let server = MockWebSocketServer::bind()
+        .await
+        .expect("Failed to bind mock server");
+    let (client, mut runner) = ClientBuilder::new(server.connector(), ())
+        .build()
+        .await
+        .expect("Failed to build client");
+    let runner_task = tokio::spawn(async move { runner.run().await });
+    tokio::time::timeout(Duration::from_secs(5), client.wait_connected())
+        .await
+        .expect("Client never connected");
+
+    client.send_text("last".to_string()).await.unwrap();
+    if disconnect {
+        client.disconnect().await.unwrap();
+    } else {
+        client.clone().shutdown().await.unwrap();
+    }
+    tokio::time::timeout(Duration::from_secs(5), client.wait_disconnected())
+        .await
+        .expect("Client never disconnected");
+
+    // The server only records the messages read before the connection dropped
+    let received = server.received().await;
+    assert!(
+        matches!(received.as_slice(), [Message::Text(text), Message::Close(None)] if text.as_str() == "last"),
+        "Close frame not received last: {received:?}"
+    );
+
+    if disconnect {
+        client.shutdown().await.unwrap();
+    }
+    tokio::time::timeout(Duration::from_secs(5), runner_task)
+        .await
+        .expect("Runner did not stop")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_close_frame_sent_on_disconnect() {
+    assert_close_frame_sent(true).await;
+}
+
+#[tokio::test]
+async fn test_close_frame_sent_on_shutdown() {
+    assert_close_frame_sent(false).await;
+}
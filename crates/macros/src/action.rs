@@ -1,28 +1,96 @@
-use darling::FromDeriveInput;
+use std::collections::HashMap;
+
+use darling::{ast::Data, util::Ignored, Error, FromDeriveInput, FromVariant};
 use quote::{quote, ToTokens};
 use syn::Ident;
 
 /// Auto implement the ActionName trait for types on the ExpertOptions API.
+///
+/// Structs use `#[action(name = "...")]`, enums use the variant name in camelCase
+/// (`OpenOrder` becomes `openOrder`) unless it's overridden with `#[action(wire_name = "...")]`.
 #[derive(FromDeriveInput)]
-#[darling(attributes(action))]
+#[darling(attributes(action), supports(struct_any, enum_any))]
 pub struct ActionImpl {
     ident: Ident,
-    name: String,
+    name: Option<String>,
+    data: Data<ActionVariant, Ignored>,
+}
+
+#[derive(FromVariant)]
+#[darling(attributes(action))]
+struct ActionVariant {
+    ident: Ident,
+    wire_name: Option<String>,
+}
+
+impl ActionVariant {
+    fn wire_name(&self) -> String {
+        self.wire_name.clone().unwrap_or_else(|| {
+            let name = self.ident.to_string();
+            let mut chars = name.chars();
+            chars
+                .next()
+                .map(|first| first.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+    }
 }
 
 impl ActionImpl {
+    /// Checks that structs have a name and that enums don't use the same wire name twice.
+    pub fn validate(self) -> darling::Result<Self> {
+        match &self.data {
+            Data::Struct(_) if self.name.is_none() => Err(Error::custom(
+                "Missing `#[action(name = \"...\")]` attribute",
+            )
+            .with_span(&self.ident)),
+            Data::Struct(_) => Ok(self),
+            Data::Enum(variants) => {
+                let mut errors = Error::accumulator();
+                let mut seen: HashMap<String, &Ident> = HashMap::new();
+                for variant in variants {
+                    let wire_name = variant.wire_name();
+                    if let Some(other) = seen.insert(wire_name.clone(), &variant.ident) {
+                        errors.push(
+                            Error::custom(format!(
+                                "Duplicate wire name \"{wire_name}\", already used by `{other}`"
+                            ))
+                            .with_span(&variant.ident),
+                        );
+                    }
+                }
+                errors.finish()?;
+                Ok(self)
+            }
+        }
+    }
+
+    /// Wire names of the action, one for structs and one per variant for enums.
+    fn wire_names(&self) -> Vec<String> {
+        match &self.data {
+            Data::Struct(_) => self.name.iter().cloned().collect(),
+            Data::Enum(variants) => variants.iter().map(ActionVariant::wire_name).collect(),
+        }
+    }
+
     /// As most of the ExpertOptions API responses contains the action name, this macro also generates a struct implementing the Rule trait.
     fn generate_rule(&self) -> proc_macro2::TokenStream {
         let rule_name = format!("{}Rule", self.ident);
         let rule_ident = Ident::new(&rule_name, self.ident.span());
-        let pattern = format!("{{\"action\":\"{}\"", self.name);
+        let patterns: Vec<String> = self
+            .wire_names()
+            .iter()
+            .map(|name| format!("{{\"action\":\"{name}\""))
+            .collect();
         quote! {
             pub struct #rule_ident;
 
             impl ::binary_options_tools_core_pre::traits::Rule for #rule_ident {
                 fn call(&self, msg: &::binary_options_tools_core_pre::reimports::Message) -> bool {
                     if let ::binary_options_tools_core_pre::reimports::Message::Binary(text) = msg {
-                        text.starts_with(#pattern.as_bytes())
+                        [#(#patterns),*]
+                            .iter()
+                            .any(|pattern| text.starts_with(pattern.as_bytes()))
                     } else {
                         false
                     }
@@ -64,17 +132,45 @@ impl ActionImpl {
     /// Generate the implementation tokens for the ActionName trait
     pub fn generate_impl(&self) -> proc_macro2::TokenStream {
         let ident = &self.ident;
-        let action_name = &self.name;
         let rule = self.generate_rule();
-        quote! {
-            #rule
+        match &self.data {
+            Data::Struct(_) => {
+                let action_name = &self.name;
+                quote! {
+                    #rule
 
-            impl ActionName for #ident {
-                fn name(&self) -> &str {
-                    #action_name
+                    impl ActionName for #ident {
+                        fn name(&self) -> &str {
+                            #action_name
+                        }
+                    }
                 }
             }
+            Data::Enum(variants) => {
+                let arms = variants.iter().map(|variant| {
+                    let variant_ident = &variant.ident;
+                    let wire_name = variant.wire_name();
+                    quote! { Self::#variant_ident { .. } => #wire_name }
+                });
+                quote! {
+                    #rule
+
+                    impl #ident {
+                        /// Name of the action sent to the server for this variant.
+                        pub fn wire_name(&self) -> &'static str {
+                            match self {
+                                #(#arms),*
+                            }
+                        }
+                    }
 
+                    impl ActionName for #ident {
+                        fn name(&self) -> &str {
+                            self.wire_name()
+                        }
+                    }
+                }
+            }
         }
     }
 }
@@ -2,7 +2,7 @@ use proc_macro2::TokenStream as TokenStream2;
 
 use darling::{ast, util, FromDeriveInput, FromField, FromMeta};
 use quote::{quote, ToTokens};
-use syn::{Generics, Ident, Type};
+use syn::{Expr, GenericArgument, Generics, Ident, PathArguments, Type};
 
 // Step 1: Parsing attributes into intermediate structs.
 // `FieldConfig` defines special configurations that can be applied to a field
@@ -34,6 +34,9 @@ struct ConfigField {
     ty: Type,             // The type of the field.
     // `extra`: Captures any `FieldConfig` applied to this field via `#[config(...)]`.
     extra: Option<FieldConfig>,
+    // `default`: `#[config(default = expr)]`, only valid on `Option<T>` fields.
+    // The value returned by the generated `field_name()` accessor when the field is `None`.
+    default: Option<Expr>,
 }
 
 // `Config` represents the entire struct to which the `#[derive(Config)]` macro is applied.
@@ -269,16 +272,56 @@ impl ToTokens for ConfigField {
             quote! {}
         };
 
+        // `Option<T>` fields get a setter accepting either `T` or `Option<T>`, an `is_field_name_set` checker
+        // and a `field_name()` accessor returning the value, the `#[config(default = ...)]` value or `T::default()`.
+        // Those never fail: a poisoned lock still holds a valid `Option`.
+        let (set_type, set_value, option) = if let Some(inner) = self.option_inner() {
+            let is_set_name = Ident::new(&format!("is_{name}_set"), name.span());
+            let fallback = match &self.default {
+                Some(default) => quote! { .unwrap_or_else(|| #default) },
+                None => quote! { .unwrap_or_default() },
+            };
+            let option = quote! {
+                pub fn #is_set_name(&self) -> bool {
+                    self.#name.lock().unwrap_or_else(::std::sync::PoisonError::into_inner).is_some()
+                }
+
+                pub fn #name(&self) -> #inner {
+                    self.#name.lock().unwrap_or_else(::std::sync::PoisonError::into_inner).clone()#fallback
+                }
+            };
+            (
+                quote! { impl ::std::convert::Into<#dtype> },
+                quote! { value.into() },
+                option,
+            )
+        } else if let Some(default) = &self.default {
+            let error = syn::Error::new_spanned(
+                default,
+                "`#[config(default = ...)]` is only supported on `Option<T>` fields",
+            );
+            (
+                quote! { #dtype },
+                quote! { value },
+                error.to_compile_error(),
+            )
+        } else {
+            (quote! { #dtype }, quote! { value }, quote! {})
+        };
+
         tokens.extend(quote! {
             // Append the `add_` method if generated.
             #extra
 
+            // Append the `Option<T>` accessors if generated.
+            #option
+
             // Generate the `set_field_name` method.
             // It locks the Mutex and replaces the entire value.
             // `value` here is of `dtype` (the full type of the field, e.g., `Vec<String>`).
-            pub fn #set_name(&self, value: #dtype) -> ::anyhow::Result<()> {
+            pub fn #set_name(&self, value: #set_type) -> ::anyhow::Result<()> {
                 let mut field = self.#name.lock().map_err(|e| ::anyhow::anyhow!("Poison error {e}"))?;
-                *field = value;
+                *field = #set_value;
                 Ok(())
             }
 
@@ -299,11 +342,46 @@ impl ConfigField {
     fn builder(&self) -> TokenStream2 {
         let name = self.ident.as_ref().expect("should have a name");
         let dtype = &self.ty;
+        // For `Option<T>` fields a `with_field_name(value: T)` method is also generated,
+        // so callers don't need to wrap the value in `Some` themselves.
+        let with = self.option_inner().map(|inner| {
+            let with_name = Ident::new(&format!("with_{name}"), name.span());
+            quote! {
+                pub fn #with_name(mut self, value: #inner) -> Self {
+                    self.#name = Some(Some(value));
+                    self
+                }
+            }
+        });
         quote! {
             pub fn #name(mut self, value: #dtype) -> Self {
                 self.#name = Some(value);
                 self
             }
+
+            #with
+        }
+    }
+
+    // `option_inner()`: Returns `T` if the field's type is `Option<T>`.
+    // Only the last path segment is checked, so `Option<T>`, `std::option::Option<T>`
+    // and `::std::option::Option<T>` are all detected.
+    fn option_inner(&self) -> Option<&Type> {
+        let Type::Path(path) = &self.ty else {
+            return None;
+        };
+        let segment = path.path.segments.last()?;
+        if segment.ident != "Option" {
+            return None;
+        }
+        match &segment.arguments {
+            PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+                match args.args.first() {
+                    Some(GenericArgument::Type(inner)) => Some(inner),
+                    _ => None,
+                }
+            }
+            _ => None,
         }
     }
 
@@ -353,6 +431,12 @@ impl ConfigField {
                     }
                 }
             }
+        } else if self.option_inner().is_some() {
+            // `Option<T>` fields behave like `#[config(optional)]` ones: leaving them
+            // unset in the builder results in `None`, and the accessor falls back to the default.
+            quote! {
+                #name: ::std::sync::Arc::new(::std::sync::Mutex::new(value.#name.unwrap_or(::std::option::Option::None)))
+            }
         } else {
             // If no special `#[config(...)]` attribute (i.e., it's a required field):
             // The field in the builder is `Option<FieldType>`.
@@ -33,6 +33,7 @@ pub fn serialize(input: TokenStream) -> TokenStream {
 /// This macro wraps any async function and transforms it's output `T` into `anyhow::Result<T>`,
 /// if the function doesn't end before the timout it will rais an error
 /// The macro also supports creating a `#[tracing::instrument]` macro with all the params inside `tracing(args)`
+/// It can be used on methods too, `self` is then added to the skipped tracing arguments so the type doesn't need to implement `Debug`
 /// Example:
 ///     #[timeout(10, tracing(skip(non_debug_input)))]
 ///     #[timeout(12)]
@@ -64,7 +65,7 @@ pub fn region(input: TokenStream) -> TokenStream {
 #[proc_macro_derive(ActionImpl, attributes(action))]
 pub fn action_impl(input: TokenStream) -> TokenStream {
     let parsed = parse_macro_input!(input as DeriveInput);
-    let action = match ActionImpl::from_derive_input(&parsed) {
+    let action = match ActionImpl::from_derive_input(&parsed).and_then(ActionImpl::validate) {
         Ok(action) => action,
         Err(e) => return e.write_errors().into(),
     };
@@ -98,6 +98,11 @@ impl ToTokens for Regions {
         let reals: Vec<&Region> = regions.iter().filter_map(|r| r.get_real()).collect();
         let reals_stream = reals.iter().map(|r| r.to_stream());
         let reals_url = reals.iter().map(|r| r.url());
+        // Sorted by name so the order doesn't depend on the `HashSet` iteration
+        let mut all: Vec<&Region> = regions.clone();
+        all.sort_by(|a, b| a.name.cmp(&b.name));
+        let all_stream = all.iter().map(|r| r.to_stream());
+        let count = all.len();
 
         tokens.extend(quote! {
             #(#regions)*
@@ -117,6 +122,17 @@ impl ToTokens for Regions {
             pub fn regions_str() -> Vec<&'static str> {
                 ::std::vec::Vec::from([#(#reals_url),*])
             }
+
+            /// Every region, demo and real, sorted by name.
+            pub const ALL_REGIONS: [(&'static str, f64, f64); #count] = [#(#all_stream),*];
+
+            pub fn all_regions() -> &'static [(&'static str, f64, f64)] {
+                &Self::ALL_REGIONS
+            }
+
+            pub fn region_count() -> usize {
+                #count
+            }
         });
     }
 }
@@ -1,6 +1,6 @@
 use proc_macro2::Span;
 use quote::{quote, ToTokens};
-use syn::{parse::Parse, Expr, FnArg, ItemFn, Pat, PatIdent, Token};
+use syn::{parse::Parse, parse_quote, Expr, FnArg, ItemFn, Pat, PatIdent, Token};
 
 pub struct Timeout {
     args: TimeoutArgs,
@@ -72,6 +72,32 @@ impl Parse for TimeoutBody {
     }
 }
 
+impl TracingArgs {
+    /// Adds `self` to the `skip` argument unless all the arguments are already skipped, so the
+    /// receiver doesn't need to implement `Debug`.
+    fn skip_self(&self) -> Self {
+        let is_named =
+            |expr: &Expr, name: &str| matches!(expr, Expr::Path(p) if p.path.is_ident(name));
+        let mut args = self.0.clone();
+        if args.iter().any(|arg| is_named(arg, "skip_all")) {
+            return Self(args);
+        }
+        let skip = args.iter_mut().find_map(|arg| match arg {
+            Expr::Call(call) if is_named(&call.func, "skip") => Some(call),
+            _ => None,
+        });
+        match skip {
+            Some(skip) => {
+                if !skip.args.iter().any(|arg| is_named(arg, "self")) {
+                    skip.args.push(parse_quote!(self));
+                }
+            }
+            None => args.push(parse_quote!(skip(self))),
+        }
+        Self(args)
+    }
+}
+
 impl ToTokens for TracingArgs {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let args = &self.0;
@@ -139,6 +165,30 @@ impl ToTokens for Timeout {
         // };
         let output = &body.sig.output;
 
+        if inputs.iter().any(|a| matches!(a, FnArg::Receiver(_))) {
+            // Methods can't be redeclared inside their own body, the body is awaited as an
+            // async block instead, moving the receiver and the arguments into it.
+            let tracing_args = tracing_args.as_ref().map(TracingArgs::skip_self);
+            let ItemFn {
+                attrs,
+                vis,
+                sig,
+                block,
+            } = body;
+            tokens.extend(quote! {
+                #tracing_args
+                #(#attrs)*
+                #vis #sig {
+                    let res = ::tokio::select! {
+                        res = async move #block => Ok(res),
+                        _ = ::tokio::time::sleep(#time_args) => Err(::binary_options_tools_core_pre::error::CoreError::TimeoutError { task: ::std::string::ToString::to_string(#fn_name_str), duration: #time_args })
+                    };
+                    res?
+                }
+            });
+            return;
+        }
+
         tokens.extend( quote! {
             #tracing_args
             async fn #fn_name(#inputs) #output {